@@ -0,0 +1,11 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS user_identities (
+  id SERIAL PRIMARY KEY,
+  user_id INTEGER NOT NULL,
+  provider VARCHAR(64) NOT NULL,
+  subject VARCHAR(256) NOT NULL,
+  email VARCHAR(256),
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE,
+  CONSTRAINT uq_user_identities_subject UNIQUE(provider, subject)
+);
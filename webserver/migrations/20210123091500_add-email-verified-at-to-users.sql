@@ -0,0 +1,3 @@
+-- Add migration script here
+ALTER TABLE users
+ADD COLUMN email_verified_at TIMESTAMP WITH TIME ZONE;
@@ -0,0 +1,4 @@
+-- Add migration script here
+ALTER TABLE todos
+DROP CONSTRAINT fk_user,
+  ADD CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE;
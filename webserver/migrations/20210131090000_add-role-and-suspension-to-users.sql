@@ -0,0 +1,5 @@
+-- Add migration script here
+ALTER TABLE users
+ADD COLUMN role VARCHAR(16) NOT NULL DEFAULT 'user',
+ADD COLUMN suspended_at TIMESTAMP WITH TIME ZONE,
+ADD COLUMN password_reset_required BOOLEAN NOT NULL DEFAULT FALSE;
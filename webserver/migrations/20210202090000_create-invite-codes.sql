@@ -0,0 +1,21 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS invite_codes (
+  id SERIAL PRIMARY KEY,
+  code_hash VARCHAR(64) UNIQUE NOT NULL,
+  created_by INTEGER,
+  max_uses INTEGER NOT NULL,
+  use_count INTEGER NOT NULL DEFAULT 0,
+  expires_at TIMESTAMP WITH TIME ZONE,
+  revoked_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  CONSTRAINT fk_created_by FOREIGN KEY(created_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE TABLE IF NOT EXISTS invite_redemptions (
+  id SERIAL PRIMARY KEY,
+  invite_code_id INTEGER NOT NULL,
+  user_id INTEGER NOT NULL,
+  redeemed_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  CONSTRAINT fk_invite_code FOREIGN KEY(invite_code_id) REFERENCES invite_codes(id) ON DELETE CASCADE,
+  CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE
+);
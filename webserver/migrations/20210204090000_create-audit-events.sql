@@ -0,0 +1,16 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS audit_events (
+  id SERIAL PRIMARY KEY,
+  event_type VARCHAR(32) NOT NULL,
+  user_id INTEGER,
+  actor_id INTEGER,
+  account VARCHAR(255),
+  detail TEXT,
+  ip_address VARCHAR(64),
+  user_agent TEXT,
+  request_id VARCHAR(64),
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS audit_events_user_id ON audit_events (user_id);
+CREATE INDEX IF NOT EXISTS audit_events_created_at ON audit_events (created_at);
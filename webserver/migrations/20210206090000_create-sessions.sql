@@ -0,0 +1,20 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS sessions (
+  id SERIAL PRIMARY KEY,
+  user_id INTEGER NOT NULL,
+  family VARCHAR(64) UNIQUE NOT NULL,
+  user_agent TEXT,
+  ip_address VARCHAR(64),
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  last_seen_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS sessions_user_id ON sessions (user_id);
+
+-- Logins from before sessions were recorded keep working, without their device.
+INSERT INTO sessions (user_id, family, created_at, last_seen_at)
+SELECT user_id, family, MIN(created_at), MAX(created_at)
+FROM refresh_tokens
+GROUP BY user_id, family
+ON CONFLICT (family) DO NOTHING;
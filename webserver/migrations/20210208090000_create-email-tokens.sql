@@ -0,0 +1,5 @@
+-- Add migration script here
+-- Password reset links and login links share the table, told apart by their purpose.
+ALTER TABLE password_reset_tokens RENAME TO email_tokens;
+ALTER TABLE email_tokens ADD COLUMN purpose VARCHAR(32) NOT NULL DEFAULT 'password_reset';
+ALTER TABLE email_tokens ALTER COLUMN purpose DROP DEFAULT;
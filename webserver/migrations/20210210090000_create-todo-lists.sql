@@ -0,0 +1,34 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS todo_lists (
+  id SERIAL PRIMARY KEY,
+  user_id INTEGER NOT NULL,
+  name VARCHAR(255) NOT NULL,
+  is_default BOOLEAN NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS todo_lists_user_id ON todo_lists (user_id);
+-- A user has at most one default list, todos without a list go there.
+CREATE UNIQUE INDEX IF NOT EXISTS todo_lists_default ON todo_lists (user_id) WHERE is_default;
+
+-- Todos from before lists move into a default list of their user.
+INSERT INTO todo_lists (user_id, name, is_default, created_at, updated_at)
+SELECT DISTINCT user_id, 'Todos', true, NOW(), NOW()
+FROM todos
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE todos
+ADD COLUMN list_id INTEGER,
+  ADD CONSTRAINT fk_list FOREIGN KEY(list_id) REFERENCES todo_lists(id) ON DELETE CASCADE;
+
+UPDATE todos
+SET list_id = todo_lists.id
+FROM todo_lists
+WHERE todo_lists.user_id = todos.user_id AND todo_lists.is_default;
+
+ALTER TABLE todos
+ALTER COLUMN list_id SET NOT NULL;
+
+CREATE INDEX IF NOT EXISTS todos_list_id ON todos (list_id);
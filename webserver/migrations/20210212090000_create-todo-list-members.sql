@@ -0,0 +1,38 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS todo_list_members (
+  id SERIAL PRIMARY KEY,
+  list_id INTEGER NOT NULL,
+  user_id INTEGER NOT NULL,
+  role VARCHAR(16) NOT NULL,
+  invited_by INTEGER,
+  -- Invitations are pending until the invited user accepts them.
+  accepted_at TIMESTAMP WITH TIME ZONE,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  CONSTRAINT todo_list_members_list_user UNIQUE (list_id, user_id),
+  CONSTRAINT fk_list FOREIGN KEY(list_id) REFERENCES todo_lists(id) ON DELETE CASCADE,
+  CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE,
+  CONSTRAINT fk_invited_by FOREIGN KEY(invited_by) REFERENCES users(id) ON DELETE SET NULL
+);
+
+CREATE INDEX IF NOT EXISTS todo_list_members_user_id ON todo_list_members (user_id);
+
+-- The user of a list owns it.
+INSERT INTO todo_list_members (list_id, user_id, role, accepted_at, created_at, updated_at)
+SELECT id, user_id, 'owner', created_at, created_at, created_at
+FROM todo_lists
+ON CONFLICT DO NOTHING;
+
+-- Todos of a shared list are written by several users, who may delete their accounts.
+ALTER TABLE todos
+RENAME COLUMN user_id TO created_by;
+
+ALTER TABLE todos
+ALTER COLUMN created_by DROP NOT NULL,
+  DROP CONSTRAINT fk_user,
+  ADD CONSTRAINT fk_created_by FOREIGN KEY(created_by) REFERENCES users(id) ON DELETE SET NULL,
+  ADD COLUMN updated_by INTEGER,
+  ADD CONSTRAINT fk_updated_by FOREIGN KEY(updated_by) REFERENCES users(id) ON DELETE SET NULL;
+
+UPDATE todos
+SET updated_by = created_by;
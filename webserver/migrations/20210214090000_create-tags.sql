@@ -0,0 +1,25 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS tags (
+  id SERIAL PRIMARY KEY,
+  user_id INTEGER NOT NULL,
+  name VARCHAR(64) NOT NULL,
+  -- A hex color like #1e88e5.
+  color VARCHAR(7) NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  CONSTRAINT fk_user FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Names of the tags of a user are unique regardless of case.
+CREATE UNIQUE INDEX IF NOT EXISTS tags_user_name ON tags (user_id, LOWER(name));
+
+CREATE TABLE IF NOT EXISTS todo_tags (
+  todo_id INTEGER NOT NULL,
+  tag_id INTEGER NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  PRIMARY KEY (todo_id, tag_id),
+  CONSTRAINT fk_todo FOREIGN KEY(todo_id) REFERENCES todos(id) ON DELETE CASCADE,
+  CONSTRAINT fk_tag FOREIGN KEY(tag_id) REFERENCES tags(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS todo_tags_tag_id ON todo_tags (tag_id);
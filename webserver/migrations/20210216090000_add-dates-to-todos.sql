@@ -0,0 +1,7 @@
+-- Add migration script here
+ALTER TABLE todos
+ADD COLUMN start_at TIMESTAMP WITH TIME ZONE,
+  ADD COLUMN due_at TIMESTAMP WITH TIME ZONE,
+  ADD CONSTRAINT todos_start_before_due CHECK (start_at <= due_at);
+
+CREATE INDEX IF NOT EXISTS todos_list_id_due_at ON todos (list_id, due_at);
@@ -0,0 +1,33 @@
+-- Add migration script here
+CREATE TABLE IF NOT EXISTS todo_series (
+  id SERIAL PRIMARY KEY,
+  list_id INTEGER NOT NULL,
+  -- The body every new occurrence gets.
+  body VARCHAR NOT NULL,
+  -- An RFC 5545 RRULE like FREQ=WEEKLY;BYDAY=MO.
+  rrule VARCHAR(255) NOT NULL,
+  -- The IANA time zone occurrences keep their local time in.
+  time_zone VARCHAR(64) NOT NULL,
+  -- The due time of the first occurrence, the rule counts from there.
+  dtstart TIMESTAMP WITH TIME ZONE NOT NULL,
+  created_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  updated_at TIMESTAMP WITH TIME ZONE NOT NULL,
+  -- Occurrences before dtstart, which count against a COUNT once the series is moved.
+  occurrences_before INTEGER NOT NULL DEFAULT 0,
+  CONSTRAINT fk_list FOREIGN KEY(list_id) REFERENCES todo_lists(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS todo_series_list_id ON todo_series (list_id);
+
+-- An occurrence remembers when the rule scheduled it, which stays when only its due time is moved,
+-- and the occurrence whose completion created it.
+ALTER TABLE todos
+ADD COLUMN series_id INTEGER,
+  ADD COLUMN occurrence_at TIMESTAMP WITH TIME ZONE,
+  ADD COLUMN previous_id INTEGER,
+  ADD CONSTRAINT fk_series FOREIGN KEY(series_id) REFERENCES todo_series(id) ON DELETE SET NULL,
+  ADD CONSTRAINT fk_previous FOREIGN KEY(previous_id) REFERENCES todos(id) ON DELETE SET NULL;
+
+-- Completing an occurrence twice creates the next one once, and a series has every occurrence once.
+CREATE UNIQUE INDEX IF NOT EXISTS todos_previous ON todos (previous_id);
+CREATE UNIQUE INDEX IF NOT EXISTS todos_series_occurrence ON todos (series_id, occurrence_at);
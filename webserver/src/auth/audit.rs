@@ -0,0 +1,96 @@
+use crate::auth::rate_limit::ClientIp;
+use crate::domains::entities::audit_event::RequestMetadata;
+use crate::State;
+use rand::RngCore;
+use std::env;
+use tide::http::headers::USER_AGENT;
+use tide::{Middleware, Next, Request};
+
+const REQUEST_ID_HEADER: &str = "X-Request-Id";
+const MAX_REQUEST_ID_LENGTH: usize = 64;
+const MAX_USER_AGENT_LENGTH: usize = 512;
+
+/// Id of the request, set by `RequestIdMiddleware`.
+#[derive(Debug, Clone)]
+pub struct RequestId(pub String);
+
+/// Gives every request an id, which is returned in `X-Request-Id` and recorded with its audit events.
+pub struct RequestIdMiddleware {
+    /// Keep the id a proxy set in `X-Request-Id`, only safe behind a proxy which sets it.
+    trust_proxy: bool,
+}
+
+impl RequestIdMiddleware {
+    pub fn from_env() -> Self {
+        Self {
+            trust_proxy: env::var("TRUST_PROXY").map_or(false, |value| value == "true"),
+        }
+    }
+
+    fn request_id(&self, request: &Request<State>) -> String {
+        let forwarded = request
+            .header(REQUEST_ID_HEADER)
+            .map(|values| values.last().as_str())
+            .filter(|_| self.trust_proxy);
+        match forwarded {
+            Some(id) if valid_request_id(id) => id.to_owned(),
+            _ => generate_request_id(),
+        }
+    }
+}
+
+#[tide::utils::async_trait]
+impl Middleware<State> for RequestIdMiddleware {
+    async fn handle(&self, mut request: Request<State>, next: Next<'_, State>) -> tide::Result {
+        let id = self.request_id(&request);
+        request.set_ext(RequestId(id.to_owned()));
+        let mut response = next.run(request).await;
+        response.insert_header(REQUEST_ID_HEADER, id);
+        Ok(response)
+    }
+}
+
+/// Ids from elsewhere end up in logs, so only short plain ones are kept.
+fn valid_request_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_REQUEST_ID_LENGTH
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
+
+fn generate_request_id() -> String {
+    let mut bytes = [0u8; 16];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Where the request came from, for its audit events.
+pub fn request_metadata(request: &Request<State>) -> RequestMetadata {
+    RequestMetadata {
+        ip_address: request.ext::<ClientIp>().map(|ip| ip.0.to_owned()),
+        user_agent: request.header(USER_AGENT).map(|values| {
+            values
+                .last()
+                .as_str()
+                .chars()
+                .take(MAX_USER_AGENT_LENGTH)
+                .collect()
+        }),
+        request_id: request.ext::<RequestId>().map(|id| id.0.to_owned()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_request_id() {
+        assert!(valid_request_id("0f8fad5b-d9cb-469f-a165-70867728950e"));
+        assert!(!valid_request_id(""));
+        assert!(!valid_request_id("id\nforged log line"));
+        assert!(!valid_request_id(&"a".repeat(MAX_REQUEST_ID_LENGTH + 1)));
+        assert_eq!(32, generate_request_id().len());
+    }
+}
@@ -0,0 +1,109 @@
+use crate::auth::Principal;
+use crate::domains::entities::personal_access_token::Scope;
+use crate::domains::entities::user::Role;
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::ApplicationResult;
+
+/// What a resolver needs from its caller.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Requirement {
+    /// A login session, or a personal access token with the scope.
+    Scope(Scope),
+    /// A login session, personal access tokens are refused.
+    Session,
+    /// A user with the role or a higher one.
+    Role(Role),
+}
+
+/// Admin resolvers are only called from a login session.
+pub const ADMIN: &[Requirement] = &[Requirement::Session, Requirement::Role(Role::Admin)];
+
+impl Requirement {
+    pub fn check(self, principal: &Principal) -> ApplicationResult<()> {
+        let (satisfied, message) = match self {
+            Requirement::Scope(scope) => (
+                principal.has_scope(scope),
+                format!("token doesn't have the {} scope", scope.as_ref()),
+            ),
+            Requirement::Session => (
+                principal.is_session(),
+                "personal access tokens can't call this field".to_owned(),
+            ),
+            Requirement::Role(role) => (
+                principal.role >= role,
+                format!("user doesn't have the {} role", role.as_ref()),
+            ),
+        };
+        if satisfied {
+            return Ok(());
+        }
+        Err(ApplicationError {
+            code: ErrorCode::Forbidden,
+            message,
+        })
+    }
+}
+
+/// Check the caller against every requirement, an anonymous caller is rejected even without any.
+pub fn authorize<'a>(
+    principal: Option<&'a Principal>,
+    requirements: &[Requirement],
+) -> ApplicationResult<&'a Principal> {
+    let principal = match principal {
+        Some(principal) => principal,
+        None => {
+            return Err(ApplicationError {
+                code: ErrorCode::UnAuthenticated,
+                message: "user is not authenticated".to_owned(),
+            })
+        }
+    };
+    for requirement in requirements {
+        requirement.check(principal)?;
+    }
+    Ok(principal)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn principal(role: Role, scopes: Option<Vec<Scope>>) -> Principal {
+        Principal {
+            user_id: 1,
+            family: None,
+            session_id: None,
+            scopes,
+            role,
+        }
+    }
+
+    #[test]
+    fn test_authorize_anonymous() {
+        let err = authorize(None, &[]).err().unwrap();
+        assert!(matches!(err.code, ErrorCode::UnAuthenticated));
+    }
+
+    #[test]
+    fn test_authorize_admin() {
+        let admin = principal(Role::Admin, None);
+        assert!(authorize(Some(&admin), ADMIN).is_ok());
+        assert!(authorize(Some(&admin), &[Requirement::Role(Role::User)]).is_ok());
+
+        let user = principal(Role::User, None);
+        let err = authorize(Some(&user), ADMIN).err().unwrap();
+        assert!(matches!(err.code, ErrorCode::Forbidden));
+    }
+
+    #[test]
+    fn test_authorize_personal_access_token() {
+        let token = principal(Role::Admin, Some(vec![Scope::TodosRead]));
+        assert!(authorize(Some(&token), &[Requirement::Scope(Scope::TodosRead)]).is_ok());
+        let err = authorize(Some(&token), &[Requirement::Scope(Scope::TodosWrite)])
+            .err()
+            .unwrap();
+        assert!(matches!(err.code, ErrorCode::Forbidden));
+        let err = authorize(Some(&token), ADMIN).err().unwrap();
+        assert!(matches!(err.code, ErrorCode::Forbidden));
+    }
+}
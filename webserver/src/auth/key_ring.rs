@@ -0,0 +1,545 @@
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::ApplicationResult;
+use anyhow::{anyhow, bail, Context};
+use jsonwebtoken::errors::ErrorKind;
+use jsonwebtoken::{
+    decode, decode_header, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation,
+};
+use ring::error::KeyRejected;
+use ring::signature::{
+    EcdsaKeyPair, Ed25519KeyPair, KeyPair, RsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING,
+};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+
+/// How long a retired key keeps verifying tokens it signed, by default the access token lifetime.
+const DEFAULT_GRACE_PERIOD_SECONDS: i64 = 60 * 60;
+/// Key id used when the ring is built from the legacy `JWT_SECRET`.
+const DEFAULT_KID: &str = "default";
+
+/// Key ring file referenced by `JWT_KEYS_FILE`.
+#[derive(Deserialize)]
+struct KeyRingConfig {
+    signing_key: String,
+    grace_period_seconds: Option<i64>,
+    keys: Vec<KeyConfig>,
+}
+
+#[derive(Deserialize)]
+struct KeyConfig {
+    kid: String,
+    algorithm: String,
+    /// Shared secret for HMAC keys.
+    secret: Option<String>,
+    /// PKCS#8 PEM private key for RSA, ECDSA and EdDSA keys.
+    private_key_file: Option<String>,
+    /// RFC 3339 time the key stopped signing tokens.
+    retired_at: Option<String>,
+}
+
+/// A public key in the JWK format (RFC 7517).
+/// Other issuers may leave out the optional members, so they default to empty strings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Jwk {
+    pub kty: String,
+    #[serde(default)]
+    pub kid: String,
+    #[serde(default)]
+    pub alg: String,
+    #[serde(rename = "use", default)]
+    pub usage: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub n: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub e: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crv: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub x: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub y: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JwkSet {
+    pub keys: Vec<Jwk>,
+}
+
+#[derive(Clone)]
+struct Key {
+    kid: String,
+    algorithm: Algorithm,
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    /// Only asymmetric keys are published.
+    jwk: Option<Jwk>,
+    retired_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// Keys used to sign and verify JWTs.
+/// One key signs new tokens, the others only verify tokens which were signed before a rotation.
+#[derive(Clone)]
+pub struct KeyRing {
+    signing_kid: String,
+    grace_period: chrono::Duration,
+    keys: Vec<Key>,
+}
+
+impl KeyRing {
+    /// Load the key ring from `JWT_KEYS_FILE`, or fall back to a single HS512 `JWT_SECRET`.
+    pub fn from_env() -> anyhow::Result<Self> {
+        if let Ok(path) = env::var("JWT_KEYS_FILE") {
+            return Self::from_file(&path);
+        }
+        match env::var("JWT_SECRET") {
+            Ok(secret) if !secret.is_empty() => Ok(Self::from_secret(&secret)),
+            _ => bail!("either JWT_KEYS_FILE or JWT_SECRET must be set"),
+        }
+    }
+
+    pub fn from_secret(secret: &str) -> Self {
+        Self {
+            signing_kid: DEFAULT_KID.to_owned(),
+            grace_period: chrono::Duration::seconds(DEFAULT_GRACE_PERIOD_SECONDS),
+            keys: vec![Key {
+                kid: DEFAULT_KID.to_owned(),
+                algorithm: Algorithm::HS512,
+                encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+                decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+                jwk: None,
+                retired_at: None,
+            }],
+        }
+    }
+
+    pub fn from_file(path: &str) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read key ring file {}", path))?;
+        let config: KeyRingConfig = serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse key ring file {}", path))?;
+        let mut keys = vec![];
+        for key in config.keys {
+            let retired_at = match key.retired_at.as_ref() {
+                Some(retired_at) => Some(
+                    chrono::DateTime::parse_from_rfc3339(retired_at)
+                        .with_context(|| format!("invalid retired_at of key {}", key.kid))?
+                        .with_timezone(&chrono::Utc),
+                ),
+                None => None,
+            };
+            let algorithm = key
+                .algorithm
+                .parse::<Algorithm>()
+                .map_err(|_| anyhow!("unsupported algorithm of key {}", key.kid))?;
+            let mut loaded = match (key.secret.as_ref(), key.private_key_file.as_ref()) {
+                (Some(secret), None) => Key::from_secret(&key.kid, algorithm, secret)?,
+                (None, Some(file)) => {
+                    let pem = fs::read(file)
+                        .with_context(|| format!("failed to read private key {}", file))?;
+                    Key::from_pem(&key.kid, algorithm, &pem)?
+                }
+                _ => bail!(
+                    "key {} needs either a secret or a private_key_file",
+                    key.kid
+                ),
+            };
+            loaded.retired_at = retired_at;
+            keys.push(loaded);
+        }
+        let grace_period = chrono::Duration::seconds(
+            config
+                .grace_period_seconds
+                .unwrap_or(DEFAULT_GRACE_PERIOD_SECONDS),
+        );
+        Self::new(config.signing_key, grace_period, keys)
+    }
+
+    fn new(
+        signing_kid: String,
+        grace_period: chrono::Duration,
+        keys: Vec<Key>,
+    ) -> anyhow::Result<Self> {
+        for (i, key) in keys.iter().enumerate() {
+            if keys[..i].iter().any(|other| other.kid == key.kid) {
+                bail!("key id {} is duplicated", key.kid);
+            }
+        }
+        match keys.iter().find(|key| key.kid == signing_kid) {
+            Some(key) if key.retired_at.is_some() => {
+                bail!("signing key {} is retired", signing_kid)
+            }
+            Some(_) => {}
+            None => bail!("signing key {} is not in the key ring", signing_kid),
+        }
+        Ok(Self {
+            signing_kid,
+            grace_period,
+            keys,
+        })
+    }
+
+    /// Sign claims with the current signing key, the key id is put in the `kid` header.
+    pub fn encode<T: Serialize>(&self, claims: &T) -> ApplicationResult<String> {
+        let key = match self.keys.iter().find(|key| key.kid == self.signing_kid) {
+            Some(key) => key,
+            None => return Err(token_creation_error()),
+        };
+        let mut header = Header::new(key.algorithm);
+        header.kid = Some(key.kid.to_owned());
+        encode(&header, claims, &key.encoding_key).map_err(|_| token_creation_error())
+    }
+
+    /// Verify a token with the key named by its `kid` header.
+    /// Keys retired longer than the grace period ago are no longer accepted.
+    pub fn decode<T: DeserializeOwned>(&self, token: &str) -> ApplicationResult<T> {
+        let header = decode_header(token).map_err(invalid_token)?;
+        let key = match header
+            .kid
+            .as_ref()
+            .and_then(|kid| self.keys.iter().find(|key| &key.kid == kid))
+        {
+            Some(key) if self.is_verifying(key) => key,
+            _ => {
+                return Err(ApplicationError {
+                    code: ErrorCode::UnAuthenticated,
+                    message: "token is signed by an unknown key".to_owned(),
+                })
+            }
+        };
+        match decode::<T>(token, &key.decoding_key, &Validation::new(key.algorithm)) {
+            Ok(data) => Ok(data.claims),
+            Err(err) => match err.kind() {
+                ErrorKind::ExpiredSignature => Err(ApplicationError {
+                    code: ErrorCode::TokenExpired,
+                    message: "token is expired".to_owned(),
+                }),
+                _ => Err(invalid_token(err)),
+            },
+        }
+    }
+
+    /// Public keys which currently verify tokens, for `/.well-known/jwks.json`.
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .keys
+                .iter()
+                .filter(|key| self.is_verifying(key))
+                .filter_map(|key| key.jwk.clone())
+                .collect(),
+        }
+    }
+
+    fn is_verifying(&self, key: &Key) -> bool {
+        match key.retired_at {
+            Some(retired_at) => chrono::Utc::now() < retired_at + self.grace_period,
+            None => true,
+        }
+    }
+}
+
+impl Key {
+    fn from_secret(kid: &str, algorithm: Algorithm, secret: &str) -> anyhow::Result<Self> {
+        match algorithm {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => Ok(Self {
+                kid: kid.to_owned(),
+                algorithm,
+                encoding_key: EncodingKey::from_secret(secret.as_bytes()),
+                decoding_key: DecodingKey::from_secret(secret.as_bytes()),
+                jwk: None,
+                retired_at: None,
+            }),
+            _ => bail!("key {} with a secret must use an HMAC algorithm", kid),
+        }
+    }
+
+    fn from_pem(kid: &str, algorithm: Algorithm, pem: &[u8]) -> anyhow::Result<Self> {
+        let der = pem::parse(pem)
+            .with_context(|| format!("failed to parse private key of key {}", kid))?;
+        let rejected =
+            |err: KeyRejected| anyhow!("private key of key {} is rejected: {}", kid, err);
+        let (encoding_key, decoding_key, jwk) = match algorithm {
+            Algorithm::RS256 => {
+                let key_pair = match der.tag.as_str() {
+                    "RSA PRIVATE KEY" => RsaKeyPair::from_der(&der.contents),
+                    _ => RsaKeyPair::from_pkcs8(&der.contents),
+                }
+                .map_err(rejected)?;
+                let n = key_pair.public_key().modulus();
+                let e = key_pair.public_key().exponent();
+                let (n, e) = (
+                    n.big_endian_without_leading_zero(),
+                    e.big_endian_without_leading_zero(),
+                );
+                (
+                    EncodingKey::from_rsa_pem(pem)?,
+                    DecodingKey::from_rsa_raw_components(n, e),
+                    Jwk {
+                        kty: "RSA".to_owned(),
+                        n: Some(base64_url(n)),
+                        e: Some(base64_url(e)),
+                        ..Jwk::new(kid, "RS256")
+                    },
+                )
+            }
+            Algorithm::ES256 => {
+                let key_pair =
+                    EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &der.contents)
+                        .map_err(rejected)?;
+                // Uncompressed point, 0x04 followed by 32 bytes of x and 32 bytes of y.
+                let point = key_pair.public_key().as_ref();
+                (
+                    EncodingKey::from_ec_pem(pem)?,
+                    DecodingKey::from_ec_der(point),
+                    Jwk {
+                        kty: "EC".to_owned(),
+                        crv: Some("P-256".to_owned()),
+                        x: Some(base64_url(&point[1..33])),
+                        y: Some(base64_url(&point[33..])),
+                        ..Jwk::new(kid, "ES256")
+                    },
+                )
+            }
+            Algorithm::EdDSA => {
+                let key_pair =
+                    Ed25519KeyPair::from_pkcs8_maybe_unchecked(&der.contents).map_err(rejected)?;
+                let public_key = key_pair.public_key().as_ref();
+                (
+                    EncodingKey::from_ed_pem(pem)?,
+                    DecodingKey::from_ed_der(public_key),
+                    Jwk {
+                        kty: "OKP".to_owned(),
+                        crv: Some("Ed25519".to_owned()),
+                        x: Some(base64_url(public_key)),
+                        ..Jwk::new(kid, "EdDSA")
+                    },
+                )
+            }
+            _ => bail!("key {} uses an unsupported algorithm", kid),
+        };
+        Ok(Self {
+            kid: kid.to_owned(),
+            algorithm,
+            encoding_key,
+            decoding_key,
+            jwk: Some(jwk),
+            retired_at: None,
+        })
+    }
+}
+
+impl Jwk {
+    /// Key which verifies tokens signed by the owner of this public key.
+    pub fn decoding_key(&self) -> anyhow::Result<DecodingKey> {
+        let member = |value: &Option<String>, name: &str| match value.as_ref() {
+            Some(value) => base64::decode_config(value, base64::URL_SAFE_NO_PAD)
+                .with_context(|| format!("member {} of key {} is invalid", name, self.kid)),
+            None => Err(anyhow!("key {} doesn't have member {}", self.kid, name)),
+        };
+        match (self.kty.as_str(), self.crv.as_deref()) {
+            ("RSA", _) => Ok(DecodingKey::from_rsa_raw_components(
+                &member(&self.n, "n")?,
+                &member(&self.e, "e")?,
+            )),
+            ("EC", Some("P-256")) => {
+                let mut point = vec![0x04];
+                point.extend(member(&self.x, "x")?);
+                point.extend(member(&self.y, "y")?);
+                Ok(DecodingKey::from_ec_der(&point))
+            }
+            ("OKP", Some("Ed25519")) => Ok(DecodingKey::from_ed_der(&member(&self.x, "x")?)),
+            _ => bail!("key {} has an unsupported key type {}", self.kid, self.kty),
+        }
+    }
+
+    fn new(kid: &str, alg: &str) -> Self {
+        Self {
+            kty: "".to_owned(),
+            kid: kid.to_owned(),
+            alg: alg.to_owned(),
+            usage: "sig".to_owned(),
+            n: None,
+            e: None,
+            crv: None,
+            x: None,
+            y: None,
+        }
+    }
+}
+
+fn base64_url(bytes: &[u8]) -> String {
+    base64::encode_config(bytes, base64::URL_SAFE_NO_PAD)
+}
+
+fn token_creation_error() -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::JWTTokenCreationError,
+        message: "faied to create JWT token".to_owned(),
+    }
+}
+
+fn invalid_token(err: jsonwebtoken::errors::Error) -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::UnAuthenticated,
+        message: format!("token is invalid, err: {:}", err),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::rand::SystemRandom;
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct TestClaims {
+        sub: String,
+        exp: usize,
+    }
+
+    fn claims() -> TestClaims {
+        TestClaims {
+            sub: "1".to_owned(),
+            exp: (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp() as usize,
+        }
+    }
+
+    fn pkcs8_pem(der: &[u8]) -> Vec<u8> {
+        pem::encode(&pem::Pem {
+            tag: "PRIVATE KEY".to_owned(),
+            contents: der.to_vec(),
+        })
+        .into_bytes()
+    }
+
+    fn hmac_key(kid: &str, secret: &str) -> Key {
+        Key::from_secret(kid, Algorithm::HS512, secret).unwrap()
+    }
+
+    #[test]
+    fn test_encode_sets_kid() {
+        let key_ring = KeyRing::from_secret("secret");
+        let token = key_ring.encode(&claims()).unwrap();
+        let header = decode_header(&token).unwrap();
+        assert_eq!(Some(DEFAULT_KID.to_owned()), header.kid);
+        assert_eq!(Algorithm::HS512, header.alg);
+        let decoded = key_ring.decode::<TestClaims>(&token).unwrap();
+        assert_eq!("1", decoded.sub);
+    }
+
+    #[test]
+    fn test_decode_rejects_unknown_kid() {
+        let token = KeyRing::from_secret("secret").encode(&claims()).unwrap();
+        let other = KeyRing::new(
+            "other".to_owned(),
+            chrono::Duration::hours(1),
+            vec![hmac_key("other", "secret")],
+        )
+        .unwrap();
+        assert!(other.decode::<TestClaims>(&token).is_err());
+    }
+
+    #[test]
+    fn test_decode_expired_token() {
+        let key_ring = KeyRing::from_secret("secret");
+        let token = key_ring
+            .encode(&TestClaims {
+                sub: "1".to_owned(),
+                exp: (chrono::Utc::now() - chrono::Duration::hours(1)).timestamp() as usize,
+            })
+            .unwrap();
+        let err = key_ring.decode::<TestClaims>(&token).err().unwrap();
+        assert!(matches!(err.code, ErrorCode::TokenExpired));
+    }
+
+    #[test]
+    fn test_retired_key_in_grace_period() {
+        let old = KeyRing::new(
+            "old".to_owned(),
+            chrono::Duration::hours(1),
+            vec![hmac_key("old", "old")],
+        )
+        .unwrap();
+        let token = old.encode(&claims()).unwrap();
+
+        let mut retired = hmac_key("old", "old");
+        retired.retired_at = Some(chrono::Utc::now() - chrono::Duration::minutes(30));
+        let rotated = KeyRing::new(
+            "new".to_owned(),
+            chrono::Duration::hours(1),
+            vec![hmac_key("new", "new"), retired.clone()],
+        )
+        .unwrap();
+        assert!(rotated.decode::<TestClaims>(&token).is_ok());
+        let header = decode_header(&rotated.encode(&claims()).unwrap()).unwrap();
+        assert_eq!(Some("new".to_owned()), header.kid);
+
+        retired.retired_at = Some(chrono::Utc::now() - chrono::Duration::hours(2));
+        let expired = KeyRing::new(
+            "new".to_owned(),
+            chrono::Duration::hours(1),
+            vec![hmac_key("new", "new"), retired],
+        )
+        .unwrap();
+        assert!(expired.decode::<TestClaims>(&token).is_err());
+    }
+
+    #[test]
+    fn test_signing_key_must_exist() {
+        assert!(KeyRing::new(
+            "missing".to_owned(),
+            chrono::Duration::hours(1),
+            vec![hmac_key("default", "secret")],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_es256_key() {
+        let der =
+            EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &SystemRandom::new())
+                .unwrap();
+        let key = Key::from_pem("es", Algorithm::ES256, &pkcs8_pem(der.as_ref())).unwrap();
+        let key_ring =
+            KeyRing::new("es".to_owned(), chrono::Duration::hours(1), vec![key]).unwrap();
+        let token = key_ring.encode(&claims()).unwrap();
+        assert!(key_ring.decode::<TestClaims>(&token).is_ok());
+
+        let jwks = key_ring.jwks();
+        assert_eq!(1, jwks.keys.len());
+        assert_eq!("EC", jwks.keys[0].kty);
+        assert_eq!("es", jwks.keys[0].kid);
+        assert_eq!(Some("P-256".to_owned()), jwks.keys[0].crv);
+
+        // Anyone holding the published key can verify the token.
+        let decoding_key = jwks.keys[0].decoding_key().unwrap();
+        assert!(
+            decode::<TestClaims>(&token, &decoding_key, &Validation::new(Algorithm::ES256)).is_ok()
+        );
+    }
+
+    #[test]
+    fn test_eddsa_key() {
+        let der = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new()).unwrap();
+        let key = Key::from_pem("ed", Algorithm::EdDSA, &pkcs8_pem(der.as_ref())).unwrap();
+        let key_ring = KeyRing::new(
+            "ed".to_owned(),
+            chrono::Duration::hours(1),
+            vec![key, hmac_key("hs", "secret")],
+        )
+        .unwrap();
+        let token = key_ring.encode(&claims()).unwrap();
+        assert!(key_ring.decode::<TestClaims>(&token).is_ok());
+
+        // HMAC secrets are never published.
+        let jwks = key_ring.jwks();
+        assert_eq!(1, jwks.keys.len());
+        assert_eq!("OKP", jwks.keys[0].kty);
+        assert_eq!(Some("Ed25519".to_owned()), jwks.keys[0].crv);
+        let decoding_key = jwks.keys[0].decoding_key().unwrap();
+        assert!(
+            decode::<TestClaims>(&token, &decoding_key, &Validation::new(Algorithm::EdDSA)).is_ok()
+        );
+    }
+}
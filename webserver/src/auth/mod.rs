@@ -0,0 +1,231 @@
+pub mod audit;
+pub mod authorization;
+pub mod key_ring;
+pub mod oidc;
+pub mod password_policy;
+pub mod password_strength;
+pub mod rate_limit;
+pub mod totp;
+
+use crate::auth::key_ring::KeyRing;
+use crate::domains::entities::audit_event::RequestMetadata;
+use crate::domains::entities::personal_access_token::Scope;
+use crate::domains::entities::user::Role;
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::ApplicationResult;
+use crate::State;
+use chrono::Utc;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tide::http::headers::HeaderValues;
+use tide::{Body, Request, Response};
+
+/// Our claims struct, it needs to derive `Serialize` and/or `Deserialize`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Claims {
+    pub sub: String,
+    pub exp: usize,
+    /// Refresh token family the access token was issued for.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fam: Option<String>,
+    /// Session the access token was issued for, checked on each request.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sid: Option<i32>,
+    /// Role of the user when the token was issued, tokens without one are of a plain user.
+    #[serde(default)]
+    pub role: Role,
+}
+
+/// The authenticated caller of a request.
+#[derive(Debug, Clone)]
+pub struct Principal {
+    pub user_id: i32,
+    pub family: Option<String>,
+    /// Session of a login, `None` for a personal access token.
+    pub session_id: Option<i32>,
+    /// Granted scopes of a personal access token, `None` for a login session with full access.
+    pub scopes: Option<Vec<Scope>>,
+    /// Personal access tokens always act as a plain user.
+    pub role: Role,
+}
+
+impl Principal {
+    pub fn has_scope(&self, scope: Scope) -> bool {
+        match self.scopes.as_ref() {
+            Some(scopes) => scopes.contains(&scope),
+            None => true,
+        }
+    }
+
+    pub fn is_session(&self) -> bool {
+        self.scopes.is_none()
+    }
+}
+
+const BEARER: &str = "Bearer";
+/// Lets `authenticate` tell personal access tokens from JWTs.
+pub const PERSONAL_ACCESS_TOKEN_PREFIX: &str = "rsapps_pat_";
+
+pub fn create_jwt(
+    key_ring: &KeyRing,
+    uid: i32,
+    family: Option<String>,
+    session_id: Option<i32>,
+    role: Role,
+) -> ApplicationResult<String> {
+    let expiration = Utc::now()
+        .checked_add_signed(chrono::Duration::hours(1))
+        .expect("valid timestamp")
+        .timestamp();
+    let claims = Claims {
+        sub: uid.to_string(),
+        exp: expiration as usize,
+        fam: family,
+        sid: session_id,
+        role,
+    };
+    key_ring.encode(&claims)
+}
+
+fn get_bearer_token(headers: &HeaderValues) -> ApplicationResult<&str> {
+    let auth_header = match headers.get(0) {
+        Some(v) => v.as_str(),
+        None => {
+            return Err(ApplicationError {
+                code: ErrorCode::NoAuthHeaderError,
+                message: "auth header is invalid".to_owned(),
+            })
+        }
+    };
+    if !auth_header.starts_with(BEARER) {
+        return Err(ApplicationError {
+            code: ErrorCode::NoAuthHeaderError,
+            message: "auth header is invalid".to_owned(),
+        });
+    }
+    Ok(auth_header.trim_start_matches(BEARER).trim())
+}
+
+/// Resolve the caller from the authorization header, which carries either a JWT
+/// or a personal access token.
+/// A request without the header is anonymous, but a malformed or invalid token is rejected.
+pub async fn authenticate(
+    state: &State,
+    headers: Option<&HeaderValues>,
+    metadata: &RequestMetadata,
+) -> ApplicationResult<Option<Principal>> {
+    let token = match headers {
+        Some(headers) => get_bearer_token(headers)?,
+        None => return Ok(None),
+    };
+    if token.starts_with(PERSONAL_ACCESS_TOKEN_PREFIX) {
+        let principal = state
+            .personal_access_token_service
+            .authenticate(token)
+            .await?;
+        return Ok(Some(principal));
+    }
+    let claims = state.key_ring.decode::<Claims>(token)?;
+    let user_id = match claims.sub.parse::<i32>() {
+        Ok(user_id) => user_id,
+        Err(_) => {
+            return Err(ApplicationError {
+                code: ErrorCode::UnAuthenticated,
+                message: format!("token subject is invalid, sub: {}", claims.sub),
+            })
+        }
+    };
+    // Tokens issued before sessions were recorded only carry their family,
+    // and those without either can't be revoked when the user is suspended.
+    match (claims.sid, claims.fam.as_ref()) {
+        (Some(session_id), _) => {
+            state
+                .token_service
+                .ensure_session_active(session_id, user_id, metadata)
+                .await?
+        }
+        (None, Some(family)) => {
+            state
+                .token_service
+                .ensure_family_active(family.to_owned())
+                .await?
+        }
+        (None, None) => {
+            return Err(ApplicationError {
+                code: ErrorCode::UnAuthenticated,
+                message: "token has no session".to_owned(),
+            })
+        }
+    }
+    Ok(Some(Principal {
+        user_id,
+        family: claims.fam,
+        session_id: claims.sid,
+        scopes: None,
+        role: claims.role,
+    }))
+}
+
+/// Generate an opaque random token, hex encoded.
+pub fn generate_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    hex::encode(bytes)
+}
+
+/// Opaque tokens are only stored as their SHA-256 digest.
+pub fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Publish the public signing keys so other services can verify our tokens.
+pub async fn handle_jwks(request: Request<State>) -> tide::Result<impl Into<Response>> {
+    Ok(Response::builder(200)
+        .body(Body::from_json(&request.state().key_ring.jwks())?)
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generate_token_is_unique() {
+        let token = generate_token();
+        assert_eq!(64, token.len());
+        assert_ne!(token, generate_token());
+    }
+
+    #[test]
+    fn test_hash_token() {
+        let token = generate_token();
+        assert_eq!(hash_token(&token), hash_token(&token));
+        assert_ne!(token, hash_token(&token));
+        assert_eq!(64, hash_token(&token).len());
+    }
+
+    #[test]
+    fn test_principal_scopes() {
+        let session = Principal {
+            user_id: 1,
+            family: None,
+            session_id: None,
+            scopes: None,
+            role: Role::User,
+        };
+        assert!(session.has_scope(Scope::TodosWrite));
+        assert!(session.is_session());
+
+        let token = Principal {
+            user_id: 1,
+            family: None,
+            session_id: None,
+            scopes: Some(vec![Scope::TodosRead]),
+            role: Role::User,
+        };
+        assert!(token.has_scope(Scope::TodosRead));
+        assert!(!token.has_scope(Scope::TodosWrite));
+        assert!(!token.is_session());
+    }
+}
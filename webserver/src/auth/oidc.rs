@@ -0,0 +1,634 @@
+use crate::auth::audit::request_metadata;
+use crate::auth::generate_token;
+use crate::auth::key_ring::{Jwk, JwkSet};
+use crate::domains::entities::audit_event::{AuditEvent, AuditEventType};
+use crate::domains::entities::login_result::LoginResult;
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::ApplicationResult;
+use crate::State;
+use anyhow::{anyhow, bail, Context};
+use async_std::sync::RwLock;
+use jsonwebtoken::{decode, decode_header, Algorithm, Validation};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::env;
+use std::fmt::Display;
+use std::fs;
+use std::sync::Arc;
+use tide::http::cookies::{Cookie, SameSite};
+use tide::http::headers::ACCEPT;
+use tide::http::Url;
+use tide::{Body, Redirect, Request, Response, StatusCode};
+
+/// Cookie which carries the login state from the redirect to the provider until its callback.
+const LOGIN_STATE_COOKIE: &str = "rsapps_oidc";
+const LOGIN_STATE_MINUTES: i64 = 10;
+
+/// Provider file referenced by `OIDC_PROVIDERS_FILE`.
+#[derive(Deserialize)]
+struct OidcConfig {
+    /// Frontend page which receives our tokens in the URL fragment after a login.
+    redirect_url: String,
+    providers: Vec<ProviderConfig>,
+}
+
+#[derive(Deserialize)]
+struct ProviderConfig {
+    name: String,
+    issuer: String,
+    client_id: String,
+    client_secret: Option<String>,
+    /// Our callback `/auth/<name>/callback`, as registered with the provider.
+    redirect_uri: String,
+    #[serde(default)]
+    scopes: Vec<String>,
+    /// Endpoints are discovered from the issuer unless all of them are configured.
+    authorization_endpoint: Option<String>,
+    token_endpoint: Option<String>,
+    jwks_uri: Option<String>,
+}
+
+/// The part of the OpenID Provider Metadata we need.
+#[derive(Deserialize)]
+struct Discovery {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+}
+
+#[derive(Serialize)]
+struct TokenRequest<'a> {
+    grant_type: &'a str,
+    code: &'a str,
+    redirect_uri: &'a str,
+    client_id: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_secret: Option<&'a str>,
+    code_verifier: &'a str,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: Option<String>,
+}
+
+/// Claims of an ID token we use to sign in, `iss`, `aud` and `exp` are checked on decoding.
+#[derive(Debug, Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub nonce: Option<String>,
+    pub email: Option<String>,
+    pub preferred_username: Option<String>,
+    pub name: Option<String>,
+}
+
+/// Login state, signed by our key ring so the callback can trust it.
+#[derive(Serialize, Deserialize)]
+struct LoginState {
+    provider: String,
+    state: String,
+    nonce: String,
+    verifier: String,
+    exp: usize,
+}
+
+#[derive(Deserialize)]
+struct CallbackQuery {
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+}
+
+/// An OpenID Connect provider we accept logins from with the authorization code flow and PKCE.
+#[derive(Clone)]
+pub struct OidcProvider {
+    pub name: String,
+    issuer: String,
+    client_id: String,
+    client_secret: Option<String>,
+    redirect_uri: String,
+    scopes: Vec<String>,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    /// Signing keys of the provider, fetched again when a token names an unknown key.
+    jwks: Arc<RwLock<JwkSet>>,
+}
+
+#[derive(Clone)]
+pub struct OidcProviders {
+    redirect_url: String,
+    providers: Vec<OidcProvider>,
+}
+
+impl OidcProviders {
+    /// Load the providers from `OIDC_PROVIDERS_FILE`, without it only password logins are enabled.
+    pub async fn from_env() -> anyhow::Result<Self> {
+        match env::var("OIDC_PROVIDERS_FILE") {
+            Ok(path) if !path.is_empty() => Self::from_file(&path).await,
+            _ => Ok(Self {
+                redirect_url: "/".to_owned(),
+                providers: vec![],
+            }),
+        }
+    }
+
+    pub async fn from_file(path: &str) -> anyhow::Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("failed to read OIDC providers file {}", path))?;
+        let config: OidcConfig = serde_json::from_str(&content)
+            .with_context(|| format!("failed to parse OIDC providers file {}", path))?;
+        Url::parse(&config.redirect_url)
+            .with_context(|| format!("invalid redirect_url {}", config.redirect_url))?;
+        let mut providers: Vec<OidcProvider> = vec![];
+        for provider in config.providers {
+            if providers.iter().any(|other| other.name == provider.name) {
+                bail!("provider {} is duplicated", provider.name);
+            }
+            providers.push(OidcProvider::load(provider).await?);
+        }
+        Ok(Self {
+            redirect_url: config.redirect_url,
+            providers,
+        })
+    }
+
+    pub fn names(&self) -> Vec<String> {
+        self.providers
+            .iter()
+            .map(|provider| provider.name.to_owned())
+            .collect()
+    }
+
+    pub fn get(&self, name: &str) -> ApplicationResult<&OidcProvider> {
+        match self.providers.iter().find(|provider| provider.name == name) {
+            Some(provider) => Ok(provider),
+            None => Err(ApplicationError {
+                code: ErrorCode::NotFound,
+                message: format!("login provider {} is not configured", name),
+            }),
+        }
+    }
+}
+
+impl OidcProvider {
+    async fn load(config: ProviderConfig) -> anyhow::Result<Self> {
+        let (authorization_endpoint, token_endpoint, jwks_uri) = match (
+            config.authorization_endpoint,
+            config.token_endpoint,
+            config.jwks_uri,
+        ) {
+            (Some(authorization_endpoint), Some(token_endpoint), Some(jwks_uri)) => {
+                (authorization_endpoint, token_endpoint, jwks_uri)
+            }
+            _ => {
+                let discovery = discover(&config.issuer).await?;
+                (
+                    discovery.authorization_endpoint,
+                    discovery.token_endpoint,
+                    discovery.jwks_uri,
+                )
+            }
+        };
+        Url::parse(&authorization_endpoint).with_context(|| {
+            format!("invalid authorization endpoint of provider {}", config.name)
+        })?;
+        let mut scopes = config.scopes;
+        if !scopes.iter().any(|scope| scope == "openid") {
+            scopes.insert(0, "openid".to_owned());
+        }
+        Ok(Self {
+            name: config.name,
+            issuer: config.issuer,
+            client_id: config.client_id,
+            client_secret: config.client_secret,
+            redirect_uri: config.redirect_uri,
+            scopes,
+            authorization_endpoint,
+            token_endpoint,
+            jwks_uri,
+            jwks: Arc::new(RwLock::new(JwkSet { keys: vec![] })),
+        })
+    }
+
+    /// Where to send the browser to log in, with an S256 PKCE challenge of the verifier.
+    pub fn authorization_url(&self, state: &str, nonce: &str, verifier: &str) -> Url {
+        let mut url = Url::parse(&self.authorization_endpoint).expect("validated on load");
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.client_id)
+            .append_pair("redirect_uri", &self.redirect_uri)
+            .append_pair("scope", &self.scopes.join(" "))
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", &pkce_challenge(verifier))
+            .append_pair("code_challenge_method", "S256");
+        url
+    }
+
+    /// Redeem the authorization code at the token endpoint, returns the ID token.
+    pub async fn exchange_code(&self, code: &str, verifier: &str) -> ApplicationResult<String> {
+        let form = TokenRequest {
+            grant_type: "authorization_code",
+            code,
+            redirect_uri: &self.redirect_uri,
+            client_id: &self.client_id,
+            client_secret: self.client_secret.as_deref(),
+            code_verifier: verifier,
+        };
+        let body = Body::from_form(&form).map_err(|err| provider_error(&self.name, err))?;
+        let mut response = surf::post(&self.token_endpoint)
+            .header(ACCEPT, "application/json")
+            .body(body)
+            .await
+            .map_err(|err| provider_error(&self.name, err))?;
+        if !response.status().is_success() {
+            return Err(ApplicationError {
+                code: ErrorCode::UnAuthenticated,
+                message: format!(
+                    "provider {} rejected the authorization code, status: {}",
+                    self.name,
+                    response.status()
+                ),
+            });
+        }
+        let tokens: TokenResponse = response
+            .body_json()
+            .await
+            .map_err(|err| provider_error(&self.name, err))?;
+        match tokens.id_token {
+            Some(id_token) => Ok(id_token),
+            None => Err(ApplicationError {
+                code: ErrorCode::UnAuthenticated,
+                message: format!("provider {} didn't return an ID token", self.name),
+            }),
+        }
+    }
+
+    /// Verify the signature, issuer, audience, expiry and nonce of an ID token.
+    pub async fn verify_id_token(
+        &self,
+        id_token: &str,
+        nonce: &str,
+    ) -> ApplicationResult<IdTokenClaims> {
+        let header = decode_header(id_token).map_err(invalid_id_token)?;
+        match header.alg {
+            Algorithm::RS256
+            | Algorithm::RS384
+            | Algorithm::RS512
+            | Algorithm::PS256
+            | Algorithm::PS384
+            | Algorithm::PS512
+            | Algorithm::ES256
+            | Algorithm::EdDSA => {}
+            alg => {
+                return Err(ApplicationError {
+                    code: ErrorCode::UnAuthenticated,
+                    message: format!("ID token algorithm {:?} is not allowed", alg),
+                })
+            }
+        }
+        let jwk = self.find_jwk(header.kid.as_deref()).await?;
+        let decoding_key = jwk.decoding_key().map_err(|err| ApplicationError {
+            code: ErrorCode::UnAuthenticated,
+            message: format!(
+                "signing key of provider {} is invalid, err: {:}",
+                self.name, err
+            ),
+        })?;
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&self.issuer]);
+        validation.set_audience(&[&self.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        let claims = decode::<IdTokenClaims>(id_token, &decoding_key, &validation)
+            .map_err(invalid_id_token)?
+            .claims;
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(ApplicationError {
+                code: ErrorCode::UnAuthenticated,
+                message: "ID token nonce doesn't match".to_owned(),
+            });
+        }
+        Ok(claims)
+    }
+
+    /// Look the key up in the cached key set, refetching it once in case the provider rotated keys.
+    async fn find_jwk(&self, kid: Option<&str>) -> ApplicationResult<Jwk> {
+        if let Some(jwk) = select_jwk(&*self.jwks.read().await, kid) {
+            return Ok(jwk);
+        }
+        let jwks: JwkSet = surf::get(&self.jwks_uri)
+            .recv_json()
+            .await
+            .map_err(|err| provider_error(&self.name, err))?;
+        let jwk = select_jwk(&jwks, kid);
+        *self.jwks.write().await = jwks;
+        match jwk {
+            Some(jwk) => Ok(jwk),
+            None => Err(ApplicationError {
+                code: ErrorCode::UnAuthenticated,
+                message: format!("ID token is signed by an unknown key of {}", self.name),
+            }),
+        }
+    }
+}
+
+/// A token without `kid` is only accepted from a provider which publishes a single key.
+fn select_jwk(jwks: &JwkSet, kid: Option<&str>) -> Option<Jwk> {
+    match kid {
+        Some(kid) => jwks.keys.iter().find(|jwk| jwk.kid == kid).cloned(),
+        None if jwks.keys.len() == 1 => Some(jwks.keys[0].clone()),
+        None => None,
+    }
+}
+
+async fn discover(issuer: &str) -> anyhow::Result<Discovery> {
+    let url = format!(
+        "{}/.well-known/openid-configuration",
+        issuer.trim_end_matches('/')
+    );
+    let discovery: Discovery = surf::get(&url)
+        .recv_json()
+        .await
+        .map_err(|err| anyhow!("failed to discover {}, error: {}", url, err))?;
+    if discovery.issuer != issuer {
+        bail!(
+            "issuer {} doesn't match the discovered issuer {}",
+            issuer,
+            discovery.issuer
+        );
+    }
+    Ok(discovery)
+}
+
+/// S256 code challenge of a PKCE code verifier (RFC 7636).
+pub fn pkce_challenge(verifier: &str) -> String {
+    base64::encode_config(Sha256::digest(verifier.as_bytes()), base64::URL_SAFE_NO_PAD)
+}
+
+fn provider_error(name: &str, err: impl Display) -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::SystemError,
+        message: format!("failed to call provider {}, error: {:}", name, err),
+    }
+}
+
+fn invalid_id_token(err: jsonwebtoken::errors::Error) -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::UnAuthenticated,
+        message: format!("ID token is invalid, err: {:}", err),
+    }
+}
+
+/// Start a login by redirecting to the provider.
+pub async fn handle_oidc_login(request: Request<State>) -> tide::Result<impl Into<Response>> {
+    let state = request.state();
+    let provider = match state.oidc_providers.get(request.param("provider")?) {
+        Ok(provider) => provider,
+        Err(err) => {
+            return Ok(Response::builder(StatusCode::NotFound)
+                .body(Body::from_json(&err)?)
+                .build())
+        }
+    };
+    let login_state = LoginState {
+        provider: provider.name.to_owned(),
+        state: generate_token(),
+        nonce: generate_token(),
+        verifier: generate_token(),
+        exp: (chrono::Utc::now() + chrono::Duration::minutes(LOGIN_STATE_MINUTES)).timestamp()
+            as usize,
+    };
+    let cookie = match state.key_ring.encode(&login_state) {
+        Ok(cookie) => cookie,
+        Err(err) => {
+            return Ok(Response::builder(StatusCode::InternalServerError)
+                .body(Body::from_json(&err)?)
+                .build())
+        }
+    };
+    let url = provider.authorization_url(
+        &login_state.state,
+        &login_state.nonce,
+        &login_state.verifier,
+    );
+    let mut response: Response = Redirect::new(url.as_str()).into();
+    // Lax, the callback is a top level navigation coming from the provider.
+    response.insert_cookie(
+        Cookie::build(LOGIN_STATE_COOKIE, cookie)
+            .path("/auth")
+            .http_only(true)
+            .secure(provider.redirect_uri.starts_with("https://"))
+            .same_site(SameSite::Lax)
+            .finish(),
+    );
+    Ok(response)
+}
+
+/// Finish a login and hand our tokens to the frontend in the URL fragment,
+/// which browsers never send to a server.
+pub async fn handle_oidc_callback(request: Request<State>) -> tide::Result<impl Into<Response>> {
+    let result = oidc_callback(&request).await;
+    audit_oidc_login(&request, &result).await;
+    let fragment = match result {
+        Ok((
+            _,
+            LoginResult {
+                tokens: Some(tokens),
+                ..
+            },
+        )) => format!(
+            "access_token={}&refresh_token={}",
+            tokens.access_token, tokens.refresh_token
+        ),
+        // The frontend asks for the second factor like after a password login.
+        Ok((
+            _,
+            LoginResult {
+                challenge_token: Some(challenge_token),
+                ..
+            },
+        )) => format!("challenge_token={}", challenge_token),
+        Ok(_) => format!("error={}", ErrorCode::SystemError.as_ref()),
+        Err(err) => {
+            println!("failed to log in with OIDC, err: {:}", err);
+            format!("error={}", err.code.as_ref())
+        }
+    };
+    let mut url = Url::parse(&request.state().oidc_providers.redirect_url)?;
+    url.set_fragment(Some(&fragment));
+    let mut response: Response = Redirect::new(url.as_str()).into();
+    response.remove_cookie(Cookie::build(LOGIN_STATE_COOKIE, "").path("/auth").finish());
+    Ok(response)
+}
+
+/// Record the login like a password one, failures with the provider as the account.
+async fn audit_oidc_login(
+    request: &Request<State>,
+    result: &ApplicationResult<(i32, LoginResult)>,
+) {
+    let provider = request.param("provider").unwrap_or_default().to_owned();
+    let metadata = request_metadata(request);
+    let audit_event = match result {
+        Ok((user_id, login_result)) => AuditEvent {
+            user_id: Some(*user_id),
+            detail: Some(format!("oidc:{}", provider)),
+            ..AuditEvent::new(
+                match login_result.tokens {
+                    Some(_) => AuditEventType::LoginSucceeded,
+                    None => AuditEventType::SecondFactorChallenged,
+                },
+                &metadata,
+            )
+        },
+        Err(err) => AuditEvent {
+            account: Some(provider),
+            detail: Some(err.code.as_ref().to_owned()),
+            ..AuditEvent::new(AuditEventType::LoginFailed, &metadata)
+        },
+    };
+    request.state().audit_logger.record(audit_event).await;
+}
+
+/// Returns the id of the user who logged in with the result of the login.
+async fn oidc_callback(request: &Request<State>) -> ApplicationResult<(i32, LoginResult)> {
+    let state = request.state();
+    let query: CallbackQuery = request.query().map_err(|err| ApplicationError {
+        code: ErrorCode::BadRequest,
+        message: format!("callback query is invalid, err: {:}", err),
+    })?;
+    if let Some(error) = query.error {
+        return Err(ApplicationError {
+            code: ErrorCode::UnAuthenticated,
+            message: format!("provider refused the login, error: {}", error),
+        });
+    }
+    let login_state = match request.cookie(LOGIN_STATE_COOKIE) {
+        Some(cookie) => state.key_ring.decode::<LoginState>(cookie.value())?,
+        None => {
+            return Err(ApplicationError {
+                code: ErrorCode::UnAuthenticated,
+                message: "login state cookie is missing".to_owned(),
+            })
+        }
+    };
+    let provider = state.oidc_providers.get(&login_state.provider)?;
+    if request.param("provider").ok() != Some(provider.name.as_str())
+        || query.state.as_deref() != Some(login_state.state.as_str())
+    {
+        return Err(ApplicationError {
+            code: ErrorCode::UnAuthenticated,
+            message: "login state doesn't match".to_owned(),
+        });
+    }
+    let code = match query.code {
+        Some(code) => code,
+        None => {
+            return Err(ApplicationError {
+                code: ErrorCode::BadRequest,
+                message: "authorization code is missing".to_owned(),
+            })
+        }
+    };
+    let id_token = provider.exchange_code(&code, &login_state.verifier).await?;
+    let claims = provider
+        .verify_id_token(&id_token, &login_state.nonce)
+        .await?;
+    let username_hint = claims
+        .preferred_username
+        .or(claims.name)
+        .or_else(|| claims.email.to_owned());
+    let user = state
+        .identity_service
+        .sign_in(
+            provider.name.to_owned(),
+            claims.sub,
+            claims.email,
+            username_hint,
+        )
+        .await?;
+    let login_result = state
+        .two_factor_service
+        .start_login(user.id, &request_metadata(request))
+        .await?;
+    Ok((user.id, login_result))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn provider() -> OidcProvider {
+        OidcProvider {
+            name: "idp".to_owned(),
+            issuer: "https://idp.example.com".to_owned(),
+            client_id: "client".to_owned(),
+            client_secret: None,
+            redirect_uri: "https://api.example.com/auth/idp/callback".to_owned(),
+            scopes: vec!["openid".to_owned(), "email".to_owned()],
+            authorization_endpoint: "https://idp.example.com/authorize?prompt=login".to_owned(),
+            token_endpoint: "https://idp.example.com/token".to_owned(),
+            jwks_uri: "https://idp.example.com/jwks".to_owned(),
+            jwks: Arc::new(RwLock::new(JwkSet { keys: vec![] })),
+        }
+    }
+
+    #[test]
+    fn test_pkce_challenge() {
+        // Appendix B of RFC 7636.
+        assert_eq!(
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM",
+            pkce_challenge("dBjftJeZ4CVP-1B0ZlMHRlWhjfQBdkgHpmUqMXWlvDU")
+        );
+    }
+
+    #[test]
+    fn test_authorization_url() {
+        let url = provider().authorization_url("state", "nonce", "verifier");
+        let query: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        let param = |name: &str| {
+            query
+                .iter()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.as_str())
+        };
+        assert_eq!(Some("login"), param("prompt"));
+        assert_eq!(Some("code"), param("response_type"));
+        assert_eq!(Some("client"), param("client_id"));
+        assert_eq!(Some("openid email"), param("scope"));
+        assert_eq!(Some("state"), param("state"));
+        assert_eq!(Some("nonce"), param("nonce"));
+        assert_eq!(Some("S256"), param("code_challenge_method"));
+        assert_eq!(
+            Some(pkce_challenge("verifier").as_str()),
+            param("code_challenge")
+        );
+    }
+
+    #[test]
+    fn test_select_jwk() {
+        let jwk = |kid: &str| Jwk {
+            kty: "RSA".to_owned(),
+            kid: kid.to_owned(),
+            alg: "RS256".to_owned(),
+            usage: "sig".to_owned(),
+            n: None,
+            e: None,
+            crv: None,
+            x: None,
+            y: None,
+        };
+        let single = JwkSet {
+            keys: vec![jwk("a")],
+        };
+        assert_eq!("a", select_jwk(&single, None).unwrap().kid);
+        assert!(select_jwk(&single, Some("b")).is_none());
+
+        let rotated = JwkSet {
+            keys: vec![jwk("a"), jwk("b")],
+        };
+        assert_eq!("b", select_jwk(&rotated, Some("b")).unwrap().kid);
+        assert!(select_jwk(&rotated, None).is_none());
+    }
+}
@@ -0,0 +1,151 @@
+use crate::auth::password_strength;
+use crate::domains::errors::{ApplicationError, ErrorCode, Violation};
+use crate::domains::ApplicationResult;
+use anyhow::Context;
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+
+const DEFAULT_MIN_LENGTH: usize = 8;
+const DEFAULT_MIN_SCORE: u8 = 3;
+
+/// Rules a new password must follow.
+pub struct PasswordPolicy {
+    min_length: usize,
+    /// The least `password_strength` score, from 0 to 4.
+    min_score: u8,
+    /// Lower-cased passwords known from breaches.
+    breached_passwords: HashSet<String>,
+}
+
+impl PasswordPolicy {
+    pub fn new(min_length: usize, min_score: u8, breached_passwords: HashSet<String>) -> Self {
+        Self {
+            min_length,
+            min_score,
+            breached_passwords: breached_passwords
+                .into_iter()
+                .map(|password| password.to_lowercase())
+                .collect(),
+        }
+    }
+
+    /// `PASSWORD_MIN_LENGTH` and `PASSWORD_MIN_SCORE`, with breached passwords
+    /// listed one per line in `BREACHED_PASSWORDS_FILE`.
+    pub fn from_env() -> anyhow::Result<Self> {
+        let min_length = match env::var("PASSWORD_MIN_LENGTH") {
+            Ok(value) if !value.is_empty() => value
+                .parse()
+                .with_context(|| format!("invalid PASSWORD_MIN_LENGTH {}", value))?,
+            _ => DEFAULT_MIN_LENGTH,
+        };
+        let min_score = match env::var("PASSWORD_MIN_SCORE") {
+            Ok(value) if !value.is_empty() => value
+                .parse()
+                .with_context(|| format!("invalid PASSWORD_MIN_SCORE {}", value))?,
+            _ => DEFAULT_MIN_SCORE,
+        };
+        let breached_passwords = match env::var("BREACHED_PASSWORDS_FILE") {
+            Ok(path) if !path.is_empty() => fs::read_to_string(&path)
+                .with_context(|| format!("failed to read breached passwords file {}", path))?
+                .lines()
+                .map(|line| line.trim().to_owned())
+                .filter(|line| !line.is_empty())
+                .collect(),
+            _ => HashSet::new(),
+        };
+        Ok(Self::new(min_length, min_score, breached_passwords))
+    }
+
+    /// Every rule the password breaks, `user_inputs` are the username or email it shouldn't be built on.
+    pub fn violations(&self, password: &str, user_inputs: &[&str]) -> Vec<Violation> {
+        let mut violations = vec![];
+        if password.chars().count() < self.min_length {
+            violations.push(violation(
+                "minLength",
+                format!("password must be at least {} characters", self.min_length),
+            ));
+        }
+        if self
+            .breached_passwords
+            .contains(&password.trim().to_lowercase())
+        {
+            violations.push(violation(
+                "breached",
+                "password has appeared in a data breach".to_owned(),
+            ));
+        }
+        let strength = password_strength::estimate(password, user_inputs);
+        if strength.score < self.min_score {
+            violations.push(violation(
+                "strength",
+                match strength.warning {
+                    Some(warning) => format!("password is too easy to guess, {}", warning),
+                    None => "password is too easy to guess".to_owned(),
+                },
+            ));
+        }
+        violations
+    }
+
+    pub fn validate(&self, password: &str, user_inputs: &[&str]) -> ApplicationResult<()> {
+        let violations = self.violations(password, user_inputs);
+        if violations.is_empty() {
+            return Ok(());
+        }
+        Err(ApplicationError {
+            code: ErrorCode::ValidationFailed(violations),
+            message: "password doesn't follow the password policy".to_owned(),
+        })
+    }
+}
+
+fn violation(rule: &str, message: String) -> Violation {
+    Violation {
+        field: "password".to_owned(),
+        rule: rule.to_owned(),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules(violations: Vec<Violation>) -> Vec<String> {
+        violations
+            .into_iter()
+            .map(|violation| violation.rule)
+            .collect()
+    }
+
+    #[test]
+    fn test_violations() {
+        let breached = vec!["Kettle-Drift-Amber".to_owned()].into_iter().collect();
+        let policy = PasswordPolicy::new(8, 3, breached);
+        assert_eq!(
+            vec!["minLength", "strength"],
+            rules(policy.violations("", &[]))
+        );
+        assert_eq!(vec!["strength"], rules(policy.violations("password", &[])));
+        assert_eq!(
+            vec!["breached"],
+            rules(policy.violations("kettle-drift-amber", &[]))
+        );
+        assert_eq!(
+            vec!["strength"],
+            rules(policy.violations("violetcrane", &["violetcrane"]))
+        );
+        assert!(policy.violations("violet crane 48 harbor", &[]).is_empty());
+    }
+
+    #[test]
+    fn test_validate() {
+        let policy = PasswordPolicy::new(8, 3, HashSet::new());
+        assert!(policy.validate("violet crane 48 harbor", &[]).is_ok());
+        match policy.validate("abc", &[]).err().unwrap().code {
+            ErrorCode::ValidationFailed(violations) => assert_eq!(2, violations.len()),
+            code => panic!("unexpected code {:?}", code),
+        }
+    }
+}
@@ -0,0 +1,449 @@
+//! Password strength estimation in the manner of zxcvbn: the password is split into the patterns
+//! a guesser tries first, like common passwords, sequences and keyboard rows,
+//! and the score follows the guesses which the cheapest split needs.
+
+/// Ranked by how common they are, the rank is the number of guesses.
+const COMMON_PASSWORDS: &[&str] = &[
+    "123456",
+    "password",
+    "12345678",
+    "qwerty",
+    "123456789",
+    "12345",
+    "1234",
+    "111111",
+    "1234567",
+    "dragon",
+    "123123",
+    "baseball",
+    "abc123",
+    "football",
+    "monkey",
+    "letmein",
+    "696969",
+    "shadow",
+    "master",
+    "666666",
+    "qwertyuiop",
+    "123321",
+    "mustang",
+    "1234567890",
+    "michael",
+    "654321",
+    "superman",
+    "1qaz2wsx",
+    "7777777",
+    "121212",
+    "000000",
+    "qazwsx",
+    "123qwe",
+    "killer",
+    "trustno1",
+    "jordan",
+    "jennifer",
+    "zxcvbnm",
+    "asdfgh",
+    "hunter",
+    "buster",
+    "soccer",
+    "harley",
+    "batman",
+    "andrew",
+    "tigger",
+    "sunshine",
+    "iloveyou",
+    "2000",
+    "charlie",
+    "robert",
+    "thomas",
+    "hockey",
+    "ranger",
+    "daniel",
+    "starwars",
+    "klaster",
+    "112233",
+    "george",
+    "computer",
+    "michelle",
+    "jessica",
+    "pepper",
+    "1111",
+    "zxcvbn",
+    "555555",
+    "11111111",
+    "131313",
+    "freedom",
+    "777777",
+    "pass",
+    "maggie",
+    "159753",
+    "aaaaaa",
+    "ginger",
+    "princess",
+    "joshua",
+    "cheese",
+    "amanda",
+    "summer",
+    "love",
+    "ashley",
+    "nicole",
+    "chelsea",
+    "biteme",
+    "matthew",
+    "access",
+    "yankees",
+    "987654321",
+    "dallas",
+    "austin",
+    "thunder",
+    "taylor",
+    "matrix",
+    "admin",
+    "welcome",
+    "secret",
+    "login",
+    "hello",
+    "flower",
+    "passw0rd",
+    "qwerty123",
+    "changeme",
+    "default",
+];
+const KEYBOARD_ROWS: &[&str] = &["1234567890", "qwertyuiop", "asdfghjkl", "zxcvbnm"];
+/// Guesses below each threshold get the score of its index.
+const SCORE_THRESHOLDS: [f64; 4] = [1e3, 1e6, 1e8, 1e10];
+/// Guesses of a user input, like the username, which a guesser knows.
+const USER_INPUT_GUESSES: f64 = 1.0;
+const MIN_PATTERN_LENGTH: usize = 3;
+/// Only the start of a longer password is estimated, which keeps the search quick.
+const MAX_ESTIMATED_LENGTH: usize = 64;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Pattern {
+    UserInput,
+    Dictionary,
+    Sequence,
+    Keyboard,
+    Repeat,
+    BruteForce,
+}
+
+#[derive(Debug)]
+pub struct Strength {
+    /// From 0, guessed right away, to 4, beyond an offline attack.
+    pub score: u8,
+    /// The pattern which makes the password weak, when it is.
+    pub warning: Option<&'static str>,
+}
+
+struct Match {
+    start: usize,
+    end: usize,
+    guesses: f64,
+    pattern: Pattern,
+}
+
+/// Estimate the strength, `user_inputs` are words the guesser knows about the user.
+pub fn estimate(password: &str, user_inputs: &[&str]) -> Strength {
+    let chars: Vec<char> = password.chars().take(MAX_ESTIMATED_LENGTH).collect();
+    let (guesses, patterns) = minimum_guesses(&chars, user_inputs);
+    let score = SCORE_THRESHOLDS
+        .iter()
+        .position(|threshold| guesses < *threshold)
+        .unwrap_or(SCORE_THRESHOLDS.len()) as u8;
+    let warning = if score < 3 {
+        Some(warning(&patterns))
+    } else {
+        None
+    };
+    Strength { score, warning }
+}
+
+/// The guesses of the cheapest split, and the characters each pattern of it covers.
+fn minimum_guesses(chars: &[char], user_inputs: &[&str]) -> (f64, Vec<(Pattern, usize)>) {
+    let matches = find_matches(chars, user_inputs);
+    // best[i] is the cheapest split of the first i characters, with the match it ends with.
+    let mut best: Vec<(f64, Option<usize>)> = vec![(1.0, None); chars.len() + 1];
+    for end in 1..=chars.len() {
+        best[end] = (best[end - 1].0 * cardinality(chars[end - 1]), None);
+        for (index, m) in matches.iter().enumerate().filter(|(_, m)| m.end == end) {
+            let guesses = best[m.start].0 * m.guesses;
+            if guesses < best[end].0 {
+                best[end] = (guesses, Some(index));
+            }
+        }
+    }
+    let mut patterns = vec![];
+    let mut end = chars.len();
+    while end > 0 {
+        match best[end].1 {
+            Some(index) => {
+                let m = &matches[index];
+                patterns.push((m.pattern, m.end - m.start));
+                end = m.start;
+            }
+            None => {
+                patterns.push((Pattern::BruteForce, 1));
+                end -= 1;
+            }
+        }
+    }
+    (best[chars.len()].0, patterns)
+}
+
+fn find_matches(chars: &[char], user_inputs: &[&str]) -> Vec<Match> {
+    let lower: Vec<char> = chars.iter().map(|c| c.to_ascii_lowercase()).collect();
+    let unleeted: Vec<char> = lower.iter().map(|c| unleet(*c)).collect();
+    let mut matches = vec![];
+    let words = user_inputs
+        .iter()
+        .map(|input| (input.to_lowercase(), USER_INPUT_GUESSES, Pattern::UserInput))
+        .chain(
+            COMMON_PASSWORDS
+                .iter()
+                .enumerate()
+                .map(|(rank, word)| (word.to_string(), (rank + 1) as f64, Pattern::Dictionary)),
+        );
+    for (word, rank, pattern) in words {
+        let word: Vec<char> = word.chars().collect();
+        if word.len() < MIN_PATTERN_LENGTH || word.len() > chars.len() {
+            continue;
+        }
+        for start in 0..=chars.len() - word.len() {
+            let end = start + word.len();
+            let leet = if lower[start..end] == word[..] {
+                1.0
+            } else if unleeted[start..end] == word[..] {
+                2.0
+            } else {
+                continue;
+            };
+            matches.push(Match {
+                start,
+                end,
+                guesses: rank * leet * case_variations(&chars[start..end]),
+                pattern,
+            });
+        }
+    }
+    matches.extend(sequence_matches(&lower));
+    matches.extend(keyboard_matches(&lower));
+    matches.extend(repeat_matches(&lower, user_inputs));
+    matches
+}
+
+/// Runs like `abcd` or `9876`.
+fn sequence_matches(chars: &[char]) -> Vec<Match> {
+    let mut matches = vec![];
+    let mut start = 0;
+    while start + 1 < chars.len() {
+        let delta = chars[start + 1] as i64 - chars[start] as i64;
+        let mut end = start + 2;
+        while end < chars.len() && chars[end] as i64 - chars[end - 1] as i64 == delta {
+            end += 1;
+        }
+        if delta.abs() == 1 && end - start >= MIN_PATTERN_LENGTH {
+            let base = match chars[start] {
+                'a' | 'z' | '0' | '1' | '9' => 4.0,
+                c if c.is_ascii_digit() => 10.0,
+                _ => 26.0,
+            };
+            let descending = if delta < 0 { 2.0 } else { 1.0 };
+            matches.push(Match {
+                start,
+                end,
+                guesses: base * descending * (end - start) as f64,
+                pattern: Pattern::Sequence,
+            });
+            start = end - 1;
+        } else {
+            start += 1;
+        }
+    }
+    matches
+}
+
+/// Runs along a row of the keyboard, either way.
+fn keyboard_matches(chars: &[char]) -> Vec<Match> {
+    let keys: f64 = KEYBOARD_ROWS.iter().map(|row| row.len() as f64).sum();
+    let mut matches = vec![];
+    for row in KEYBOARD_ROWS {
+        for reversed in &[false, true] {
+            let row: Vec<char> = if *reversed {
+                row.chars().rev().collect()
+            } else {
+                row.chars().collect()
+            };
+            let direction = if *reversed { 2.0 } else { 1.0 };
+            let mut start = 0;
+            while start < chars.len() {
+                let mut end = start;
+                if let Some(position) = row.iter().position(|c| *c == chars[start]) {
+                    end += 1;
+                    while end < chars.len()
+                        && position + end - start < row.len()
+                        && row[position + end - start] == chars[end]
+                    {
+                        end += 1;
+                    }
+                }
+                if end - start >= MIN_PATTERN_LENGTH {
+                    matches.push(Match {
+                        start,
+                        end,
+                        guesses: keys * direction * (end - start) as f64,
+                        pattern: Pattern::Keyboard,
+                    });
+                    start = end;
+                } else {
+                    start += 1;
+                }
+            }
+        }
+    }
+    matches
+}
+
+/// Repeats of a character or a substring, like `aaaa` or `abcabc`.
+fn repeat_matches(chars: &[char], user_inputs: &[&str]) -> Vec<Match> {
+    let mut matches = vec![];
+    for start in 0..chars.len() {
+        for unit in 1..=(chars.len() - start) / 2 {
+            let mut count = 1;
+            while start + (count + 1) * unit <= chars.len()
+                && chars[start..start + unit]
+                    == chars[start + count * unit..start + (count + 1) * unit]
+            {
+                count += 1;
+            }
+            if count > 1 && count * unit >= MIN_PATTERN_LENGTH {
+                let (unit_guesses, _) = minimum_guesses(&chars[start..start + unit], user_inputs);
+                matches.push(Match {
+                    start,
+                    end: start + count * unit,
+                    guesses: unit_guesses * count as f64,
+                    pattern: Pattern::Repeat,
+                });
+            }
+        }
+    }
+    matches
+}
+
+/// Characters to try for each one guessed blindly.
+fn cardinality(c: char) -> f64 {
+    if c.is_ascii_digit() {
+        10.0
+    } else if c.is_ascii_alphabetic() {
+        26.0
+    } else if c.is_ascii() {
+        33.0
+    } else {
+        100.0
+    }
+}
+
+/// A capital first letter or all capitals cost one more guess, other mixes cost more.
+fn case_variations(chars: &[char]) -> f64 {
+    let upper = chars.iter().filter(|c| c.is_ascii_uppercase()).count();
+    let lower = chars.iter().filter(|c| c.is_ascii_lowercase()).count();
+    if upper == 0 {
+        1.0
+    } else if lower == 0 || (upper == 1 && chars[0].is_ascii_uppercase()) {
+        2.0
+    } else {
+        2f64.powi(upper.min(lower) as i32 + 1)
+    }
+}
+
+fn unleet(c: char) -> char {
+    match c {
+        '0' => 'o',
+        '1' | '!' => 'i',
+        '3' => 'e',
+        '4' | '@' => 'a',
+        '5' | '$' => 's',
+        '7' => 't',
+        _ => c,
+    }
+}
+
+/// Feedback on the pattern which covers most of the password.
+fn warning(patterns: &[(Pattern, usize)]) -> &'static str {
+    let mut totals: Vec<(Pattern, usize)> = vec![];
+    for (pattern, length) in patterns {
+        match totals.iter_mut().find(|(p, _)| p == pattern) {
+            Some((_, total)) => *total += length,
+            None => totals.push((*pattern, *length)),
+        }
+    }
+    let pattern = totals
+        .iter()
+        .filter(|(pattern, _)| *pattern != Pattern::BruteForce)
+        .max_by_key(|(_, total)| *total)
+        .map(|(pattern, _)| *pattern)
+        .unwrap_or(Pattern::BruteForce);
+    match pattern {
+        Pattern::UserInput => "avoid your username or email in the password",
+        Pattern::Dictionary => "it is similar to a commonly used password",
+        Pattern::Sequence => "sequences like abc or 6543 are easy to guess",
+        Pattern::Keyboard => "rows of keys like qwerty are easy to guess",
+        Pattern::Repeat => "repeats like aaa or abcabc are easy to guess",
+        Pattern::BruteForce => "add a few more words, uncommon ones are better",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_common_passwords() {
+        assert_eq!(0, estimate("password", &[]).score);
+        assert_eq!(0, estimate("Password1", &[]).score);
+        assert_eq!(0, estimate("p@ssw0rd", &[]).score);
+        assert_eq!(
+            Some("it is similar to a commonly used password"),
+            estimate("password", &[]).warning
+        );
+    }
+
+    #[test]
+    fn test_patterns() {
+        let sequence = estimate("abcdefghijkl", &[]);
+        assert!(sequence.score < 2);
+        assert_eq!(
+            Some("sequences like abc or 6543 are easy to guess"),
+            sequence.warning
+        );
+        let keyboard = estimate("asdfghjkl", &[]);
+        assert!(keyboard.score < 2);
+        assert_eq!(
+            Some("rows of keys like qwerty are easy to guess"),
+            keyboard.warning
+        );
+        let repeat = estimate("xyzqxyzqxyzq", &[]);
+        assert!(repeat.score < 3);
+        assert_eq!(
+            Some("repeats like aaa or abcabc are easy to guess"),
+            repeat.warning
+        );
+    }
+
+    #[test]
+    fn test_user_inputs() {
+        assert!(estimate("johndoe1987", &[]).score > estimate("johndoe1987", &["johndoe"]).score);
+        assert_eq!(
+            Some("avoid your username or email in the password"),
+            estimate("johndoe", &["johndoe"]).warning
+        );
+    }
+
+    #[test]
+    fn test_strong_passwords() {
+        let strength = estimate("violet crane 48 harbor", &[]);
+        assert_eq!(4, strength.score);
+        assert_eq!(None, strength.warning);
+        assert!(estimate("Zq7#vR2!mK9p", &[]).score >= 3);
+    }
+}
@@ -0,0 +1,87 @@
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::State;
+use std::env;
+use std::net::SocketAddr;
+use std::time::Duration;
+use tide::http::headers::RETRY_AFTER;
+use tide::{Body, Middleware, Next, Request, Response, StatusCode};
+
+/// IP address of the client, set by `RateLimitMiddleware`.
+#[derive(Debug, Clone)]
+pub struct ClientIp(pub String);
+
+/// Throttles each client with a token bucket of its IP address.
+pub struct RateLimitMiddleware {
+    /// Take the address from `Forwarded` or `X-Forwarded-For`, only safe behind a proxy which sets them.
+    trust_proxy: bool,
+}
+
+impl RateLimitMiddleware {
+    pub fn from_env() -> Self {
+        Self {
+            trust_proxy: env::var("TRUST_PROXY").map_or(false, |value| value == "true"),
+        }
+    }
+
+    fn client_ip(&self, request: &Request<State>) -> Option<String> {
+        let addr = if self.trust_proxy {
+            request.remote()
+        } else {
+            request.peer_addr()
+        }?;
+        // Buckets are per address, not per connection.
+        match addr.parse::<SocketAddr>() {
+            Ok(addr) => Some(addr.ip().to_string()),
+            Err(_) => Some(addr.to_owned()),
+        }
+    }
+}
+
+#[tide::utils::async_trait]
+impl Middleware<State> for RateLimitMiddleware {
+    async fn handle(&self, mut request: Request<State>, next: Next<'_, State>) -> tide::Result {
+        if let Some(ip) = self.client_ip(&request) {
+            if let Some(wait) = request.state().rate_limit_service.check_request(&ip).await {
+                return rate_limited(wait);
+            }
+            request.set_ext(ClientIp(ip));
+        }
+        Ok(next.run(request).await)
+    }
+}
+
+/// Whole seconds to wait, as `Retry-After` takes them.
+pub fn retry_after_seconds(wait: Duration) -> u64 {
+    let seconds = wait.as_secs();
+    if wait.subsec_nanos() > 0 {
+        seconds + 1
+    } else {
+        seconds.max(1)
+    }
+}
+
+/// The throttled response, with `Retry-After`.
+pub fn rate_limited(wait: Duration) -> tide::Result {
+    let seconds = retry_after_seconds(wait);
+    let err = ApplicationError {
+        code: ErrorCode::RateLimited,
+        message: format!("too many requests, retry after {} seconds", seconds),
+    };
+    Ok(Response::builder(StatusCode::TooManyRequests)
+        .header(RETRY_AFTER, seconds.to_string())
+        .body(Body::from_json(&err)?)
+        .build())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_after_seconds() {
+        assert_eq!(1, retry_after_seconds(Duration::from_millis(1)));
+        assert_eq!(1, retry_after_seconds(Duration::from_secs(0)));
+        assert_eq!(3, retry_after_seconds(Duration::from_secs(3)));
+        assert_eq!(4, retry_after_seconds(Duration::from_millis(3001)));
+    }
+}
@@ -0,0 +1,174 @@
+//! Time-based one-time passwords (RFC 6238) as authenticator apps generate them:
+//! HMAC-SHA1, 6 digits and 30 second steps.
+use rand::RngCore;
+use ring::{constant_time, hmac};
+use tide::http::Url;
+
+const STEP_SECONDS: i64 = 30;
+const DIGITS: u32 = 6;
+const SECRET_BYTES: usize = 20;
+/// Steps accepted before and after the current one, to allow for clock drift.
+const SKEW_STEPS: i64 = 1;
+const BASE32_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Generate a random secret, base32 encoded.
+pub fn generate_secret() -> String {
+    let mut bytes = [0u8; SECRET_BYTES];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    encode_base32(&bytes)
+}
+
+/// Base32 (RFC 4648) without padding.
+pub fn encode_base32(bytes: &[u8]) -> String {
+    let mut encoded = String::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in bytes {
+        buffer = (buffer << 8) | *byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        encoded.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+    encoded
+}
+
+/// Decode base32, ignoring case, padding and spaces.
+pub fn decode_base32(encoded: &str) -> Option<Vec<u8>> {
+    let mut decoded = vec![];
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in encoded.chars().filter(|c| *c != '=' && !c.is_whitespace()) {
+        let value = BASE32_ALPHABET
+            .iter()
+            .position(|a| *a as char == c.to_ascii_uppercase())?;
+        buffer = (buffer << 5) | value as u32;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+        }
+    }
+    Some(decoded)
+}
+
+/// Time step of a unix timestamp.
+pub fn step(timestamp: i64) -> i64 {
+    timestamp.div_euclid(STEP_SECONDS)
+}
+
+/// The code of a time step.
+pub fn code(secret: &[u8], step: i64) -> String {
+    let key = hmac::Key::new(hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY, secret);
+    let digest = hmac::sign(&key, &step.to_be_bytes());
+    let digest = digest.as_ref();
+    // Dynamic truncation (RFC 4226).
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+    format!(
+        "{:0width$}",
+        binary % 10u32.pow(DIGITS),
+        width = DIGITS as usize
+    )
+}
+
+/// Check a code around the time, returns the step it matched.
+pub fn verify(secret: &[u8], code: &str, timestamp: i64) -> Option<i64> {
+    let current = step(timestamp);
+    (current - SKEW_STEPS..=current + SKEW_STEPS).find(|step| {
+        constant_time::verify_slices_are_equal(
+            self::code(secret, *step).as_bytes(),
+            code.as_bytes(),
+        )
+        .is_ok()
+    })
+}
+
+/// Whether the input looks like a TOTP code rather than a recovery code.
+pub fn is_code(input: &str) -> bool {
+    input.len() == DIGITS as usize && input.chars().all(|c| c.is_ascii_digit())
+}
+
+/// The `otpauth://` URI authenticator apps scan from a QR code.
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    let mut url = Url::parse("otpauth://totp/").expect("valid URI");
+    url.set_path(&format!("{}:{}", issuer, account));
+    url.query_pairs_mut()
+        .append_pair("secret", secret)
+        .append_pair("issuer", issuer)
+        .append_pair("algorithm", "SHA1")
+        .append_pair("digits", &DIGITS.to_string())
+        .append_pair("period", &STEP_SECONDS.to_string());
+    url.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base32() {
+        assert_eq!("MZXW6YTBOI", encode_base32(b"foobar"));
+        assert_eq!("MZXW6", encode_base32(b"foo"));
+        assert_eq!(
+            b"foobar".to_vec(),
+            decode_base32("mzxw 6ytb oi======").unwrap()
+        );
+        assert!(decode_base32("MZXW1").is_none());
+
+        let secret = generate_secret();
+        assert_eq!(32, secret.len());
+        assert_eq!(SECRET_BYTES, decode_base32(&secret).unwrap().len());
+    }
+
+    #[test]
+    fn test_code() {
+        // SHA1 vectors of RFC 6238, truncated to 6 digits.
+        let secret = b"12345678901234567890";
+        for (timestamp, expected) in &[
+            (59, "287082"),
+            (1111111109, "081804"),
+            (1111111111, "050471"),
+            (1234567890, "005924"),
+            (2000000000, "279037"),
+            (20000000000, "353130"),
+        ] {
+            assert_eq!(*expected, code(secret, step(*timestamp)));
+        }
+    }
+
+    #[test]
+    fn test_verify() {
+        let secret = b"12345678901234567890";
+        assert_eq!(Some(step(59)), verify(secret, "287082", 59));
+        // The previous and next steps are accepted, older ones are not.
+        assert_eq!(Some(step(59)), verify(secret, "287082", 89));
+        assert_eq!(Some(step(59)), verify(secret, "287082", 29));
+        assert_eq!(None, verify(secret, "287082", 120));
+        assert_eq!(None, verify(secret, "000000", 59));
+    }
+
+    #[test]
+    fn test_is_code() {
+        assert!(is_code("012345"));
+        assert!(!is_code("01234"));
+        assert!(!is_code("abcde-12345"));
+    }
+
+    #[test]
+    fn test_otpauth_uri() {
+        assert_eq!(
+            "otpauth://totp/rsapps:john%20doe?secret=MZXW6YTBOI&issuer=rsapps&algorithm=SHA1&digits=6&period=30",
+            otpauth_uri("rsapps", "john doe", "MZXW6YTBOI")
+        );
+    }
+}
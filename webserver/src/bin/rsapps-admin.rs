@@ -0,0 +1,115 @@
+use rsapps_webserver::auth::password_policy::PasswordPolicy;
+use rsapps_webserver::domains::entities::user::{Role, User};
+use rsapps_webserver::domains::repositories::user_repository::UserRepository;
+use rsapps_webserver::infrastructures::database::create_pool;
+use rsapps_webserver::infrastructures::di_container::{DIContainer, PgDIContainer};
+use rsapps_webserver::infrastructures::mailers::create_mailer;
+use rsapps_webserver::infrastructures::password_hashers::create_password_hasher;
+use rsapps_webserver::services::admin_service::AdminService;
+use rsapps_webserver::services::data_export_service::DataExportService;
+use rsapps_webserver::services::email_service::EmailService;
+use rsapps_webserver::services::password_reset_service::PasswordResetService;
+use std::env;
+use std::fs;
+use std::process;
+use std::str::FromStr;
+use std::sync::Arc;
+
+const USAGE: &str = "usage:
+  rsapps-admin export-user <username> [<output file>]
+  rsapps-admin set-role <username> <user|admin>";
+
+async fn get_user(di_container: &PgDIContainer, username: &str) -> anyhow::Result<User> {
+    match di_container
+        .user_repository()
+        .get_user_by_username(username.to_owned())
+        .await?
+    {
+        Some(user) => Ok(user),
+        None => anyhow::bail!("user {} is not found", username),
+    }
+}
+
+/// Write the data export of the user to the output file, or to its suggested file name.
+async fn export_user(
+    di_container: Arc<PgDIContainer>,
+    username: &str,
+    output: Option<&str>,
+) -> anyhow::Result<()> {
+    let user = get_user(&di_container, username).await?;
+    let export = DataExportService::new(di_container)
+        .export_user(user.id)
+        .await
+        .map_err(|err| anyhow::anyhow!("{}", err))?;
+    let path = output.unwrap_or(&export.file_name);
+    fs::write(path, &export.content)?;
+    println!("exported user {} to {}", user.username, path);
+    Ok(())
+}
+
+/// Promote or demote the user, which is how the first admin is made.
+async fn set_role(
+    di_container: Arc<PgDIContainer>,
+    username: &str,
+    role: &str,
+) -> anyhow::Result<()> {
+    let role = Role::from_str(role).map_err(|_| anyhow::anyhow!("role {} is unknown", role))?;
+    let user = get_user(&di_container, username).await?;
+    let email_service = EmailService::new(
+        create_mailer()?,
+        env::var("APP_URL").unwrap_or_else(|_| "http://localhost:8080".to_owned()),
+    );
+    let password_reset_service = PasswordResetService::new(
+        di_container.clone(),
+        email_service,
+        create_password_hasher()?,
+        Arc::new(PasswordPolicy::from_env()?),
+    );
+    AdminService::new(di_container, password_reset_service)
+        .set_role(None, user.id, role)
+        .await
+        .map_err(|err| anyhow::anyhow!("{}", err))?;
+    println!("set role of user {} to {}", user.username, role.as_ref());
+    Ok(())
+}
+
+enum Command<'a> {
+    ExportUser {
+        username: &'a str,
+        output: Option<&'a str>,
+    },
+    SetRole {
+        username: &'a str,
+        role: &'a str,
+    },
+}
+
+#[async_std::main]
+async fn main() -> anyhow::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    let command = match args.as_slice() {
+        ["export-user", username] => Command::ExportUser {
+            username,
+            output: None,
+        },
+        ["export-user", username, output] => Command::ExportUser {
+            username,
+            output: Some(output),
+        },
+        ["set-role", username, role] => Command::SetRole { username, role },
+        _ => {
+            eprintln!("{}", USAGE);
+            process::exit(2);
+        }
+    };
+    let di_container = Arc::new(PgDIContainer {
+        db: create_pool::<sqlx::Postgres>(1, &env::var("DATABASE_URL")?).await?,
+    });
+    match command {
+        Command::ExportUser { username, output } => {
+            export_user(di_container, username, output).await
+        }
+        Command::SetRole { username, role } => set_role(di_container, username, role).await,
+    }
+}
@@ -0,0 +1,98 @@
+use std::str::FromStr;
+
+/// What happened, events of authentication and of admins.
+#[derive(AsRefStr, EnumString, GraphQLEnum, Debug, Clone, Copy, PartialEq)]
+pub enum AuditEventType {
+    #[strum(serialize = "sign_up")]
+    SignUp,
+    #[strum(serialize = "login_succeeded")]
+    LoginSucceeded,
+    #[strum(serialize = "login_failed")]
+    LoginFailed,
+    /// The password was right and the login waits for the second factor.
+    #[strum(serialize = "second_factor_challenged")]
+    SecondFactorChallenged,
+    /// A request came with a token which was rejected.
+    #[strum(serialize = "authentication_failed")]
+    AuthenticationFailed,
+    #[strum(serialize = "token_refreshed")]
+    TokenRefreshed,
+    #[strum(serialize = "token_refresh_failed")]
+    TokenRefreshFailed,
+    /// A login session or a personal access token was revoked.
+    #[strum(serialize = "token_revoked")]
+    TokenRevoked,
+    /// The password was changed by the user or with a reset link.
+    #[strum(serialize = "password_changed")]
+    PasswordChanged,
+    #[strum(serialize = "admin_action")]
+    AdminAction,
+}
+
+/// Where a request came from, recorded with its events.
+#[derive(Debug, Clone, Default)]
+pub struct RequestMetadata {
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct AuditEvent {
+    pub id: i32,
+    pub event_type: String,
+    /// The user the event is about, unknown for a login to an account which may not exist.
+    pub user_id: Option<i32>,
+    /// Who caused the event when it isn't the user, like an admin.
+    pub actor_id: Option<i32>,
+    /// The username, email or provider a login was tried with.
+    pub account: Option<String>,
+    pub detail: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl AuditEvent {
+    /// An event of the request, the rest of the fields can be filled in with struct update syntax.
+    pub fn new(event_type: AuditEventType, metadata: &RequestMetadata) -> Self {
+        Self {
+            id: 0,
+            event_type: event_type.as_ref().to_owned(),
+            user_id: None,
+            actor_id: None,
+            account: None,
+            detail: None,
+            ip_address: metadata.ip_address.to_owned(),
+            user_agent: metadata.user_agent.to_owned(),
+            request_id: metadata.request_id.to_owned(),
+            created_at: chrono::Utc::now(),
+        }
+    }
+
+    /// Events of a type which is no longer known have none.
+    pub fn known_event_type(&self) -> Option<AuditEventType> {
+        AuditEventType::from_str(&self.event_type).ok()
+    }
+}
+
+/// Which events to list, every given condition has to match.
+#[derive(GraphQLInputObject, Debug, Clone, Default)]
+pub struct AuditEventFilter {
+    pub event_type: Option<AuditEventType>,
+    /// Events about the user or caused by them.
+    pub user_id: Option<i32>,
+    pub ip_address: Option<String>,
+    /// Events at or after the time.
+    pub since: Option<chrono::DateTime<chrono::Utc>>,
+    /// Events before the time.
+    pub until: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// A page of audit events with the number of all events which match the filter.
+#[derive(Clone)]
+pub struct AuditEventPage {
+    pub audit_events: Vec<AuditEvent>,
+    pub total_count: i64,
+}
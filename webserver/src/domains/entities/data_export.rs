@@ -0,0 +1,6 @@
+/// An archive of the personal data of a user.
+#[derive(Clone)]
+pub struct DataExport {
+    pub file_name: String,
+    pub content: Vec<u8>,
+}
@@ -0,0 +1,20 @@
+/// What a link mailed to a user is for.
+#[derive(AsRefStr, EnumString, Debug, Clone, Copy, PartialEq)]
+pub enum EmailTokenPurpose {
+    #[strum(serialize = "password_reset")]
+    PasswordReset,
+    #[strum(serialize = "magic_link")]
+    MagicLink,
+}
+
+/// A single use token of a link mailed to a user, only its hash is stored.
+#[derive(Clone)]
+pub struct EmailToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub purpose: String,
+    pub token_hash: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
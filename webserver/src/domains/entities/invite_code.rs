@@ -0,0 +1,54 @@
+use std::env;
+use std::str::FromStr;
+
+/// Who may sign up, set with `REGISTRATION_MODE`.
+#[derive(AsRefStr, EnumString, GraphQLEnum, Debug, Clone, Copy, PartialEq)]
+pub enum RegistrationMode {
+    /// Anyone can sign up, an invite code is optional.
+    #[strum(serialize = "open")]
+    Open,
+    /// Signing up needs an invite code.
+    #[strum(serialize = "invite_only")]
+    InviteOnly,
+    /// Nobody can sign up.
+    #[strum(serialize = "closed")]
+    Closed,
+}
+
+impl RegistrationMode {
+    pub fn from_env() -> anyhow::Result<Self> {
+        match env::var("REGISTRATION_MODE") {
+            Ok(mode) if !mode.is_empty() => RegistrationMode::from_str(&mode)
+                .map_err(|_| anyhow::anyhow!("REGISTRATION_MODE {} is unknown", mode)),
+            _ => Ok(RegistrationMode::Open),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct InviteCode {
+    pub id: i32,
+    pub code_hash: String,
+    pub created_by: Option<i32>,
+    pub max_uses: i32,
+    pub use_count: i32,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A newly created invite code, the plain code is only shown once.
+#[derive(Clone)]
+pub struct CreatedInviteCode {
+    pub code: String,
+    pub invite_code: InviteCode,
+}
+
+/// A sign up with an invite code.
+#[derive(Clone)]
+pub struct InviteRedemption {
+    pub id: i32,
+    pub invite_code_id: i32,
+    pub user_id: i32,
+    pub redeemed_at: chrono::DateTime<chrono::Utc>,
+}
@@ -0,0 +1,8 @@
+use crate::domains::entities::token_pair::TokenPair;
+
+/// Outcome of a login, either the tokens or a challenge for the second factor.
+#[derive(Clone)]
+pub struct LoginResult {
+    pub tokens: Option<TokenPair>,
+    pub challenge_token: Option<String>,
+}
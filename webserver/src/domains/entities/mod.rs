@@ -1,2 +1,20 @@
-pub(crate) mod todo;
-pub(crate) mod user;
+pub mod audit_event;
+pub mod data_export;
+pub mod email_token;
+pub mod invite_code;
+pub mod login_result;
+pub mod mail;
+pub mod personal_access_token;
+pub mod quota;
+pub mod recovery_code;
+pub mod refresh_token;
+pub mod session;
+pub mod tag;
+pub mod todo;
+pub mod todo_list;
+pub mod todo_list_member;
+pub mod todo_series;
+pub mod token_pair;
+pub mod totp_credential;
+pub mod user;
+pub mod user_identity;
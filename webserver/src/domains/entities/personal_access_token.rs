@@ -0,0 +1,72 @@
+use std::str::FromStr;
+
+/// What a personal access token is allowed to do.
+#[derive(AsRefStr, EnumString, GraphQLEnum, Debug, Clone, Copy, PartialEq)]
+pub enum Scope {
+    #[strum(serialize = "todos:read")]
+    TodosRead,
+    #[strum(serialize = "todos:write")]
+    TodosWrite,
+    #[strum(serialize = "profile")]
+    Profile,
+}
+
+#[derive(Clone)]
+pub struct PersonalAccessToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub name: String,
+    pub token_hash: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub last_used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl PersonalAccessToken {
+    /// Scopes which are no longer known are dropped.
+    pub fn granted_scopes(&self) -> Vec<Scope> {
+        self.scopes
+            .iter()
+            .filter_map(|scope| Scope::from_str(scope).ok())
+            .collect()
+    }
+}
+
+/// A newly minted token, the plain token is only shown once.
+#[derive(Clone)]
+pub struct CreatedPersonalAccessToken {
+    pub token: String,
+    pub personal_access_token: PersonalAccessToken,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scopes() {
+        let now = chrono::Utc::now();
+        let token = PersonalAccessToken {
+            id: 1,
+            user_id: 1,
+            name: "script".to_owned(),
+            token_hash: "hash".to_owned(),
+            scopes: vec![
+                "todos:read".to_owned(),
+                "unknown".to_owned(),
+                "profile".to_owned(),
+            ],
+            expires_at: None,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: now,
+        };
+        assert_eq!(
+            vec![Scope::TodosRead, Scope::Profile],
+            token.granted_scopes()
+        );
+        assert_eq!("todos:write", Scope::TodosWrite.as_ref());
+    }
+}
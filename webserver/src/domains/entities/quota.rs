@@ -0,0 +1,8 @@
+use std::time::Duration;
+
+/// A token bucket which holds up to `burst` tokens and gets one back every `period`.
+#[derive(Debug, Clone, Copy)]
+pub struct Quota {
+    pub burst: u32,
+    pub period: Duration,
+}
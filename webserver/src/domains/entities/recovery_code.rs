@@ -0,0 +1,9 @@
+/// A one-time code which stands in for a TOTP code when the authenticator is lost.
+#[derive(Clone)]
+pub struct RecoveryCode {
+    pub id: i32,
+    pub user_id: i32,
+    pub code_hash: String,
+    pub used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
@@ -0,0 +1,11 @@
+#[derive(Clone)]
+pub struct RefreshToken {
+    pub id: i32,
+    pub user_id: i32,
+    pub family: String,
+    pub token_hash: String,
+    pub expires_at: chrono::DateTime<chrono::Utc>,
+    pub used_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub revoked_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
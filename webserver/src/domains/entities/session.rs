@@ -0,0 +1,91 @@
+/// A login on a device, which lasts as long as its refresh token family.
+#[derive(Clone)]
+pub struct Session {
+    pub id: i32,
+    pub user_id: i32,
+    /// Refresh token family of the login, revoking it ends the session.
+    pub family: String,
+    pub user_agent: Option<String>,
+    /// Where the session was last seen from.
+    pub ip_address: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub last_seen_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl Session {
+    /// A readable name of the device like "Firefox on Linux", from the user agent.
+    pub fn device_name(&self) -> String {
+        let user_agent = match self.user_agent.as_deref() {
+            Some(user_agent) if !user_agent.is_empty() => user_agent,
+            _ => return "Unknown device".to_owned(),
+        };
+        // Browsers claim to be each other, so the more specific names are looked for first.
+        let browser = [
+            ("Edg/", "Edge"),
+            ("OPR/", "Opera"),
+            ("Firefox/", "Firefox"),
+            ("Chrome/", "Chrome"),
+            ("Safari/", "Safari"),
+            ("curl/", "curl"),
+        ]
+        .iter()
+        .find(|(token, _)| user_agent.contains(token))
+        .map(|(_, name)| *name);
+        let os = [
+            ("Android", "Android"),
+            ("iPhone", "iOS"),
+            ("iPad", "iOS"),
+            ("Windows", "Windows"),
+            ("Mac OS X", "macOS"),
+            ("Linux", "Linux"),
+        ]
+        .iter()
+        .find(|(token, _)| user_agent.contains(token))
+        .map(|(_, name)| *name);
+        match (browser, os) {
+            (Some(browser), Some(os)) => format!("{} on {}", browser, os),
+            (Some(name), None) | (None, Some(name)) => name.to_owned(),
+            (None, None) => user_agent.to_owned(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(user_agent: Option<&str>) -> Session {
+        let now = chrono::Utc::now();
+        Session {
+            id: 1,
+            user_id: 1,
+            family: "family".to_owned(),
+            user_agent: user_agent.map(|user_agent| user_agent.to_owned()),
+            ip_address: None,
+            created_at: now,
+            last_seen_at: now,
+        }
+    }
+
+    #[test]
+    fn test_device_name() {
+        assert_eq!(
+            "Firefox on Linux",
+            session(Some(
+                "Mozilla/5.0 (X11; Linux x86_64; rv:85.0) Gecko/20100101 Firefox/85.0"
+            ))
+            .device_name()
+        );
+        assert_eq!(
+            "Chrome on Windows",
+            session(Some("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/88.0.4324.150 Safari/537.36")).device_name()
+        );
+        assert_eq!(
+            "Safari on iOS",
+            session(Some("Mozilla/5.0 (iPhone; CPU iPhone OS 14_4 like Mac OS X) AppleWebKit/605.1.15 (KHTML, like Gecko) Version/14.0.3 Mobile/15E148 Safari/604.1")).device_name()
+        );
+        assert_eq!("curl", session(Some("curl/7.74.0")).device_name());
+        assert_eq!("rsapps-cli", session(Some("rsapps-cli")).device_name());
+        assert_eq!("Unknown device", session(None).device_name());
+    }
+}
@@ -0,0 +1,35 @@
+/// A label of the user, which they put on todos.
+#[derive(Clone)]
+pub struct Tag {
+    pub id: i32,
+    pub user_id: i32,
+    /// Unique among the tags of the user regardless of case.
+    pub name: String,
+    /// A hex color like `#1e88e5`.
+    pub color: String,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A tag put on a todo.
+#[derive(Clone)]
+pub struct TodoTag {
+    pub todo_id: i32,
+    pub tag_id: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// Whether todos need any or all of the tags of a filter.
+#[derive(GraphQLEnum, Debug, Clone, Copy, PartialEq)]
+pub enum TagMatch {
+    Any,
+    All,
+}
+
+/// Which tags todos need to have.
+#[derive(GraphQLInputObject, Debug, Clone)]
+pub struct TagFilter {
+    pub tag_ids: Vec<i32>,
+    /// Any of the tags without one.
+    pub tag_match: Option<TagMatch>,
+}
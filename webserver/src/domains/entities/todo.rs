@@ -1,3 +1,5 @@
+use crate::domains::entities::tag::TagFilter;
+
 #[derive(Clone)]
 pub struct Todo {
     pub id: i32,
@@ -5,5 +7,85 @@ pub struct Todo {
     pub complete: bool,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
-    pub user_id: i32,
+    pub list_id: i32,
+    /// Who created the todo, `None` once their account is deleted.
+    pub created_by: Option<i32>,
+    /// Who last changed the todo, `None` once their account is deleted.
+    pub updated_by: Option<i32>,
+    /// When work on the todo can start, never after its due time.
+    pub start_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub due_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// The series of a recurring todo.
+    pub series_id: Option<i32>,
+    /// When the rule of the series scheduled the occurrence, which stays when its due time is moved.
+    pub occurrence_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// The occurrence whose completion created this one.
+    pub previous_id: Option<i32>,
+}
+
+impl Todo {
+    /// An incomplete todo is overdue once its due time has passed.
+    pub fn is_overdue(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        !self.complete && self.due_at.map_or(false, |due_at| due_at < now)
+    }
+}
+
+/// How todos are sorted.
+#[derive(GraphQLEnum, Debug, Clone, Copy, PartialEq)]
+pub enum TodoOrder {
+    /// In the order they were created.
+    Created,
+    /// The earliest due first, the ones without a due time last.
+    DueAt,
+}
+
+impl Default for TodoOrder {
+    fn default() -> Self {
+        TodoOrder::Created
+    }
+}
+
+/// Which todos of a list to get, every given condition has to match.
+#[derive(Debug, Clone, Default)]
+pub struct TodoFilter {
+    pub tag_filter: Option<TagFilter>,
+    /// Todos due before the time.
+    pub due_before: Option<chrono::DateTime<chrono::Utc>>,
+    /// Todos due at or after the time.
+    pub due_after: Option<chrono::DateTime<chrono::Utc>>,
+    /// Only the overdue todos, or only the others with `false`.
+    pub overdue: Option<bool>,
+    pub order_by: TodoOrder,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_overdue() {
+        let now = chrono::Utc::now();
+        let mut todo = Todo {
+            id: 1,
+            body: "report".to_owned(),
+            complete: false,
+            created_at: now,
+            updated_at: now,
+            list_id: 1,
+            created_by: None,
+            updated_by: None,
+            start_at: None,
+            due_at: None,
+            series_id: None,
+            occurrence_at: None,
+            previous_id: None,
+        };
+        assert!(!todo.is_overdue(now));
+        todo.due_at = Some(now + chrono::Duration::hours(1));
+        assert!(!todo.is_overdue(now));
+        todo.due_at = Some(now - chrono::Duration::hours(1));
+        assert!(todo.is_overdue(now));
+        todo.complete = true;
+        assert!(!todo.is_overdue(now));
+    }
 }
@@ -0,0 +1,12 @@
+/// A named list of todos of a user, like "Work" or "Groceries".
+#[derive(Clone)]
+pub struct TodoList {
+    pub id: i32,
+    /// The owner, who created the list.
+    pub user_id: i32,
+    pub name: String,
+    /// The list todos of the owner go to when no list is given, every user has one.
+    pub is_default: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
@@ -0,0 +1,80 @@
+use std::str::FromStr;
+
+/// What a member may do with a shared todo list.
+#[derive(AsRefStr, EnumString, GraphQLEnum, Debug, Clone, Copy, PartialEq)]
+pub enum TodoListRole {
+    /// Created the list, manages it and its members.
+    #[strum(serialize = "owner")]
+    Owner,
+    #[strum(serialize = "editor")]
+    Editor,
+    /// Only reads the todos.
+    #[strum(serialize = "viewer")]
+    Viewer,
+}
+
+/// What is done with a todo list, checked against the role of the member.
+#[derive(AsRefStr, Debug, Clone, Copy, PartialEq)]
+pub enum TodoListAccess {
+    #[strum(serialize = "view")]
+    View,
+    /// Create, change and delete todos of the list.
+    #[strum(serialize = "edit")]
+    Edit,
+    /// Rename or delete the list and manage its members.
+    #[strum(serialize = "manage")]
+    Manage,
+}
+
+impl TodoListRole {
+    pub fn allows(self, access: TodoListAccess) -> bool {
+        match access {
+            TodoListAccess::View => true,
+            TodoListAccess::Edit => self != TodoListRole::Viewer,
+            TodoListAccess::Manage => self == TodoListRole::Owner,
+        }
+    }
+}
+
+/// A user a todo list is shared with, or invited to share it.
+#[derive(Clone)]
+pub struct TodoListMember {
+    pub id: i32,
+    pub list_id: i32,
+    pub user_id: i32,
+    pub role: String,
+    pub invited_by: Option<i32>,
+    /// `None` while the invitation is pending.
+    pub accepted_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+    /// Username of the member, read along with the membership.
+    pub username: String,
+    /// Name of the list, read along with the membership.
+    pub list_name: String,
+}
+
+impl TodoListMember {
+    /// An unknown role grants nothing more than a viewer.
+    pub fn granted_role(&self) -> TodoListRole {
+        TodoListRole::from_str(&self.role).unwrap_or(TodoListRole::Viewer)
+    }
+
+    pub fn is_accepted(&self) -> bool {
+        self.accepted_at.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_role_allows() {
+        assert!(TodoListRole::Viewer.allows(TodoListAccess::View));
+        assert!(!TodoListRole::Viewer.allows(TodoListAccess::Edit));
+        assert!(TodoListRole::Editor.allows(TodoListAccess::Edit));
+        assert!(!TodoListRole::Editor.allows(TodoListAccess::Manage));
+        assert!(TodoListRole::Owner.allows(TodoListAccess::Manage));
+    }
+}
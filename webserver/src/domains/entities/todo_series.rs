@@ -0,0 +1,148 @@
+use crate::domains::recurrence::RecurrenceRule;
+use chrono_tz::Tz;
+use std::convert::TryFrom;
+
+/// Which todos of a series a change is for.
+#[derive(GraphQLEnum, Debug, Clone, Copy, PartialEq)]
+pub enum RecurrenceScope {
+    /// Only the occurrence, the series goes on as scheduled.
+    This,
+    /// The occurrence and the ones to come.
+    Series,
+}
+
+impl Default for RecurrenceScope {
+    fn default() -> Self {
+        RecurrenceScope::This
+    }
+}
+
+/// A recurring todo, whose next occurrence is created once one is completed.
+#[derive(Clone)]
+pub struct TodoSeries {
+    pub id: i32,
+    pub list_id: i32,
+    /// The body every new occurrence gets.
+    pub body: String,
+    /// An RFC 5545 RRULE like `FREQ=WEEKLY;BYDAY=MO`.
+    pub rrule: String,
+    /// The IANA time zone occurrences keep their local time in.
+    pub time_zone: String,
+    /// The due time of the first occurrence, the rule counts from there.
+    pub dtstart: chrono::DateTime<chrono::Utc>,
+    /// Occurrences before `dtstart` once the series is moved, a `COUNT` includes them.
+    pub occurrences_before: i32,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+    pub updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl TodoSeries {
+    /// The first occurrence after the time, `None` once the series has ended.
+    pub fn next_occurrence(
+        &self,
+        after: chrono::DateTime<chrono::Utc>,
+    ) -> Option<chrono::DateTime<chrono::Utc>> {
+        let (rule, time_zone) = self.remaining_rule()?;
+        rule.next_after(self.dtstart, time_zone, after)
+    }
+
+    /// Occurrences of the series before the time, those before `dtstart` included.
+    pub fn occurrences_until(&self, time: chrono::DateTime<chrono::Utc>) -> i32 {
+        let counted = match self.remaining_rule() {
+            Some((rule, time_zone)) => rule
+                .occurrences(self.dtstart, time_zone)
+                .take_while(|occurrence| *occurrence < time)
+                .count() as i32,
+            None => 0,
+        };
+        self.occurrences_before + counted
+    }
+
+    /// The rule from `dtstart` on, its `COUNT` lowered by the occurrences before,
+    /// `None` when it has none left or doesn't parse.
+    fn remaining_rule(&self) -> Option<(RecurrenceRule, Tz)> {
+        let mut rule = self.rrule.parse::<RecurrenceRule>().ok()?;
+        let time_zone = self.time_zone.parse::<Tz>().ok()?;
+        if let Some(count) = rule.count {
+            let used = u32::try_from(self.occurrences_before).unwrap_or(0);
+            if used >= count {
+                return None;
+            }
+            rule.count = Some(count - used);
+        }
+        Some((rule, time_zone))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_next_occurrence() {
+        let dtstart = chrono::Utc.ymd(2021, 3, 1).and_hms(9, 0, 0);
+        let mut series = TodoSeries {
+            id: 1,
+            list_id: 1,
+            body: "pay rent".to_owned(),
+            rrule: "FREQ=MONTHLY;COUNT=2".to_owned(),
+            time_zone: "Asia/Tokyo".to_owned(),
+            dtstart,
+            occurrences_before: 0,
+            created_at: dtstart,
+            updated_at: dtstart,
+        };
+        assert_eq!(
+            Some(chrono::Utc.ymd(2021, 4, 1).and_hms(9, 0, 0)),
+            series.next_occurrence(dtstart)
+        );
+        assert_eq!(
+            None,
+            series.next_occurrence(chrono::Utc.ymd(2021, 4, 1).and_hms(9, 0, 0))
+        );
+        series.time_zone = "Mars/Olympus_Mons".to_owned();
+        assert_eq!(None, series.next_occurrence(dtstart));
+    }
+
+    #[test]
+    fn test_moved_series() {
+        let dtstart = chrono::Utc.ymd(2031, 1, 6).and_hms(18, 0, 0);
+        let mut series = TodoSeries {
+            id: 1,
+            list_id: 1,
+            body: "water plants".to_owned(),
+            rrule: "FREQ=WEEKLY;COUNT=3".to_owned(),
+            time_zone: "UTC".to_owned(),
+            dtstart,
+            occurrences_before: 0,
+            created_at: dtstart,
+            updated_at: dtstart,
+        };
+        let second = dtstart + chrono::Duration::weeks(1);
+        assert_eq!(0, series.occurrences_until(dtstart));
+        assert_eq!(1, series.occurrences_until(second));
+        assert_eq!(
+            3,
+            series.occurrences_until(dtstart + chrono::Duration::weeks(5))
+        );
+
+        // Moved to Tuesdays from the second occurrence on, one is left after it.
+        series.occurrences_before = series.occurrences_until(second);
+        series.dtstart = second + chrono::Duration::days(1);
+        assert_eq!(
+            Some(series.dtstart + chrono::Duration::weeks(1)),
+            series.next_occurrence(series.dtstart)
+        );
+        assert_eq!(
+            None,
+            series.next_occurrence(series.dtstart + chrono::Duration::weeks(1))
+        );
+        assert_eq!(
+            3,
+            series.occurrences_until(dtstart + chrono::Duration::weeks(5))
+        );
+        series.occurrences_before = 3;
+        assert_eq!(None, series.next_occurrence(dtstart));
+    }
+}
@@ -0,0 +1,6 @@
+/// Tokens handed to a client once it has been authenticated.
+#[derive(Clone)]
+pub struct TokenPair {
+    pub access_token: String,
+    pub refresh_token: String,
+}
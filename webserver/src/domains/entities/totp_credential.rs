@@ -0,0 +1,19 @@
+/// A TOTP secret of a user, two-factor authentication is enabled once it is confirmed.
+#[derive(Clone)]
+pub struct TotpCredential {
+    pub id: i32,
+    pub user_id: i32,
+    /// Base32 encoded, as authenticator apps take it.
+    pub secret: String,
+    pub confirmed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Time step of the last accepted code, a code can't be replayed.
+    pub last_used_step: Option<i64>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+/// A secret to add to an authenticator app, it is confirmed with a code from the app.
+#[derive(Clone)]
+pub struct TotpEnrollment {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
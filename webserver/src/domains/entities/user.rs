@@ -1,4 +1,33 @@
-use bcrypt::verify;
+use crate::domains::password_hasher::PasswordHasher;
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// What a user is allowed to do, an admin can do everything a user can.
+#[derive(
+    AsRefStr,
+    EnumString,
+    GraphQLEnum,
+    Serialize,
+    Deserialize,
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    PartialOrd,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Role {
+    #[strum(serialize = "user")]
+    User,
+    #[strum(serialize = "admin")]
+    Admin,
+}
+
+impl Default for Role {
+    fn default() -> Self {
+        Role::User
+    }
+}
 
 #[derive(Clone)]
 pub struct User {
@@ -8,28 +37,51 @@ pub struct User {
     pub password_hash: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
     pub updated_at: chrono::DateTime<chrono::Utc>,
+    pub email_verified_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub role: String,
+    pub suspended_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Set by an admin, the user has to reset the password before logging in again.
+    pub password_reset_required: bool,
 }
 
 impl User {
-    pub fn valid_password(&self, password: String) -> bool {
+    /// An unknown role grants nothing more than a user.
+    pub fn granted_role(&self) -> Role {
+        Role::from_str(&self.role).unwrap_or_default()
+    }
+
+    pub fn is_suspended(&self) -> bool {
+        self.suspended_at.is_some()
+    }
+
+    pub fn valid_password(&self, password_hasher: &dyn PasswordHasher, password: &str) -> bool {
         let hash = match self.password_hash.as_ref() {
             Some(hash) => hash,
             None => return false,
         };
-        verify(password, &hash).unwrap_or(false)
+        password_hasher.verify(password, hash).unwrap_or(false)
     }
 }
 
+/// A page of users with the number of all users.
+#[derive(Clone)]
+pub struct UserPage {
+    pub users: Vec<User>,
+    pub total_count: i64,
+}
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    use crate::infrastructures::password_hashers::bcrypt_password_hasher::BcryptPasswordHasher;
 
     #[test]
     fn test_valid_password() {
         let now = chrono::Utc::now();
         let password = "password";
-        let hash = bcrypt::hash(password, 10).unwrap();
+        let hasher = BcryptPasswordHasher::new(4).unwrap();
+        let hash = hasher.hash(password).unwrap();
         let user = User {
             id: 1,
             username: "testuser".to_owned(),
@@ -37,15 +89,20 @@ mod tests {
             password_hash: Some(hash),
             created_at: now,
             updated_at: now,
+            email_verified_at: None,
+            role: "user".to_owned(),
+            suspended_at: None,
+            password_reset_required: false,
         };
-        assert_eq!(true, user.valid_password(password.to_owned()));
+        assert_eq!(true, user.valid_password(&hasher, password));
     }
 
     #[test]
     fn test_valid_password_failed() {
         let now = chrono::Utc::now();
         let password = "password";
-        let hash = bcrypt::hash(password, 10).unwrap();
+        let hasher = BcryptPasswordHasher::new(4).unwrap();
+        let hash = hasher.hash(password).unwrap();
         let user = User {
             id: 1,
             username: "testuser".to_owned(),
@@ -53,7 +110,33 @@ mod tests {
             password_hash: Some(hash),
             created_at: now,
             updated_at: now,
+            email_verified_at: None,
+            role: "user".to_owned(),
+            suspended_at: None,
+            password_reset_required: false,
+        };
+        assert_eq!(false, user.valid_password(&hasher, "invalid"));
+    }
+
+    #[test]
+    fn test_granted_role() {
+        let now = chrono::Utc::now();
+        let mut user = User {
+            id: 1,
+            username: "testuser".to_owned(),
+            email: None,
+            password_hash: None,
+            created_at: now,
+            updated_at: now,
+            email_verified_at: None,
+            role: "admin".to_owned(),
+            suspended_at: None,
+            password_reset_required: false,
         };
-        assert_eq!(false, user.valid_password("invalid".to_owned()));
+        assert_eq!(Role::Admin, user.granted_role());
+        user.role = "unknown".to_owned();
+        assert_eq!(Role::User, user.granted_role());
+        assert!(Role::User < Role::Admin);
+        assert_eq!("admin", Role::Admin.as_ref());
     }
 }
@@ -0,0 +1,10 @@
+/// Links the subject of an external identity provider to a `User`.
+#[derive(Clone)]
+pub struct UserIdentity {
+    pub id: i32,
+    pub user_id: i32,
+    pub provider: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
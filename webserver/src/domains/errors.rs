@@ -5,14 +5,32 @@ use std::fmt::{Display, Formatter};
 #[derive(AsRefStr, Debug, Serialize)]
 pub enum ErrorCode {
     UnAuthenticated,
+    InvalidCredentials,
+    EmailNotVerified,
+    AccountSuspended,
+    PasswordResetRequired,
+    TokenExpired,
+    TokenRevoked,
+    Forbidden,
     NoAuthHeaderError,
     JWTTokenCreationError,
     NotFound,
     Conflict,
-    OperationNameIsNotDefined,
+    BadRequest,
+    /// The input broke the listed rules.
+    ValidationFailed(Vec<Violation>),
+    RateLimited,
     SystemError,
 }
 
+/// A rule which an input broke.
+#[derive(Clone, Debug, Serialize)]
+pub struct Violation {
+    pub field: String,
+    pub rule: String,
+    pub message: String,
+}
+
 #[derive(Serialize)]
 pub struct ApplicationError {
     pub code: ErrorCode,
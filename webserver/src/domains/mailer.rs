@@ -0,0 +1,10 @@
+use crate::domains::entities::mail::Mail;
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+
+#[async_trait]
+pub trait Mailer: DynClone {
+    async fn send(&self, mail: Mail) -> anyhow::Result<()>;
+}
+
+dyn_clone::clone_trait_object!(Mailer);
@@ -1,5 +1,9 @@
 pub mod entities;
 pub mod errors;
+pub mod mailer;
+pub mod password_hasher;
+pub mod rate_limit_store;
+pub mod recurrence;
 pub mod repositories;
 
 use errors::ApplicationError;
@@ -0,0 +1,17 @@
+use dyn_clone::DynClone;
+
+/// Hashes passwords with the current algorithm, and verifies hashes of any supported one.
+/// Hashes are in the modular crypt format, the prefix like `$2b$` or `$argon2id$` names the algorithm.
+pub trait PasswordHasher: DynClone {
+    fn hash(&self, password: &str) -> anyhow::Result<String>;
+
+    fn verify(&self, password: &str, hash: &str) -> anyhow::Result<bool>;
+
+    /// Whether the hash is of another algorithm or parameters than `hash` makes now.
+    fn needs_rehash(&self, hash: &str) -> bool;
+
+    /// Spend as long as `verify` does, for when there is no hash to verify against.
+    fn verify_dummy(&self, password: &str);
+}
+
+dyn_clone::clone_trait_object!(PasswordHasher);
@@ -0,0 +1,19 @@
+use crate::domains::entities::quota::Quota;
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+use std::time::Duration;
+
+/// Token buckets by key, a bucket which was never used is full.
+#[async_trait]
+pub trait RateLimitStore: DynClone {
+    /// Take a token from the bucket, returns how long to wait when it is empty.
+    async fn take(&self, key: &str, quota: Quota) -> anyhow::Result<Option<Duration>>;
+
+    /// Like `take`, but leaves the token in the bucket.
+    async fn peek(&self, key: &str, quota: Quota) -> anyhow::Result<Option<Duration>>;
+
+    /// Fill the bucket up again.
+    async fn reset(&self, key: &str) -> anyhow::Result<()>;
+}
+
+dyn_clone::clone_trait_object!(RateLimitStore);
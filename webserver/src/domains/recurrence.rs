@@ -0,0 +1,828 @@
+//! Recurrence rules (RFC 5545 RRULE) of recurring todos.
+//! Occurrences are expanded in a time zone, so they keep their local time across daylight saving time.
+//! `FREQ` is daily, weekly, monthly or yearly with `INTERVAL`, `COUNT`, `UNTIL`, `BYDAY`,
+//! `BYMONTHDAY`, `BYMONTH` and `WKST`, other parts are refused.
+use chrono::{
+    DateTime, Datelike, Duration, LocalResult, NaiveDate, NaiveDateTime, Offset, TimeZone, Utc,
+    Weekday,
+};
+use chrono_tz::Tz;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+/// Periods in a row without an occurrence after which a rule ends,
+/// so one which never matches like `FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30` does.
+const MAX_EMPTY_PERIODS: u32 = 10_000;
+const MAX_INTERVAL: i64 = 1_000;
+const MAX_COUNT: i64 = 10_000;
+const WEEKDAYS: [(&str, Weekday); 7] = [
+    ("MO", Weekday::Mon),
+    ("TU", Weekday::Tue),
+    ("WE", Weekday::Wed),
+    ("TH", Weekday::Thu),
+    ("FR", Weekday::Fri),
+    ("SA", Weekday::Sat),
+    ("SU", Weekday::Sun),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A weekday of `BYDAY`, with an ordinal the n-th one of the month, counted from its end when negative.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct WeekdayNum {
+    pub ordinal: Option<i32>,
+    pub weekday: Weekday,
+}
+
+/// The last occurrence, a date is the last local day of them.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Until {
+    Date(NaiveDate),
+    Time(DateTime<Utc>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecurrenceRule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    /// Number of occurrences, the first one included.
+    pub count: Option<u32>,
+    pub until: Option<Until>,
+    pub by_day: Vec<WeekdayNum>,
+    /// Days of the month, counted from its end when negative.
+    pub by_month_day: Vec<i32>,
+    pub by_month: Vec<u32>,
+    /// The first day of a week, which matters for weekly rules every few weeks.
+    pub week_start: Weekday,
+}
+
+impl RecurrenceRule {
+    /// Occurrences from `dtstart` on at its local time in the time zone, `dtstart` is always the first one.
+    pub fn occurrences(&self, dtstart: DateTime<Utc>, time_zone: Tz) -> Occurrences<'_> {
+        Occurrences {
+            rule: self,
+            time_zone,
+            start: dtstart.with_timezone(&time_zone).naive_local(),
+            dtstart,
+            started: false,
+            period: 0,
+            empty_periods: 0,
+            dates: vec![],
+            emitted: 0,
+            done: false,
+        }
+    }
+
+    /// The first occurrence after the time, `None` once the rule has ended.
+    pub fn next_after(
+        &self,
+        dtstart: DateTime<Utc>,
+        time_zone: Tz,
+        after: DateTime<Utc>,
+    ) -> Option<DateTime<Utc>> {
+        self.occurrences(dtstart, time_zone)
+            .find(|occurrence| *occurrence > after)
+    }
+
+    /// Dates of a period in order, the period counted in intervals from the one of `start`.
+    fn period_dates(&self, start: NaiveDate, period: i64) -> Option<Vec<NaiveDate>> {
+        let step = period * self.interval as i64;
+        let dates = match self.frequency {
+            Frequency::Daily => {
+                let date = start.checked_add_signed(Duration::days(step))?;
+                Some(date)
+                    .filter(|date| self.day_matches(*date))
+                    .into_iter()
+                    .collect()
+            }
+            Frequency::Weekly => {
+                let offset = (7 + start.weekday().num_days_from_monday()
+                    - self.week_start.num_days_from_monday())
+                    % 7;
+                let week = start.checked_add_signed(Duration::days(step * 7 - offset as i64))?;
+                (0..7)
+                    .filter_map(|day| week.checked_add_signed(Duration::days(day)))
+                    .filter(|date| {
+                        if self.by_day.is_empty() {
+                            date.weekday() == start.weekday()
+                        } else {
+                            self.by_day.iter().any(|day| day.weekday == date.weekday())
+                        }
+                    })
+                    .filter(|date| self.month_matches(*date))
+                    .collect()
+            }
+            Frequency::Monthly => {
+                let months = start.year() as i64 * 12 + start.month0() as i64 + step;
+                let year = i32::try_from(months.div_euclid(12)).ok()?;
+                let month = months.rem_euclid(12) as u32 + 1;
+                if !self.by_month.is_empty() && !self.by_month.contains(&month) {
+                    return Some(vec![]);
+                }
+                self.month_dates(start, year, month)?
+            }
+            Frequency::Yearly => {
+                let year = i32::try_from(start.year() as i64 + step).ok()?;
+                let months = if self.by_month.is_empty() {
+                    vec![start.month()]
+                } else {
+                    self.by_month.to_owned()
+                };
+                let mut dates = vec![];
+                for month in months {
+                    dates.extend(self.month_dates(start, year, month)?);
+                }
+                dates
+            }
+        };
+        Some(dates)
+    }
+
+    /// Dates of a month of a monthly or yearly rule, the day of `start` without `BYDAY` and `BYMONTHDAY`.
+    fn month_dates(&self, start: NaiveDate, year: i32, month: u32) -> Option<Vec<NaiveDate>> {
+        let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+        let length = days_in_month(year, month)?;
+        if self.by_month_day.is_empty() && self.by_day.is_empty() {
+            // Months without the day are skipped, like February for the 30th.
+            return Some(
+                NaiveDate::from_ymd_opt(year, month, start.day())
+                    .into_iter()
+                    .collect(),
+            );
+        }
+        let dates = (0..length)
+            .filter_map(|day| first.checked_add_signed(Duration::days(day as i64)))
+            .filter(|date| {
+                self.by_month_day.is_empty()
+                    || self
+                        .by_month_day
+                        .iter()
+                        .any(|day| month_day(*day, length) == Some(date.day()))
+            })
+            .filter(|date| {
+                self.by_day.is_empty()
+                    || self
+                        .by_day
+                        .iter()
+                        .any(|day| weekday_matches(*day, *date, length))
+            })
+            .collect();
+        Some(dates)
+    }
+
+    /// Whether a day of a daily rule is one of the rule.
+    fn day_matches(&self, date: NaiveDate) -> bool {
+        let length = match days_in_month(date.year(), date.month()) {
+            Some(length) => length,
+            None => return false,
+        };
+        self.month_matches(date)
+            && (self.by_month_day.is_empty()
+                || self
+                    .by_month_day
+                    .iter()
+                    .any(|day| month_day(*day, length) == Some(date.day())))
+            && (self.by_day.is_empty()
+                || self.by_day.iter().any(|day| day.weekday == date.weekday()))
+    }
+
+    fn month_matches(&self, date: NaiveDate) -> bool {
+        self.by_month.is_empty() || self.by_month.contains(&date.month())
+    }
+
+    fn is_after_until(&self, local: NaiveDateTime, time: DateTime<Utc>) -> bool {
+        match self.until {
+            Some(Until::Date(until)) => local.date() > until,
+            Some(Until::Time(until)) => time > until,
+            None => false,
+        }
+    }
+}
+
+/// Occurrences of a rule in order, see `RecurrenceRule::occurrences`.
+pub struct Occurrences<'a> {
+    rule: &'a RecurrenceRule,
+    time_zone: Tz,
+    /// `dtstart` at its local time.
+    start: NaiveDateTime,
+    dtstart: DateTime<Utc>,
+    started: bool,
+    /// The next period to expand.
+    period: i64,
+    empty_periods: u32,
+    /// Dates of the current period which are left, in reverse order.
+    dates: Vec<NaiveDate>,
+    emitted: u32,
+    done: bool,
+}
+
+impl<'a> Iterator for Occurrences<'a> {
+    type Item = DateTime<Utc>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.done || self.rule.count.map_or(false, |count| self.emitted >= count) {
+                return None;
+            }
+            if !self.started {
+                // `dtstart` is the first occurrence even when the rule doesn't match it.
+                self.started = true;
+                return self.emit(self.start, self.dtstart);
+            }
+            let date = match self.dates.pop() {
+                Some(date) => date,
+                None => {
+                    if self.empty_periods >= MAX_EMPTY_PERIODS {
+                        self.done = true;
+                        continue;
+                    }
+                    match self.rule.period_dates(self.start.date(), self.period) {
+                        Some(dates) if dates.is_empty() => self.empty_periods += 1,
+                        Some(dates) => {
+                            self.empty_periods = 0;
+                            self.dates = dates.into_iter().rev().collect();
+                        }
+                        None => self.done = true,
+                    }
+                    self.period += 1;
+                    continue;
+                }
+            };
+            let local = date.and_time(self.start.time());
+            if local <= self.start {
+                continue;
+            }
+            match from_local(self.time_zone, local) {
+                Some(time) => return self.emit(local, time),
+                None => self.done = true,
+            }
+        }
+    }
+}
+
+impl<'a> Occurrences<'a> {
+    fn emit(&mut self, local: NaiveDateTime, time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if self.rule.is_after_until(local, time) {
+            self.done = true;
+            return None;
+        }
+        self.emitted += 1;
+        Some(time)
+    }
+}
+
+/// The time of a local time in the time zone.
+/// A time skipped when clocks go forward is moved forward by the gap,
+/// and of a time repeated when clocks go back the first one is taken, as RFC 5545 says.
+pub fn from_local(time_zone: Tz, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+    match time_zone.from_local_datetime(&local) {
+        LocalResult::Single(time) => Some(time.with_timezone(&Utc)),
+        LocalResult::Ambiguous(earliest, _) => Some(earliest.with_timezone(&Utc)),
+        LocalResult::None => {
+            // The offset before the gap, clocks don't change twice a day.
+            let before = time_zone
+                .from_local_datetime(&(local - Duration::days(1)))
+                .earliest()?;
+            before
+                .offset()
+                .fix()
+                .from_local_datetime(&local)
+                .single()
+                .map(|time| time.with_timezone(&Utc))
+        }
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> Option<u32> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let next = match month {
+        12 => NaiveDate::from_ymd_opt(year + 1, 1, 1)?,
+        _ => NaiveDate::from_ymd_opt(year, month + 1, 1)?,
+    };
+    Some(next.signed_duration_since(first).num_days() as u32)
+}
+
+/// The day of a month of a `BYMONTHDAY` day, `None` when the month is too short.
+fn month_day(day: i32, length: u32) -> Option<u32> {
+    let day = match day {
+        day if day > 0 => day,
+        day => length as i32 + 1 + day,
+    };
+    if day >= 1 && day <= length as i32 {
+        Some(day as u32)
+    } else {
+        None
+    }
+}
+
+fn weekday_matches(day: WeekdayNum, date: NaiveDate, length: u32) -> bool {
+    if day.weekday != date.weekday() {
+        return false;
+    }
+    match day.ordinal {
+        Some(ordinal) if ordinal > 0 => (date.day() as i32 - 1) / 7 + 1 == ordinal,
+        Some(ordinal) => (length as i32 - date.day() as i32) / 7 + 1 == -ordinal,
+        None => true,
+    }
+}
+
+impl FromStr for RecurrenceRule {
+    type Err = String;
+
+    fn from_str(rule: &str) -> Result<Self, Self::Err> {
+        let rule = rule.trim();
+        let rule = match rule.get(..6) {
+            Some(prefix) if prefix.eq_ignore_ascii_case("RRULE:") => &rule[6..],
+            _ => rule,
+        };
+        let mut names = vec![];
+        let mut frequency = None;
+        let mut interval = 1;
+        let mut count = None;
+        let mut until = None;
+        let mut by_day = vec![];
+        let mut by_month_day = vec![];
+        let mut by_month = vec![];
+        let mut week_start = Weekday::Mon;
+        for part in rule.split(';').filter(|part| !part.trim().is_empty()) {
+            let mut pair = part.splitn(2, '=');
+            let name = pair.next().unwrap_or_default().trim().to_ascii_uppercase();
+            let value = match pair.next() {
+                Some(value) if !value.trim().is_empty() => value.trim().to_ascii_uppercase(),
+                _ => return Err(format!("{} has no value", name)),
+            };
+            if names.contains(&name) {
+                return Err(format!("{} is given twice", name));
+            }
+            match name.as_str() {
+                "FREQ" => frequency = Some(parse_frequency(&value)?),
+                "INTERVAL" => interval = parse_number(&name, &value, 1, MAX_INTERVAL)? as u32,
+                "COUNT" => count = Some(parse_number(&name, &value, 1, MAX_COUNT)? as u32),
+                "UNTIL" => until = Some(parse_until(&value)?),
+                "BYDAY" => {
+                    by_day = value
+                        .split(',')
+                        .map(parse_weekday_num)
+                        .collect::<Result<Vec<WeekdayNum>, String>>()?
+                }
+                "BYMONTHDAY" => {
+                    by_month_day = value
+                        .split(',')
+                        .map(|day| match parse_number(&name, day, -31, 31)? {
+                            0 => Err("BYMONTHDAY can't be 0".to_owned()),
+                            day => Ok(day as i32),
+                        })
+                        .collect::<Result<Vec<i32>, String>>()?
+                }
+                "BYMONTH" => {
+                    by_month = value
+                        .split(',')
+                        .map(|month| Ok(parse_number(&name, month, 1, 12)? as u32))
+                        .collect::<Result<Vec<u32>, String>>()?
+                }
+                "WKST" => week_start = parse_weekday(&value)?,
+                _ => return Err(format!("{} is not supported", name)),
+            }
+            names.push(name);
+        }
+        let frequency = frequency.ok_or_else(|| "FREQ is required".to_owned())?;
+        if count.is_some() && until.is_some() {
+            return Err("COUNT and UNTIL can't be used together".to_owned());
+        }
+        if frequency == Frequency::Weekly && !by_month_day.is_empty() {
+            return Err("BYMONTHDAY can't be used with FREQ=WEEKLY".to_owned());
+        }
+        if frequency == Frequency::Yearly && !by_day.is_empty() && by_month.is_empty() {
+            return Err("BYDAY with FREQ=YEARLY needs BYMONTH".to_owned());
+        }
+        let monthly = frequency == Frequency::Monthly || frequency == Frequency::Yearly;
+        if !monthly && by_day.iter().any(|day| day.ordinal.is_some()) {
+            return Err("BYDAY can only have ordinals with FREQ=MONTHLY or FREQ=YEARLY".to_owned());
+        }
+        by_month.sort_unstable();
+        by_month.dedup();
+        Ok(Self {
+            frequency,
+            interval,
+            count,
+            until,
+            by_day,
+            by_month_day,
+            by_month,
+            week_start,
+        })
+    }
+}
+
+fn parse_frequency(value: &str) -> Result<Frequency, String> {
+    match value {
+        "DAILY" => Ok(Frequency::Daily),
+        "WEEKLY" => Ok(Frequency::Weekly),
+        "MONTHLY" => Ok(Frequency::Monthly),
+        "YEARLY" => Ok(Frequency::Yearly),
+        _ => Err(format!("FREQ={} is not supported", value)),
+    }
+}
+
+fn parse_number(name: &str, value: &str, min: i64, max: i64) -> Result<i64, String> {
+    match value.trim().parse::<i64>() {
+        Ok(number) if number >= min && number <= max => Ok(number),
+        _ => Err(format!(
+            "{} has to be a number between {} and {}, value: {}",
+            name, min, max, value
+        )),
+    }
+}
+
+fn parse_until(value: &str) -> Result<Until, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y%m%d") {
+        return Ok(Until::Date(date));
+    }
+    match NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%SZ") {
+        Ok(time) => Ok(Until::Time(DateTime::from_utc(time, Utc))),
+        Err(_) => Err(format!(
+            "UNTIL has to be a date or a UTC time like 20210301T090000Z, value: {}",
+            value
+        )),
+    }
+}
+
+fn parse_weekday(value: &str) -> Result<Weekday, String> {
+    WEEKDAYS
+        .iter()
+        .find(|(name, _)| *name == value)
+        .map(|(_, weekday)| *weekday)
+        .ok_or_else(|| format!("{} is not a weekday", value))
+}
+
+fn parse_weekday_num(value: &str) -> Result<WeekdayNum, String> {
+    let value = value.trim();
+    if value.len() < 2 || !value.is_char_boundary(value.len() - 2) {
+        return Err(format!("{} is not a weekday", value));
+    }
+    let (ordinal, weekday) = value.split_at(value.len() - 2);
+    let ordinal = match ordinal {
+        "" => None,
+        ordinal => match parse_number("BYDAY", ordinal.trim_start_matches('+'), -5, 5)? {
+            0 => return Err("BYDAY ordinals can't be 0".to_owned()),
+            ordinal => Some(ordinal as i32),
+        },
+    };
+    Ok(WeekdayNum {
+        ordinal,
+        weekday: parse_weekday(weekday)?,
+    })
+}
+
+fn weekday_name(weekday: Weekday) -> &'static str {
+    WEEKDAYS
+        .iter()
+        .find(|(_, day)| *day == weekday)
+        .map(|(name, _)| *name)
+        .unwrap_or_default()
+}
+
+fn join<T, F: Fn(&T) -> String>(values: &[T], format: F) -> String {
+    values.iter().map(format).collect::<Vec<String>>().join(",")
+}
+
+/// The rule in a normal form, without the defaults.
+impl fmt::Display for RecurrenceRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={}", frequency)?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={}", count)?;
+        }
+        match self.until {
+            Some(Until::Date(date)) => write!(f, ";UNTIL={}", date.format("%Y%m%d"))?,
+            Some(Until::Time(time)) => write!(f, ";UNTIL={}", time.format("%Y%m%dT%H%M%SZ"))?,
+            None => {}
+        }
+        if !self.by_month.is_empty() {
+            write!(f, ";BYMONTH={}", join(&self.by_month, u32::to_string))?;
+        }
+        if !self.by_month_day.is_empty() {
+            write!(
+                f,
+                ";BYMONTHDAY={}",
+                join(&self.by_month_day, i32::to_string)
+            )?;
+        }
+        if !self.by_day.is_empty() {
+            let by_day = join(&self.by_day, |day| match day.ordinal {
+                Some(ordinal) => format!("{}{}", ordinal, weekday_name(day.weekday)),
+                None => weekday_name(day.weekday).to_owned(),
+            });
+            write!(f, ";BYDAY={}", by_day)?;
+        }
+        if self.week_start != Weekday::Mon {
+            write!(f, ";WKST={}", weekday_name(self.week_start))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn expand(rule: &str, dtstart: &str, time_zone: Tz, take: usize) -> Vec<String> {
+        let rule = rule.parse::<RecurrenceRule>().unwrap();
+        rule.occurrences(utc(dtstart), time_zone)
+            .take(take)
+            .map(|time| time.to_rfc3339())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let err = "rrule:freq=weekly;interval=2;byday=mo,-1fr;wkst=su"
+            .parse::<RecurrenceRule>()
+            .err()
+            .unwrap();
+        assert!(err.contains("ordinals"));
+        let rule = "RRULE:freq=monthly;interval=2;byday=MO,-1FR;bymonth=3,1,3"
+            .parse::<RecurrenceRule>()
+            .unwrap();
+        assert_eq!(Frequency::Monthly, rule.frequency);
+        assert_eq!(2, rule.interval);
+        assert_eq!(vec![1, 3], rule.by_month);
+        assert_eq!(
+            WeekdayNum {
+                ordinal: Some(-1),
+                weekday: Weekday::Fri
+            },
+            rule.by_day[1]
+        );
+        assert_eq!(
+            "FREQ=MONTHLY;INTERVAL=2;BYMONTH=1,3;BYDAY=MO,-1FR",
+            rule.to_string()
+        );
+        assert_eq!(
+            "FREQ=DAILY;UNTIL=20210301T090000Z;WKST=SU",
+            "FREQ=DAILY;UNTIL=20210301T090000Z;WKST=SU"
+                .parse::<RecurrenceRule>()
+                .unwrap()
+                .to_string()
+        );
+
+        for rule in &[
+            "",
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=DAILY;FREQ=WEEKLY",
+            "FREQ=DAILY;COUNT=2;UNTIL=20210301",
+            "FREQ=DAILY;COUNT=0",
+            "FREQ=DAILY;INTERVAL",
+            "FREQ=DAILY;UNTIL=2021-03-01",
+            "FREQ=WEEKLY;BYDAY=XX",
+            "FREQ=WEEKLY;BYMONTHDAY=1",
+            "FREQ=MONTHLY;BYMONTHDAY=0",
+            "FREQ=MONTHLY;BYDAY=0MO",
+            "FREQ=MONTHLY;BYSETPOS=-1",
+            "FREQ=YEARLY;BYDAY=MO",
+            "FREQ=YEARLY;BYMONTH=13",
+        ] {
+            assert!(rule.parse::<RecurrenceRule>().is_err(), "{}", rule);
+        }
+    }
+
+    #[test]
+    fn test_daily_and_weekly() {
+        assert_eq!(
+            vec![
+                "2021-03-01T09:00:00+00:00",
+                "2021-03-03T09:00:00+00:00",
+                "2021-03-05T09:00:00+00:00",
+            ],
+            expand(
+                "FREQ=DAILY;INTERVAL=2;COUNT=3",
+                "2021-03-01T09:00:00Z",
+                Tz::UTC,
+                10
+            )
+        );
+        // Every other week on Tuesday and Sunday, weeks starting on Monday or on Sunday.
+        assert_eq!(
+            vec![
+                "2021-03-02T09:00:00+00:00",
+                "2021-03-07T09:00:00+00:00",
+                "2021-03-16T09:00:00+00:00",
+                "2021-03-21T09:00:00+00:00",
+            ],
+            expand(
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,SU",
+                "2021-03-02T09:00:00Z",
+                Tz::UTC,
+                4
+            )
+        );
+        assert_eq!(
+            vec![
+                "2021-03-02T09:00:00+00:00",
+                "2021-03-14T09:00:00+00:00",
+                "2021-03-16T09:00:00+00:00",
+                "2021-03-28T09:00:00+00:00",
+            ],
+            expand(
+                "FREQ=WEEKLY;INTERVAL=2;BYDAY=TU,SU;WKST=SU",
+                "2021-03-02T09:00:00Z",
+                Tz::UTC,
+                4
+            )
+        );
+        // Weekdays only, until the end of the local day.
+        assert_eq!(
+            vec![
+                "2021-03-05T09:00:00+00:00",
+                "2021-03-08T09:00:00+00:00",
+                "2021-03-09T09:00:00+00:00",
+            ],
+            expand(
+                "FREQ=DAILY;BYDAY=MO,TU,WE,TH,FR;UNTIL=20210309",
+                "2021-03-05T09:00:00Z",
+                Tz::UTC,
+                10
+            )
+        );
+    }
+
+    #[test]
+    fn test_monthly_and_yearly() {
+        // Months without the 31st are skipped.
+        assert_eq!(
+            vec![
+                "2021-01-31T09:00:00+00:00",
+                "2021-03-31T09:00:00+00:00",
+                "2021-05-31T09:00:00+00:00",
+            ],
+            expand("FREQ=MONTHLY", "2021-01-31T09:00:00Z", Tz::UTC, 3)
+        );
+        assert_eq!(
+            vec![
+                "2021-01-29T09:00:00+00:00",
+                "2021-02-26T09:00:00+00:00",
+                "2021-03-26T09:00:00+00:00",
+            ],
+            expand(
+                "FREQ=MONTHLY;BYDAY=-1FR",
+                "2021-01-29T09:00:00Z",
+                Tz::UTC,
+                3
+            )
+        );
+        assert_eq!(
+            vec![
+                "2021-01-01T09:00:00+00:00",
+                "2021-01-31T09:00:00+00:00",
+                "2021-02-01T09:00:00+00:00",
+                "2021-02-28T09:00:00+00:00",
+            ],
+            expand(
+                "FREQ=MONTHLY;BYMONTHDAY=1,-1",
+                "2021-01-01T09:00:00Z",
+                Tz::UTC,
+                4
+            )
+        );
+        // The first Monday of March and September.
+        assert_eq!(
+            vec![
+                "2021-03-01T09:00:00+00:00",
+                "2021-09-06T09:00:00+00:00",
+                "2022-03-07T09:00:00+00:00",
+            ],
+            expand(
+                "FREQ=YEARLY;BYMONTH=3,9;BYDAY=1MO",
+                "2021-03-01T09:00:00Z",
+                Tz::UTC,
+                3
+            )
+        );
+        // Leap days only come every four years.
+        assert_eq!(
+            vec!["2020-02-29T09:00:00+00:00", "2024-02-29T09:00:00+00:00"],
+            expand("FREQ=YEARLY", "2020-02-29T09:00:00Z", Tz::UTC, 2)
+        );
+        assert_eq!(
+            vec!["2021-02-01T09:00:00+00:00"],
+            expand(
+                "FREQ=YEARLY;BYMONTH=2;BYMONTHDAY=30",
+                "2021-02-01T09:00:00Z",
+                Tz::UTC,
+                2
+            )
+        );
+    }
+
+    #[test]
+    fn test_dst_transitions() {
+        // 09:00 in Berlin stays 09:00 when clocks go forward on March 28th and back on October 31st.
+        assert_eq!(
+            vec![
+                "2021-03-27T08:00:00+00:00",
+                "2021-03-28T07:00:00+00:00",
+                "2021-03-29T07:00:00+00:00",
+            ],
+            expand(
+                "FREQ=DAILY",
+                "2021-03-27T08:00:00Z",
+                chrono_tz::Europe::Berlin,
+                3
+            )
+        );
+        assert_eq!(
+            vec!["2021-10-30T07:00:00+00:00", "2021-10-31T08:00:00+00:00"],
+            expand(
+                "FREQ=DAILY",
+                "2021-10-30T07:00:00Z",
+                chrono_tz::Europe::Berlin,
+                2
+            )
+        );
+        // 02:30 doesn't exist in New York on March 14th, it moves to 03:30.
+        assert_eq!(
+            vec![
+                "2021-03-13T07:30:00+00:00",
+                "2021-03-14T07:30:00+00:00",
+                "2021-03-15T06:30:00+00:00",
+            ],
+            expand(
+                "FREQ=DAILY",
+                "2021-03-13T07:30:00Z",
+                chrono_tz::America::New_York,
+                3
+            )
+        );
+        // 01:30 happens twice on November 7th, the first one is taken.
+        assert_eq!(
+            vec![
+                "2021-11-06T05:30:00+00:00",
+                "2021-11-07T05:30:00+00:00",
+                "2021-11-08T06:30:00+00:00",
+            ],
+            expand(
+                "FREQ=DAILY",
+                "2021-11-06T05:30:00Z",
+                chrono_tz::America::New_York,
+                3
+            )
+        );
+        // A weekly rule keeps its local time and weekday across the transition too.
+        assert_eq!(
+            vec!["2021-03-22T08:00:00+00:00", "2021-03-29T07:00:00+00:00"],
+            expand(
+                "FREQ=WEEKLY",
+                "2021-03-22T08:00:00Z",
+                chrono_tz::Europe::Berlin,
+                2
+            )
+        );
+    }
+
+    #[test]
+    fn test_next_after() {
+        let rule = "FREQ=WEEKLY;COUNT=3".parse::<RecurrenceRule>().unwrap();
+        let dtstart = utc("2021-03-01T09:00:00Z");
+        assert_eq!(
+            Some(utc("2021-03-08T09:00:00Z")),
+            rule.next_after(dtstart, Tz::UTC, dtstart)
+        );
+        // A late completion gets the occurrence after it, not the missed one.
+        assert_eq!(
+            Some(utc("2021-03-15T09:00:00Z")),
+            rule.next_after(dtstart, Tz::UTC, utc("2021-03-10T00:00:00Z"))
+        );
+        assert_eq!(
+            None,
+            rule.next_after(dtstart, Tz::UTC, utc("2021-03-15T09:00:00Z"))
+        );
+        assert!(from_local(
+            chrono_tz::Europe::Berlin,
+            NaiveDate::from_ymd(2021, 3, 28).and_hms(2, 30, 0)
+        )
+        .is_some());
+    }
+}
@@ -0,0 +1,23 @@
+use crate::domains::entities::audit_event::{AuditEvent, AuditEventFilter};
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+
+#[async_trait]
+pub trait AuditEventRepository: DynClone {
+    async fn create_audit_event(&self, audit_event: AuditEvent) -> anyhow::Result<AuditEvent>;
+
+    /// The events which match the filter, the latest first.
+    async fn get_audit_events(
+        &self,
+        filter: &AuditEventFilter,
+        offset: i64,
+        limit: i64,
+    ) -> anyhow::Result<Vec<AuditEvent>>;
+
+    async fn count_audit_events(&self, filter: &AuditEventFilter) -> anyhow::Result<i64>;
+
+    /// Every event about the user, the oldest first.
+    async fn get_user_audit_events(&self, user_id: i32) -> anyhow::Result<Vec<AuditEvent>>;
+}
+
+dyn_clone::clone_trait_object!(AuditEventRepository);
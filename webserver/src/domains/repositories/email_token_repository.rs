@@ -0,0 +1,34 @@
+use crate::domains::entities::email_token::EmailToken;
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+
+#[async_trait]
+pub trait EmailTokenRepository: DynClone {
+    /// A token of another purpose isn't found, so a link only works for what it was sent for.
+    async fn get_email_token_by_hash(
+        &self,
+        purpose: String,
+        token_hash: String,
+    ) -> anyhow::Result<Option<EmailToken>>;
+
+    async fn get_user_email_tokens(&self, user_id: i32) -> anyhow::Result<Vec<EmailToken>>;
+
+    async fn create_email_token(&self, token: EmailToken) -> anyhow::Result<EmailToken>;
+
+    /// Mark the token as used, returns false if it was used already.
+    async fn use_email_token(
+        &self,
+        id: i32,
+        used_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+
+    /// Void the other links of the purpose sent to the user.
+    async fn use_user_email_tokens(
+        &self,
+        user_id: i32,
+        purpose: String,
+        used_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+}
+
+dyn_clone::clone_trait_object!(EmailTokenRepository);
@@ -0,0 +1,33 @@
+use crate::domains::entities::invite_code::{InviteCode, InviteRedemption};
+use crate::domains::entities::user::User;
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+
+#[async_trait]
+pub trait InviteCodeRepository: DynClone {
+    async fn get_invite_codes(&self) -> anyhow::Result<Vec<InviteCode>>;
+
+    async fn create_invite_code(&self, invite_code: InviteCode) -> anyhow::Result<InviteCode>;
+
+    async fn revoke_invite_code(
+        &self,
+        id: i32,
+        revoked_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+
+    async fn get_invite_redemptions(
+        &self,
+        invite_code_id: i32,
+    ) -> anyhow::Result<Vec<InviteRedemption>>;
+
+    /// Consume a use of the invite code and create the user together,
+    /// returns `None` without creating the user if the code is unknown, used up, expired or revoked.
+    async fn create_user_with_invite_code(
+        &self,
+        user: User,
+        code_hash: String,
+        redeemed_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<User>>;
+}
+
+dyn_clone::clone_trait_object!(InviteCodeRepository);
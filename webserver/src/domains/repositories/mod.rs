@@ -1,2 +1,15 @@
+pub mod audit_event_repository;
+pub mod email_token_repository;
+pub mod invite_code_repository;
+pub mod personal_access_token_repository;
+pub mod recovery_code_repository;
+pub mod refresh_token_repository;
+pub mod session_repository;
+pub mod tag_repository;
+pub mod todo_list_member_repository;
+pub mod todo_list_repository;
 pub mod todo_repository;
+pub mod todo_series_repository;
+pub mod totp_credential_repository;
+pub mod user_identity_repository;
 pub mod user_repository;
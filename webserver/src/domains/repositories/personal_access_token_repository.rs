@@ -0,0 +1,42 @@
+use crate::domains::entities::personal_access_token::PersonalAccessToken;
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+
+#[async_trait]
+pub trait PersonalAccessTokenRepository: DynClone {
+    async fn get_personal_access_tokens(
+        &self,
+        user_id: i32,
+    ) -> anyhow::Result<Vec<PersonalAccessToken>>;
+
+    /// Every token of the user, the revoked ones too.
+    async fn get_user_personal_access_tokens(
+        &self,
+        user_id: i32,
+    ) -> anyhow::Result<Vec<PersonalAccessToken>>;
+
+    async fn get_personal_access_token_by_hash(
+        &self,
+        token_hash: String,
+    ) -> anyhow::Result<Option<PersonalAccessToken>>;
+
+    async fn create_personal_access_token(
+        &self,
+        token: PersonalAccessToken,
+    ) -> anyhow::Result<PersonalAccessToken>;
+
+    async fn touch_personal_access_token(
+        &self,
+        id: i32,
+        last_used_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+
+    async fn revoke_personal_access_token(
+        &self,
+        id: i32,
+        user_id: i32,
+        revoked_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+}
+
+dyn_clone::clone_trait_object!(PersonalAccessTokenRepository);
@@ -0,0 +1,28 @@
+use crate::domains::entities::recovery_code::RecoveryCode;
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+
+#[async_trait]
+pub trait RecoveryCodeRepository: DynClone {
+    async fn get_recovery_codes(&self, user_id: i32) -> anyhow::Result<Vec<RecoveryCode>>;
+
+    /// Replace all recovery codes of the user.
+    async fn replace_recovery_codes(
+        &self,
+        user_id: i32,
+        code_hashes: Vec<String>,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<RecoveryCode>>;
+
+    /// Mark the code as used, returns false if it is unknown or used already.
+    async fn use_recovery_code(
+        &self,
+        user_id: i32,
+        code_hash: String,
+        used_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+
+    async fn delete_user_recovery_codes(&self, user_id: i32) -> anyhow::Result<bool>;
+}
+
+dyn_clone::clone_trait_object!(RecoveryCodeRepository);
@@ -0,0 +1,49 @@
+use crate::domains::entities::refresh_token::RefreshToken;
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+
+#[async_trait]
+pub trait RefreshTokenRepository: DynClone {
+    async fn get_refresh_token_by_hash(
+        &self,
+        token_hash: String,
+    ) -> anyhow::Result<Option<RefreshToken>>;
+
+    /// Every token the user has been issued, oldest first.
+    async fn get_user_refresh_tokens(&self, user_id: i32) -> anyhow::Result<Vec<RefreshToken>>;
+
+    async fn create_refresh_token(&self, token: RefreshToken) -> anyhow::Result<RefreshToken>;
+
+    /// Mark the token as rotated, returns false if it was already used or revoked.
+    async fn use_refresh_token(
+        &self,
+        id: i32,
+        used_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+
+    async fn revoke_family(
+        &self,
+        family: String,
+        revoked_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+
+    /// Revoke every family of the user, which ends all of their sessions.
+    async fn revoke_user_refresh_tokens(
+        &self,
+        user_id: i32,
+        revoked_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+
+    /// Revoke every family of the user but one, which ends their other sessions.
+    async fn revoke_other_refresh_tokens(
+        &self,
+        user_id: i32,
+        family: String,
+        revoked_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+
+    /// A family without tokens, like one of a deleted user, counts as revoked.
+    async fn is_family_revoked(&self, family: String) -> anyhow::Result<bool>;
+}
+
+dyn_clone::clone_trait_object!(RefreshTokenRepository);
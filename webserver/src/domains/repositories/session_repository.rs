@@ -0,0 +1,36 @@
+use crate::domains::entities::session::Session;
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+
+#[async_trait]
+pub trait SessionRepository: DynClone {
+    async fn create_session(&self, session: Session) -> anyhow::Result<Session>;
+
+    /// Every session of the user, ended ones too.
+    async fn get_user_sessions(&self, user_id: i32) -> anyhow::Result<Vec<Session>>;
+
+    async fn get_session_by_family(&self, family: String) -> anyhow::Result<Option<Session>>;
+
+    /// The session unless it has ended, its family has to hold an unrevoked and unexpired refresh token.
+    async fn get_active_session(
+        &self,
+        id: i32,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<Session>>;
+
+    /// Sessions of the user which haven't ended, the last seen first.
+    async fn get_active_user_sessions(
+        &self,
+        user_id: i32,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<Session>>;
+
+    async fn touch_session(
+        &self,
+        id: i32,
+        ip_address: Option<String>,
+        last_seen_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+}
+
+dyn_clone::clone_trait_object!(SessionRepository);
@@ -0,0 +1,48 @@
+use crate::domains::entities::tag::{Tag, TodoTag};
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+
+#[async_trait]
+pub trait TagRepository: DynClone {
+    /// Tags of the user by name.
+    async fn get_user_tags(&self, user_id: i32) -> anyhow::Result<Vec<Tag>>;
+
+    async fn get_tag_by_id(&self, id: i32, user_id: i32) -> anyhow::Result<Option<Tag>>;
+
+    /// Returns `None` when the user has a tag with the name already.
+    async fn create_tag(&self, tag: Tag) -> anyhow::Result<Option<Tag>>;
+
+    /// Returns `None` when the tag isn't found or another tag of the user has the name.
+    async fn update_tag(
+        &self,
+        id: i32,
+        user_id: i32,
+        name: String,
+        color: String,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<Tag>>;
+
+    async fn delete_tag(&self, id: i32, user_id: i32) -> anyhow::Result<bool>;
+
+    /// Move the todos of the source tag to the target tag and delete the source, in a transaction.
+    async fn merge_tags(
+        &self,
+        source_id: i32,
+        target_id: i32,
+        user_id: i32,
+        merged_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<Tag>>;
+
+    /// Tags of the user on the todo, by name.
+    async fn get_todo_tags(&self, todo_id: i32, user_id: i32) -> anyhow::Result<Vec<Tag>>;
+
+    /// Every tag the user put on todos.
+    async fn get_user_todo_tags(&self, user_id: i32) -> anyhow::Result<Vec<TodoTag>>;
+
+    /// Returns `false` when the tag is on the todo already.
+    async fn add_todo_tag(&self, todo_tag: TodoTag) -> anyhow::Result<bool>;
+
+    async fn remove_todo_tag(&self, todo_id: i32, tag_id: i32) -> anyhow::Result<bool>;
+}
+
+dyn_clone::clone_trait_object!(TagRepository);
@@ -0,0 +1,41 @@
+use crate::domains::entities::todo_list_member::TodoListMember;
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+
+#[async_trait]
+pub trait TodoListMemberRepository: DynClone {
+    /// The membership of the user, accepted or not.
+    async fn get_todo_list_member(
+        &self,
+        list_id: i32,
+        user_id: i32,
+    ) -> anyhow::Result<Option<TodoListMember>>;
+
+    /// Members and pending invitations of the list, the owner first.
+    async fn get_todo_list_members(&self, list_id: i32) -> anyhow::Result<Vec<TodoListMember>>;
+
+    /// Invitations the user hasn't accepted yet.
+    async fn get_user_invitations(&self, user_id: i32) -> anyhow::Result<Vec<TodoListMember>>;
+
+    /// Returns `false` when the user is a member or invited already.
+    async fn create_todo_list_member(&self, member: TodoListMember) -> anyhow::Result<bool>;
+
+    async fn accept_invitation(
+        &self,
+        list_id: i32,
+        user_id: i32,
+        accepted_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+
+    async fn update_member_role(
+        &self,
+        list_id: i32,
+        user_id: i32,
+        role: String,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+
+    async fn delete_todo_list_member(&self, list_id: i32, user_id: i32) -> anyhow::Result<bool>;
+}
+
+dyn_clone::clone_trait_object!(TodoListMemberRepository);
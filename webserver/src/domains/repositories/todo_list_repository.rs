@@ -0,0 +1,29 @@
+use crate::domains::entities::todo_list::TodoList;
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+
+#[async_trait]
+pub trait TodoListRepository: DynClone {
+    /// Lists the user owns or accepted to share, their default one first.
+    async fn get_user_todo_lists(&self, user_id: i32) -> anyhow::Result<Vec<TodoList>>;
+
+    async fn get_todo_list_by_id(&self, id: i32) -> anyhow::Result<Option<TodoList>>;
+
+    async fn get_default_todo_list(&self, user_id: i32) -> anyhow::Result<Option<TodoList>>;
+
+    /// Create the list with its user as the owner.
+    /// Returns `None` when the list is a default one and the user has one already.
+    async fn create_todo_list(&self, todo_list: TodoList) -> anyhow::Result<Option<TodoList>>;
+
+    async fn rename_todo_list(
+        &self,
+        id: i32,
+        name: String,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<TodoList>>;
+
+    /// Delete the list with its todos and members.
+    async fn delete_todo_list(&self, id: i32) -> anyhow::Result<bool>;
+}
+
+dyn_clone::clone_trait_object!(TodoListRepository);
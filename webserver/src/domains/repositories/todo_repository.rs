@@ -1,31 +1,43 @@
-use crate::domains::entities::todo::Todo;
+use crate::domains::entities::todo::{Todo, TodoFilter};
 use async_trait::async_trait;
 use dyn_clone::DynClone;
 
 #[async_trait]
 pub trait TodoRepository: DynClone {
+    /// Todos the user created in every list.
     async fn get_all_todos(&self, user_id: i32) -> anyhow::Result<Vec<Todo>>;
 
+    /// Todos of the list which match the filter, todos are overdue when they are due before `now`.
+    async fn get_list_todos(
+        &self,
+        list_id: i32,
+        filter: &TodoFilter,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<Todo>>;
+
     async fn get_todo_by_id(&self, id: i32) -> anyhow::Result<Option<Todo>>;
 
     async fn create_todo(&self, todo: Todo) -> anyhow::Result<Todo>;
 
-    async fn update_todo(&self, todo: Todo) -> anyhow::Result<Todo>;
+    async fn update_todo(&self, todo: Todo) -> anyhow::Result<Option<Todo>>;
 
     async fn toggle_complete(
         &self,
         id: i32,
+        updated_by: i32,
         updated_at: chrono::DateTime<chrono::Utc>,
     ) -> anyhow::Result<bool>;
 
     async fn toggle_all_complete(
         &self,
+        list_id: i32,
+        updated_by: i32,
         updated_at: chrono::DateTime<chrono::Utc>,
     ) -> anyhow::Result<bool>;
 
     async fn delete_todo(&self, id: i32) -> anyhow::Result<bool>;
 
-    async fn delete_completed_todo(&self) -> anyhow::Result<bool>;
+    async fn delete_completed_todo(&self, list_id: i32) -> anyhow::Result<bool>;
 }
 
 dyn_clone::clone_trait_object!(TodoRepository);
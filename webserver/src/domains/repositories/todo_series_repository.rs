@@ -0,0 +1,37 @@
+use crate::domains::entities::todo::Todo;
+use crate::domains::entities::todo_series::TodoSeries;
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+
+#[async_trait]
+pub trait TodoSeriesRepository: DynClone {
+    async fn get_todo_series(&self, id: i32) -> anyhow::Result<Option<TodoSeries>>;
+
+    /// Series of the todos the user created.
+    async fn get_user_todo_series(&self, user_id: i32) -> anyhow::Result<Vec<TodoSeries>>;
+
+    /// Create the series and make the todo its first occurrence, in a transaction.
+    async fn create_todo_series(
+        &self,
+        series: TodoSeries,
+        todo_id: i32,
+    ) -> anyhow::Result<TodoSeries>;
+
+    /// Update the series, the body of its open occurrences and when the todo occurs,
+    /// in a transaction.
+    async fn update_todo_series(
+        &self,
+        series: TodoSeries,
+        todo_id: i32,
+        occurrence_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<TodoSeries>>;
+
+    /// Delete the series, its todos stay as plain todos.
+    async fn delete_todo_series(&self, id: i32) -> anyhow::Result<bool>;
+
+    /// Create the occurrence with the tags of the previous one, in a transaction.
+    /// Returns `None` when the previous one has its next occurrence or the series has the occurrence already.
+    async fn create_next_occurrence(&self, todo: Todo) -> anyhow::Result<Option<Todo>>;
+}
+
+dyn_clone::clone_trait_object!(TodoSeriesRepository);
@@ -0,0 +1,28 @@
+use crate::domains::entities::totp_credential::TotpCredential;
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+
+#[async_trait]
+pub trait TotpCredentialRepository: DynClone {
+    async fn get_totp_credential(&self, user_id: i32) -> anyhow::Result<Option<TotpCredential>>;
+
+    /// Replace an unconfirmed secret, returns `None` if a confirmed one exists.
+    async fn save_totp_credential(
+        &self,
+        credential: TotpCredential,
+    ) -> anyhow::Result<Option<TotpCredential>>;
+
+    async fn confirm_totp_credential(
+        &self,
+        user_id: i32,
+        step: i64,
+        confirmed_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+
+    /// Record the step of an accepted code, returns false if it or a later step was used.
+    async fn use_totp_step(&self, user_id: i32, step: i64) -> anyhow::Result<bool>;
+
+    async fn delete_totp_credential(&self, user_id: i32) -> anyhow::Result<bool>;
+}
+
+dyn_clone::clone_trait_object!(TotpCredentialRepository);
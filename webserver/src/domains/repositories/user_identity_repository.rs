@@ -0,0 +1,24 @@
+use crate::domains::entities::user::User;
+use crate::domains::entities::user_identity::UserIdentity;
+use async_trait::async_trait;
+use dyn_clone::DynClone;
+
+#[async_trait]
+pub trait UserIdentityRepository: DynClone {
+    async fn get_user_identity(
+        &self,
+        provider: String,
+        subject: String,
+    ) -> anyhow::Result<Option<UserIdentity>>;
+
+    async fn get_user_identities(&self, user_id: i32) -> anyhow::Result<Vec<UserIdentity>>;
+
+    /// Create the user and its first identity together, so a failed link doesn't leave a user behind.
+    async fn create_user_with_identity(
+        &self,
+        user: User,
+        identity: UserIdentity,
+    ) -> anyhow::Result<UserIdentity>;
+}
+
+dyn_clone::clone_trait_object!(UserIdentityRepository);
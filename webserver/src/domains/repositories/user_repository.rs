@@ -4,7 +4,10 @@ use dyn_clone::DynClone;
 
 #[async_trait]
 pub trait UserRepository: DynClone {
-    async fn get_all_users(&self) -> anyhow::Result<Vec<User>>;
+    /// A page of users in the order they signed up.
+    async fn get_users(&self, offset: i64, limit: i64) -> anyhow::Result<Vec<User>>;
+
+    async fn count_users(&self) -> anyhow::Result<i64>;
 
     async fn get_user_by_id(&self, id: i32) -> anyhow::Result<Option<User>>;
 
@@ -13,6 +16,56 @@ pub trait UserRepository: DynClone {
     async fn get_user_by_username(&self, username: String) -> anyhow::Result<Option<User>>;
 
     async fn create_user(&self, user: User) -> anyhow::Result<User>;
+
+    /// Mark the email as verified, unless the user changed it since the link was sent.
+    async fn verify_email(
+        &self,
+        id: i32,
+        email: String,
+        verified_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<User>>;
+
+    /// Set a new password hash, which also satisfies a forced password reset.
+    async fn update_password(
+        &self,
+        id: i32,
+        password_hash: String,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool>;
+
+    /// Change the username and email, a changed email has to be verified again.
+    async fn update_profile(
+        &self,
+        id: i32,
+        username: String,
+        email: Option<String>,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<User>>;
+
+    async fn update_role(
+        &self,
+        id: i32,
+        role: String,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<User>>;
+
+    /// Suspend the user at the given time, or lift the suspension with `None`.
+    async fn update_suspension(
+        &self,
+        id: i32,
+        suspended_at: Option<chrono::DateTime<chrono::Utc>>,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<User>>;
+
+    /// Make the user reset the password before logging in again.
+    async fn require_password_reset(
+        &self,
+        id: i32,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<User>>;
+
+    /// Delete the user, rows of the user are deleted with it.
+    async fn delete_user(&self, id: i32) -> anyhow::Result<bool>;
 }
 
 dyn_clone::clone_trait_object!(UserRepository);
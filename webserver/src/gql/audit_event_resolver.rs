@@ -0,0 +1,78 @@
+use crate::domains::entities::audit_event::{AuditEvent, AuditEventPage, AuditEventType};
+
+#[graphql_object]
+#[graphql(description = "A recorded authentication event or admin action")]
+impl AuditEvent {
+    #[graphql(description = "An audit event id")]
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    #[graphql(
+        name = "eventType",
+        description = "What happened, null for an event type this server doesn't know"
+    )]
+    fn event_type(&self) -> Option<AuditEventType> {
+        self.known_event_type()
+    }
+
+    #[graphql(name = "userId", description = "Id of the user the event is about")]
+    fn user_id(&self) -> Option<i32> {
+        self.user_id
+    }
+
+    #[graphql(name = "actorId", description = "Id of the admin who caused the event")]
+    fn actor_id(&self) -> Option<i32> {
+        self.actor_id
+    }
+
+    #[graphql(description = "The username, email or provider a login was tried with")]
+    fn account(&self) -> Option<&String> {
+        self.account.as_ref()
+    }
+
+    #[graphql(description = "More about the event, like the error of a failure")]
+    fn detail(&self) -> Option<&String> {
+        self.detail.as_ref()
+    }
+
+    #[graphql(name = "ipAddress", description = "IP address of the client")]
+    fn ip_address(&self) -> Option<&String> {
+        self.ip_address.as_ref()
+    }
+
+    #[graphql(name = "userAgent", description = "User agent of the client")]
+    fn user_agent(&self) -> Option<&String> {
+        self.user_agent.as_ref()
+    }
+
+    #[graphql(
+        name = "requestId",
+        description = "Id of the request, as in X-Request-Id"
+    )]
+    fn request_id(&self) -> Option<&String> {
+        self.request_id.as_ref()
+    }
+
+    #[graphql(name = "createdAt", description = "When the event happened")]
+    fn created_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.created_at
+    }
+}
+
+#[graphql_object]
+#[graphql(description = "A page of audit events")]
+impl AuditEventPage {
+    #[graphql(name = "auditEvents", description = "Audit events of the page")]
+    fn audit_events(&self) -> Vec<AuditEvent> {
+        self.audit_events.clone()
+    }
+
+    #[graphql(
+        name = "totalCount",
+        description = "The number of all audit events which match the filter"
+    )]
+    fn total_count(&self) -> i32 {
+        self.total_count as i32
+    }
+}
@@ -0,0 +1,23 @@
+use crate::domains::entities::data_export::DataExport;
+
+#[graphql_object]
+#[graphql(description = "A zip archive of the personal data of a user")]
+impl DataExport {
+    #[graphql(
+        name = "fileName",
+        description = "A suggested file name of the archive"
+    )]
+    fn file_name(&self) -> &str {
+        self.file_name.as_str()
+    }
+
+    #[graphql(name = "contentType", description = "A media type of the archive")]
+    fn content_type(&self) -> &str {
+        "application/zip"
+    }
+
+    #[graphql(description = "The archive encoded in base64")]
+    fn content(&self) -> String {
+        base64::encode(&self.content)
+    }
+}
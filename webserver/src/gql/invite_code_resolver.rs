@@ -0,0 +1,77 @@
+use crate::domains::entities::invite_code::{CreatedInviteCode, InviteCode, InviteRedemption};
+
+#[graphql_object]
+#[graphql(description = "An invite code to sign up with")]
+impl InviteCode {
+    #[graphql(description = "An invite code id")]
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    #[graphql(
+        name = "createdBy",
+        description = "Id of the admin who created the code"
+    )]
+    fn created_by(&self) -> Option<i32> {
+        self.created_by
+    }
+
+    #[graphql(
+        name = "maxUses",
+        description = "How many users can sign up with the code"
+    )]
+    fn max_uses(&self) -> i32 {
+        self.max_uses
+    }
+
+    #[graphql(
+        name = "useCount",
+        description = "How many users signed up with the code"
+    )]
+    fn use_count(&self) -> i32 {
+        self.use_count
+    }
+
+    #[graphql(name = "expiresAt", description = "An invite code expiry time")]
+    fn expires_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.expires_at
+    }
+
+    #[graphql(name = "revokedAt", description = "An invite code revoked time")]
+    fn revoked_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.revoked_at
+    }
+
+    #[graphql(name = "createdAt", description = "An invite code created time")]
+    fn created_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.created_at
+    }
+}
+
+#[graphql_object]
+#[graphql(description = "A newly created invite code")]
+impl CreatedInviteCode {
+    #[graphql(description = "The code to sign up with, it is shown only once")]
+    fn code(&self) -> &str {
+        self.code.as_str()
+    }
+
+    #[graphql(name = "inviteCode")]
+    fn invite_code(&self) -> &InviteCode {
+        &self.invite_code
+    }
+}
+
+#[graphql_object]
+#[graphql(description = "A sign up with an invite code")]
+impl InviteRedemption {
+    #[graphql(name = "userId", description = "Id of the user who signed up")]
+    fn user_id(&self) -> i32 {
+        self.user_id
+    }
+
+    #[graphql(name = "redeemedAt", description = "When the user signed up")]
+    fn redeemed_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.redeemed_at
+    }
+}
@@ -0,0 +1,19 @@
+use crate::domains::entities::login_result::LoginResult;
+use crate::domains::entities::token_pair::TokenPair;
+
+#[graphql_object]
+#[graphql(description = "Outcome of a login")]
+impl LoginResult {
+    #[graphql(description = "Tokens, when no second factor is needed")]
+    fn tokens(&self) -> Option<&TokenPair> {
+        self.tokens.as_ref()
+    }
+
+    #[graphql(
+        name = "challengeToken",
+        description = "A short-lived token to pass to verifySecondFactor with a code"
+    )]
+    fn challenge_token(&self) -> Option<&str> {
+        self.challenge_token.as_deref()
+    }
+}
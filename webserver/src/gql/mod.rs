@@ -1,35 +1,236 @@
+pub(crate) mod audit_event_resolver;
+pub(crate) mod data_export_resolver;
+pub(crate) mod invite_code_resolver;
+pub(crate) mod login_result_resolver;
 pub(crate) mod mutation;
+pub(crate) mod personal_access_token_resolver;
 pub(crate) mod query;
+pub(crate) mod session_resolver;
+pub(crate) mod tag_resolver;
+pub(crate) mod todo_list_member_resolver;
+pub(crate) mod todo_list_resolver;
 pub(crate) mod todo_resolver;
+pub(crate) mod todo_series_resolver;
+pub(crate) mod token_pair_resolver;
+pub(crate) mod totp_enrollment_resolver;
 pub(crate) mod user_resolver;
+pub(crate) mod violation_resolver;
 
 use crate::auth;
+use crate::auth::audit::request_metadata;
+use crate::auth::authorization::{self, Requirement};
+use crate::auth::rate_limit::{rate_limited, retry_after_seconds};
+use crate::auth::Principal;
+use crate::domains::entities::audit_event::{AuditEvent, AuditEventType, RequestMetadata};
+use crate::domains::entities::login_result::LoginResult;
+use crate::domains::entities::personal_access_token::Scope;
 use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::ApplicationResult;
 use crate::gql::mutation::MutationRoot;
 use crate::gql::query::QueryRoot;
 use crate::State;
 use juniper::http::{graphiql, GraphQLRequest, GraphQLResponse};
-use juniper::{Context, EmptySubscription, FieldError, IntoFieldError, RootNode, ScalarValue};
+use juniper::{
+    Context, EmptySubscription, FieldError, FieldResult, IntoFieldError, RootNode, ScalarValue,
+    Value,
+};
 use lazy_static::lazy_static;
 use std::convert::AsRef;
+use std::sync::Mutex;
+use std::time::Duration;
 use tide::http::mime;
 use tide::{Body, Request, Response, StatusCode};
 
 impl<S: ScalarValue> IntoFieldError<S> for ApplicationError {
     fn into_field_error(self) -> FieldError<S> {
         let code = self.code.as_ref();
-        FieldError::new(
-            self.message,
-            graphql_value!({
-              "code": code,
-            }),
-        )
+        let mut extensions = graphql_value!({
+          "code": code,
+        });
+        // Clients get each broken rule, not only the message.
+        if let (ErrorCode::ValidationFailed(violations), Some(object)) =
+            (&self.code, extensions.as_mut_object_value())
+        {
+            let violations = violations
+                .iter()
+                .map(|violation| {
+                    let (field, rule, message) = (
+                        violation.field.as_str(),
+                        violation.rule.as_str(),
+                        violation.message.as_str(),
+                    );
+                    graphql_value!({
+                      "field": field,
+                      "rule": rule,
+                      "message": message,
+                    })
+                })
+                .collect();
+            object.add_field("violations", Value::list(violations));
+        }
+        FieldError::new(self.message, extensions)
     }
 }
 
 pub struct GraphQLContext {
     state: State,
-    user_id: i32,
+    principal: Option<Principal>,
+    request: RequestMetadata,
+    /// Set when a resolver was throttled, the response becomes a 429.
+    retry_after: Mutex<Option<Duration>>,
+}
+
+impl GraphQLContext {
+    /// Guard for resolvers, the caller has to meet every requirement.
+    fn require(&self, requirements: &[Requirement]) -> FieldResult<&Principal> {
+        match authorization::authorize(self.principal.as_ref(), requirements) {
+            Ok(principal) => Ok(principal),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    /// Guard for resolvers which need an authenticated caller.
+    fn principal(&self) -> FieldResult<&Principal> {
+        self.require(&[])
+    }
+
+    /// Guard for resolvers which personal access tokens may call with the given scope.
+    fn authorize(&self, scope: Scope) -> FieldResult<&Principal> {
+        self.require(&[Requirement::Scope(scope)])
+    }
+
+    /// Guard for resolvers which need a login session, not a personal access token.
+    fn session(&self) -> FieldResult<&Principal> {
+        self.require(&[Requirement::Session])
+    }
+
+    /// Guard for resolvers which verify credentials of the account,
+    /// throttled attempts don't get to the password hash.
+    /// Attempts count towards the user once the account is known to belong to one.
+    async fn throttle_login(&self, account: &str, user_id: Option<i32>) -> FieldResult<()> {
+        let wait = match self
+            .state
+            .rate_limit_service
+            .check_login(self.request.ip_address.as_deref(), account, user_id)
+            .await
+        {
+            Some(wait) => wait,
+            None => return Ok(()),
+        };
+        *self.retry_after.lock().unwrap() = Some(wait);
+        let err = ApplicationError {
+            code: ErrorCode::RateLimited,
+            message: format!(
+                "too many login attempts, retry after {} seconds",
+                retry_after_seconds(wait)
+            ),
+        };
+        self.audit_login_failure(account, None, &err).await;
+        Err(err.into_field_error())
+    }
+
+    /// Guard for resolvers which mail a link, the address is throttled whether it is registered or not.
+    async fn throttle_email(&self, email: &str) -> FieldResult<()> {
+        let wait = match self
+            .state
+            .rate_limit_service
+            .check_email(self.request.ip_address.as_deref(), email)
+            .await
+        {
+            Some(wait) => wait,
+            None => return Ok(()),
+        };
+        *self.retry_after.lock().unwrap() = Some(wait);
+        Err(ApplicationError {
+            code: ErrorCode::RateLimited,
+            message: format!(
+                "too many emails asked for, retry after {} seconds",
+                retry_after_seconds(wait)
+            ),
+        }
+        .into_field_error())
+    }
+
+    /// Count a rejected login towards the lockout of the account, a successful one clears it.
+    /// Failures which aren't about the credentials, like a weak new password, don't count.
+    async fn record_login<T>(
+        &self,
+        account: &str,
+        user_id: Option<i32>,
+        result: &ApplicationResult<T>,
+    ) {
+        let rate_limit_service = &self.state.rate_limit_service;
+        match result {
+            Ok(_) => {
+                rate_limit_service
+                    .record_login_success(account, user_id)
+                    .await
+            }
+            Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                ..
+            })
+            | Err(ApplicationError {
+                code: ErrorCode::ValidationFailed(_),
+                ..
+            }) => {}
+            Err(_) => {
+                rate_limit_service
+                    .record_login_failure(account, user_id)
+                    .await
+            }
+        }
+    }
+
+    /// An audit event of the request, the rest of the fields are up to the resolver.
+    fn audit_event(&self, event_type: AuditEventType) -> AuditEvent {
+        AuditEvent::new(event_type, &self.request)
+    }
+
+    async fn audit(&self, audit_event: AuditEvent) {
+        self.state.audit_logger.record(audit_event).await;
+    }
+
+    /// Record a login which got past the credentials, it either got tokens or waits for the second factor.
+    async fn audit_login(&self, user_id: i32, method: &str, result: &LoginResult) {
+        let event_type = match result.tokens {
+            Some(_) => AuditEventType::LoginSucceeded,
+            None => AuditEventType::SecondFactorChallenged,
+        };
+        self.audit(AuditEvent {
+            user_id: Some(user_id),
+            detail: Some(method.to_owned()),
+            ..self.audit_event(event_type)
+        })
+        .await;
+    }
+
+    /// Record a rejected login with the code of the error, the user is unknown for a wrong username.
+    async fn audit_login_failure(
+        &self,
+        account: &str,
+        user_id: Option<i32>,
+        err: &ApplicationError,
+    ) {
+        self.audit(AuditEvent {
+            user_id,
+            account: Some(account.to_owned()),
+            detail: Some(err.code.as_ref().to_owned()),
+            ..self.audit_event(AuditEventType::LoginFailed)
+        })
+        .await;
+    }
+
+    /// Record an action of an admin, on a user unless it is about something else.
+    async fn audit_admin_action(&self, admin_id: i32, user_id: Option<i32>, detail: String) {
+        self.audit(AuditEvent {
+            user_id,
+            actor_id: Some(admin_id),
+            detail: Some(detail),
+            ..self.audit_event(AuditEventType::AdminAction)
+        })
+        .await;
+    }
 }
 
 impl Context for GraphQLContext {}
@@ -42,35 +243,40 @@ lazy_static! {
 
 pub async fn handle_graphql(mut request: Request<State>) -> tide::Result<impl Into<Response>> {
     let query: GraphQLRequest = request.body_json().await?;
-    let mut user_id = 0;
-    if let Some(op) = query.operation_name() {
-        if op != "SignUpUser" && op != "Login" && op != "IntrospectionQuery" {
-            let claim =
-                match auth::get_jwt_claims(request.header(tide::http::headers::AUTHORIZATION)) {
-                    Ok(c) => c,
-                    Err(err) => {
-                        println!("failed to get claim, err: {:}", err);
-                        return Ok(Response::builder(StatusCode::Unauthorized)
-                            .body(Body::from_json(&err)?)
-                            .build());
-                    }
-                };
-            user_id = claim.sub.parse::<i32>().unwrap();
+    let metadata = request_metadata(&request);
+    let principal = match auth::authenticate(
+        request.state(),
+        request.header(tide::http::headers::AUTHORIZATION),
+        &metadata,
+    )
+    .await
+    {
+        Ok(principal) => principal,
+        Err(err) => {
+            request
+                .state()
+                .audit_logger
+                .record(AuditEvent {
+                    detail: Some(format!("{}: {}", err.code.as_ref(), err.message)),
+                    ..AuditEvent::new(AuditEventType::AuthenticationFailed, &metadata)
+                })
+                .await;
+            return Ok(Response::builder(StatusCode::Unauthorized)
+                .body(Body::from_json(&err)?)
+                .build());
         }
-    } else {
-        return Ok(Response::builder(StatusCode::BadRequest)
-            .body(Body::from_json(&ApplicationError {
-                code: ErrorCode::OperationNameIsNotDefined,
-                message: "GraphQL operation name is not defined".to_owned(),
-            })?)
-            .build());
-    }
+    };
 
     let gql_ctx = GraphQLContext {
         state: request.state().clone(),
-        user_id,
+        principal,
+        request: metadata,
+        retry_after: Mutex::new(None),
     };
     let response: GraphQLResponse = query.execute(&SCHEMA, &gql_ctx).await;
+    if let Some(wait) = *gql_ctx.retry_after.lock().unwrap() {
+        return rate_limited(wait);
+    }
     let status = if response.is_ok() {
         StatusCode::Ok
     } else {
@@ -1,37 +1,91 @@
-use crate::auth::create_jwt;
+use crate::auth::authorization::ADMIN;
+use crate::auth::Claims;
+use crate::domains::entities::audit_event::{AuditEvent, AuditEventType};
+use crate::domains::entities::data_export::DataExport;
+use crate::domains::entities::invite_code::CreatedInviteCode;
+use crate::domains::entities::login_result::LoginResult;
+use crate::domains::entities::personal_access_token::{CreatedPersonalAccessToken, Scope};
+use crate::domains::entities::tag::Tag;
 use crate::domains::entities::todo::Todo;
+use crate::domains::entities::todo_list::TodoList;
+use crate::domains::entities::todo_list_member::{TodoListMember, TodoListRole};
+use crate::domains::entities::todo_series::RecurrenceScope;
+use crate::domains::entities::token_pair::TokenPair;
+use crate::domains::entities::totp_credential::TotpEnrollment;
+use crate::domains::entities::user::{Role, User};
+use crate::domains::errors::{ApplicationError, ErrorCode};
 use crate::gql::GraphQLContext;
-use juniper::{FieldResult, IntoFieldError};
+use juniper::{FieldResult, IntoFieldError, Nullable};
 
 pub struct MutationRoot;
 
 #[derive(juniper::GraphQLInputObject)]
 struct NewTodo {
     body: String,
+    #[graphql(description = "The list to add the todo to, the default list without one")]
+    list_id: Option<i32>,
+    start_at: Option<chrono::DateTime<chrono::Utc>>,
+    due_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "The todo as a whole, dates left out stay and null ones are cleared")]
 struct UpdatedTodo {
     id: i32,
     body: String,
     complete: bool,
+    start_at: Nullable<chrono::DateTime<chrono::Utc>>,
+    due_at: Nullable<chrono::DateTime<chrono::Utc>>,
+    #[graphql(
+        description = "What a change to a recurring todo is for, only this occurrence without one"
+    )]
+    scope: Option<RecurrenceScope>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+struct NewPersonalAccessToken {
+    name: String,
+    scopes: Vec<Scope>,
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "An invite code to create, it never expires without an expiry time")]
+struct NewInviteCode {
+    max_uses: i32,
+    expires_at: Option<chrono::DateTime<chrono::Utc>>,
 }
 
 #[derive(juniper::GraphQLInputObject)]
 struct NewUser {
     username: String,
     password: String,
+    email: Option<String>,
+}
+
+#[derive(juniper::GraphQLInputObject)]
+#[graphql(description = "Fields to change, the ones left out stay")]
+struct UpdatedProfile {
+    username: Option<String>,
+    email: Option<String>,
 }
 
 #[graphql_object(Context = GraphQLContext)]
 impl MutationRoot {
     #[graphql(description = "Create new todo")]
     async fn create_todo(context: &GraphQLContext, new_todo: NewTodo) -> FieldResult<Todo> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
         match context
             .state
             .todo_service
             .clone()
-            .create_todo(new_todo.body, context.user_id)
+            .create_todo(
+                new_todo.body,
+                user_id,
+                new_todo.list_id,
+                new_todo.start_at,
+                new_todo.due_at,
+            )
             .await
         {
             Ok(created) => Ok(created),
@@ -41,13 +95,35 @@ impl MutationRoot {
 
     #[graphql(description = "Update todo")]
     async fn update_todo(context: &GraphQLContext, updated_todo: UpdatedTodo) -> FieldResult<Todo> {
-        match context
-            .state
-            .todo_service
-            .clone()
-            .update_todo(updated_todo.id, updated_todo.body, updated_todo.complete)
-            .await
-        {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        let todo_service = context.state.todo_service.clone();
+        let updated = match updated_todo.scope.unwrap_or_default() {
+            RecurrenceScope::This => {
+                todo_service
+                    .update_todo(
+                        updated_todo.id,
+                        user_id,
+                        updated_todo.body,
+                        updated_todo.complete,
+                        updated_todo.start_at.explicit(),
+                        updated_todo.due_at.explicit(),
+                    )
+                    .await
+            }
+            RecurrenceScope::Series => {
+                todo_service
+                    .update_todo_series(
+                        updated_todo.id,
+                        user_id,
+                        updated_todo.body,
+                        updated_todo.complete,
+                        updated_todo.start_at.explicit(),
+                        updated_todo.due_at.explicit(),
+                    )
+                    .await
+            }
+        };
+        match updated {
             Ok(updatde) => Ok(updatde),
             Err(err) => Err(err.into_field_error()),
         }
@@ -55,19 +131,33 @@ impl MutationRoot {
 
     #[graphql(name = "toggleComplete", description = "Toggle todo complete")]
     async fn toggle_complete(context: &GraphQLContext, id: i32) -> FieldResult<bool> {
-        match context.state.todo_service.clone().toggle_complete(id).await {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .todo_service
+            .clone()
+            .toggle_complete(id, user_id)
+            .await
+        {
             Ok(ret) => Ok(ret),
             Err(err) => Err(err.into_field_error()),
         }
     }
 
-    #[graphql(name = "toggleAllComplete", description = "Toggle all todo complete")]
-    async fn toggle_all_complete(context: &GraphQLContext) -> FieldResult<bool> {
+    #[graphql(
+        name = "toggleAllComplete",
+        description = "Toggle all todo complete in a list, the default list without one"
+    )]
+    async fn toggle_all_complete(
+        context: &GraphQLContext,
+        list_id: Option<i32>,
+    ) -> FieldResult<bool> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
         match context
             .state
             .todo_service
             .clone()
-            .toggle_all_complete()
+            .toggle_all_complete(user_id, list_id)
             .await
         {
             Ok(ret) => Ok(ret),
@@ -77,40 +167,978 @@ impl MutationRoot {
 
     #[graphql(name = "deleteTodo", description = "Delete todo")]
     async fn delete_todo(context: &GraphQLContext, id: i32) -> FieldResult<bool> {
-        match context.state.todo_service.clone().delete_todo(id).await {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .todo_service
+            .clone()
+            .delete_todo(id, user_id)
+            .await
+        {
             Ok(ret) => Ok(ret),
             Err(err) => Err(err.into_field_error()),
         }
     }
 
-    #[graphql(name = "clearCompletedTodo", description = "Delete all completed todo")]
-    async fn clear_completed_todo(context: &GraphQLContext) -> FieldResult<bool> {
+    #[graphql(
+        name = "clearCompletedTodo",
+        description = "Delete all completed todo in a list, the default list without one"
+    )]
+    async fn clear_completed_todo(
+        context: &GraphQLContext,
+        list_id: Option<i32>,
+    ) -> FieldResult<bool> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
         match context
             .state
             .todo_service
             .clone()
-            .clear_completed_todo()
+            .clear_completed_todo(user_id, list_id)
+            .await
+        {
+            Ok(ret) => Ok(ret),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(name = "createTodoList", description = "Create a todo list")]
+    async fn create_todo_list(context: &GraphQLContext, name: String) -> FieldResult<TodoList> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .todo_list_service
+            .create_todo_list(user_id, name)
+            .await
+        {
+            Ok(created) => Ok(created),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(name = "renameTodoList", description = "Rename a todo list")]
+    async fn rename_todo_list(
+        context: &GraphQLContext,
+        id: i32,
+        name: String,
+    ) -> FieldResult<TodoList> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .todo_list_service
+            .rename_todo_list(id, user_id, name)
+            .await
+        {
+            Ok(renamed) => Ok(renamed),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "deleteTodoList",
+        description = "Delete a todo list with its todos, the default list can't be deleted"
+    )]
+    async fn delete_todo_list(context: &GraphQLContext, id: i32) -> FieldResult<bool> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .todo_list_service
+            .delete_todo_list(id, user_id)
+            .await
+        {
+            Ok(ret) => Ok(ret),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "createTag",
+        description = "Create a tag, a gray one without a color"
+    )]
+    async fn create_tag(
+        context: &GraphQLContext,
+        name: String,
+        color: Option<String>,
+    ) -> FieldResult<Tag> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .tag_service
+            .create_tag(user_id, name, color)
             .await
         {
+            Ok(created) => Ok(created),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "updateTag",
+        description = "Rename a tag or change its color, a name of another tag is refused"
+    )]
+    async fn update_tag(
+        context: &GraphQLContext,
+        id: i32,
+        name: String,
+        color: Option<String>,
+    ) -> FieldResult<Tag> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .tag_service
+            .update_tag(id, user_id, name, color)
+            .await
+        {
+            Ok(updated) => Ok(updated),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "deleteTag",
+        description = "Delete a tag, it is taken off every todo"
+    )]
+    async fn delete_tag(context: &GraphQLContext, id: i32) -> FieldResult<bool> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context.state.tag_service.delete_tag(id, user_id).await {
             Ok(ret) => Ok(ret),
             Err(err) => Err(err.into_field_error()),
         }
     }
 
-    #[graphql(name = "signUp", description = "Sign up user")]
-    async fn sing_up(context: &GraphQLContext, new_user: NewUser) -> FieldResult<String> {
+    #[graphql(
+        name = "mergeTags",
+        description = "Put the target tag on the todos of the source tag and delete the source tag"
+    )]
+    async fn merge_tags(
+        context: &GraphQLContext,
+        source_id: i32,
+        target_id: i32,
+    ) -> FieldResult<Tag> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .tag_service
+            .merge_tags(source_id, target_id, user_id)
+            .await
+        {
+            Ok(merged) => Ok(merged),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(name = "addTagToTodo", description = "Put a tag on a todo")]
+    async fn add_tag_to_todo(
+        context: &GraphQLContext,
+        todo_id: i32,
+        tag_id: i32,
+    ) -> FieldResult<bool> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .todo_service
+            .add_tag(todo_id, user_id, tag_id)
+            .await
+        {
+            Ok(ret) => Ok(ret),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(name = "removeTagFromTodo", description = "Take a tag off a todo")]
+    async fn remove_tag_from_todo(
+        context: &GraphQLContext,
+        todo_id: i32,
+        tag_id: i32,
+    ) -> FieldResult<bool> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .todo_service
+            .remove_tag(todo_id, user_id, tag_id)
+            .await
+        {
+            Ok(ret) => Ok(ret),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "setTodoRecurrence",
+        description = "Make a todo recur by an RFC 5545 RRULE in an IANA time zone, UTC without one, counting from its due time, no rule stops it"
+    )]
+    async fn set_todo_recurrence(
+        context: &GraphQLContext,
+        todo_id: i32,
+        rrule: Option<String>,
+        time_zone: Option<String>,
+    ) -> FieldResult<Todo> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .todo_service
+            .set_recurrence(todo_id, user_id, rrule, time_zone)
+            .await
+        {
+            Ok(todo) => Ok(todo),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "inviteToTodoList",
+        description = "Invite a user to share a todo list, only its owner can"
+    )]
+    async fn invite_to_todo_list(
+        context: &GraphQLContext,
+        list_id: i32,
+        username: String,
+        role: TodoListRole,
+    ) -> FieldResult<TodoListMember> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .todo_list_service
+            .invite_member(list_id, user_id, username, role)
+            .await
+        {
+            Ok(member) => Ok(member),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "acceptTodoListInvitation",
+        description = "Accept an invitation to share a todo list"
+    )]
+    async fn accept_todo_list_invitation(
+        context: &GraphQLContext,
+        list_id: i32,
+    ) -> FieldResult<TodoList> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .todo_list_service
+            .accept_invitation(list_id, user_id)
+            .await
+        {
+            Ok(todo_list) => Ok(todo_list),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "leaveTodoList",
+        description = "Stop sharing a todo list or decline an invitation to it, the owner can't"
+    )]
+    async fn leave_todo_list(context: &GraphQLContext, list_id: i32) -> FieldResult<bool> {
+        let user_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .todo_list_service
+            .remove_member(list_id, user_id, user_id)
+            .await
+        {
+            Ok(ret) => Ok(ret),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "removeTodoListMember",
+        description = "Remove a member or an invitation from a todo list, only its owner can"
+    )]
+    async fn remove_todo_list_member(
+        context: &GraphQLContext,
+        list_id: i32,
+        user_id: i32,
+    ) -> FieldResult<bool> {
+        let owner_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .todo_list_service
+            .remove_member(list_id, owner_id, user_id)
+            .await
+        {
+            Ok(ret) => Ok(ret),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "setTodoListMemberRole",
+        description = "Change the role of a member of a todo list, only its owner can"
+    )]
+    async fn set_todo_list_member_role(
+        context: &GraphQLContext,
+        list_id: i32,
+        user_id: i32,
+        role: TodoListRole,
+    ) -> FieldResult<TodoListMember> {
+        let owner_id = context.authorize(Scope::TodosWrite)?.user_id;
+        match context
+            .state
+            .todo_list_service
+            .set_member_role(list_id, owner_id, user_id, role)
+            .await
+        {
+            Ok(member) => Ok(member),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "signUp",
+        description = "Sign up user, an invite code is needed when registration is invite only"
+    )]
+    async fn sing_up(
+        context: &GraphQLContext,
+        new_user: NewUser,
+        invite_code: Option<String>,
+    ) -> FieldResult<TokenPair> {
+        let detail = invite_code.as_ref().map(|_| "invite code".to_owned());
         let user = match context
             .state
             .user_service
             .clone()
-            .sign_up(new_user.username, new_user.password)
+            .sign_up(
+                new_user.username,
+                new_user.password,
+                new_user.email,
+                invite_code,
+            )
             .await
         {
             Ok(created) => created,
             Err(err) => return Err(err.into_field_error()),
         };
-        match create_jwt(user.id) {
-            Ok(jwt) => Ok(jwt),
+        context
+            .audit(AuditEvent {
+                user_id: Some(user.id),
+                account: Some(user.username.to_owned()),
+                detail,
+                ..context.audit_event(AuditEventType::SignUp)
+            })
+            .await;
+        match context
+            .state
+            .token_service
+            .issue_tokens(user.id, &context.request)
+            .await
+        {
+            Ok(tokens) => Ok(tokens),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "verifyEmail",
+        description = "Verify an email with the token of the link sent to it"
+    )]
+    async fn verify_email(context: &GraphQLContext, token: String) -> FieldResult<User> {
+        match context.state.user_service.clone().verify_email(token).await {
+            Ok(user) => Ok(user),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "sendVerificationEmail",
+        description = "Send the email verification link again"
+    )]
+    async fn send_verification_email(context: &GraphQLContext) -> FieldResult<bool> {
+        let user_id = context.session()?.user_id;
+        context.throttle_email(&format!("user:{}", user_id)).await?;
+        match context
+            .state
+            .user_service
+            .clone()
+            .send_email_verification(user_id)
+            .await
+        {
+            Ok(()) => Ok(true),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "changePassword",
+        description = "Change the password with the current one, other sessions are logged out"
+    )]
+    async fn change_password(
+        context: &GraphQLContext,
+        current_password: String,
+        new_password: String,
+    ) -> FieldResult<bool> {
+        let principal = context.session()?;
+        let account = format!("user:{}", principal.user_id);
+        context
+            .throttle_login(&account, Some(principal.user_id))
+            .await?;
+        let result = context
+            .state
+            .user_service
+            .clone()
+            .change_password(
+                principal.user_id,
+                principal.family.to_owned(),
+                current_password,
+                new_password,
+            )
+            .await;
+        context
+            .record_login(&account, Some(principal.user_id), &result)
+            .await;
+        match result {
+            Ok(()) => {
+                context
+                    .audit(AuditEvent {
+                        user_id: Some(principal.user_id),
+                        detail: Some("change".to_owned()),
+                        ..context.audit_event(AuditEventType::PasswordChanged)
+                    })
+                    .await;
+                Ok(true)
+            }
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "updateProfile",
+        description = "Change the username or email, a new email has to be verified again"
+    )]
+    async fn update_profile(
+        context: &GraphQLContext,
+        updated_profile: UpdatedProfile,
+    ) -> FieldResult<User> {
+        let user_id = context.session()?.user_id;
+        match context
+            .state
+            .user_service
+            .clone()
+            .update_profile(user_id, updated_profile.username, updated_profile.email)
+            .await
+        {
+            Ok(user) => Ok(user),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "exportMyData",
+        description = "Export everything tied to the user as a zip of JSON files"
+    )]
+    async fn export_my_data(context: &GraphQLContext) -> FieldResult<DataExport> {
+        let user_id = context.session()?.user_id;
+        match context.state.data_export_service.export_user(user_id).await {
+            Ok(export) => Ok(export),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "deleteAccount",
+        description = "Delete the user with everything they own, users with a password confirm it"
+    )]
+    async fn delete_account(
+        context: &GraphQLContext,
+        password: Option<String>,
+    ) -> FieldResult<bool> {
+        let user_id = context.session()?.user_id;
+        let account = format!("user:{}", user_id);
+        context.throttle_login(&account, Some(user_id)).await?;
+        let result = context
+            .state
+            .user_service
+            .clone()
+            .delete_account(user_id, password)
+            .await;
+        context.record_login(&account, Some(user_id), &result).await;
+        match result {
+            Ok(ret) => Ok(ret),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "requestPasswordReset",
+        description = "Mail a password reset link, the response is the same whether the email is registered or not"
+    )]
+    async fn request_password_reset(context: &GraphQLContext, email: String) -> FieldResult<bool> {
+        context.throttle_email(&email).await?;
+        // Sending runs in the background, so the response time doesn't tell either.
+        let password_reset_service = context.state.password_reset_service.clone();
+        async_std::task::spawn(async move {
+            if let Err(err) = password_reset_service.request_password_reset(email).await {
+                println!("failed to request password reset, err: {:}", err);
+            }
+        });
+        Ok(true)
+    }
+
+    #[graphql(
+        name = "resetPassword",
+        description = "Set a new password with the token of a reset link"
+    )]
+    async fn reset_password(
+        context: &GraphQLContext,
+        token: String,
+        new_password: String,
+    ) -> FieldResult<bool> {
+        match context
+            .state
+            .password_reset_service
+            .reset_password(token, new_password)
+            .await
+        {
+            Ok(user_id) => {
+                context
+                    .audit(AuditEvent {
+                        user_id: Some(user_id),
+                        detail: Some("reset link".to_owned()),
+                        ..context.audit_event(AuditEventType::PasswordChanged)
+                    })
+                    .await;
+                Ok(true)
+            }
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "requestMagicLink",
+        description = "Mail a login link, the response is the same whether the email is registered or not"
+    )]
+    async fn request_magic_link(context: &GraphQLContext, email: String) -> FieldResult<bool> {
+        context.throttle_email(&email).await?;
+        let magic_link_service = context.state.magic_link_service.clone();
+        async_std::task::spawn(async move {
+            if let Err(err) = magic_link_service.request_magic_link(email).await {
+                println!("failed to request magic link, err: {:}", err);
+            }
+        });
+        Ok(true)
+    }
+
+    #[graphql(
+        name = "redeemMagicLink",
+        description = "Log in with the token of a login link, a challenge is returned when a second factor is needed"
+    )]
+    async fn redeem_magic_link(
+        context: &GraphQLContext,
+        token: String,
+    ) -> FieldResult<LoginResult> {
+        let user = match context
+            .state
+            .magic_link_service
+            .redeem_magic_link(token)
+            .await
+        {
+            Ok(user) => user,
+            Err(err) => {
+                context.audit_login_failure("magic_link", None, &err).await;
+                return Err(err.into_field_error());
+            }
+        };
+        match context
+            .state
+            .two_factor_service
+            .start_login(user.id, &context.request)
+            .await
+        {
+            Ok(result) => {
+                context.audit_login(user.id, "magic_link", &result).await;
+                Ok(result)
+            }
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "verifySecondFactor",
+        description = "Complete a login with a TOTP code or a recovery code"
+    )]
+    async fn verify_second_factor(
+        context: &GraphQLContext,
+        challenge_token: String,
+        code: String,
+    ) -> FieldResult<TokenPair> {
+        let two_factor_service = &context.state.two_factor_service;
+        // Codes are short, so guesses count against the user rather than the challenge.
+        let user_id = match two_factor_service.challenged_user_id(&challenge_token) {
+            Ok(user_id) => user_id,
+            Err(err) => return Err(err.into_field_error()),
+        };
+        let account = format!("second_factor:{}", user_id);
+        context.throttle_login(&account, None).await?;
+        let result = two_factor_service
+            .verify_second_factor(challenge_token, code, &context.request)
+            .await;
+        context.record_login(&account, None, &result).await;
+        match result {
+            Ok(tokens) => {
+                context
+                    .audit(AuditEvent {
+                        user_id: Some(user_id),
+                        detail: Some("second_factor".to_owned()),
+                        ..context.audit_event(AuditEventType::LoginSucceeded)
+                    })
+                    .await;
+                Ok(tokens)
+            }
+            Err(err) => {
+                context
+                    .audit_login_failure(&account, Some(user_id), &err)
+                    .await;
+                Err(err.into_field_error())
+            }
+        }
+    }
+
+    #[graphql(
+        name = "enrollTotp",
+        description = "Start enrolling a TOTP secret, it is enabled once confirmed with a code"
+    )]
+    async fn enroll_totp(context: &GraphQLContext) -> FieldResult<TotpEnrollment> {
+        let user_id = context.session()?.user_id;
+        match context.state.two_factor_service.enroll_totp(user_id).await {
+            Ok(enrollment) => Ok(enrollment),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "confirmTotp",
+        description = "Enable two-factor authentication, the recovery codes are returned only once"
+    )]
+    async fn confirm_totp(context: &GraphQLContext, code: String) -> FieldResult<Vec<String>> {
+        let user_id = context.session()?.user_id;
+        match context
+            .state
+            .two_factor_service
+            .confirm_totp(user_id, code)
+            .await
+        {
+            Ok(recovery_codes) => Ok(recovery_codes),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "regenerateRecoveryCodes",
+        description = "Replace the recovery codes, the new ones are returned only once"
+    )]
+    async fn regenerate_recovery_codes(
+        context: &GraphQLContext,
+        code: String,
+    ) -> FieldResult<Vec<String>> {
+        let user_id = context.session()?.user_id;
+        match context
+            .state
+            .two_factor_service
+            .regenerate_recovery_codes(user_id, code)
+            .await
+        {
+            Ok(recovery_codes) => Ok(recovery_codes),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "disableTotp",
+        description = "Disable two-factor authentication with a TOTP code or a recovery code"
+    )]
+    async fn disable_totp(context: &GraphQLContext, code: String) -> FieldResult<bool> {
+        let user_id = context.session()?.user_id;
+        match context
+            .state
+            .two_factor_service
+            .disable_totp(user_id, code)
+            .await
+        {
+            Ok(ret) => Ok(ret),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "refreshToken",
+        description = "Exchange a refresh token for a new token pair"
+    )]
+    async fn refresh_token(context: &GraphQLContext, token: String) -> FieldResult<TokenPair> {
+        match context.state.token_service.refresh_tokens(token).await {
+            Ok(tokens) => {
+                // The refresh token is opaque, the user is the one the new access token was issued for.
+                let user_id = context
+                    .state
+                    .key_ring
+                    .decode::<Claims>(&tokens.access_token)
+                    .ok()
+                    .and_then(|claims| claims.sub.parse::<i32>().ok());
+                context
+                    .audit(AuditEvent {
+                        user_id,
+                        ..context.audit_event(AuditEventType::TokenRefreshed)
+                    })
+                    .await;
+                Ok(tokens)
+            }
+            Err(err) => {
+                context
+                    .audit(AuditEvent {
+                        detail: Some(err.code.as_ref().to_owned()),
+                        ..context.audit_event(AuditEventType::TokenRefreshFailed)
+                    })
+                    .await;
+                Err(err.into_field_error())
+            }
+        }
+    }
+
+    #[graphql(description = "Revoke the refresh tokens of the current login")]
+    async fn logout(context: &GraphQLContext) -> FieldResult<bool> {
+        let principal = context.session()?;
+        let family = match principal.family.to_owned() {
+            Some(family) => family,
+            None => {
+                return Err(ApplicationError {
+                    code: ErrorCode::UnAuthenticated,
+                    message: "token is not bound to a login".to_owned(),
+                }
+                .into_field_error())
+            }
+        };
+        match context.state.token_service.revoke_family(family).await {
+            Ok(ret) => {
+                context
+                    .audit(AuditEvent {
+                        user_id: Some(principal.user_id),
+                        detail: Some("logout".to_owned()),
+                        ..context.audit_event(AuditEventType::TokenRevoked)
+                    })
+                    .await;
+                Ok(ret)
+            }
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "revokeSession",
+        description = "Sign out a session of the user, like one on a lost device"
+    )]
+    async fn revoke_session(context: &GraphQLContext, id: i32) -> FieldResult<bool> {
+        let user_id = context.session()?.user_id;
+        match context
+            .state
+            .token_service
+            .revoke_session(user_id, id)
+            .await
+        {
+            Ok(ret) => {
+                context
+                    .audit(AuditEvent {
+                        user_id: Some(user_id),
+                        detail: Some(format!("session {}", id)),
+                        ..context.audit_event(AuditEventType::TokenRevoked)
+                    })
+                    .await;
+                Ok(ret)
+            }
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "createPersonalAccessToken",
+        description = "Mint a personal access token, the token is only returned once"
+    )]
+    async fn create_personal_access_token(
+        context: &GraphQLContext,
+        new_token: NewPersonalAccessToken,
+    ) -> FieldResult<CreatedPersonalAccessToken> {
+        let user_id = context.session()?.user_id;
+        match context
+            .state
+            .personal_access_token_service
+            .create_personal_access_token(
+                user_id,
+                new_token.name,
+                new_token.scopes,
+                new_token.expires_at,
+            )
+            .await
+        {
+            Ok(created) => Ok(created),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "revokePersonalAccessToken",
+        description = "Revoke a personal access token"
+    )]
+    async fn revoke_personal_access_token(context: &GraphQLContext, id: i32) -> FieldResult<bool> {
+        let user_id = context.session()?.user_id;
+        match context
+            .state
+            .personal_access_token_service
+            .revoke_personal_access_token(id, user_id)
+            .await
+        {
+            Ok(ret) => {
+                if ret {
+                    context
+                        .audit(AuditEvent {
+                            user_id: Some(user_id),
+                            detail: Some(format!("personal access token {}", id)),
+                            ..context.audit_event(AuditEventType::TokenRevoked)
+                        })
+                        .await;
+                }
+                Ok(ret)
+            }
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "suspendUser",
+        description = "Suspend the user, who is logged out and can't log in, for admins"
+    )]
+    async fn suspend_user(context: &GraphQLContext, user_id: i32) -> FieldResult<User> {
+        let admin_id = context.require(ADMIN)?.user_id;
+        match context
+            .state
+            .admin_service
+            .suspend_user(admin_id, user_id)
+            .await
+        {
+            Ok(user) => {
+                context
+                    .audit_admin_action(admin_id, Some(user_id), "suspendUser".to_owned())
+                    .await;
+                Ok(user)
+            }
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "unsuspendUser",
+        description = "Lift the suspension of the user, for admins"
+    )]
+    async fn unsuspend_user(context: &GraphQLContext, user_id: i32) -> FieldResult<User> {
+        let admin_id = context.require(ADMIN)?.user_id;
+        match context.state.admin_service.unsuspend_user(user_id).await {
+            Ok(user) => {
+                context
+                    .audit_admin_action(admin_id, Some(user_id), "unsuspendUser".to_owned())
+                    .await;
+                Ok(user)
+            }
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "forcePasswordReset",
+        description = "Log the user out and make them reset the password by the mailed link, for admins"
+    )]
+    async fn force_password_reset(context: &GraphQLContext, user_id: i32) -> FieldResult<User> {
+        let admin_id = context.require(ADMIN)?.user_id;
+        match context
+            .state
+            .admin_service
+            .force_password_reset(user_id)
+            .await
+        {
+            Ok(user) => {
+                context
+                    .audit_admin_action(admin_id, Some(user_id), "forcePasswordReset".to_owned())
+                    .await;
+                Ok(user)
+            }
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "setUserRole",
+        description = "Change the role of the user, who has to log in again, for admins"
+    )]
+    async fn set_user_role(
+        context: &GraphQLContext,
+        user_id: i32,
+        role: Role,
+    ) -> FieldResult<User> {
+        let admin_id = context.require(ADMIN)?.user_id;
+        match context
+            .state
+            .admin_service
+            .set_role(Some(admin_id), user_id, role)
+            .await
+        {
+            Ok(user) => {
+                context
+                    .audit_admin_action(
+                        admin_id,
+                        Some(user_id),
+                        format!("setUserRole: {}", role.as_ref()),
+                    )
+                    .await;
+                Ok(user)
+            }
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "createInviteCode",
+        description = "Create an invite code, the code is only returned once, for admins"
+    )]
+    async fn create_invite_code(
+        context: &GraphQLContext,
+        new_invite_code: NewInviteCode,
+    ) -> FieldResult<CreatedInviteCode> {
+        let admin_id = context.require(ADMIN)?.user_id;
+        match context
+            .state
+            .invite_service
+            .create_invite_code(
+                Some(admin_id),
+                new_invite_code.max_uses,
+                new_invite_code.expires_at,
+            )
+            .await
+        {
+            Ok(created) => {
+                context
+                    .audit_admin_action(
+                        admin_id,
+                        None,
+                        format!("createInviteCode: {}", created.invite_code.id),
+                    )
+                    .await;
+                Ok(created)
+            }
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "revokeInviteCode",
+        description = "Revoke an invite code before it is used up, for admins"
+    )]
+    async fn revoke_invite_code(context: &GraphQLContext, id: i32) -> FieldResult<bool> {
+        let admin_id = context.require(ADMIN)?.user_id;
+        match context.state.invite_service.revoke_invite_code(id).await {
+            Ok(ret) => {
+                context
+                    .audit_admin_action(admin_id, None, format!("revokeInviteCode: {}", id))
+                    .await;
+                Ok(ret)
+            }
             Err(err) => Err(err.into_field_error()),
         }
     }
@@ -0,0 +1,60 @@
+use crate::domains::entities::personal_access_token::{
+    CreatedPersonalAccessToken, PersonalAccessToken, Scope,
+};
+
+#[graphql_object]
+#[graphql(description = "A personal access token")]
+impl PersonalAccessToken {
+    #[graphql(description = "A personal access token id")]
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    #[graphql(description = "A personal access token name")]
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    #[graphql(description = "Scopes granted to the personal access token")]
+    fn scopes(&self) -> Vec<Scope> {
+        self.granted_scopes()
+    }
+
+    #[graphql(
+        name = "expiresAt",
+        description = "A personal access token expiry time"
+    )]
+    fn expires_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.expires_at
+    }
+
+    #[graphql(
+        name = "lastUsedAt",
+        description = "A personal access token last used time"
+    )]
+    fn last_used_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.last_used_at
+    }
+
+    #[graphql(
+        name = "createdAt",
+        description = "A personal access token created time"
+    )]
+    fn created_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.created_at
+    }
+}
+
+#[graphql_object]
+#[graphql(description = "A newly minted personal access token")]
+impl CreatedPersonalAccessToken {
+    #[graphql(description = "The token to send as the bearer token, it is shown only once")]
+    fn token(&self) -> &str {
+        self.token.as_str()
+    }
+
+    #[graphql(name = "personalAccessToken")]
+    fn personal_access_token(&self) -> &PersonalAccessToken {
+        &self.personal_access_token
+    }
+}
@@ -1,44 +1,92 @@
-use crate::auth::create_jwt;
-use crate::domains::entities::todo::Todo;
-use crate::domains::entities::user::User;
+use crate::auth::authorization::ADMIN;
+use crate::domains::entities::audit_event::{AuditEventFilter, AuditEventPage};
+use crate::domains::entities::invite_code::{InviteCode, InviteRedemption, RegistrationMode};
+use crate::domains::entities::login_result::LoginResult;
+use crate::domains::entities::personal_access_token::{PersonalAccessToken, Scope};
+use crate::domains::entities::session::Session;
+use crate::domains::entities::tag::{Tag, TagFilter};
+use crate::domains::entities::todo::{Todo, TodoFilter, TodoOrder};
+use crate::domains::entities::todo_list::TodoList;
+use crate::domains::entities::todo_list_member::TodoListMember;
+use crate::domains::entities::user::UserPage;
+use crate::domains::errors::Violation;
 use crate::gql::GraphQLContext;
 use juniper::{FieldResult, IntoFieldError};
 
+const DEFAULT_PAGE_SIZE: i32 = 20;
+
 pub struct QueryRoot;
 
 #[graphql_object(Context = GraphQLContext)]
 impl QueryRoot {
     #[graphql(name = "apiVersion")]
-    fn api_version() -> &str {
-        "0.1.0"
+    fn api_version(context: &GraphQLContext) -> FieldResult<&str> {
+        context.principal()?;
+        Ok("0.1.0")
     }
 
-    #[graphql(description = "Get all Users")]
-    async fn users(context: &GraphQLContext) -> FieldResult<Vec<User>> {
-        match context.state.user_service.clone().get_all_users().await {
-            Ok(users) => Ok(users),
+    #[graphql(description = "Get a page of users in the order they signed up, for admins")]
+    async fn users(
+        context: &GraphQLContext,
+        offset: Option<i32>,
+        limit: Option<i32>,
+    ) -> FieldResult<UserPage> {
+        context.require(ADMIN)?;
+        match context
+            .state
+            .admin_service
+            .get_users(offset.unwrap_or(0), limit.unwrap_or(DEFAULT_PAGE_SIZE))
+            .await
+        {
+            Ok(page) => Ok(page),
             Err(err) => Err(err.into_field_error()),
         }
     }
 
-    #[graphql(name = "login", description = "User login")]
+    #[graphql(
+        name = "login",
+        description = "User login, a challenge is returned when a second factor is needed"
+    )]
     async fn login(
         context: &GraphQLContext,
         username: String,
         password: String,
-    ) -> FieldResult<String> {
-        let user = match context
+    ) -> FieldResult<LoginResult> {
+        let user_id = match context
             .state
             .user_service
             .clone()
-            .get_user_by_username(username, password)
+            .find_user_id_by_username(username.to_owned())
             .await
         {
-            Ok(user) => user,
+            Ok(user_id) => user_id,
             Err(err) => return Err(err.into_field_error()),
         };
-        match create_jwt(user.id) {
-            Ok(jwt) => Ok(jwt),
+        context.throttle_login(&username, user_id).await?;
+        let result = context
+            .state
+            .user_service
+            .clone()
+            .get_user_by_username(username.to_owned(), password)
+            .await;
+        context.record_login(&username, user_id, &result).await;
+        let user = match result {
+            Ok(user) => user,
+            Err(err) => {
+                context.audit_login_failure(&username, None, &err).await;
+                return Err(err.into_field_error());
+            }
+        };
+        match context
+            .state
+            .two_factor_service
+            .start_login(user.id, &context.request)
+            .await
+        {
+            Ok(result) => {
+                context.audit_login(user.id, "password", &result).await;
+                Ok(result)
+            }
             Err(err) => Err(err.into_field_error()),
         }
     }
@@ -48,34 +96,259 @@ impl QueryRoot {
         context: &GraphQLContext,
         email: String,
         password: String,
-    ) -> FieldResult<String> {
-        let user = match context
+    ) -> FieldResult<LoginResult> {
+        let user_id = match context
             .state
             .user_service
             .clone()
-            .get_user_by_email(email, password)
+            .find_user_id_by_email(email.to_owned())
             .await
         {
-            Ok(user) => user,
+            Ok(user_id) => user_id,
             Err(err) => return Err(err.into_field_error()),
         };
-        match create_jwt(user.id) {
-            Ok(jwt) => Ok(jwt),
+        context.throttle_login(&email, user_id).await?;
+        let result = context
+            .state
+            .user_service
+            .clone()
+            .get_user_by_email(email.to_owned(), password)
+            .await;
+        context.record_login(&email, user_id, &result).await;
+        let user = match result {
+            Ok(user) => user,
+            Err(err) => {
+                context.audit_login_failure(&email, None, &err).await;
+                return Err(err.into_field_error());
+            }
+        };
+        match context
+            .state
+            .two_factor_service
+            .start_login(user.id, &context.request)
+            .await
+        {
+            Ok(result) => {
+                context.audit_login(user.id, "email", &result).await;
+                Ok(result)
+            }
             Err(err) => Err(err.into_field_error()),
         }
     }
 
-    #[graphql(description = "Get all todos")]
-    async fn todos(context: &GraphQLContext) -> FieldResult<Vec<Todo>> {
+    #[graphql(
+        name = "loginProviders",
+        description = "Names of the OpenID Connect providers users can log in with"
+    )]
+    fn login_providers(context: &GraphQLContext) -> Vec<String> {
+        context.state.oidc_providers.names()
+    }
+
+    #[graphql(name = "registrationMode", description = "Who may sign up")]
+    fn registration_mode(context: &GraphQLContext) -> RegistrationMode {
+        context.state.user_service.registration_mode()
+    }
+
+    #[graphql(
+        name = "passwordViolations",
+        description = "Rules the password of a new user would break, empty when it is fine"
+    )]
+    fn password_violations(
+        context: &GraphQLContext,
+        username: String,
+        email: Option<String>,
+        password: String,
+    ) -> Vec<Violation> {
+        context
+            .state
+            .user_service
+            .password_violations(&username, email.as_deref(), &password)
+    }
+
+    #[graphql(
+        name = "twoFactorEnabled",
+        description = "Whether the user logs in with a second factor"
+    )]
+    async fn two_factor_enabled(context: &GraphQLContext) -> FieldResult<bool> {
+        let user_id = context.session()?.user_id;
+        match context.state.two_factor_service.is_enabled(user_id).await {
+            Ok(enabled) => Ok(enabled),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        description = "Get todos of a list, of the default list without one, filtered by tags and due time"
+    )]
+    async fn todos(
+        context: &GraphQLContext,
+        list_id: Option<i32>,
+        tag_filter: Option<TagFilter>,
+        due_before: Option<chrono::DateTime<chrono::Utc>>,
+        due_after: Option<chrono::DateTime<chrono::Utc>>,
+        overdue: Option<bool>,
+        order_by: Option<TodoOrder>,
+    ) -> FieldResult<Vec<Todo>> {
+        let user_id = context.authorize(Scope::TodosRead)?.user_id;
+        let filter = TodoFilter {
+            tag_filter,
+            due_before,
+            due_after,
+            overdue,
+            order_by: order_by.unwrap_or_default(),
+        };
         match context
             .state
             .todo_service
             .clone()
-            .get_all_todos(context.user_id)
+            .get_list_todos(user_id, list_id, filter)
             .await
         {
             Ok(todos) => Ok(todos),
             Err(err) => Err(err.into_field_error()),
         }
     }
+
+    #[graphql(
+        name = "todoLists",
+        description = "Get todo lists the user owns or shares, their default one first"
+    )]
+    async fn todo_lists(context: &GraphQLContext) -> FieldResult<Vec<TodoList>> {
+        let user_id = context.authorize(Scope::TodosRead)?.user_id;
+        match context
+            .state
+            .todo_list_service
+            .get_todo_lists(user_id)
+            .await
+        {
+            Ok(todo_lists) => Ok(todo_lists),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(description = "Get tags of the user by name")]
+    async fn tags(context: &GraphQLContext) -> FieldResult<Vec<Tag>> {
+        let user_id = context.authorize(Scope::TodosRead)?.user_id;
+        match context.state.tag_service.get_tags(user_id).await {
+            Ok(tags) => Ok(tags),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "todoListInvitations",
+        description = "Get invitations to todo lists the user hasn't accepted yet"
+    )]
+    async fn todo_list_invitations(context: &GraphQLContext) -> FieldResult<Vec<TodoListMember>> {
+        let user_id = context.authorize(Scope::TodosRead)?.user_id;
+        match context
+            .state
+            .todo_list_service
+            .get_invitations(user_id)
+            .await
+        {
+            Ok(invitations) => Ok(invitations),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(name = "todoList", description = "Get a todo list")]
+    async fn todo_list(context: &GraphQLContext, id: i32) -> FieldResult<TodoList> {
+        let user_id = context.authorize(Scope::TodosRead)?.user_id;
+        match context
+            .state
+            .todo_list_service
+            .get_todo_list(id, user_id)
+            .await
+        {
+            Ok(todo_list) => Ok(todo_list),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "personalAccessTokens",
+        description = "Get personal access tokens of the user"
+    )]
+    async fn personal_access_tokens(
+        context: &GraphQLContext,
+    ) -> FieldResult<Vec<PersonalAccessToken>> {
+        let user_id = context.session()?.user_id;
+        match context
+            .state
+            .personal_access_token_service
+            .get_personal_access_tokens(user_id)
+            .await
+        {
+            Ok(tokens) => Ok(tokens),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "mySessions",
+        description = "Get the sessions the user is logged in with, the last seen first"
+    )]
+    async fn my_sessions(context: &GraphQLContext) -> FieldResult<Vec<Session>> {
+        let user_id = context.session()?.user_id;
+        match context.state.token_service.get_sessions(user_id).await {
+            Ok(sessions) => Ok(sessions),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(name = "inviteCodes", description = "Get all invite codes, for admins")]
+    async fn invite_codes(context: &GraphQLContext) -> FieldResult<Vec<InviteCode>> {
+        context.require(ADMIN)?;
+        match context.state.invite_service.get_invite_codes().await {
+            Ok(invite_codes) => Ok(invite_codes),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "inviteRedemptions",
+        description = "Get who signed up with an invite code, for admins"
+    )]
+    async fn invite_redemptions(
+        context: &GraphQLContext,
+        invite_code_id: i32,
+    ) -> FieldResult<Vec<InviteRedemption>> {
+        context.require(ADMIN)?;
+        match context
+            .state
+            .invite_service
+            .get_invite_redemptions(invite_code_id)
+            .await
+        {
+            Ok(redemptions) => Ok(redemptions),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "auditEvents",
+        description = "Get a page of audit events, the latest first, for admins"
+    )]
+    async fn audit_events(
+        context: &GraphQLContext,
+        filter: Option<AuditEventFilter>,
+        offset: Option<i32>,
+        limit: Option<i32>,
+    ) -> FieldResult<AuditEventPage> {
+        context.require(ADMIN)?;
+        match context
+            .state
+            .audit_logger
+            .get_audit_events(
+                filter.unwrap_or_default(),
+                offset.unwrap_or(0),
+                limit.unwrap_or(DEFAULT_PAGE_SIZE),
+            )
+            .await
+        {
+            Ok(page) => Ok(page),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
 }
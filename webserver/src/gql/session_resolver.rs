@@ -0,0 +1,50 @@
+use crate::domains::entities::session::Session;
+use crate::gql::GraphQLContext;
+
+#[graphql_object(Context = GraphQLContext)]
+#[graphql(description = "A login of the user on a device")]
+impl Session {
+    #[graphql(description = "A session id")]
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    #[graphql(description = "A readable name of the device, like \"Firefox on Linux\"")]
+    fn device(&self) -> String {
+        self.device_name()
+    }
+
+    #[graphql(name = "userAgent", description = "User agent of the login")]
+    fn user_agent(&self) -> Option<&String> {
+        self.user_agent.as_ref()
+    }
+
+    #[graphql(
+        name = "ipAddress",
+        description = "IP address the session was last seen from"
+    )]
+    fn ip_address(&self) -> Option<&String> {
+        self.ip_address.as_ref()
+    }
+
+    #[graphql(name = "createdAt", description = "When the user logged in")]
+    fn created_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.created_at
+    }
+
+    #[graphql(
+        name = "lastSeenAt",
+        description = "When the session was last used, to the minute"
+    )]
+    fn last_seen_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.last_seen_at
+    }
+
+    #[graphql(description = "Whether it is the session of the request")]
+    fn current(&self, context: &GraphQLContext) -> bool {
+        context
+            .principal
+            .as_ref()
+            .map_or(false, |principal| principal.session_id == Some(self.id))
+    }
+}
@@ -0,0 +1,30 @@
+use crate::domains::entities::tag::Tag;
+
+#[graphql_object]
+#[graphql(description = "A tag of the user to label todos with")]
+impl Tag {
+    #[graphql(description = "A tag id")]
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    #[graphql(description = "A tag name, unique among the tags of the user")]
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    #[graphql(description = "A hex color like #1e88e5")]
+    fn color(&self) -> &str {
+        self.color.as_str()
+    }
+
+    #[graphql(name = "createdAt", description = "A tag created time")]
+    fn created_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.created_at
+    }
+
+    #[graphql(name = "updatedAt", description = "A tag updated time")]
+    fn updated_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.updated_at
+    }
+}
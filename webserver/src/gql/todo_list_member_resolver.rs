@@ -0,0 +1,51 @@
+use crate::domains::entities::todo_list_member::{TodoListMember, TodoListRole};
+
+#[graphql_object]
+#[graphql(description = "A user a todo list is shared with, or invited to share it")]
+impl TodoListMember {
+    #[graphql(name = "listId", description = "Id of the shared list")]
+    fn list_id(&self) -> i32 {
+        self.list_id
+    }
+
+    #[graphql(name = "listName", description = "Name of the shared list")]
+    fn list_name(&self) -> &str {
+        self.list_name.as_str()
+    }
+
+    #[graphql(name = "userId", description = "Id of the member")]
+    fn user_id(&self) -> i32 {
+        self.user_id
+    }
+
+    #[graphql(description = "Username of the member")]
+    fn username(&self) -> &str {
+        self.username.as_str()
+    }
+
+    #[graphql(description = "What the member may do with the list")]
+    fn role(&self) -> TodoListRole {
+        self.granted_role()
+    }
+
+    #[graphql(
+        name = "invitedBy",
+        description = "Id of the user who invited the member"
+    )]
+    fn invited_by(&self) -> Option<i32> {
+        self.invited_by
+    }
+
+    #[graphql(
+        name = "acceptedAt",
+        description = "When the invitation was accepted, null while it is pending"
+    )]
+    fn accepted_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.accepted_at
+    }
+
+    #[graphql(name = "createdAt", description = "When the member was invited")]
+    fn created_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.created_at
+    }
+}
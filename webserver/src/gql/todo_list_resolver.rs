@@ -0,0 +1,85 @@
+use crate::domains::entities::personal_access_token::Scope;
+use crate::domains::entities::todo::{Todo, TodoFilter};
+use crate::domains::entities::todo_list::TodoList;
+use crate::domains::entities::todo_list_member::{TodoListMember, TodoListRole};
+use crate::gql::GraphQLContext;
+use juniper::{FieldResult, IntoFieldError};
+
+#[graphql_object(Context = GraphQLContext)]
+#[graphql(description = "A named list of todos")]
+impl TodoList {
+    #[graphql(description = "A todo list id")]
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    #[graphql(description = "A todo list name")]
+    fn name(&self) -> &str {
+        self.name.as_str()
+    }
+
+    #[graphql(
+        name = "isDefault",
+        description = "Whether todos of the owner without a list go to this one, it can't be deleted"
+    )]
+    fn is_default(&self) -> bool {
+        self.is_default
+    }
+
+    #[graphql(name = "ownerId", description = "Id of the user who owns the list")]
+    fn owner_id(&self) -> i32 {
+        self.user_id
+    }
+
+    #[graphql(description = "Role of the user in the list")]
+    async fn role(&self, context: &GraphQLContext) -> FieldResult<TodoListRole> {
+        let user_id = context.authorize(Scope::TodosRead)?.user_id;
+        match context
+            .state
+            .todo_list_service
+            .get_role(self.id, user_id)
+            .await
+        {
+            Ok(role) => Ok(role),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(description = "Members of the list and pending invitations, the owner first")]
+    async fn members(&self, context: &GraphQLContext) -> FieldResult<Vec<TodoListMember>> {
+        let user_id = context.authorize(Scope::TodosRead)?.user_id;
+        match context
+            .state
+            .todo_list_service
+            .get_members(self.id, user_id)
+            .await
+        {
+            Ok(members) => Ok(members),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(description = "Todos of the list")]
+    async fn todos(&self, context: &GraphQLContext) -> FieldResult<Vec<Todo>> {
+        let user_id = context.authorize(Scope::TodosRead)?.user_id;
+        match context
+            .state
+            .todo_service
+            .get_list_todos(user_id, Some(self.id), TodoFilter::default())
+            .await
+        {
+            Ok(todos) => Ok(todos),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(name = "createdAt", description = "A todo list created time")]
+    fn created_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.created_at
+    }
+
+    #[graphql(name = "updatedAt", description = "A todo list updated time")]
+    fn updated_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.updated_at
+    }
+}
@@ -1,6 +1,11 @@
+use crate::domains::entities::personal_access_token::Scope;
+use crate::domains::entities::tag::Tag;
 use crate::domains::entities::todo::Todo;
+use crate::domains::entities::todo_series::TodoSeries;
+use crate::gql::GraphQLContext;
+use juniper::{FieldResult, IntoFieldError};
 
-#[graphql_object]
+#[graphql_object(Context = GraphQLContext)]
 #[graphql(description = "A todo")]
 impl Todo {
     #[graphql(description = "A todo id")]
@@ -18,6 +23,93 @@ impl Todo {
         self.complete
     }
 
+    #[graphql(
+        name = "startAt",
+        description = "When work on the todo can start, never after its due time"
+    )]
+    fn start_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.start_at
+    }
+
+    #[graphql(name = "dueAt", description = "When the todo is due")]
+    fn due_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.due_at
+    }
+
+    #[graphql(description = "Whether the todo isn't complete and its due time has passed")]
+    fn overdue(&self) -> bool {
+        self.is_overdue(chrono::Utc::now())
+    }
+
+    #[graphql(
+        name = "seriesId",
+        description = "Id of the series of a recurring todo, null for a plain todo"
+    )]
+    fn series_id(&self) -> Option<i32> {
+        self.series_id
+    }
+
+    #[graphql(
+        name = "occurrenceAt",
+        description = "When the series scheduled the occurrence, which stays when its due time is moved"
+    )]
+    fn occurrence_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.occurrence_at
+    }
+
+    #[graphql(description = "The series of a recurring todo")]
+    async fn series(&self, context: &GraphQLContext) -> FieldResult<Option<TodoSeries>> {
+        let user_id = context.authorize(Scope::TodosRead)?.user_id;
+        let series_id = match self.series_id {
+            Some(series_id) => series_id,
+            None => return Ok(None),
+        };
+        match context
+            .state
+            .todo_service
+            .get_todo_series(series_id, user_id)
+            .await
+        {
+            Ok(series) => Ok(Some(series)),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(name = "listId", description = "Id of the list the todo is in")]
+    fn list_id(&self) -> i32 {
+        self.list_id
+    }
+
+    #[graphql(description = "Tags the user put on the todo, by name")]
+    async fn tags(&self, context: &GraphQLContext) -> FieldResult<Vec<Tag>> {
+        let user_id = context.authorize(Scope::TodosRead)?.user_id;
+        match context
+            .state
+            .tag_service
+            .get_todo_tags(self.id, user_id)
+            .await
+        {
+            Ok(tags) => Ok(tags),
+            Err(err) => Err(err.into_field_error()),
+        }
+    }
+
+    #[graphql(
+        name = "createdBy",
+        description = "Id of the user who created the todo, null once they are deleted"
+    )]
+    fn created_by(&self) -> Option<i32> {
+        self.created_by
+    }
+
+    #[graphql(
+        name = "updatedBy",
+        description = "Id of the user who last changed the todo, null once they are deleted"
+    )]
+    fn updated_by(&self) -> Option<i32> {
+        self.updated_by
+    }
+
     #[graphql(name = "createdAt", description = "A todo created time")]
     fn created_at(&self) -> chrono::DateTime<chrono::Utc> {
         self.created_at
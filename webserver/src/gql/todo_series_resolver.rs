@@ -0,0 +1,43 @@
+use crate::domains::entities::todo_series::TodoSeries;
+
+#[graphql_object]
+#[graphql(description = "A recurring todo, completing an occurrence creates the next one")]
+impl TodoSeries {
+    #[graphql(description = "A todo series id")]
+    fn id(&self) -> i32 {
+        self.id
+    }
+
+    #[graphql(description = "The body every new occurrence gets")]
+    fn body(&self) -> &str {
+        self.body.as_str()
+    }
+
+    #[graphql(description = "An RFC 5545 RRULE like FREQ=WEEKLY;BYDAY=MO")]
+    fn rrule(&self) -> &str {
+        self.rrule.as_str()
+    }
+
+    #[graphql(
+        name = "timeZone",
+        description = "The IANA time zone occurrences keep their local time in"
+    )]
+    fn time_zone(&self) -> &str {
+        self.time_zone.as_str()
+    }
+
+    #[graphql(description = "The due time of the first occurrence, the rule counts from there")]
+    fn dtstart(&self) -> chrono::DateTime<chrono::Utc> {
+        self.dtstart
+    }
+
+    #[graphql(name = "createdAt", description = "A todo series created time")]
+    fn created_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.created_at
+    }
+
+    #[graphql(name = "updatedAt", description = "A todo series updated time")]
+    fn updated_at(&self) -> chrono::DateTime<chrono::Utc> {
+        self.updated_at
+    }
+}
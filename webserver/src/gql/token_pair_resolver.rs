@@ -0,0 +1,21 @@
+use crate::domains::entities::token_pair::TokenPair;
+
+#[graphql_object]
+#[graphql(description = "Tokens issued to an authenticated user")]
+impl TokenPair {
+    #[graphql(
+        name = "accessToken",
+        description = "A short-lived JWT to send as the bearer token"
+    )]
+    fn access_token(&self) -> &str {
+        self.access_token.as_str()
+    }
+
+    #[graphql(
+        name = "refreshToken",
+        description = "A single-use token to get a new token pair"
+    )]
+    fn refresh_token(&self) -> &str {
+        self.refresh_token.as_str()
+    }
+}
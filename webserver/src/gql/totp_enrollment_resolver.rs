@@ -0,0 +1,18 @@
+use crate::domains::entities::totp_credential::TotpEnrollment;
+
+#[graphql_object]
+#[graphql(description = "A TOTP secret to add to an authenticator app")]
+impl TotpEnrollment {
+    #[graphql(description = "The base32 encoded secret, for manual entry")]
+    fn secret(&self) -> &str {
+        self.secret.as_str()
+    }
+
+    #[graphql(
+        name = "otpauthUri",
+        description = "The otpauth URI to show as a QR code"
+    )]
+    fn otpauth_uri(&self) -> &str {
+        self.otpauth_uri.as_str()
+    }
+}
@@ -1,4 +1,4 @@
-use crate::domains::entities::user::User;
+use crate::domains::entities::user::{Role, User, UserPage};
 
 #[graphql_object]
 #[graphql(description = "A user")]
@@ -18,6 +18,35 @@ impl User {
         self.email.as_ref()
     }
 
+    #[graphql(
+        name = "emailVerified",
+        description = "Whether the user email is verified"
+    )]
+    fn email_verified(&self) -> bool {
+        self.email_verified_at.is_some()
+    }
+
+    #[graphql(description = "What the user is allowed to do")]
+    fn role(&self) -> Role {
+        self.granted_role()
+    }
+
+    #[graphql(
+        name = "suspendedAt",
+        description = "When the user was suspended, null unless suspended"
+    )]
+    fn suspended_at(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.suspended_at
+    }
+
+    #[graphql(
+        name = "passwordResetRequired",
+        description = "Whether the user has to reset the password before logging in"
+    )]
+    fn password_reset_required(&self) -> bool {
+        self.password_reset_required
+    }
+
     #[graphql(name = "createdAt", description = "A user created time")]
     fn created_at(&self) -> chrono::DateTime<chrono::Utc> {
         self.created_at
@@ -28,3 +57,17 @@ impl User {
         self.updated_at
     }
 }
+
+#[graphql_object]
+#[graphql(description = "A page of users")]
+impl UserPage {
+    #[graphql(description = "Users of the page")]
+    fn users(&self) -> Vec<User> {
+        self.users.clone()
+    }
+
+    #[graphql(name = "totalCount", description = "The number of all users")]
+    fn total_count(&self) -> i32 {
+        self.total_count as i32
+    }
+}
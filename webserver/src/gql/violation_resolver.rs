@@ -0,0 +1,19 @@
+use crate::domains::errors::Violation;
+
+#[graphql_object]
+#[graphql(description = "A rule which an input broke")]
+impl Violation {
+    #[graphql(description = "Input the rule is about")]
+    fn field(&self) -> &str {
+        self.field.as_str()
+    }
+
+    #[graphql(description = "Name of the rule, like minLength")]
+    fn rule(&self) -> &str {
+        self.rule.as_str()
+    }
+
+    fn message(&self) -> &str {
+        self.message.as_str()
+    }
+}
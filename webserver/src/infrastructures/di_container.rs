@@ -1,11 +1,52 @@
+use crate::domains::repositories::audit_event_repository::AuditEventRepository;
+use crate::domains::repositories::email_token_repository::EmailTokenRepository;
+use crate::domains::repositories::invite_code_repository::InviteCodeRepository;
+use crate::domains::repositories::personal_access_token_repository::PersonalAccessTokenRepository;
+use crate::domains::repositories::recovery_code_repository::RecoveryCodeRepository;
+use crate::domains::repositories::refresh_token_repository::RefreshTokenRepository;
+use crate::domains::repositories::session_repository::SessionRepository;
+use crate::domains::repositories::tag_repository::TagRepository;
+use crate::domains::repositories::todo_list_member_repository::TodoListMemberRepository;
+use crate::domains::repositories::todo_list_repository::TodoListRepository;
 use crate::domains::repositories::todo_repository::TodoRepository;
+use crate::domains::repositories::todo_series_repository::TodoSeriesRepository;
+use crate::domains::repositories::totp_credential_repository::TotpCredentialRepository;
+use crate::domains::repositories::user_identity_repository::UserIdentityRepository;
 use crate::domains::repositories::user_repository::UserRepository;
+use crate::infrastructures::repositories::audit_event_repository::PostgreSQLAuditEventRepository;
+use crate::infrastructures::repositories::email_token_repository::PostgreSQLEmailTokenRepository;
+use crate::infrastructures::repositories::invite_code_repository::PostgreSQLInviteCodeRepository;
+use crate::infrastructures::repositories::personal_access_token_repository::PostgreSQLPersonalAccessTokenRepository;
+use crate::infrastructures::repositories::recovery_code_repository::PostgreSQLRecoveryCodeRepository;
+use crate::infrastructures::repositories::refresh_token_repository::PostgreSQLRefreshTokenRepository;
+use crate::infrastructures::repositories::session_repository::PostgreSQLSessionRepository;
+use crate::infrastructures::repositories::tag_repository::PostgreSQLTagRepository;
+use crate::infrastructures::repositories::todo_list_member_repository::PostgreSQLTodoListMemberRepository;
+use crate::infrastructures::repositories::todo_list_repository::PostgreSQLTodoListRepository;
 use crate::infrastructures::repositories::todo_repository::PostgreSQLTodoRepository;
+use crate::infrastructures::repositories::todo_series_repository::PostgreSQLTodoSeriesRepository;
+use crate::infrastructures::repositories::totp_credential_repository::PostgreSQLTotpCredentialRepository;
+use crate::infrastructures::repositories::user_identity_repository::PostgreSQLUserIdentityRepository;
 use crate::infrastructures::repositories::user_repository::PostgreSQLUserRepository;
 
 pub trait DIContainer {
     fn user_repository(&self) -> Box<dyn UserRepository + Send + Sync>;
     fn todo_repository(&self) -> Box<dyn TodoRepository + Send + Sync>;
+    fn todo_list_repository(&self) -> Box<dyn TodoListRepository + Send + Sync>;
+    fn todo_list_member_repository(&self) -> Box<dyn TodoListMemberRepository + Send + Sync>;
+    fn todo_series_repository(&self) -> Box<dyn TodoSeriesRepository + Send + Sync>;
+    fn tag_repository(&self) -> Box<dyn TagRepository + Send + Sync>;
+    fn refresh_token_repository(&self) -> Box<dyn RefreshTokenRepository + Send + Sync>;
+    fn personal_access_token_repository(
+        &self,
+    ) -> Box<dyn PersonalAccessTokenRepository + Send + Sync>;
+    fn user_identity_repository(&self) -> Box<dyn UserIdentityRepository + Send + Sync>;
+    fn email_token_repository(&self) -> Box<dyn EmailTokenRepository + Send + Sync>;
+    fn totp_credential_repository(&self) -> Box<dyn TotpCredentialRepository + Send + Sync>;
+    fn recovery_code_repository(&self) -> Box<dyn RecoveryCodeRepository + Send + Sync>;
+    fn invite_code_repository(&self) -> Box<dyn InviteCodeRepository + Send + Sync>;
+    fn audit_event_repository(&self) -> Box<dyn AuditEventRepository + Send + Sync>;
+    fn session_repository(&self) -> Box<dyn SessionRepository + Send + Sync>;
 }
 
 #[derive(Clone)]
@@ -25,4 +66,84 @@ impl DIContainer for PgDIContainer {
             db: self.db.clone(),
         })
     }
+
+    fn todo_list_repository(&self) -> Box<dyn TodoListRepository + Send + Sync> {
+        Box::new(PostgreSQLTodoListRepository {
+            db: self.db.clone(),
+        })
+    }
+
+    fn todo_list_member_repository(&self) -> Box<dyn TodoListMemberRepository + Send + Sync> {
+        Box::new(PostgreSQLTodoListMemberRepository {
+            db: self.db.clone(),
+        })
+    }
+
+    fn todo_series_repository(&self) -> Box<dyn TodoSeriesRepository + Send + Sync> {
+        Box::new(PostgreSQLTodoSeriesRepository {
+            db: self.db.clone(),
+        })
+    }
+
+    fn tag_repository(&self) -> Box<dyn TagRepository + Send + Sync> {
+        Box::new(PostgreSQLTagRepository {
+            db: self.db.clone(),
+        })
+    }
+
+    fn refresh_token_repository(&self) -> Box<dyn RefreshTokenRepository + Send + Sync> {
+        Box::new(PostgreSQLRefreshTokenRepository {
+            db: self.db.clone(),
+        })
+    }
+
+    fn personal_access_token_repository(
+        &self,
+    ) -> Box<dyn PersonalAccessTokenRepository + Send + Sync> {
+        Box::new(PostgreSQLPersonalAccessTokenRepository {
+            db: self.db.clone(),
+        })
+    }
+
+    fn user_identity_repository(&self) -> Box<dyn UserIdentityRepository + Send + Sync> {
+        Box::new(PostgreSQLUserIdentityRepository {
+            db: self.db.clone(),
+        })
+    }
+
+    fn email_token_repository(&self) -> Box<dyn EmailTokenRepository + Send + Sync> {
+        Box::new(PostgreSQLEmailTokenRepository {
+            db: self.db.clone(),
+        })
+    }
+
+    fn totp_credential_repository(&self) -> Box<dyn TotpCredentialRepository + Send + Sync> {
+        Box::new(PostgreSQLTotpCredentialRepository {
+            db: self.db.clone(),
+        })
+    }
+
+    fn recovery_code_repository(&self) -> Box<dyn RecoveryCodeRepository + Send + Sync> {
+        Box::new(PostgreSQLRecoveryCodeRepository {
+            db: self.db.clone(),
+        })
+    }
+
+    fn invite_code_repository(&self) -> Box<dyn InviteCodeRepository + Send + Sync> {
+        Box::new(PostgreSQLInviteCodeRepository {
+            db: self.db.clone(),
+        })
+    }
+
+    fn audit_event_repository(&self) -> Box<dyn AuditEventRepository + Send + Sync> {
+        Box::new(PostgreSQLAuditEventRepository {
+            db: self.db.clone(),
+        })
+    }
+
+    fn session_repository(&self) -> Box<dyn SessionRepository + Send + Sync> {
+        Box::new(PostgreSQLSessionRepository {
+            db: self.db.clone(),
+        })
+    }
 }
@@ -0,0 +1,26 @@
+pub mod outbox_mailer;
+pub mod smtp_mailer;
+
+use crate::domains::mailer::Mailer;
+use crate::infrastructures::mailers::outbox_mailer::OutboxMailer;
+use crate::infrastructures::mailers::smtp_mailer::SMTPMailer;
+use std::env;
+use std::path::PathBuf;
+
+/// Send mails through `SMTP_URL` from `MAIL_FROM`,
+/// or keep them in an outbox written to `MAIL_OUTBOX_DIR` when SMTP isn't configured.
+pub fn create_mailer() -> anyhow::Result<Box<dyn Mailer + Send + Sync>> {
+    match env::var("SMTP_URL") {
+        Ok(url) if !url.is_empty() => {
+            let from = env::var("MAIL_FROM")
+                .map_err(|_| anyhow::anyhow!("MAIL_FROM must be set with SMTP_URL"))?;
+            Ok(Box::new(SMTPMailer::new(&url, &from)?))
+        }
+        _ => Ok(Box::new(OutboxMailer {
+            dir: Some(PathBuf::from(
+                env::var("MAIL_OUTBOX_DIR").unwrap_or_else(|_| "outbox".to_owned()),
+            )),
+            ..OutboxMailer::default()
+        })),
+    }
+}
@@ -0,0 +1,34 @@
+use crate::domains::entities::mail::Mail;
+use crate::domains::mailer::Mailer;
+use async_trait::async_trait;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+/// Keeps sent mails in memory, and writes them to a directory when one is given,
+/// for local development and tests.
+#[derive(Clone, Default)]
+pub struct OutboxMailer {
+    /// Mails sent so far, oldest first.
+    pub mails: Arc<Mutex<Vec<Mail>>>,
+    pub dir: Option<PathBuf>,
+}
+
+#[async_trait]
+impl Mailer for OutboxMailer {
+    async fn send(&self, mail: Mail) -> anyhow::Result<()> {
+        if let Some(dir) = self.dir.as_ref() {
+            async_std::fs::create_dir_all(dir).await?;
+            let file = dir.join(format!(
+                "{}.txt",
+                chrono::Utc::now().format("%Y%m%d%H%M%S%.f")
+            ));
+            let content = format!(
+                "To: {}\nSubject: {}\n\n{}\n",
+                mail.to, mail.subject, mail.body
+            );
+            async_std::fs::write(file, content).await?;
+        }
+        self.mails.lock().unwrap().push(mail);
+        Ok(())
+    }
+}
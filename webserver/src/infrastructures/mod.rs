@@ -1,3 +1,6 @@
 pub mod database;
 pub mod di_container;
+pub mod mailers;
+pub mod password_hashers;
+pub mod rate_limit_stores;
 pub mod repositories;
@@ -0,0 +1,114 @@
+use crate::domains::password_hasher::PasswordHasher;
+use crate::infrastructures::password_hashers::verify_password;
+use argon2::{Config, ThreadMode, Variant, Version};
+use rand::RngCore;
+
+/// Parameters OWASP recommends for Argon2id.
+pub const DEFAULT_MEMORY_KIB: u32 = 19456;
+pub const DEFAULT_ITERATIONS: u32 = 2;
+pub const DEFAULT_PARALLELISM: u32 = 1;
+
+const SALT_BYTES: usize = 16;
+const HASH_BYTES: u32 = 32;
+
+#[derive(Clone)]
+pub struct Argon2PasswordHasher {
+    memory_kib: u32,
+    iterations: u32,
+    parallelism: u32,
+    dummy_hash: String,
+}
+
+impl Argon2PasswordHasher {
+    pub fn new(memory_kib: u32, iterations: u32, parallelism: u32) -> anyhow::Result<Self> {
+        let mut hasher = Self {
+            memory_kib,
+            iterations,
+            parallelism,
+            dummy_hash: String::new(),
+        };
+        hasher.dummy_hash = hasher.hash("dummy password")?;
+        Ok(hasher)
+    }
+
+    fn config(&self) -> Config<'static> {
+        Config {
+            variant: Variant::Argon2id,
+            version: Version::Version13,
+            mem_cost: self.memory_kib,
+            time_cost: self.iterations,
+            lanes: self.parallelism,
+            thread_mode: ThreadMode::Sequential,
+            secret: &[],
+            ad: &[],
+            hash_length: HASH_BYTES,
+        }
+    }
+}
+
+impl PasswordHasher for Argon2PasswordHasher {
+    fn hash(&self, password: &str) -> anyhow::Result<String> {
+        let mut salt = [0u8; SALT_BYTES];
+        rand::thread_rng().fill_bytes(&mut salt);
+        Ok(argon2::hash_encoded(
+            password.as_bytes(),
+            &salt,
+            &self.config(),
+        )?)
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> anyhow::Result<bool> {
+        verify_password(password, hash)
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        // `$argon2id$v=19$m=19456,t=2,p=1$<salt>$<hash>`
+        let mut parts = hash.split('$').skip(1);
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some("argon2id"), Some("v=19"), Some(params)) => {
+                params
+                    != format!(
+                        "m={},t={},p={}",
+                        self.memory_kib, self.iterations, self.parallelism
+                    )
+            }
+            _ => true,
+        }
+    }
+
+    fn verify_dummy(&self, password: &str) {
+        let _ = argon2::verify_encoded(&self.dummy_hash, password.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash() {
+        let hasher = Argon2PasswordHasher::new(1024, 1, 1).unwrap();
+        let hash = hasher.hash("password").unwrap();
+        assert!(hash.starts_with("$argon2id$v=19$m=1024,t=1,p=1$"));
+        assert_ne!(hash, hasher.hash("password").unwrap());
+        assert!(hasher.verify("password", &hash).unwrap());
+        assert!(!hasher.verify("invalid", &hash).unwrap());
+        // Legacy bcrypt hashes still verify.
+        let legacy = bcrypt::hash("password", 4).unwrap();
+        assert!(hasher.verify("password", &legacy).unwrap());
+    }
+
+    #[test]
+    fn test_needs_rehash() {
+        let hasher = Argon2PasswordHasher::new(1024, 1, 1).unwrap();
+        assert!(!hasher.needs_rehash(&hasher.hash("password").unwrap()));
+        let stronger = Argon2PasswordHasher::new(2048, 1, 1).unwrap();
+        assert!(stronger.needs_rehash(&hasher.hash("password").unwrap()));
+        assert!(hasher.needs_rehash(&bcrypt::hash("password", 4).unwrap()));
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        assert!(Argon2PasswordHasher::new(1024, 0, 1).is_err());
+    }
+}
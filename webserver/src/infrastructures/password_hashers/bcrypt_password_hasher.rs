@@ -0,0 +1,67 @@
+use crate::domains::password_hasher::PasswordHasher;
+use crate::infrastructures::password_hashers::verify_password;
+
+pub const DEFAULT_COST: u32 = 10;
+
+#[derive(Clone)]
+pub struct BcryptPasswordHasher {
+    cost: u32,
+    dummy_hash: String,
+}
+
+impl BcryptPasswordHasher {
+    pub fn new(cost: u32) -> anyhow::Result<Self> {
+        Ok(Self {
+            cost,
+            dummy_hash: bcrypt::hash("dummy password", cost)?,
+        })
+    }
+}
+
+impl PasswordHasher for BcryptPasswordHasher {
+    fn hash(&self, password: &str) -> anyhow::Result<String> {
+        Ok(bcrypt::hash(password, self.cost)?)
+    }
+
+    fn verify(&self, password: &str, hash: &str) -> anyhow::Result<bool> {
+        verify_password(password, hash)
+    }
+
+    fn needs_rehash(&self, hash: &str) -> bool {
+        // `$2b$10$<salt and hash>`
+        let mut parts = hash.split('$').skip(1);
+        match (parts.next(), parts.next()) {
+            (Some(version), Some(cost)) if version.starts_with('2') => {
+                cost.parse::<u32>().map_or(true, |cost| cost != self.cost)
+            }
+            _ => true,
+        }
+    }
+
+    fn verify_dummy(&self, password: &str) {
+        let _ = bcrypt::verify(password, &self.dummy_hash);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash() {
+        let hasher = BcryptPasswordHasher::new(4).unwrap();
+        let hash = hasher.hash("password").unwrap();
+        assert!(hash.starts_with("$2b$04$"));
+        assert!(hasher.verify("password", &hash).unwrap());
+        assert!(!hasher.verify("invalid", &hash).unwrap());
+        assert!(hasher.verify("invalid", "plain").is_err());
+    }
+
+    #[test]
+    fn test_needs_rehash() {
+        let hasher = BcryptPasswordHasher::new(4).unwrap();
+        assert!(!hasher.needs_rehash(&bcrypt::hash("password", 4).unwrap()));
+        assert!(hasher.needs_rehash(&bcrypt::hash("password", 5).unwrap()));
+        assert!(hasher.needs_rehash("$argon2id$v=19$m=4096,t=1,p=1$c2FsdHNhbHQ$aGFzaA"));
+    }
+}
@@ -0,0 +1,52 @@
+pub mod argon2_password_hasher;
+pub mod bcrypt_password_hasher;
+
+use crate::domains::password_hasher::PasswordHasher;
+use crate::infrastructures::password_hashers::argon2_password_hasher::{
+    Argon2PasswordHasher, DEFAULT_ITERATIONS, DEFAULT_MEMORY_KIB, DEFAULT_PARALLELISM,
+};
+use crate::infrastructures::password_hashers::bcrypt_password_hasher::BcryptPasswordHasher;
+use std::env;
+use std::str::FromStr;
+
+/// Hash new passwords with Argon2id, or bcrypt when `PASSWORD_HASHER` is `bcrypt`.
+/// Hashes of the other algorithm still verify, and are replaced on the next login.
+pub fn create_password_hasher() -> anyhow::Result<Box<dyn PasswordHasher + Send + Sync>> {
+    match env::var("PASSWORD_HASHER").as_deref() {
+        Ok("bcrypt") => {
+            let cost = env_or("BCRYPT_COST", bcrypt_password_hasher::DEFAULT_COST)?;
+            Ok(Box::new(BcryptPasswordHasher::new(cost)?))
+        }
+        Ok("argon2id") | Ok("") | Err(_) => {
+            let memory_kib = env_or("ARGON2_MEMORY_KIB", DEFAULT_MEMORY_KIB)?;
+            let iterations = env_or("ARGON2_ITERATIONS", DEFAULT_ITERATIONS)?;
+            let parallelism = env_or("ARGON2_PARALLELISM", DEFAULT_PARALLELISM)?;
+            Ok(Box::new(Argon2PasswordHasher::new(
+                memory_kib,
+                iterations,
+                parallelism,
+            )?))
+        }
+        Ok(hasher) => Err(anyhow::anyhow!("unknown PASSWORD_HASHER: {}", hasher)),
+    }
+}
+
+/// Verify with the algorithm the prefix of the hash names.
+pub fn verify_password(password: &str, hash: &str) -> anyhow::Result<bool> {
+    if hash.starts_with("$argon2") {
+        Ok(argon2::verify_encoded(hash, password.as_bytes())?)
+    } else if hash.starts_with("$2") {
+        Ok(bcrypt::verify(password, hash)?)
+    } else {
+        Err(anyhow::anyhow!("unknown password hash format"))
+    }
+}
+
+fn env_or<T: FromStr>(name: &str, default: T) -> anyhow::Result<T> {
+    match env::var(name) {
+        Ok(value) if !value.is_empty() => value
+            .parse()
+            .map_err(|_| anyhow::anyhow!("{} is invalid: {}", name, value)),
+        _ => Ok(default),
+    }
+}
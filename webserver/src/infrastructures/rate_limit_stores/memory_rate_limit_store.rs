@@ -0,0 +1,128 @@
+use crate::domains::entities::quota::Quota;
+use crate::domains::rate_limit_store::RateLimitStore;
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Full buckets are dropped once there are this many.
+const MAX_BUCKETS: usize = 10_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated_at: Instant,
+}
+
+impl Bucket {
+    fn full(quota: Quota, now: Instant) -> Self {
+        Self {
+            tokens: quota.burst as f64,
+            updated_at: now,
+        }
+    }
+
+    fn refill(&mut self, quota: Quota, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated_at);
+        self.tokens = (self.tokens + elapsed.as_secs_f64() / quota.period.as_secs_f64())
+            .min(quota.burst as f64);
+        self.updated_at = now;
+    }
+
+    /// How long until the bucket has a token.
+    fn wait(&self, quota: Quota) -> Option<Duration> {
+        if self.tokens >= 1.0 {
+            return None;
+        }
+        Some(quota.period.mul_f64(1.0 - self.tokens))
+    }
+
+    fn is_full(&self, quota: Quota) -> bool {
+        self.tokens >= quota.burst as f64
+    }
+}
+
+/// Buckets of this process only, for a single server and tests.
+#[derive(Clone, Default)]
+pub struct MemoryRateLimitStore {
+    buckets: Arc<Mutex<HashMap<String, (Quota, Bucket)>>>,
+}
+
+impl MemoryRateLimitStore {
+    fn update(&self, key: &str, quota: Quota, take: bool) -> Option<Duration> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        if buckets.len() >= MAX_BUCKETS {
+            buckets.retain(|_, (quota, bucket)| {
+                bucket.refill(*quota, now);
+                !bucket.is_full(*quota)
+            });
+        }
+        let (_, bucket) = buckets
+            .entry(key.to_owned())
+            .or_insert_with(|| (quota, Bucket::full(quota, now)));
+        bucket.refill(quota, now);
+        let wait = bucket.wait(quota);
+        if take && wait.is_none() {
+            bucket.tokens -= 1.0;
+        }
+        wait
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn take(&self, key: &str, quota: Quota) -> anyhow::Result<Option<Duration>> {
+        Ok(self.update(key, quota, true))
+    }
+
+    async fn peek(&self, key: &str, quota: Quota) -> anyhow::Result<Option<Duration>> {
+        Ok(self.update(key, quota, false))
+    }
+
+    async fn reset(&self, key: &str) -> anyhow::Result<()> {
+        self.buckets.lock().unwrap().remove(key);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const QUOTA: Quota = Quota {
+        burst: 2,
+        period: Duration::from_secs(10),
+    };
+
+    #[test]
+    fn test_bucket() {
+        let now = Instant::now();
+        let mut bucket = Bucket::full(QUOTA, now);
+        bucket.tokens -= 2.0;
+        assert_eq!(Some(Duration::from_secs(10)), bucket.wait(QUOTA));
+
+        bucket.refill(QUOTA, now + Duration::from_secs(5));
+        assert_eq!(Some(Duration::from_secs(5)), bucket.wait(QUOTA));
+        bucket.refill(QUOTA, now + Duration::from_secs(10));
+        assert_eq!(None, bucket.wait(QUOTA));
+
+        // Tokens don't pile up beyond the burst.
+        bucket.refill(QUOTA, now + Duration::from_secs(60));
+        assert!(bucket.is_full(QUOTA));
+        bucket.tokens -= 1.0;
+        assert!(!bucket.is_full(QUOTA));
+    }
+
+    #[test]
+    fn test_take_and_peek() {
+        let store = MemoryRateLimitStore::default();
+        assert_eq!(None, store.update("key", QUOTA, false));
+        assert_eq!(None, store.update("key", QUOTA, true));
+        assert_eq!(None, store.update("key", QUOTA, true));
+        assert!(store.update("key", QUOTA, false).is_some());
+        assert!(store.update("key", QUOTA, true).is_some());
+        // Other keys have their own buckets.
+        assert_eq!(None, store.update("other", QUOTA, true));
+    }
+}
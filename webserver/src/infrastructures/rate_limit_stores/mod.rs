@@ -0,0 +1,16 @@
+pub mod memory_rate_limit_store;
+pub mod redis_rate_limit_store;
+
+use crate::domains::rate_limit_store::RateLimitStore;
+use crate::infrastructures::rate_limit_stores::memory_rate_limit_store::MemoryRateLimitStore;
+use crate::infrastructures::rate_limit_stores::redis_rate_limit_store::RedisRateLimitStore;
+use std::env;
+
+/// Share the buckets between servers through `REDIS_URL`,
+/// or keep them in the process when Redis isn't configured.
+pub async fn create_rate_limit_store() -> anyhow::Result<Box<dyn RateLimitStore + Send + Sync>> {
+    match env::var("REDIS_URL") {
+        Ok(url) if !url.is_empty() => Ok(Box::new(RedisRateLimitStore::new(&url).await?)),
+        _ => Ok(Box::new(MemoryRateLimitStore::default())),
+    }
+}
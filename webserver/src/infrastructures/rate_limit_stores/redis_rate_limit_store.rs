@@ -0,0 +1,85 @@
+use crate::domains::entities::quota::Quota;
+use crate::domains::rate_limit_store::RateLimitStore;
+use async_trait::async_trait;
+use redis::aio::MultiplexedConnection;
+use redis::Script;
+use std::time::Duration;
+
+/// Refill and take atomically, a bucket expires once it would be full again.
+/// Returns the milliseconds to wait, 0 when a token was available.
+const TAKE_SCRIPT: &str = r"
+local burst = tonumber(ARGV[1])
+local period = tonumber(ARGV[2])
+local now = tonumber(ARGV[3])
+local take = ARGV[4] == '1'
+local bucket = redis.call('HMGET', KEYS[1], 'tokens', 'updated_at')
+local tokens = tonumber(bucket[1]) or burst
+local updated_at = tonumber(bucket[2]) or now
+tokens = math.min(burst, tokens + math.max(0, now - updated_at) / period)
+if tokens < 1 then
+  return math.ceil((1 - tokens) * period)
+end
+if take then
+  tokens = tokens - 1
+  redis.call('HSET', KEYS[1], 'tokens', tostring(tokens), 'updated_at', now)
+  redis.call('PEXPIRE', KEYS[1], math.ceil((burst - tokens) * period))
+end
+return 0
+";
+
+/// Buckets shared by every server connected to the same Redis.
+#[derive(Clone)]
+pub struct RedisRateLimitStore {
+    connection: MultiplexedConnection,
+    script: Script,
+}
+
+impl RedisRateLimitStore {
+    pub async fn new(url: &str) -> anyhow::Result<Self> {
+        let client = redis::Client::open(url)?;
+        Ok(Self {
+            connection: client.get_multiplexed_async_std_connection().await?,
+            script: Script::new(TAKE_SCRIPT),
+        })
+    }
+
+    async fn update(
+        &self,
+        key: &str,
+        quota: Quota,
+        take: bool,
+    ) -> anyhow::Result<Option<Duration>> {
+        let wait: u64 = self
+            .script
+            .key(format!("rsapps:rate_limit:{}", key))
+            .arg(quota.burst)
+            .arg(quota.period.as_millis() as u64)
+            .arg(chrono::Utc::now().timestamp_millis())
+            .arg(if take { "1" } else { "0" })
+            .invoke_async(&mut self.connection.clone())
+            .await?;
+        if wait == 0 {
+            return Ok(None);
+        }
+        Ok(Some(Duration::from_millis(wait)))
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for RedisRateLimitStore {
+    async fn take(&self, key: &str, quota: Quota) -> anyhow::Result<Option<Duration>> {
+        self.update(key, quota, true).await
+    }
+
+    async fn peek(&self, key: &str, quota: Quota) -> anyhow::Result<Option<Duration>> {
+        self.update(key, quota, false).await
+    }
+
+    async fn reset(&self, key: &str) -> anyhow::Result<()> {
+        redis::cmd("DEL")
+            .arg(format!("rsapps:rate_limit:{}", key))
+            .query_async(&mut self.connection.clone())
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use crate::domains::entities::audit_event::{AuditEvent, AuditEventFilter};
+use crate::domains::repositories::audit_event_repository::AuditEventRepository;
+use async_trait::async_trait;
+
+#[derive(Clone)]
+pub struct PostgreSQLAuditEventRepository {
+    pub db: sqlx::PgPool,
+}
+
+#[async_trait]
+impl AuditEventRepository for PostgreSQLAuditEventRepository {
+    async fn create_audit_event(&self, audit_event: AuditEvent) -> anyhow::Result<AuditEvent> {
+        Ok(sqlx::query_as!(
+            AuditEvent,
+            "
+INSERT INTO audit_events (event_type, user_id, actor_id, account, detail, ip_address, user_agent, request_id, created_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+returning *
+            ",
+            audit_event.event_type,
+            audit_event.user_id,
+            audit_event.actor_id,
+            audit_event.account,
+            audit_event.detail,
+            audit_event.ip_address,
+            audit_event.user_agent,
+            audit_event.request_id,
+            audit_event.created_at,
+        )
+        .fetch_one(&self.db)
+        .await?)
+    }
+
+    async fn get_audit_events(
+        &self,
+        filter: &AuditEventFilter,
+        offset: i64,
+        limit: i64,
+    ) -> anyhow::Result<Vec<AuditEvent>> {
+        Ok(sqlx::query_as!(
+            AuditEvent,
+            "
+SELECT *
+FROM audit_events
+WHERE ($1::VARCHAR IS NULL OR event_type = $1)
+  AND ($2::INTEGER IS NULL OR user_id = $2 OR actor_id = $2)
+  AND ($3::VARCHAR IS NULL OR ip_address = $3)
+  AND ($4::TIMESTAMPTZ IS NULL OR created_at >= $4)
+  AND ($5::TIMESTAMPTZ IS NULL OR created_at < $5)
+ORDER BY id DESC
+OFFSET $6
+LIMIT $7
+            ",
+            filter
+                .event_type
+                .map(|event_type| event_type.as_ref().to_owned()),
+            filter.user_id,
+            filter.ip_address,
+            filter.since,
+            filter.until,
+            offset,
+            limit
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn count_audit_events(&self, filter: &AuditEventFilter) -> anyhow::Result<i64> {
+        let row = sqlx::query!(
+            r#"
+SELECT COUNT(*) AS "count!"
+FROM audit_events
+WHERE ($1::VARCHAR IS NULL OR event_type = $1)
+  AND ($2::INTEGER IS NULL OR user_id = $2 OR actor_id = $2)
+  AND ($3::VARCHAR IS NULL OR ip_address = $3)
+  AND ($4::TIMESTAMPTZ IS NULL OR created_at >= $4)
+  AND ($5::TIMESTAMPTZ IS NULL OR created_at < $5)
+            "#,
+            filter
+                .event_type
+                .map(|event_type| event_type.as_ref().to_owned()),
+            filter.user_id,
+            filter.ip_address,
+            filter.since,
+            filter.until,
+        )
+        .fetch_one(&self.db)
+        .await?;
+        Ok(row.count)
+    }
+
+    async fn get_user_audit_events(&self, user_id: i32) -> anyhow::Result<Vec<AuditEvent>> {
+        Ok(sqlx::query_as!(
+            AuditEvent,
+            "
+SELECT *
+FROM audit_events
+WHERE user_id = $1
+ORDER BY id
+            ",
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+}
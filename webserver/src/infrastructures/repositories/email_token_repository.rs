@@ -0,0 +1,105 @@
+use crate::domains::entities::email_token::EmailToken;
+use crate::domains::repositories::email_token_repository::EmailTokenRepository;
+use async_trait::async_trait;
+
+#[derive(Clone)]
+pub struct PostgreSQLEmailTokenRepository {
+    pub db: sqlx::PgPool,
+}
+
+#[async_trait]
+impl EmailTokenRepository for PostgreSQLEmailTokenRepository {
+    async fn get_email_token_by_hash(
+        &self,
+        purpose: String,
+        token_hash: String,
+    ) -> anyhow::Result<Option<EmailToken>> {
+        Ok(sqlx::query_as!(
+            EmailToken,
+            "
+SELECT *
+FROM email_tokens
+WHERE purpose = $1 AND token_hash = $2
+            ",
+            purpose,
+            token_hash
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn get_user_email_tokens(&self, user_id: i32) -> anyhow::Result<Vec<EmailToken>> {
+        Ok(sqlx::query_as!(
+            EmailToken,
+            "
+SELECT *
+FROM email_tokens
+WHERE user_id = $1
+ORDER BY id
+            ",
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn create_email_token(&self, token: EmailToken) -> anyhow::Result<EmailToken> {
+        Ok(sqlx::query_as!(
+            EmailToken,
+            "
+INSERT INTO email_tokens (user_id, purpose, token_hash, expires_at, created_at)
+VALUES ($1, $2, $3, $4, $5)
+returning *
+            ",
+            token.user_id,
+            token.purpose,
+            token.token_hash,
+            token.expires_at,
+            token.created_at,
+        )
+        .fetch_one(&self.db)
+        .await?)
+    }
+
+    async fn use_email_token(
+        &self,
+        id: i32,
+        used_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            EmailToken,
+            "
+UPDATE email_tokens
+SET used_at = $1
+WHERE id = $2 AND used_at IS NULL
+            ",
+            used_at,
+            id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    async fn use_user_email_tokens(
+        &self,
+        user_id: i32,
+        purpose: String,
+        used_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        sqlx::query_as!(
+            EmailToken,
+            "
+UPDATE email_tokens
+SET used_at = $1
+WHERE user_id = $2 AND purpose = $3 AND used_at IS NULL
+            ",
+            used_at,
+            user_id,
+            purpose
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(true)
+    }
+}
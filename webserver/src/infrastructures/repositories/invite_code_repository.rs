@@ -0,0 +1,142 @@
+use crate::domains::entities::invite_code::{InviteCode, InviteRedemption};
+use crate::domains::entities::user::User;
+use crate::domains::repositories::invite_code_repository::InviteCodeRepository;
+use async_trait::async_trait;
+
+#[derive(Clone)]
+pub struct PostgreSQLInviteCodeRepository {
+    pub db: sqlx::PgPool,
+}
+
+#[async_trait]
+impl InviteCodeRepository for PostgreSQLInviteCodeRepository {
+    async fn get_invite_codes(&self) -> anyhow::Result<Vec<InviteCode>> {
+        Ok(sqlx::query_as!(
+            InviteCode,
+            "
+SELECT *
+FROM invite_codes
+ORDER BY id
+            ",
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn create_invite_code(&self, invite_code: InviteCode) -> anyhow::Result<InviteCode> {
+        Ok(sqlx::query_as!(
+            InviteCode,
+            "
+INSERT INTO invite_codes (code_hash, created_by, max_uses, expires_at, created_at)
+VALUES ($1, $2, $3, $4, $5)
+returning *
+            ",
+            invite_code.code_hash,
+            invite_code.created_by,
+            invite_code.max_uses,
+            invite_code.expires_at,
+            invite_code.created_at,
+        )
+        .fetch_one(&self.db)
+        .await?)
+    }
+
+    async fn revoke_invite_code(
+        &self,
+        id: i32,
+        revoked_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            InviteCode,
+            "
+UPDATE invite_codes
+SET revoked_at = $1
+WHERE id = $2 AND revoked_at IS NULL
+            ",
+            revoked_at,
+            id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    async fn get_invite_redemptions(
+        &self,
+        invite_code_id: i32,
+    ) -> anyhow::Result<Vec<InviteRedemption>> {
+        Ok(sqlx::query_as!(
+            InviteRedemption,
+            "
+SELECT *
+FROM invite_redemptions
+WHERE invite_code_id = $1
+ORDER BY id
+            ",
+            invite_code_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn create_user_with_invite_code(
+        &self,
+        user: User,
+        code_hash: String,
+        redeemed_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<User>> {
+        let mut tx = self.db.begin().await?;
+        // The row stays locked until the commit, so concurrent sign ups can't overuse the code.
+        let invite_code = match sqlx::query_as!(
+            InviteCode,
+            "
+UPDATE invite_codes
+SET use_count = use_count + 1
+WHERE code_hash = $1
+  AND use_count < max_uses
+  AND revoked_at IS NULL
+  AND (expires_at IS NULL OR expires_at > $2)
+returning *
+            ",
+            code_hash,
+            redeemed_at
+        )
+        .fetch_optional(&mut tx)
+        .await?
+        {
+            Some(invite_code) => invite_code,
+            None => return Ok(None),
+        };
+        let created = sqlx::query_as!(
+            User,
+            "
+INSERT INTO users (username, email, password_hash, created_at, updated_at, email_verified_at, role)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+returning *
+            ",
+            user.username,
+            user.email,
+            user.password_hash,
+            user.created_at,
+            user.updated_at,
+            user.email_verified_at,
+            user.role,
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        sqlx::query_as!(
+            InviteRedemption,
+            "
+INSERT INTO invite_redemptions (invite_code_id, user_id, redeemed_at)
+VALUES ($1, $2, $3)
+            ",
+            invite_code.id,
+            created.id,
+            redeemed_at
+        )
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Some(created))
+    }
+}
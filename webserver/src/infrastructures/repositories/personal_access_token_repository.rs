@@ -0,0 +1,128 @@
+use crate::domains::entities::personal_access_token::PersonalAccessToken;
+use crate::domains::repositories::personal_access_token_repository::PersonalAccessTokenRepository;
+use async_trait::async_trait;
+
+#[derive(Clone)]
+pub struct PostgreSQLPersonalAccessTokenRepository {
+    pub db: sqlx::PgPool,
+}
+
+#[async_trait]
+impl PersonalAccessTokenRepository for PostgreSQLPersonalAccessTokenRepository {
+    async fn get_personal_access_tokens(
+        &self,
+        user_id: i32,
+    ) -> anyhow::Result<Vec<PersonalAccessToken>> {
+        Ok(sqlx::query_as!(
+            PersonalAccessToken,
+            "
+SELECT *
+FROM personal_access_tokens
+WHERE user_id = $1 AND revoked_at IS NULL
+ORDER BY id
+            ",
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn get_user_personal_access_tokens(
+        &self,
+        user_id: i32,
+    ) -> anyhow::Result<Vec<PersonalAccessToken>> {
+        Ok(sqlx::query_as!(
+            PersonalAccessToken,
+            "
+SELECT *
+FROM personal_access_tokens
+WHERE user_id = $1
+ORDER BY id
+            ",
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn get_personal_access_token_by_hash(
+        &self,
+        token_hash: String,
+    ) -> anyhow::Result<Option<PersonalAccessToken>> {
+        Ok(sqlx::query_as!(
+            PersonalAccessToken,
+            "
+SELECT *
+FROM personal_access_tokens
+WHERE token_hash = $1
+            ",
+            token_hash
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn create_personal_access_token(
+        &self,
+        token: PersonalAccessToken,
+    ) -> anyhow::Result<PersonalAccessToken> {
+        Ok(sqlx::query_as!(
+            PersonalAccessToken,
+            "
+INSERT INTO personal_access_tokens (user_id, name, token_hash, scopes, expires_at, created_at)
+VALUES ($1, $2, $3, $4, $5, $6)
+returning *
+            ",
+            token.user_id,
+            token.name,
+            token.token_hash,
+            token.scopes,
+            token.expires_at,
+            token.created_at,
+        )
+        .fetch_one(&self.db)
+        .await?)
+    }
+
+    async fn touch_personal_access_token(
+        &self,
+        id: i32,
+        last_used_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            PersonalAccessToken,
+            "
+UPDATE personal_access_tokens
+SET last_used_at = $1
+WHERE id = $2
+            ",
+            last_used_at,
+            id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    async fn revoke_personal_access_token(
+        &self,
+        id: i32,
+        user_id: i32,
+        revoked_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            PersonalAccessToken,
+            "
+UPDATE personal_access_tokens
+SET revoked_at = $1
+WHERE id = $2 AND user_id = $3 AND revoked_at IS NULL
+            ",
+            revoked_at,
+            id,
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+}
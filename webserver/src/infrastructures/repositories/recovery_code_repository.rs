@@ -0,0 +1,88 @@
+use crate::domains::entities::recovery_code::RecoveryCode;
+use crate::domains::repositories::recovery_code_repository::RecoveryCodeRepository;
+use async_trait::async_trait;
+
+#[derive(Clone)]
+pub struct PostgreSQLRecoveryCodeRepository {
+    pub db: sqlx::PgPool,
+}
+
+#[async_trait]
+impl RecoveryCodeRepository for PostgreSQLRecoveryCodeRepository {
+    async fn get_recovery_codes(&self, user_id: i32) -> anyhow::Result<Vec<RecoveryCode>> {
+        Ok(sqlx::query_as!(
+            RecoveryCode,
+            "
+SELECT *
+FROM recovery_codes
+WHERE user_id = $1
+ORDER BY id
+            ",
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn replace_recovery_codes(
+        &self,
+        user_id: i32,
+        code_hashes: Vec<String>,
+        created_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<RecoveryCode>> {
+        Ok(sqlx::query_as!(
+            RecoveryCode,
+            "
+WITH deleted AS (
+  DELETE FROM recovery_codes
+  WHERE user_id = $1
+)
+INSERT INTO recovery_codes (user_id, code_hash, created_at)
+SELECT $1, code_hash, $2
+FROM UNNEST($3::VARCHAR[]) AS code_hash
+returning *
+            ",
+            user_id,
+            created_at,
+            code_hashes,
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn use_recovery_code(
+        &self,
+        user_id: i32,
+        code_hash: String,
+        used_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            RecoveryCode,
+            "
+UPDATE recovery_codes
+SET used_at = $1
+WHERE user_id = $2 AND code_hash = $3 AND used_at IS NULL
+            ",
+            used_at,
+            user_id,
+            code_hash
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    async fn delete_user_recovery_codes(&self, user_id: i32) -> anyhow::Result<bool> {
+        sqlx::query_as!(
+            RecoveryCode,
+            "
+DELETE FROM recovery_codes
+WHERE user_id = $1
+            ",
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(true)
+    }
+}
@@ -0,0 +1,161 @@
+use crate::domains::entities::refresh_token::RefreshToken;
+use crate::domains::repositories::refresh_token_repository::RefreshTokenRepository;
+use async_trait::async_trait;
+
+#[derive(Clone)]
+pub struct PostgreSQLRefreshTokenRepository {
+    pub db: sqlx::PgPool,
+}
+
+#[async_trait]
+impl RefreshTokenRepository for PostgreSQLRefreshTokenRepository {
+    async fn get_refresh_token_by_hash(
+        &self,
+        token_hash: String,
+    ) -> anyhow::Result<Option<RefreshToken>> {
+        Ok(sqlx::query_as!(
+            RefreshToken,
+            "
+SELECT *
+FROM refresh_tokens
+WHERE token_hash = $1
+            ",
+            token_hash
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn get_user_refresh_tokens(&self, user_id: i32) -> anyhow::Result<Vec<RefreshToken>> {
+        Ok(sqlx::query_as!(
+            RefreshToken,
+            "
+SELECT *
+FROM refresh_tokens
+WHERE user_id = $1
+ORDER BY id
+            ",
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn create_refresh_token(&self, token: RefreshToken) -> anyhow::Result<RefreshToken> {
+        Ok(sqlx::query_as!(
+            RefreshToken,
+            "
+INSERT INTO refresh_tokens (user_id, family, token_hash, expires_at, created_at)
+VALUES ($1, $2, $3, $4, $5)
+returning *
+            ",
+            token.user_id,
+            token.family,
+            token.token_hash,
+            token.expires_at,
+            token.created_at,
+        )
+        .fetch_one(&self.db)
+        .await?)
+    }
+
+    async fn use_refresh_token(
+        &self,
+        id: i32,
+        used_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            RefreshToken,
+            "
+UPDATE refresh_tokens
+SET used_at = $1
+WHERE id = $2 AND used_at IS NULL AND revoked_at IS NULL
+            ",
+            used_at,
+            id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    async fn revoke_family(
+        &self,
+        family: String,
+        revoked_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        sqlx::query_as!(
+            RefreshToken,
+            "
+UPDATE refresh_tokens
+SET revoked_at = $1
+WHERE family = $2 AND revoked_at IS NULL
+            ",
+            revoked_at,
+            family
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(true)
+    }
+
+    async fn revoke_user_refresh_tokens(
+        &self,
+        user_id: i32,
+        revoked_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        sqlx::query_as!(
+            RefreshToken,
+            "
+UPDATE refresh_tokens
+SET revoked_at = $1
+WHERE user_id = $2 AND revoked_at IS NULL
+            ",
+            revoked_at,
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(true)
+    }
+
+    async fn revoke_other_refresh_tokens(
+        &self,
+        user_id: i32,
+        family: String,
+        revoked_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        sqlx::query_as!(
+            RefreshToken,
+            "
+UPDATE refresh_tokens
+SET revoked_at = $1
+WHERE user_id = $2 AND family <> $3 AND revoked_at IS NULL
+            ",
+            revoked_at,
+            user_id,
+            family
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(true)
+    }
+
+    async fn is_family_revoked(&self, family: String) -> anyhow::Result<bool> {
+        // Revoked tokens sort first, so one row tells whether any of the family is revoked.
+        let token = sqlx::query_as!(
+            RefreshToken,
+            "
+SELECT *
+FROM refresh_tokens
+WHERE family = $1
+ORDER BY revoked_at IS NULL
+LIMIT 1
+            ",
+            family
+        )
+        .fetch_optional(&self.db)
+        .await?;
+        Ok(token.map_or(true, |token| token.revoked_at.is_some()))
+    }
+}
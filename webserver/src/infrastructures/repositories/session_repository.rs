@@ -0,0 +1,133 @@
+use crate::domains::entities::session::Session;
+use crate::domains::repositories::session_repository::SessionRepository;
+use async_trait::async_trait;
+
+#[derive(Clone)]
+pub struct PostgreSQLSessionRepository {
+    pub db: sqlx::PgPool,
+}
+
+#[async_trait]
+impl SessionRepository for PostgreSQLSessionRepository {
+    async fn create_session(&self, session: Session) -> anyhow::Result<Session> {
+        Ok(sqlx::query_as!(
+            Session,
+            "
+INSERT INTO sessions (user_id, family, user_agent, ip_address, created_at, last_seen_at)
+VALUES ($1, $2, $3, $4, $5, $6)
+returning *
+            ",
+            session.user_id,
+            session.family,
+            session.user_agent,
+            session.ip_address,
+            session.created_at,
+            session.last_seen_at,
+        )
+        .fetch_one(&self.db)
+        .await?)
+    }
+
+    async fn get_user_sessions(&self, user_id: i32) -> anyhow::Result<Vec<Session>> {
+        Ok(sqlx::query_as!(
+            Session,
+            "
+SELECT *
+FROM sessions
+WHERE user_id = $1
+ORDER BY id
+            ",
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn get_session_by_family(&self, family: String) -> anyhow::Result<Option<Session>> {
+        Ok(sqlx::query_as!(
+            Session,
+            "
+SELECT *
+FROM sessions
+WHERE family = $1
+            ",
+            family
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn get_active_session(
+        &self,
+        id: i32,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<Session>> {
+        // Revoking a family revokes all of its tokens, so one left unrevoked means the session is on.
+        Ok(sqlx::query_as!(
+            Session,
+            "
+SELECT *
+FROM sessions
+WHERE id = $1
+  AND EXISTS (
+    SELECT 1
+    FROM refresh_tokens
+    WHERE refresh_tokens.family = sessions.family
+      AND revoked_at IS NULL AND expires_at > $2
+  )
+            ",
+            id,
+            now
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn get_active_user_sessions(
+        &self,
+        user_id: i32,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<Session>> {
+        Ok(sqlx::query_as!(
+            Session,
+            "
+SELECT *
+FROM sessions
+WHERE user_id = $1
+  AND EXISTS (
+    SELECT 1
+    FROM refresh_tokens
+    WHERE refresh_tokens.family = sessions.family
+      AND revoked_at IS NULL AND expires_at > $2
+  )
+ORDER BY last_seen_at DESC, id DESC
+            ",
+            user_id,
+            now
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn touch_session(
+        &self,
+        id: i32,
+        ip_address: Option<String>,
+        last_seen_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            Session,
+            "
+UPDATE sessions
+SET ip_address = COALESCE($1, ip_address), last_seen_at = $2
+WHERE id = $3
+            ",
+            ip_address,
+            last_seen_at,
+            id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,235 @@
+use crate::domains::entities::tag::{Tag, TodoTag};
+use crate::domains::repositories::tag_repository::TagRepository;
+use async_trait::async_trait;
+
+#[derive(Clone)]
+pub struct PostgreSQLTagRepository {
+    pub db: sqlx::PgPool,
+}
+
+#[async_trait]
+impl TagRepository for PostgreSQLTagRepository {
+    async fn get_user_tags(&self, user_id: i32) -> anyhow::Result<Vec<Tag>> {
+        Ok(sqlx::query_as!(
+            Tag,
+            "
+SELECT *
+FROM tags
+WHERE user_id = $1
+ORDER BY LOWER(name), id
+            ",
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn get_tag_by_id(&self, id: i32, user_id: i32) -> anyhow::Result<Option<Tag>> {
+        Ok(sqlx::query_as!(
+            Tag,
+            "
+SELECT *
+FROM tags
+WHERE id = $1 AND user_id = $2
+            ",
+            id,
+            user_id
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn create_tag(&self, tag: Tag) -> anyhow::Result<Option<Tag>> {
+        Ok(sqlx::query_as!(
+            Tag,
+            "
+INSERT INTO tags (user_id, name, color, created_at, updated_at)
+VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT DO NOTHING
+returning *
+            ",
+            tag.user_id,
+            tag.name,
+            tag.color,
+            tag.created_at,
+            tag.updated_at,
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn update_tag(
+        &self,
+        id: i32,
+        user_id: i32,
+        name: String,
+        color: String,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<Tag>> {
+        Ok(sqlx::query_as!(
+            Tag,
+            "
+UPDATE tags
+SET name = $1, color = $2, updated_at = $3
+WHERE id = $4 AND user_id = $5
+  AND NOT EXISTS (
+    SELECT 1
+    FROM tags AS others
+    WHERE others.user_id = $5 AND LOWER(others.name) = LOWER($1) AND others.id <> $4
+  )
+returning *
+            ",
+            name,
+            color,
+            updated_at,
+            id,
+            user_id
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn delete_tag(&self, id: i32, user_id: i32) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            Tag,
+            "
+DELETE
+FROM tags
+WHERE id = $1 AND user_id = $2
+            ",
+            id,
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    async fn merge_tags(
+        &self,
+        source_id: i32,
+        target_id: i32,
+        user_id: i32,
+        merged_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<Tag>> {
+        let mut tx = self.db.begin().await?;
+        // The target row stays locked until the commit, so it can't be deleted meanwhile.
+        let target = match sqlx::query_as!(
+            Tag,
+            "
+UPDATE tags
+SET updated_at = $1
+WHERE id = $2 AND user_id = $3
+returning *
+            ",
+            merged_at,
+            target_id,
+            user_id
+        )
+        .fetch_optional(&mut tx)
+        .await?
+        {
+            Some(target) => target,
+            None => return Ok(None),
+        };
+        sqlx::query_as!(
+            TodoTag,
+            "
+INSERT INTO todo_tags (todo_id, tag_id, created_at)
+SELECT todo_tags.todo_id, $1, todo_tags.created_at
+FROM todo_tags
+JOIN tags ON tags.id = todo_tags.tag_id
+WHERE todo_tags.tag_id = $2 AND tags.user_id = $3
+ON CONFLICT DO NOTHING
+            ",
+            target.id,
+            source_id,
+            user_id
+        )
+        .execute(&mut tx)
+        .await?;
+        let done = sqlx::query_as!(
+            Tag,
+            "
+DELETE
+FROM tags
+WHERE id = $1 AND user_id = $2
+            ",
+            source_id,
+            user_id
+        )
+        .execute(&mut tx)
+        .await?;
+        if done.rows_affected() == 0 {
+            return Ok(None);
+        }
+        tx.commit().await?;
+        Ok(Some(target))
+    }
+
+    async fn get_todo_tags(&self, todo_id: i32, user_id: i32) -> anyhow::Result<Vec<Tag>> {
+        Ok(sqlx::query_as!(
+            Tag,
+            "
+SELECT tags.*
+FROM tags
+JOIN todo_tags ON todo_tags.tag_id = tags.id
+WHERE todo_tags.todo_id = $1 AND tags.user_id = $2
+ORDER BY LOWER(tags.name), tags.id
+            ",
+            todo_id,
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn get_user_todo_tags(&self, user_id: i32) -> anyhow::Result<Vec<TodoTag>> {
+        Ok(sqlx::query_as!(
+            TodoTag,
+            "
+SELECT todo_tags.*
+FROM todo_tags
+JOIN tags ON tags.id = todo_tags.tag_id
+WHERE tags.user_id = $1
+ORDER BY todo_tags.tag_id, todo_tags.todo_id
+            ",
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn add_todo_tag(&self, todo_tag: TodoTag) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            TodoTag,
+            "
+INSERT INTO todo_tags (todo_id, tag_id, created_at)
+VALUES ($1, $2, $3)
+ON CONFLICT DO NOTHING
+            ",
+            todo_tag.todo_id,
+            todo_tag.tag_id,
+            todo_tag.created_at,
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    async fn remove_todo_tag(&self, todo_id: i32, tag_id: i32) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            TodoTag,
+            "
+DELETE
+FROM todo_tags
+WHERE todo_id = $1 AND tag_id = $2
+            ",
+            todo_id,
+            tag_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+}
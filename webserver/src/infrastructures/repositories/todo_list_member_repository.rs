@@ -0,0 +1,149 @@
+use crate::domains::entities::todo_list_member::TodoListMember;
+use crate::domains::repositories::todo_list_member_repository::TodoListMemberRepository;
+use async_trait::async_trait;
+
+#[derive(Clone)]
+pub struct PostgreSQLTodoListMemberRepository {
+    pub db: sqlx::PgPool,
+}
+
+#[async_trait]
+impl TodoListMemberRepository for PostgreSQLTodoListMemberRepository {
+    async fn get_todo_list_member(
+        &self,
+        list_id: i32,
+        user_id: i32,
+    ) -> anyhow::Result<Option<TodoListMember>> {
+        Ok(sqlx::query_as!(
+            TodoListMember,
+            r#"
+SELECT todo_list_members.*, users.username AS "username!", todo_lists.name AS "list_name!"
+FROM todo_list_members
+JOIN users ON users.id = todo_list_members.user_id
+JOIN todo_lists ON todo_lists.id = todo_list_members.list_id
+WHERE todo_list_members.list_id = $1 AND todo_list_members.user_id = $2
+            "#,
+            list_id,
+            user_id
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn get_todo_list_members(&self, list_id: i32) -> anyhow::Result<Vec<TodoListMember>> {
+        Ok(sqlx::query_as!(
+            TodoListMember,
+            r#"
+SELECT todo_list_members.*, users.username AS "username!", todo_lists.name AS "list_name!"
+FROM todo_list_members
+JOIN users ON users.id = todo_list_members.user_id
+JOIN todo_lists ON todo_lists.id = todo_list_members.list_id
+WHERE todo_list_members.list_id = $1
+ORDER BY todo_list_members.role = 'owner' DESC, todo_list_members.id
+            "#,
+            list_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn get_user_invitations(&self, user_id: i32) -> anyhow::Result<Vec<TodoListMember>> {
+        Ok(sqlx::query_as!(
+            TodoListMember,
+            r#"
+SELECT todo_list_members.*, users.username AS "username!", todo_lists.name AS "list_name!"
+FROM todo_list_members
+JOIN users ON users.id = todo_list_members.user_id
+JOIN todo_lists ON todo_lists.id = todo_list_members.list_id
+WHERE todo_list_members.user_id = $1 AND todo_list_members.accepted_at IS NULL
+ORDER BY todo_list_members.id
+            "#,
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn create_todo_list_member(&self, member: TodoListMember) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            TodoListMember,
+            "
+INSERT INTO todo_list_members (list_id, user_id, role, invited_by, accepted_at, created_at, updated_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
+ON CONFLICT DO NOTHING
+            ",
+            member.list_id,
+            member.user_id,
+            member.role,
+            member.invited_by,
+            member.accepted_at,
+            member.created_at,
+            member.updated_at,
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    async fn accept_invitation(
+        &self,
+        list_id: i32,
+        user_id: i32,
+        accepted_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            TodoListMember,
+            "
+UPDATE todo_list_members
+SET accepted_at = $1, updated_at = $1
+WHERE list_id = $2 AND user_id = $3 AND accepted_at IS NULL
+            ",
+            accepted_at,
+            list_id,
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    async fn update_member_role(
+        &self,
+        list_id: i32,
+        user_id: i32,
+        role: String,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            TodoListMember,
+            "
+UPDATE todo_list_members
+SET role = $1, updated_at = $2
+WHERE list_id = $3 AND user_id = $4
+            ",
+            role,
+            updated_at,
+            list_id,
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    async fn delete_todo_list_member(&self, list_id: i32, user_id: i32) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            TodoListMember,
+            "
+DELETE
+FROM todo_list_members
+WHERE list_id = $1 AND user_id = $2
+            ",
+            list_id,
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,132 @@
+use crate::domains::entities::todo_list::TodoList;
+use crate::domains::entities::todo_list_member::{TodoListMember, TodoListRole};
+use crate::domains::repositories::todo_list_repository::TodoListRepository;
+use async_trait::async_trait;
+
+#[derive(Clone)]
+pub struct PostgreSQLTodoListRepository {
+    pub db: sqlx::PgPool,
+}
+
+#[async_trait]
+impl TodoListRepository for PostgreSQLTodoListRepository {
+    async fn get_user_todo_lists(&self, user_id: i32) -> anyhow::Result<Vec<TodoList>> {
+        Ok(sqlx::query_as!(
+            TodoList,
+            "
+SELECT todo_lists.*
+FROM todo_lists
+JOIN todo_list_members ON todo_list_members.list_id = todo_lists.id
+WHERE todo_list_members.user_id = $1 AND todo_list_members.accepted_at IS NOT NULL
+ORDER BY todo_lists.user_id = $1 AND todo_lists.is_default DESC, todo_lists.id
+            ",
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn get_todo_list_by_id(&self, id: i32) -> anyhow::Result<Option<TodoList>> {
+        Ok(sqlx::query_as!(
+            TodoList,
+            "
+SELECT *
+FROM todo_lists
+WHERE id = $1
+            ",
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn get_default_todo_list(&self, user_id: i32) -> anyhow::Result<Option<TodoList>> {
+        Ok(sqlx::query_as!(
+            TodoList,
+            "
+SELECT *
+FROM todo_lists
+WHERE user_id = $1 AND is_default
+            ",
+            user_id
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn create_todo_list(&self, todo_list: TodoList) -> anyhow::Result<Option<TodoList>> {
+        let mut tx = self.db.begin().await?;
+        let created = match sqlx::query_as!(
+            TodoList,
+            "
+INSERT INTO todo_lists (user_id, name, is_default, created_at, updated_at)
+VALUES ($1, $2, $3, $4, $5)
+ON CONFLICT DO NOTHING
+returning *
+            ",
+            todo_list.user_id,
+            todo_list.name,
+            todo_list.is_default,
+            todo_list.created_at,
+            todo_list.updated_at,
+        )
+        .fetch_optional(&mut tx)
+        .await?
+        {
+            Some(created) => created,
+            None => return Ok(None),
+        };
+        sqlx::query_as!(
+            TodoListMember,
+            "
+INSERT INTO todo_list_members (list_id, user_id, role, accepted_at, created_at, updated_at)
+VALUES ($1, $2, $3, $4, $4, $4)
+            ",
+            created.id,
+            created.user_id,
+            TodoListRole::Owner.as_ref(),
+            created.created_at,
+        )
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Some(created))
+    }
+
+    async fn rename_todo_list(
+        &self,
+        id: i32,
+        name: String,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<TodoList>> {
+        Ok(sqlx::query_as!(
+            TodoList,
+            "
+UPDATE todo_lists
+SET name = $1, updated_at = $2
+WHERE id = $3
+returning *
+            ",
+            name,
+            updated_at,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn delete_todo_list(&self, id: i32) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            TodoList,
+            "
+DELETE
+FROM todo_lists
+WHERE id = $1
+            ",
+            id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+}
@@ -1,4 +1,5 @@
-use crate::domains::entities::todo::Todo;
+use crate::domains::entities::tag::TagMatch;
+use crate::domains::entities::todo::{Todo, TodoFilter, TodoOrder};
 use crate::domains::repositories::todo_repository::TodoRepository;
 use async_trait::async_trait;
 
@@ -15,7 +16,8 @@ impl TodoRepository for PostgreSQLTodoRepository {
             "
 SELECT *
 FROM todos
-WHERE user_id = $1
+WHERE created_by = $1
+ORDER BY id
             ",
             user_id
         )
@@ -23,6 +25,54 @@ WHERE user_id = $1
         .await?)
     }
 
+    async fn get_list_todos(
+        &self,
+        list_id: i32,
+        filter: &TodoFilter,
+        now: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Vec<Todo>> {
+        let (tag_ids, min_tag_matches) = match filter.tag_filter.as_ref() {
+            Some(tag_filter) => match tag_filter.tag_match.unwrap_or(TagMatch::Any) {
+                TagMatch::Any => (tag_filter.tag_ids.as_slice(), 1),
+                TagMatch::All => (
+                    tag_filter.tag_ids.as_slice(),
+                    tag_filter.tag_ids.len() as i64,
+                ),
+            },
+            None => (&[][..], 0),
+        };
+        Ok(sqlx::query_as!(
+            Todo,
+            "
+SELECT *
+FROM todos
+WHERE list_id = $1
+  AND (
+    $3::BIGINT = 0
+    OR (
+      SELECT COUNT(*)
+      FROM todo_tags
+      WHERE todo_tags.todo_id = todos.id AND todo_tags.tag_id = ANY($2)
+    ) >= $3
+  )
+  AND ($4::TIMESTAMPTZ IS NULL OR due_at < $4)
+  AND ($5::TIMESTAMPTZ IS NULL OR due_at >= $5)
+  AND ($6::BOOLEAN IS NULL OR (NOT complete AND due_at IS NOT NULL AND due_at < $7) = $6)
+ORDER BY CASE WHEN $8 THEN due_at END NULLS LAST, id
+            ",
+            list_id,
+            tag_ids,
+            min_tag_matches,
+            filter.due_before,
+            filter.due_after,
+            filter.overdue,
+            now,
+            filter.order_by == TodoOrder::DueAt
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
     async fn get_todo_by_id(&self, id: i32) -> anyhow::Result<Option<Todo>> {
         Ok(sqlx::query_as!(
             Todo,
@@ -41,69 +91,83 @@ WHERE id = $1
         Ok(sqlx::query_as!(
             Todo,
             "
-INSERT INTO todos (body, complete, created_at, updated_at, user_id)
-VALUES ($1, $2, $3, $4, $5)
+INSERT INTO todos (body, complete, created_at, updated_at, list_id, created_by, updated_by, start_at, due_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
 returning *
             ",
             todo.body,
             todo.complete,
             todo.created_at,
             todo.updated_at,
-            todo.user_id,
+            todo.list_id,
+            todo.created_by,
+            todo.updated_by,
+            todo.start_at,
+            todo.due_at,
         )
         .fetch_one(&self.db)
         .await?)
     }
 
-    async fn update_todo(&self, todo: Todo) -> anyhow::Result<Todo> {
+    async fn update_todo(&self, todo: Todo) -> anyhow::Result<Option<Todo>> {
         Ok(sqlx::query_as!(
             Todo,
             "
 UPDATE todos
-SET body = $1, complete = $2, updated_at = $3
-WHERE id = $4
+SET body = $1, complete = $2, updated_at = $3, updated_by = $4, start_at = $5, due_at = $6
+WHERE id = $7
 returning *
             ",
             todo.body,
             todo.complete,
             todo.updated_at,
+            todo.updated_by,
+            todo.start_at,
+            todo.due_at,
             todo.id,
         )
-        .fetch_one(&self.db)
+        .fetch_optional(&self.db)
         .await?)
     }
 
     async fn toggle_complete(
         &self,
         id: i32,
+        updated_by: i32,
         updated_at: chrono::DateTime<chrono::Utc>,
     ) -> anyhow::Result<bool> {
-        sqlx::query_as!(
+        let done = sqlx::query_as!(
             Todo,
             "
 UPDATE todos
-SET complete = not complete, updated_at = $1
-WHERE id = $2
+SET complete = not complete, updated_at = $1, updated_by = $2
+WHERE id = $3
             ",
             updated_at,
+            updated_by,
             id
         )
         .execute(&self.db)
         .await?;
-        Ok(true)
+        Ok(done.rows_affected() > 0)
     }
 
     async fn toggle_all_complete(
         &self,
+        list_id: i32,
+        updated_by: i32,
         updated_at: chrono::DateTime<chrono::Utc>,
     ) -> anyhow::Result<bool> {
         sqlx::query_as!(
             Todo,
             "
 UPDATE todos
-SET complete = not complete, updated_at = $1
+SET complete = not complete, updated_at = $1, updated_by = $2
+WHERE list_id = $3
             ",
-            updated_at
+            updated_at,
+            updated_by,
+            list_id
         )
         .execute(&self.db)
         .await?;
@@ -111,7 +175,7 @@ SET complete = not complete, updated_at = $1
     }
 
     async fn delete_todo(&self, id: i32) -> anyhow::Result<bool> {
-        sqlx::query_as!(
+        let done = sqlx::query_as!(
             Todo,
             "
 DELETE
@@ -122,17 +186,18 @@ WHERE id = $1
         )
         .execute(&self.db)
         .await?;
-        Ok(true)
+        Ok(done.rows_affected() > 0)
     }
 
-    async fn delete_completed_todo(&self) -> anyhow::Result<bool> {
+    async fn delete_completed_todo(&self, list_id: i32) -> anyhow::Result<bool> {
         sqlx::query_as!(
             Todo,
             "
 DELETE
 FROM todos
-WHERE complete = true
+WHERE complete = true AND list_id = $1
             ",
+            list_id
         )
         .execute(&self.db)
         .await?;
@@ -0,0 +1,220 @@
+use crate::domains::entities::tag::TodoTag;
+use crate::domains::entities::todo::Todo;
+use crate::domains::entities::todo_series::TodoSeries;
+use crate::domains::repositories::todo_series_repository::TodoSeriesRepository;
+use async_trait::async_trait;
+
+#[derive(Clone)]
+pub struct PostgreSQLTodoSeriesRepository {
+    pub db: sqlx::PgPool,
+}
+
+#[async_trait]
+impl TodoSeriesRepository for PostgreSQLTodoSeriesRepository {
+    async fn get_todo_series(&self, id: i32) -> anyhow::Result<Option<TodoSeries>> {
+        Ok(sqlx::query_as!(
+            TodoSeries,
+            "
+SELECT *
+FROM todo_series
+WHERE id = $1
+            ",
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn get_user_todo_series(&self, user_id: i32) -> anyhow::Result<Vec<TodoSeries>> {
+        Ok(sqlx::query_as!(
+            TodoSeries,
+            "
+SELECT *
+FROM todo_series
+WHERE EXISTS (
+  SELECT 1
+  FROM todos
+  WHERE todos.series_id = todo_series.id AND todos.created_by = $1
+)
+ORDER BY id
+            ",
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn create_todo_series(
+        &self,
+        series: TodoSeries,
+        todo_id: i32,
+    ) -> anyhow::Result<TodoSeries> {
+        let mut tx = self.db.begin().await?;
+        let created = sqlx::query_as!(
+            TodoSeries,
+            "
+INSERT INTO todo_series (list_id, body, rrule, time_zone, dtstart, occurrences_before, created_at, updated_at)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+returning *
+            ",
+            series.list_id,
+            series.body,
+            series.rrule,
+            series.time_zone,
+            series.dtstart,
+            series.occurrences_before,
+            series.created_at,
+            series.updated_at,
+        )
+        .fetch_one(&mut tx)
+        .await?;
+        sqlx::query_as!(
+            Todo,
+            "
+UPDATE todos
+SET series_id = $1, occurrence_at = $2
+WHERE id = $3
+            ",
+            created.id,
+            created.dtstart,
+            todo_id
+        )
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(created)
+    }
+
+    async fn update_todo_series(
+        &self,
+        series: TodoSeries,
+        todo_id: i32,
+        occurrence_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<TodoSeries>> {
+        let mut tx = self.db.begin().await?;
+        let updated = match sqlx::query_as!(
+            TodoSeries,
+            "
+UPDATE todo_series
+SET body = $1, rrule = $2, time_zone = $3, dtstart = $4, occurrences_before = $5, updated_at = $6
+WHERE id = $7
+returning *
+            ",
+            series.body,
+            series.rrule,
+            series.time_zone,
+            series.dtstart,
+            series.occurrences_before,
+            series.updated_at,
+            series.id,
+        )
+        .fetch_optional(&mut tx)
+        .await?
+        {
+            Some(updated) => updated,
+            None => return Ok(None),
+        };
+        sqlx::query_as!(
+            Todo,
+            "
+UPDATE todos
+SET body = $1
+WHERE series_id = $2 AND NOT complete
+            ",
+            updated.body,
+            updated.id
+        )
+        .execute(&mut tx)
+        .await?;
+        sqlx::query_as!(
+            Todo,
+            "
+UPDATE todos
+SET occurrence_at = $1
+WHERE id = $2 AND series_id = $3
+            ",
+            occurrence_at,
+            todo_id,
+            updated.id
+        )
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Some(updated))
+    }
+
+    async fn delete_todo_series(&self, id: i32) -> anyhow::Result<bool> {
+        let mut tx = self.db.begin().await?;
+        sqlx::query_as!(
+            Todo,
+            "
+UPDATE todos
+SET occurrence_at = NULL
+WHERE series_id = $1
+            ",
+            id
+        )
+        .execute(&mut tx)
+        .await?;
+        let done = sqlx::query_as!(
+            TodoSeries,
+            "
+DELETE
+FROM todo_series
+WHERE id = $1
+            ",
+            id
+        )
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    async fn create_next_occurrence(&self, todo: Todo) -> anyhow::Result<Option<Todo>> {
+        let mut tx = self.db.begin().await?;
+        let created = match sqlx::query_as!(
+            Todo,
+            "
+INSERT INTO todos (body, complete, created_at, updated_at, list_id, created_by, updated_by, start_at, due_at, series_id, occurrence_at, previous_id)
+VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+ON CONFLICT DO NOTHING
+returning *
+            ",
+            todo.body,
+            todo.complete,
+            todo.created_at,
+            todo.updated_at,
+            todo.list_id,
+            todo.created_by,
+            todo.updated_by,
+            todo.start_at,
+            todo.due_at,
+            todo.series_id,
+            todo.occurrence_at,
+            todo.previous_id,
+        )
+        .fetch_optional(&mut tx)
+        .await?
+        {
+            Some(created) => created,
+            None => return Ok(None),
+        };
+        sqlx::query_as!(
+            TodoTag,
+            "
+INSERT INTO todo_tags (todo_id, tag_id, created_at)
+SELECT $1, tag_id, $2
+FROM todo_tags
+WHERE todo_id = $3
+            ",
+            created.id,
+            created.created_at,
+            todo.previous_id
+        )
+        .execute(&mut tx)
+        .await?;
+        tx.commit().await?;
+        Ok(Some(created))
+    }
+}
@@ -0,0 +1,99 @@
+use crate::domains::entities::totp_credential::TotpCredential;
+use crate::domains::repositories::totp_credential_repository::TotpCredentialRepository;
+use async_trait::async_trait;
+
+#[derive(Clone)]
+pub struct PostgreSQLTotpCredentialRepository {
+    pub db: sqlx::PgPool,
+}
+
+#[async_trait]
+impl TotpCredentialRepository for PostgreSQLTotpCredentialRepository {
+    async fn get_totp_credential(&self, user_id: i32) -> anyhow::Result<Option<TotpCredential>> {
+        Ok(sqlx::query_as!(
+            TotpCredential,
+            "
+SELECT *
+FROM totp_credentials
+WHERE user_id = $1
+            ",
+            user_id
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn save_totp_credential(
+        &self,
+        credential: TotpCredential,
+    ) -> anyhow::Result<Option<TotpCredential>> {
+        Ok(sqlx::query_as!(
+            TotpCredential,
+            "
+INSERT INTO totp_credentials (user_id, secret, created_at)
+VALUES ($1, $2, $3)
+ON CONFLICT (user_id) DO UPDATE
+SET secret = EXCLUDED.secret, last_used_step = NULL, created_at = EXCLUDED.created_at
+WHERE totp_credentials.confirmed_at IS NULL
+returning *
+            ",
+            credential.user_id,
+            credential.secret,
+            credential.created_at,
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn confirm_totp_credential(
+        &self,
+        user_id: i32,
+        step: i64,
+        confirmed_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            TotpCredential,
+            "
+UPDATE totp_credentials
+SET confirmed_at = $1, last_used_step = $2
+WHERE user_id = $3 AND confirmed_at IS NULL
+            ",
+            confirmed_at,
+            step,
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    async fn use_totp_step(&self, user_id: i32, step: i64) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            TotpCredential,
+            "
+UPDATE totp_credentials
+SET last_used_step = $1
+WHERE user_id = $2 AND (last_used_step IS NULL OR last_used_step < $1)
+            ",
+            step,
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    async fn delete_totp_credential(&self, user_id: i32) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            TotpCredential,
+            "
+DELETE FROM totp_credentials
+WHERE user_id = $1
+            ",
+            user_id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+}
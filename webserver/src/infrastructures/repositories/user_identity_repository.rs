@@ -0,0 +1,78 @@
+use crate::domains::entities::user::User;
+use crate::domains::entities::user_identity::UserIdentity;
+use crate::domains::repositories::user_identity_repository::UserIdentityRepository;
+use async_trait::async_trait;
+
+#[derive(Clone)]
+pub struct PostgreSQLUserIdentityRepository {
+    pub db: sqlx::PgPool,
+}
+
+#[async_trait]
+impl UserIdentityRepository for PostgreSQLUserIdentityRepository {
+    async fn get_user_identity(
+        &self,
+        provider: String,
+        subject: String,
+    ) -> anyhow::Result<Option<UserIdentity>> {
+        Ok(sqlx::query_as!(
+            UserIdentity,
+            "
+SELECT *
+FROM user_identities
+WHERE provider = $1 AND subject = $2
+            ",
+            provider,
+            subject
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn get_user_identities(&self, user_id: i32) -> anyhow::Result<Vec<UserIdentity>> {
+        Ok(sqlx::query_as!(
+            UserIdentity,
+            "
+SELECT *
+FROM user_identities
+WHERE user_id = $1
+ORDER BY id
+            ",
+            user_id
+        )
+        .fetch_all(&self.db)
+        .await?)
+    }
+
+    async fn create_user_with_identity(
+        &self,
+        user: User,
+        identity: UserIdentity,
+    ) -> anyhow::Result<UserIdentity> {
+        Ok(sqlx::query_as!(
+            UserIdentity,
+            "
+WITH new_user AS (
+  INSERT INTO users (username, email, password_hash, created_at, updated_at)
+  VALUES ($1, $2, $3, $4, $5)
+  returning id
+)
+INSERT INTO user_identities (user_id, provider, subject, email, created_at)
+SELECT id, $6, $7, $8, $9
+FROM new_user
+returning *
+            ",
+            user.username,
+            user.email,
+            user.password_hash,
+            user.created_at,
+            user.updated_at,
+            identity.provider,
+            identity.subject,
+            identity.email,
+            identity.created_at,
+        )
+        .fetch_one(&self.db)
+        .await?)
+    }
+}
@@ -9,18 +9,35 @@ pub struct PostgreSQLUserRepository {
 
 #[async_trait]
 impl UserRepository for PostgreSQLUserRepository {
-    async fn get_all_users(&self) -> anyhow::Result<Vec<User>> {
+    async fn get_users(&self, offset: i64, limit: i64) -> anyhow::Result<Vec<User>> {
         Ok(sqlx::query_as!(
             User,
             "
 SELECT *
 FROM users
+ORDER BY id
+OFFSET $1
+LIMIT $2
             ",
+            offset,
+            limit
         )
         .fetch_all(&self.db)
         .await?)
     }
 
+    async fn count_users(&self) -> anyhow::Result<i64> {
+        let row = sqlx::query!(
+            r#"
+SELECT COUNT(*) AS "count!"
+FROM users
+            "#,
+        )
+        .fetch_one(&self.db)
+        .await?;
+        Ok(row.count)
+    }
+
     async fn get_user_by_id(&self, id: i32) -> anyhow::Result<Option<User>> {
         Ok(sqlx::query_as!(
             User,
@@ -67,8 +84,8 @@ WHERE username = $1
         Ok(sqlx::query_as!(
             User,
             "
-INSERT INTO users (username, email, password_hash, created_at, updated_at)
-VALUES ($1, $2, $3, $4, $5)
+INSERT INTO users (username, email, password_hash, created_at, updated_at, email_verified_at, role)
+VALUES ($1, $2, $3, $4, $5, $6, $7)
 returning *
             ",
             user.username,
@@ -76,8 +93,159 @@ returning *
             user.password_hash,
             user.created_at,
             user.updated_at,
+            user.email_verified_at,
+            user.role,
         )
         .fetch_one(&self.db)
         .await?)
     }
+
+    async fn verify_email(
+        &self,
+        id: i32,
+        email: String,
+        verified_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<User>> {
+        Ok(sqlx::query_as!(
+            User,
+            "
+UPDATE users
+SET email_verified_at = $1, updated_at = $1
+WHERE id = $2 AND email = $3
+returning *
+            ",
+            verified_at,
+            id,
+            email
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn update_password(
+        &self,
+        id: i32,
+        password_hash: String,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            User,
+            "
+UPDATE users
+SET password_hash = $1, password_reset_required = FALSE, updated_at = $2
+WHERE id = $3
+            ",
+            password_hash,
+            updated_at,
+            id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
+
+    async fn update_profile(
+        &self,
+        id: i32,
+        username: String,
+        email: Option<String>,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<User>> {
+        Ok(sqlx::query_as!(
+            User,
+            "
+UPDATE users
+SET username = $1,
+  email = $2,
+  email_verified_at = CASE WHEN email IS NOT DISTINCT FROM $2::VARCHAR THEN email_verified_at END,
+  updated_at = $3
+WHERE id = $4
+returning *
+            ",
+            username,
+            email,
+            updated_at,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn update_role(
+        &self,
+        id: i32,
+        role: String,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<User>> {
+        Ok(sqlx::query_as!(
+            User,
+            "
+UPDATE users
+SET role = $1, updated_at = $2
+WHERE id = $3
+returning *
+            ",
+            role,
+            updated_at,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn update_suspension(
+        &self,
+        id: i32,
+        suspended_at: Option<chrono::DateTime<chrono::Utc>>,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<User>> {
+        Ok(sqlx::query_as!(
+            User,
+            "
+UPDATE users
+SET suspended_at = $1, updated_at = $2
+WHERE id = $3
+returning *
+            ",
+            suspended_at,
+            updated_at,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn require_password_reset(
+        &self,
+        id: i32,
+        updated_at: chrono::DateTime<chrono::Utc>,
+    ) -> anyhow::Result<Option<User>> {
+        Ok(sqlx::query_as!(
+            User,
+            "
+UPDATE users
+SET password_reset_required = TRUE, updated_at = $1
+WHERE id = $2
+returning *
+            ",
+            updated_at,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await?)
+    }
+
+    async fn delete_user(&self, id: i32) -> anyhow::Result<bool> {
+        let done = sqlx::query_as!(
+            User,
+            "
+DELETE FROM users
+WHERE id = $1
+            ",
+            id
+        )
+        .execute(&self.db)
+        .await?;
+        Ok(done.rows_affected() > 0)
+    }
 }
@@ -9,11 +9,42 @@ pub mod gql;
 pub mod infrastructures;
 pub mod services;
 
+use crate::auth::key_ring::KeyRing;
+use crate::auth::oidc::OidcProviders;
+use crate::services::admin_service::AdminService;
+use crate::services::audit_logger::AuditLogger;
+use crate::services::data_export_service::DataExportService;
+use crate::services::identity_service::IdentityService;
+use crate::services::invite_service::InviteService;
+use crate::services::magic_link_service::MagicLinkService;
+use crate::services::password_reset_service::PasswordResetService;
+use crate::services::personal_access_token_service::PersonalAccessTokenService;
+use crate::services::rate_limit_service::RateLimitService;
+use crate::services::tag_service::TagService;
+use crate::services::todo_list_service::TodoListService;
 use crate::services::todo_service::TodoService;
+use crate::services::token_service::TokenService;
+use crate::services::two_factor_service::TwoFactorService;
 use crate::services::user_service::UserService;
+use std::sync::Arc;
 
 #[derive(Clone)]
 pub struct State {
+    pub key_ring: Arc<KeyRing>,
+    pub oidc_providers: Arc<OidcProviders>,
     pub user_service: UserService,
     pub todo_service: TodoService,
+    pub todo_list_service: TodoListService,
+    pub tag_service: TagService,
+    pub token_service: TokenService,
+    pub personal_access_token_service: PersonalAccessTokenService,
+    pub identity_service: IdentityService,
+    pub password_reset_service: PasswordResetService,
+    pub magic_link_service: MagicLinkService,
+    pub two_factor_service: TwoFactorService,
+    pub rate_limit_service: RateLimitService,
+    pub data_export_service: DataExportService,
+    pub admin_service: AdminService,
+    pub invite_service: InviteService,
+    pub audit_logger: AuditLogger,
 }
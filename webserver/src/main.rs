@@ -9,10 +9,34 @@ mod gql;
 mod infrastructures;
 mod services;
 
+use crate::auth::audit::RequestIdMiddleware;
+use crate::auth::handle_jwks;
+use crate::auth::key_ring::KeyRing;
+use crate::auth::oidc::{handle_oidc_callback, handle_oidc_login, OidcProviders};
+use crate::auth::password_policy::PasswordPolicy;
+use crate::auth::rate_limit::RateLimitMiddleware;
+use crate::domains::entities::invite_code::RegistrationMode;
 use crate::gql::{handle_graphiql, handle_graphql};
 use crate::infrastructures::database::create_pool;
 use crate::infrastructures::di_container::PgDIContainer;
+use crate::infrastructures::mailers::create_mailer;
+use crate::infrastructures::password_hashers::create_password_hasher;
+use crate::infrastructures::rate_limit_stores::create_rate_limit_store;
+use crate::services::admin_service::AdminService;
+use crate::services::audit_logger::AuditLogger;
+use crate::services::data_export_service::DataExportService;
+use crate::services::email_service::EmailService;
+use crate::services::identity_service::IdentityService;
+use crate::services::invite_service::InviteService;
+use crate::services::magic_link_service::MagicLinkService;
+use crate::services::password_reset_service::PasswordResetService;
+use crate::services::personal_access_token_service::PersonalAccessTokenService;
+use crate::services::rate_limit_service::RateLimitService;
+use crate::services::tag_service::TagService;
+use crate::services::todo_list_service::TodoListService;
 use crate::services::todo_service::TodoService;
+use crate::services::token_service::TokenService;
+use crate::services::two_factor_service::TwoFactorService;
 use crate::services::user_service::UserService;
 use std::env;
 use std::sync::Arc;
@@ -22,26 +46,85 @@ use tide::{Redirect, Server};
 
 #[derive(Clone)]
 pub struct State {
+    key_ring: Arc<KeyRing>,
+    oidc_providers: Arc<OidcProviders>,
     user_service: UserService,
     todo_service: TodoService,
+    todo_list_service: TodoListService,
+    tag_service: TagService,
+    token_service: TokenService,
+    personal_access_token_service: PersonalAccessTokenService,
+    identity_service: IdentityService,
+    password_reset_service: PasswordResetService,
+    magic_link_service: MagicLinkService,
+    two_factor_service: TwoFactorService,
+    rate_limit_service: RateLimitService,
+    data_export_service: DataExportService,
+    admin_service: AdminService,
+    invite_service: InviteService,
+    audit_logger: AuditLogger,
 }
 
 async fn bootstrap(db_connections: &str) -> anyhow::Result<Server<State>> {
+    let key_ring = Arc::new(KeyRing::from_env()?);
+    let oidc_providers = Arc::new(OidcProviders::from_env().await?);
+    let email_service = EmailService::new(
+        create_mailer()?,
+        env::var("APP_URL").unwrap_or_else(|_| "http://localhost:8080".to_owned()),
+    );
     let di_container = Arc::new(PgDIContainer {
         db: create_pool::<sqlx::Postgres>(5, db_connections).await?,
     });
+    let token_service = TokenService::new(di_container.clone(), key_ring.clone());
+    let password_hasher = create_password_hasher()?;
+    let password_policy = Arc::new(PasswordPolicy::from_env()?);
+    let registration_mode = RegistrationMode::from_env()?;
+    let password_reset_service = PasswordResetService::new(
+        di_container.clone(),
+        email_service.clone(),
+        password_hasher.clone(),
+        password_policy.clone(),
+    );
     let cors = CorsMiddleware::new()
         .allow_methods("GET, POST, OPTIONS".parse::<HeaderValue>().unwrap())
         .allow_origin(Origin::from("*"))
+        .expose_headers("Retry-After, X-Request-Id".parse::<HeaderValue>().unwrap())
         .allow_credentials(false);
     let mut app = Server::with_state(State {
-        user_service: UserService::new(di_container.clone()),
+        key_ring: key_ring.clone(),
+        oidc_providers,
+        user_service: UserService::new(
+            di_container.clone(),
+            key_ring.clone(),
+            email_service.clone(),
+            password_hasher,
+            password_policy,
+            registration_mode,
+        ),
         todo_service: TodoService::new(di_container.clone()),
+        todo_list_service: TodoListService::new(di_container.clone()),
+        tag_service: TagService::new(di_container.clone()),
+        token_service: token_service.clone(),
+        personal_access_token_service: PersonalAccessTokenService::new(di_container.clone()),
+        identity_service: IdentityService::new(di_container.clone(), registration_mode),
+        password_reset_service: password_reset_service.clone(),
+        magic_link_service: MagicLinkService::new(di_container.clone(), email_service.clone()),
+        two_factor_service: TwoFactorService::new(di_container.clone(), key_ring, token_service),
+        rate_limit_service: RateLimitService::new(create_rate_limit_store().await?),
+        data_export_service: DataExportService::new(di_container.clone()),
+        admin_service: AdminService::new(di_container.clone(), password_reset_service),
+        invite_service: InviteService::new(di_container.clone()),
+        audit_logger: AuditLogger::new(di_container),
     });
     app.with(cors);
+    app.with(RequestIdMiddleware::from_env());
+    app.with(RateLimitMiddleware::from_env());
     app.at("/").get(Redirect::permanent("/graphiql"));
     app.at("/graphql").post(handle_graphql);
     app.at("/graphiql").get(handle_graphiql);
+    app.at("/.well-known/jwks.json").get(handle_jwks);
+    app.at("/auth/:provider/login").get(handle_oidc_login);
+    app.at("/auth/:provider/callback").get(handle_oidc_callback);
     Ok(app)
 }
 
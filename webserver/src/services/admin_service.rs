@@ -0,0 +1,170 @@
+use crate::domains::entities::user::{Role, User, UserPage};
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::repositories::refresh_token_repository::RefreshTokenRepository;
+use crate::domains::repositories::user_repository::UserRepository;
+use crate::domains::ApplicationResult;
+use crate::infrastructures::di_container::DIContainer;
+use crate::services::password_reset_service::PasswordResetService;
+use std::sync::Arc;
+
+pub const MAX_PAGE_SIZE: i32 = 100;
+
+/// Operations of admins on other users.
+/// Every change which affects what a user may do logs them out, so their tokens pick it up.
+#[derive(Clone)]
+pub struct AdminService {
+    user_repository: Box<dyn UserRepository + Send + Sync>,
+    refresh_token_repository: Box<dyn RefreshTokenRepository + Send + Sync>,
+    password_reset_service: PasswordResetService,
+}
+
+impl AdminService {
+    pub fn new(
+        di_container: Arc<dyn DIContainer>,
+        password_reset_service: PasswordResetService,
+    ) -> Self {
+        Self {
+            user_repository: di_container.user_repository(),
+            refresh_token_repository: di_container.refresh_token_repository(),
+            password_reset_service,
+        }
+    }
+
+    pub async fn get_users(&self, offset: i32, limit: i32) -> ApplicationResult<UserPage> {
+        if offset < 0 || limit < 1 || limit > MAX_PAGE_SIZE {
+            return Err(ApplicationError {
+                code: ErrorCode::BadRequest,
+                message: format!(
+                    "offset can't be negative and limit has to be between 1 and {}",
+                    MAX_PAGE_SIZE
+                ),
+            });
+        }
+        let users = self
+            .user_repository
+            .get_users(offset as i64, limit as i64)
+            .await;
+        let total_count = self.user_repository.count_users().await;
+        match (users, total_count) {
+            (Ok(users), Ok(total_count)) => Ok(UserPage { users, total_count }),
+            (Err(err), _) | (_, Err(err)) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch users, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Suspended users are logged out and can't log in until they are unsuspended.
+    pub async fn suspend_user(&self, admin_id: i32, user_id: i32) -> ApplicationResult<User> {
+        if admin_id == user_id {
+            return Err(ApplicationError {
+                code: ErrorCode::BadRequest,
+                message: "admins can't suspend themselves".to_owned(),
+            });
+        }
+        let now = chrono::Utc::now();
+        let user = self.handle_update(
+            self.user_repository
+                .update_suspension(user_id, Some(now), now)
+                .await,
+        )?;
+        self.revoke_sessions(user_id).await?;
+        Ok(user)
+    }
+
+    pub async fn unsuspend_user(&self, user_id: i32) -> ApplicationResult<User> {
+        let now = chrono::Utc::now();
+        self.handle_update(
+            self.user_repository
+                .update_suspension(user_id, None, now)
+                .await,
+        )
+    }
+
+    /// Log the user out and mail a reset link, the password works again only after a reset.
+    pub async fn force_password_reset(&self, user_id: i32) -> ApplicationResult<User> {
+        let user = match self.user_repository.get_user_by_id(user_id).await {
+            Ok(Some(user)) => user,
+            Ok(None) => return Err(user_not_found()),
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch user, error: {:}", err),
+                })
+            }
+        };
+        if user.password_hash.is_none() || user.email.is_none() {
+            return Err(ApplicationError {
+                code: ErrorCode::BadRequest,
+                message: "user needs a password and an email to reset the password".to_owned(),
+            });
+        }
+        let now = chrono::Utc::now();
+        let user = self.handle_update(
+            self.user_repository
+                .require_password_reset(user_id, now)
+                .await,
+        )?;
+        self.revoke_sessions(user_id).await?;
+        self.password_reset_service
+            .send_password_reset_link(user.clone())
+            .await?;
+        Ok(user)
+    }
+
+    /// Change the role of the user, `admin_id` is the admin doing it, if any.
+    pub async fn set_role(
+        &self,
+        admin_id: Option<i32>,
+        user_id: i32,
+        role: Role,
+    ) -> ApplicationResult<User> {
+        if admin_id == Some(user_id) && role < Role::Admin {
+            return Err(ApplicationError {
+                code: ErrorCode::BadRequest,
+                message: "admins can't demote themselves".to_owned(),
+            });
+        }
+        let now = chrono::Utc::now();
+        let user = self.handle_update(
+            self.user_repository
+                .update_role(user_id, role.as_ref().to_owned(), now)
+                .await,
+        )?;
+        self.revoke_sessions(user_id).await?;
+        Ok(user)
+    }
+
+    fn handle_update(&self, result: anyhow::Result<Option<User>>) -> ApplicationResult<User> {
+        match result {
+            Ok(Some(user)) => Ok(user),
+            Ok(None) => Err(user_not_found()),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to update user, error: {:}", err),
+            }),
+        }
+    }
+
+    async fn revoke_sessions(&self, user_id: i32) -> ApplicationResult<()> {
+        let now = chrono::Utc::now();
+        match self
+            .refresh_token_repository
+            .revoke_user_refresh_tokens(user_id, now)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to revoke refresh tokens, error: {:}", err),
+            }),
+        }
+    }
+}
+
+fn user_not_found() -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::NotFound,
+        message: "user is not found".to_owned(),
+    }
+}
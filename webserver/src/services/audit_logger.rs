@@ -0,0 +1,67 @@
+use crate::domains::entities::audit_event::{AuditEvent, AuditEventFilter, AuditEventPage};
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::repositories::audit_event_repository::AuditEventRepository;
+use crate::domains::ApplicationResult;
+use crate::infrastructures::di_container::DIContainer;
+use crate::services::admin_service::MAX_PAGE_SIZE;
+use std::sync::Arc;
+
+/// Keeps a record of authentication events and admin actions, with where their requests came from.
+#[derive(Clone)]
+pub struct AuditLogger {
+    audit_event_repository: Box<dyn AuditEventRepository + Send + Sync>,
+}
+
+impl AuditLogger {
+    pub fn new(di_container: Arc<dyn DIContainer>) -> Self {
+        Self {
+            audit_event_repository: di_container.audit_event_repository(),
+        }
+    }
+
+    /// Record the event, a failure to do so is printed and doesn't fail what is audited.
+    pub async fn record(&self, audit_event: AuditEvent) {
+        if let Err(err) = self
+            .audit_event_repository
+            .create_audit_event(audit_event)
+            .await
+        {
+            println!("failed to record audit event, err: {:}", err);
+        }
+    }
+
+    pub async fn get_audit_events(
+        &self,
+        filter: AuditEventFilter,
+        offset: i32,
+        limit: i32,
+    ) -> ApplicationResult<AuditEventPage> {
+        if offset < 0 || limit < 1 || limit > MAX_PAGE_SIZE {
+            return Err(ApplicationError {
+                code: ErrorCode::BadRequest,
+                message: format!(
+                    "offset can't be negative and limit has to be between 1 and {}",
+                    MAX_PAGE_SIZE
+                ),
+            });
+        }
+        let audit_events = self
+            .audit_event_repository
+            .get_audit_events(&filter, offset as i64, limit as i64)
+            .await;
+        let total_count = self
+            .audit_event_repository
+            .count_audit_events(&filter)
+            .await;
+        match (audit_events, total_count) {
+            (Ok(audit_events), Ok(total_count)) => Ok(AuditEventPage {
+                audit_events,
+                total_count,
+            }),
+            (Err(err), _) | (_, Err(err)) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch audit events, error: {:}", err),
+            }),
+        }
+    }
+}
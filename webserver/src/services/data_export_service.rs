@@ -0,0 +1,412 @@
+use crate::domains::entities::data_export::DataExport;
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::repositories::audit_event_repository::AuditEventRepository;
+use crate::domains::repositories::email_token_repository::EmailTokenRepository;
+use crate::domains::repositories::personal_access_token_repository::PersonalAccessTokenRepository;
+use crate::domains::repositories::recovery_code_repository::RecoveryCodeRepository;
+use crate::domains::repositories::refresh_token_repository::RefreshTokenRepository;
+use crate::domains::repositories::session_repository::SessionRepository;
+use crate::domains::repositories::tag_repository::TagRepository;
+use crate::domains::repositories::todo_list_repository::TodoListRepository;
+use crate::domains::repositories::todo_repository::TodoRepository;
+use crate::domains::repositories::todo_series_repository::TodoSeriesRepository;
+use crate::domains::repositories::totp_credential_repository::TotpCredentialRepository;
+use crate::domains::repositories::user_identity_repository::UserIdentityRepository;
+use crate::domains::repositories::user_repository::UserRepository;
+use crate::domains::ApplicationResult;
+use crate::infrastructures::di_container::DIContainer;
+use serde_json::{json, Value};
+use std::io::{Cursor, Write};
+use std::sync::Arc;
+use zip::write::FileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+pub const EXPORT_FORMAT: &str = "rsapps-data-export";
+/// Bumped whenever a file or a field of the archive changes.
+pub const EXPORT_VERSION: i32 = 10;
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// A JSON file of the archive.
+struct ExportFile {
+    name: &'static str,
+    description: &'static str,
+    content: Value,
+}
+
+/// Collects everything tied to a user into a zip of JSON files.
+/// Password, token and TOTP secrets or their hashes are never exported.
+#[derive(Clone)]
+pub struct DataExportService {
+    user_repository: Box<dyn UserRepository + Send + Sync>,
+    todo_repository: Box<dyn TodoRepository + Send + Sync>,
+    todo_list_repository: Box<dyn TodoListRepository + Send + Sync>,
+    todo_series_repository: Box<dyn TodoSeriesRepository + Send + Sync>,
+    tag_repository: Box<dyn TagRepository + Send + Sync>,
+    refresh_token_repository: Box<dyn RefreshTokenRepository + Send + Sync>,
+    session_repository: Box<dyn SessionRepository + Send + Sync>,
+    personal_access_token_repository: Box<dyn PersonalAccessTokenRepository + Send + Sync>,
+    user_identity_repository: Box<dyn UserIdentityRepository + Send + Sync>,
+    email_token_repository: Box<dyn EmailTokenRepository + Send + Sync>,
+    totp_credential_repository: Box<dyn TotpCredentialRepository + Send + Sync>,
+    recovery_code_repository: Box<dyn RecoveryCodeRepository + Send + Sync>,
+    audit_event_repository: Box<dyn AuditEventRepository + Send + Sync>,
+}
+
+impl DataExportService {
+    pub fn new(di_container: Arc<dyn DIContainer>) -> Self {
+        Self {
+            user_repository: di_container.user_repository(),
+            todo_repository: di_container.todo_repository(),
+            todo_list_repository: di_container.todo_list_repository(),
+            todo_series_repository: di_container.todo_series_repository(),
+            tag_repository: di_container.tag_repository(),
+            refresh_token_repository: di_container.refresh_token_repository(),
+            session_repository: di_container.session_repository(),
+            personal_access_token_repository: di_container.personal_access_token_repository(),
+            user_identity_repository: di_container.user_identity_repository(),
+            email_token_repository: di_container.email_token_repository(),
+            totp_credential_repository: di_container.totp_credential_repository(),
+            recovery_code_repository: di_container.recovery_code_repository(),
+            audit_event_repository: di_container.audit_event_repository(),
+        }
+    }
+
+    pub async fn export_user(&self, user_id: i32) -> ApplicationResult<DataExport> {
+        let exported_at = chrono::Utc::now();
+        let files = match self.collect(user_id).await {
+            Ok(Some(files)) => files,
+            Ok(None) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::NotFound,
+                    message: "user is not found".to_owned(),
+                })
+            }
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to collect personal data, error: {:}", err),
+                })
+            }
+        };
+        match write_archive(user_id, exported_at, files) {
+            Ok(content) => Ok(DataExport {
+                file_name: format!(
+                    "rsapps-export-{}-{}.zip",
+                    user_id,
+                    exported_at.format("%Y%m%d%H%M%S")
+                ),
+                content,
+            }),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to write data export, error: {:}", err),
+            }),
+        }
+    }
+
+    async fn collect(&self, user_id: i32) -> anyhow::Result<Option<Vec<ExportFile>>> {
+        let user = match self.user_repository.get_user_by_id(user_id).await? {
+            Some(user) => user,
+            None => return Ok(None),
+        };
+        let todo_lists = self
+            .todo_list_repository
+            .get_user_todo_lists(user_id)
+            .await?;
+        let todos = self.todo_repository.get_all_todos(user_id).await?;
+        let todo_series = self
+            .todo_series_repository
+            .get_user_todo_series(user_id)
+            .await?;
+        let tags = self.tag_repository.get_user_tags(user_id).await?;
+        let todo_tags = self.tag_repository.get_user_todo_tags(user_id).await?;
+        let refresh_tokens = self
+            .refresh_token_repository
+            .get_user_refresh_tokens(user_id)
+            .await?;
+        let sessions = self.session_repository.get_user_sessions(user_id).await?;
+        let personal_access_tokens = self
+            .personal_access_token_repository
+            .get_user_personal_access_tokens(user_id)
+            .await?;
+        let identities = self
+            .user_identity_repository
+            .get_user_identities(user_id)
+            .await?;
+        let email_tokens = self
+            .email_token_repository
+            .get_user_email_tokens(user_id)
+            .await?;
+        let totp_credential = self
+            .totp_credential_repository
+            .get_totp_credential(user_id)
+            .await?;
+        let recovery_codes = self
+            .recovery_code_repository
+            .get_recovery_codes(user_id)
+            .await?;
+        let audit_events = self
+            .audit_event_repository
+            .get_user_audit_events(user_id)
+            .await?;
+
+        Ok(Some(vec![
+            ExportFile {
+                name: "user.json",
+                description: "The user account",
+                content: json!({
+                    "id": user.id,
+                    "username": user.username,
+                    "email": user.email,
+                    "email_verified_at": user.email_verified_at.map(|t| t.to_rfc3339()),
+                    "has_password": user.password_hash.is_some(),
+                    "role": user.role,
+                    "suspended_at": user.suspended_at.map(|t| t.to_rfc3339()),
+                    "password_reset_required": user.password_reset_required,
+                    "created_at": user.created_at.to_rfc3339(),
+                    "updated_at": user.updated_at.to_rfc3339(),
+                }),
+            },
+            ExportFile {
+                name: "todo_lists.json",
+                description: "Todo lists the user owns or shares",
+                content: todo_lists
+                    .iter()
+                    .map(|todo_list| {
+                        json!({
+                            "id": todo_list.id,
+                            "owner_id": todo_list.user_id,
+                            "name": todo_list.name,
+                            "is_default": todo_list.is_default,
+                            "created_at": todo_list.created_at.to_rfc3339(),
+                            "updated_at": todo_list.updated_at.to_rfc3339(),
+                        })
+                    })
+                    .collect(),
+            },
+            ExportFile {
+                name: "todos.json",
+                description: "Todos the user created",
+                content: todos
+                    .iter()
+                    .map(|todo| {
+                        json!({
+                            "id": todo.id,
+                            "list_id": todo.list_id,
+                            "body": todo.body,
+                            "complete": todo.complete,
+                            "created_by": todo.created_by,
+                            "updated_by": todo.updated_by,
+                            "start_at": todo.start_at.map(|t| t.to_rfc3339()),
+                            "due_at": todo.due_at.map(|t| t.to_rfc3339()),
+                            "series_id": todo.series_id,
+                            "occurrence_at": todo.occurrence_at.map(|t| t.to_rfc3339()),
+                            "previous_id": todo.previous_id,
+                            "created_at": todo.created_at.to_rfc3339(),
+                            "updated_at": todo.updated_at.to_rfc3339(),
+                        })
+                    })
+                    .collect(),
+            },
+            ExportFile {
+                name: "todo_series.json",
+                description: "Series of the recurring todos the user created",
+                content: todo_series
+                    .iter()
+                    .map(|series| {
+                        json!({
+                            "id": series.id,
+                            "list_id": series.list_id,
+                            "body": series.body,
+                            "rrule": series.rrule,
+                            "time_zone": series.time_zone,
+                            "dtstart": series.dtstart.to_rfc3339(),
+                            "occurrences_before": series.occurrences_before,
+                            "created_at": series.created_at.to_rfc3339(),
+                            "updated_at": series.updated_at.to_rfc3339(),
+                        })
+                    })
+                    .collect(),
+            },
+            ExportFile {
+                name: "tags.json",
+                description: "Tags of the user with the todos they are on",
+                content: tags
+                    .iter()
+                    .map(|tag| {
+                        json!({
+                            "id": tag.id,
+                            "name": tag.name,
+                            "color": tag.color,
+                            "todo_ids": todo_tags
+                                .iter()
+                                .filter(|todo_tag| todo_tag.tag_id == tag.id)
+                                .map(|todo_tag| todo_tag.todo_id)
+                                .collect::<Vec<i32>>(),
+                            "created_at": tag.created_at.to_rfc3339(),
+                            "updated_at": tag.updated_at.to_rfc3339(),
+                        })
+                    })
+                    .collect(),
+            },
+            ExportFile {
+                name: "sessions.json",
+                description:
+                    "Logins of the user with their device and where they were last seen from",
+                content: sessions
+                    .iter()
+                    .map(|session| {
+                        json!({
+                            "id": session.id,
+                            "family": session.family,
+                            "device_name": session.device_name(),
+                            "user_agent": session.user_agent,
+                            "ip_address": session.ip_address,
+                            "created_at": session.created_at.to_rfc3339(),
+                            "last_seen_at": session.last_seen_at.to_rfc3339(),
+                        })
+                    })
+                    .collect(),
+            },
+            ExportFile {
+                name: "refresh_tokens.json",
+                description: "Refresh tokens issued to logins, tokens of a session share a family",
+                content: refresh_tokens
+                    .iter()
+                    .map(|token| {
+                        json!({
+                            "id": token.id,
+                            "family": token.family,
+                            "expires_at": token.expires_at.to_rfc3339(),
+                            "used_at": token.used_at.map(|t| t.to_rfc3339()),
+                            "revoked_at": token.revoked_at.map(|t| t.to_rfc3339()),
+                            "created_at": token.created_at.to_rfc3339(),
+                        })
+                    })
+                    .collect(),
+            },
+            ExportFile {
+                name: "personal_access_tokens.json",
+                description: "Personal access tokens of the user",
+                content: personal_access_tokens
+                    .iter()
+                    .map(|token| {
+                        json!({
+                            "id": token.id,
+                            "name": token.name,
+                            "scopes": token.scopes,
+                            "expires_at": token.expires_at.map(|t| t.to_rfc3339()),
+                            "last_used_at": token.last_used_at.map(|t| t.to_rfc3339()),
+                            "revoked_at": token.revoked_at.map(|t| t.to_rfc3339()),
+                            "created_at": token.created_at.to_rfc3339(),
+                        })
+                    })
+                    .collect(),
+            },
+            ExportFile {
+                name: "identities.json",
+                description: "Accounts of login providers linked to the user",
+                content: identities
+                    .iter()
+                    .map(|identity| {
+                        json!({
+                            "id": identity.id,
+                            "provider": identity.provider,
+                            "subject": identity.subject,
+                            "email": identity.email,
+                            "created_at": identity.created_at.to_rfc3339(),
+                        })
+                    })
+                    .collect(),
+            },
+            ExportFile {
+                name: "email_tokens.json",
+                description: "Password reset and login links mailed to the user",
+                content: email_tokens
+                    .iter()
+                    .map(|token| {
+                        json!({
+                            "id": token.id,
+                            "purpose": token.purpose,
+                            "expires_at": token.expires_at.to_rfc3339(),
+                            "used_at": token.used_at.map(|t| t.to_rfc3339()),
+                            "created_at": token.created_at.to_rfc3339(),
+                        })
+                    })
+                    .collect(),
+            },
+            ExportFile {
+                name: "two_factor.json",
+                description: "Two-factor authentication of the user",
+                content: json!({
+                    "totp": totp_credential.map(|credential| json!({
+                        "confirmed_at": credential.confirmed_at.map(|t| t.to_rfc3339()),
+                        "created_at": credential.created_at.to_rfc3339(),
+                    })),
+                    "recovery_codes": recovery_codes
+                        .iter()
+                        .map(|code| json!({
+                            "id": code.id,
+                            "used_at": code.used_at.map(|t| t.to_rfc3339()),
+                            "created_at": code.created_at.to_rfc3339(),
+                        }))
+                        .collect::<Vec<_>>(),
+                }),
+            },
+            ExportFile {
+                name: "audit_events.json",
+                description: "Authentication events and admin actions about the user",
+                content: audit_events
+                    .iter()
+                    .map(|event| {
+                        json!({
+                            "id": event.id,
+                            "event_type": event.event_type,
+                            "actor_id": event.actor_id,
+                            "account": event.account,
+                            "detail": event.detail,
+                            "ip_address": event.ip_address,
+                            "user_agent": event.user_agent,
+                            "request_id": event.request_id,
+                            "created_at": event.created_at.to_rfc3339(),
+                        })
+                    })
+                    .collect(),
+            },
+        ]))
+    }
+}
+
+/// Write the files with a manifest, which lists them with their number of records, first.
+fn write_archive(
+    user_id: i32,
+    exported_at: chrono::DateTime<chrono::Utc>,
+    files: Vec<ExportFile>,
+) -> anyhow::Result<Vec<u8>> {
+    let manifest = json!({
+        "format": EXPORT_FORMAT,
+        "version": EXPORT_VERSION,
+        "user_id": user_id,
+        "exported_at": exported_at.to_rfc3339(),
+        "files": files
+            .iter()
+            .map(|file| json!({
+                "name": file.name,
+                "description": file.description,
+                "records": match &file.content {
+                    Value::Array(records) => records.len(),
+                    _ => 1,
+                },
+            }))
+            .collect::<Vec<_>>(),
+    });
+    let options = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file(MANIFEST_FILE, options)?;
+    zip.write_all(&serde_json::to_vec_pretty(&manifest)?)?;
+    for file in files {
+        zip.start_file(file.name, options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&file.content)?)?;
+    }
+    Ok(zip.finish()?.into_inner())
+}
@@ -0,0 +1,72 @@
+use crate::domains::entities::mail::Mail;
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::mailer::Mailer;
+use crate::domains::ApplicationResult;
+
+/// Writes the account emails, with links back to the frontend at `app_url`.
+#[derive(Clone)]
+pub struct EmailService {
+    mailer: Box<dyn Mailer + Send + Sync>,
+    app_url: String,
+}
+
+impl EmailService {
+    pub fn new(mailer: Box<dyn Mailer + Send + Sync>, app_url: String) -> Self {
+        Self {
+            mailer,
+            app_url: app_url.trim_end_matches('/').to_owned(),
+        }
+    }
+
+    pub async fn send_email_verification(
+        &self,
+        to: String,
+        token: String,
+    ) -> ApplicationResult<()> {
+        self.send(Mail {
+            to,
+            subject: "Verify your email address".to_owned(),
+            body: format!(
+                "Open the link below to verify your email address.\n\n{}/?verify_email={}\n",
+                self.app_url, token
+            ),
+        })
+        .await
+    }
+
+    pub async fn send_password_reset(&self, to: String, token: String) -> ApplicationResult<()> {
+        self.send(Mail {
+            to,
+            subject: "Reset your password".to_owned(),
+            body: format!(
+                "Open the link below to choose a new password. \
+                 If you didn't ask for it, you can ignore this mail.\n\n{}/?reset_password={}\n",
+                self.app_url, token
+            ),
+        })
+        .await
+    }
+
+    pub async fn send_magic_link(&self, to: String, token: String) -> ApplicationResult<()> {
+        self.send(Mail {
+            to,
+            subject: "Your login link".to_owned(),
+            body: format!(
+                "Open the link below to log in, it works once and expires in a few minutes. \
+                 If you didn't ask for it, you can ignore this mail.\n\n{}/?magic_link={}\n",
+                self.app_url, token
+            ),
+        })
+        .await
+    }
+
+    async fn send(&self, mail: Mail) -> ApplicationResult<()> {
+        match self.mailer.send(mail).await {
+            Ok(()) => Ok(()),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to send mail, error: {:}", err),
+            }),
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use crate::auth::{generate_token, hash_token};
+use crate::domains::entities::email_token::{EmailToken, EmailTokenPurpose};
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::repositories::email_token_repository::EmailTokenRepository;
+use crate::domains::ApplicationResult;
+use crate::infrastructures::di_container::DIContainer;
+use std::sync::Arc;
+
+/// Issues and checks the single use tokens of links mailed to users, like password reset links.
+#[derive(Clone)]
+pub struct EmailTokenService {
+    email_token_repository: Box<dyn EmailTokenRepository + Send + Sync>,
+}
+
+impl EmailTokenService {
+    pub fn new(di_container: Arc<dyn DIContainer>) -> Self {
+        Self {
+            email_token_repository: di_container.email_token_repository(),
+        }
+    }
+
+    /// Store a token for the user which expires after the lifetime, returns the token for the link.
+    pub async fn issue_token(
+        &self,
+        user_id: i32,
+        purpose: EmailTokenPurpose,
+        lifetime: chrono::Duration,
+    ) -> ApplicationResult<String> {
+        let now = chrono::Utc::now();
+        let token = generate_token();
+        let email_token = EmailToken {
+            id: 0,
+            user_id,
+            purpose: purpose.as_ref().to_owned(),
+            token_hash: hash_token(&token),
+            expires_at: now + lifetime,
+            used_at: None,
+            created_at: now,
+        };
+        match self
+            .email_token_repository
+            .create_email_token(email_token)
+            .await
+        {
+            Ok(_) => Ok(token),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to create email token, error: {:}", err),
+            }),
+        }
+    }
+
+    /// The stored token unless it is unknown, of another purpose, used or expired.
+    pub async fn find_token(
+        &self,
+        purpose: EmailTokenPurpose,
+        token: &str,
+    ) -> ApplicationResult<EmailToken> {
+        let email_token = match self
+            .email_token_repository
+            .get_email_token_by_hash(purpose.as_ref().to_owned(), hash_token(token))
+            .await
+        {
+            Ok(Some(email_token)) if email_token.used_at.is_none() => email_token,
+            Ok(_) => return Err(email_token_invalid(purpose)),
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch email token, error: {:}", err),
+                })
+            }
+        };
+        if email_token.expires_at < chrono::Utc::now() {
+            return Err(ApplicationError {
+                code: ErrorCode::TokenExpired,
+                message: format!("{} token is expired", purpose_name(purpose)),
+            });
+        }
+        Ok(email_token)
+    }
+
+    /// Use up the token and the other links of its purpose sent to the user,
+    /// it fails if a concurrent request used the token first.
+    pub async fn use_token(&self, email_token: &EmailToken) -> ApplicationResult<()> {
+        let now = chrono::Utc::now();
+        match self
+            .email_token_repository
+            .use_email_token(email_token.id, now)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::UnAuthenticated,
+                    message: "email token is used already".to_owned(),
+                })
+            }
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to use email token, error: {:}", err),
+                })
+            }
+        }
+        match self
+            .email_token_repository
+            .use_user_email_tokens(email_token.user_id, email_token.purpose.to_owned(), now)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to use email tokens, error: {:}", err),
+            }),
+        }
+    }
+}
+
+fn purpose_name(purpose: EmailTokenPurpose) -> &'static str {
+    match purpose {
+        EmailTokenPurpose::PasswordReset => "password reset",
+        EmailTokenPurpose::MagicLink => "magic link",
+    }
+}
+
+fn email_token_invalid(purpose: EmailTokenPurpose) -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::UnAuthenticated,
+        message: format!("{} token is invalid", purpose_name(purpose)),
+    }
+}
@@ -0,0 +1,155 @@
+use crate::auth::generate_token;
+use crate::domains::entities::invite_code::RegistrationMode;
+use crate::domains::entities::user::{Role, User};
+use crate::domains::entities::user_identity::UserIdentity;
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::repositories::user_identity_repository::UserIdentityRepository;
+use crate::domains::repositories::user_repository::UserRepository;
+use crate::domains::ApplicationResult;
+use crate::infrastructures::di_container::DIContainer;
+use std::sync::Arc;
+
+/// Usernames are at most 16 characters, a suffix for duplicates takes 6 of them.
+const USERNAME_HINT_LENGTH: usize = 10;
+const USERNAME_ATTEMPTS: usize = 5;
+
+#[derive(Clone)]
+pub struct IdentityService {
+    registration_mode: RegistrationMode,
+    user_repository: Box<dyn UserRepository + Send + Sync>,
+    user_identity_repository: Box<dyn UserIdentityRepository + Send + Sync>,
+}
+
+impl IdentityService {
+    pub fn new(di_container: Arc<dyn DIContainer>, registration_mode: RegistrationMode) -> Self {
+        Self {
+            registration_mode,
+            user_repository: di_container.user_repository(),
+            user_identity_repository: di_container.user_identity_repository(),
+        }
+    }
+
+    /// Find the user linked to the subject of a provider, or sign up a new one on the first login.
+    /// Existing accounts are never linked by email, the provider may not have verified it.
+    /// Providers can't take an invite code, so they sign up new users only when registration is open.
+    pub async fn sign_in(
+        &self,
+        provider: String,
+        subject: String,
+        email: Option<String>,
+        username_hint: Option<String>,
+    ) -> ApplicationResult<User> {
+        let identity = match self
+            .user_identity_repository
+            .get_user_identity(provider.to_owned(), subject.to_owned())
+            .await
+        {
+            Ok(Some(identity)) => identity,
+            Ok(None) if self.registration_mode != RegistrationMode::Open => {
+                return Err(ApplicationError {
+                    code: ErrorCode::Forbidden,
+                    message: "registration is not open to new users of login providers".to_owned(),
+                })
+            }
+            Ok(None) => {
+                let now = chrono::Utc::now();
+                let user = User {
+                    id: 0,
+                    username: self.available_username(username_hint).await?,
+                    email: None,
+                    password_hash: None,
+                    created_at: now,
+                    updated_at: now,
+                    email_verified_at: None,
+                    role: Role::User.as_ref().to_owned(),
+                    suspended_at: None,
+                    password_reset_required: false,
+                };
+                let identity = UserIdentity {
+                    id: 0,
+                    user_id: 0,
+                    provider,
+                    subject,
+                    email,
+                    created_at: now,
+                };
+                match self
+                    .user_identity_repository
+                    .create_user_with_identity(user, identity)
+                    .await
+                {
+                    Ok(identity) => identity,
+                    Err(err) => {
+                        return Err(ApplicationError {
+                            code: ErrorCode::SystemError,
+                            message: format!("failed to create user identity, error: {:}", err),
+                        })
+                    }
+                }
+            }
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch user identity, error: {:}", err),
+                })
+            }
+        };
+        match self.user_repository.get_user_by_id(identity.user_id).await {
+            Ok(Some(user)) => Ok(user),
+            Ok(None) => Err(ApplicationError {
+                code: ErrorCode::NotFound,
+                message: "user is not registered".to_owned(),
+            }),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch user, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Derive a username from the name the provider suggests, with a random suffix when it is taken.
+    async fn available_username(&self, hint: Option<String>) -> ApplicationResult<String> {
+        let base = username_base(hint.as_deref().unwrap_or(""));
+        let mut username = base.to_owned();
+        for _ in 0..USERNAME_ATTEMPTS {
+            match self
+                .user_repository
+                .get_user_by_username(username.to_owned())
+                .await
+            {
+                Ok(None) => return Ok(username),
+                Ok(Some(_)) => {
+                    username = format!("{}_{}", base, &generate_token()[..5]);
+                }
+                Err(err) => {
+                    return Err(ApplicationError {
+                        code: ErrorCode::SystemError,
+                        message: format!("failed to fetch user, error: {:}", err),
+                    })
+                }
+            }
+        }
+        Err(ApplicationError {
+            code: ErrorCode::Conflict,
+            message: "failed to find an available username".to_owned(),
+        })
+    }
+}
+
+/// Keep the lowercase alphanumerics of the part before `@`, so emails become usernames too.
+fn username_base(hint: &str) -> String {
+    let base: String = hint
+        .split('@')
+        .next()
+        .unwrap_or("")
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+        .map(|c| c.to_ascii_lowercase())
+        .take(USERNAME_HINT_LENGTH)
+        .collect();
+    if base.is_empty() {
+        "user".to_owned()
+    } else {
+        base
+    }
+}
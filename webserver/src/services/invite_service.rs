@@ -0,0 +1,112 @@
+use crate::auth::{generate_token, hash_token};
+use crate::domains::entities::invite_code::{CreatedInviteCode, InviteCode, InviteRedemption};
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::repositories::invite_code_repository::InviteCodeRepository;
+use crate::domains::ApplicationResult;
+use crate::infrastructures::di_container::DIContainer;
+use std::sync::Arc;
+
+/// Invite codes which let users sign up when registration is invite only.
+/// Codes are redeemed by `UserService::sign_up`.
+#[derive(Clone)]
+pub struct InviteService {
+    invite_code_repository: Box<dyn InviteCodeRepository + Send + Sync>,
+}
+
+impl InviteService {
+    pub fn new(di_container: Arc<dyn DIContainer>) -> Self {
+        Self {
+            invite_code_repository: di_container.invite_code_repository(),
+        }
+    }
+
+    pub async fn get_invite_codes(&self) -> ApplicationResult<Vec<InviteCode>> {
+        match self.invite_code_repository.get_invite_codes().await {
+            Ok(invite_codes) => Ok(invite_codes),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch invite codes, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Create a code which signs up `max_uses` users until it expires,
+    /// `created_by` is the admin creating it, if any.
+    pub async fn create_invite_code(
+        &self,
+        created_by: Option<i32>,
+        max_uses: i32,
+        expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> ApplicationResult<CreatedInviteCode> {
+        if max_uses < 1 {
+            return Err(ApplicationError {
+                code: ErrorCode::BadRequest,
+                message: "invite code needs at least one use".to_owned(),
+            });
+        }
+        let now = chrono::Utc::now();
+        if let Some(expires_at) = expires_at {
+            if expires_at <= now {
+                return Err(ApplicationError {
+                    code: ErrorCode::BadRequest,
+                    message: "invite code has to expire in the future".to_owned(),
+                });
+            }
+        }
+        let code = generate_token();
+        let invite_code = InviteCode {
+            id: 0,
+            code_hash: hash_token(&code),
+            created_by,
+            max_uses,
+            use_count: 0,
+            expires_at,
+            revoked_at: None,
+            created_at: now,
+        };
+        match self
+            .invite_code_repository
+            .create_invite_code(invite_code)
+            .await
+        {
+            Ok(invite_code) => Ok(CreatedInviteCode { code, invite_code }),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to create invite code, error: {:}", err),
+            }),
+        }
+    }
+
+    pub async fn revoke_invite_code(&self, id: i32) -> ApplicationResult<bool> {
+        let now = chrono::Utc::now();
+        match self
+            .invite_code_repository
+            .revoke_invite_code(id, now)
+            .await
+        {
+            Ok(ret) => Ok(ret),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to revoke invite code, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Who signed up with the code and when.
+    pub async fn get_invite_redemptions(
+        &self,
+        invite_code_id: i32,
+    ) -> ApplicationResult<Vec<InviteRedemption>> {
+        match self
+            .invite_code_repository
+            .get_invite_redemptions(invite_code_id)
+            .await
+        {
+            Ok(redemptions) => Ok(redemptions),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch invite redemptions, error: {:}", err),
+            }),
+        }
+    }
+}
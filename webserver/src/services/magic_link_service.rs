@@ -0,0 +1,106 @@
+use crate::domains::entities::email_token::EmailTokenPurpose;
+use crate::domains::entities::user::User;
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::repositories::user_repository::UserRepository;
+use crate::domains::ApplicationResult;
+use crate::infrastructures::di_container::DIContainer;
+use crate::services::email_service::EmailService;
+use crate::services::email_token_service::EmailTokenService;
+use std::sync::Arc;
+
+const MAGIC_LINK_MINUTES: i64 = 15;
+
+/// Passwordless login with single use links mailed to verified emails.
+#[derive(Clone)]
+pub struct MagicLinkService {
+    email_service: EmailService,
+    email_token_service: EmailTokenService,
+    user_repository: Box<dyn UserRepository + Send + Sync>,
+}
+
+impl MagicLinkService {
+    pub fn new(di_container: Arc<dyn DIContainer>, email_service: EmailService) -> Self {
+        Self {
+            email_service,
+            email_token_service: EmailTokenService::new(di_container.clone()),
+            user_repository: di_container.user_repository(),
+        }
+    }
+
+    /// Mail a login link if a user has verified the email.
+    /// An unknown email isn't an error, so callers can't tell which emails are registered.
+    pub async fn request_magic_link(&self, email: String) -> ApplicationResult<()> {
+        let user = match self
+            .user_repository
+            .get_user_by_email(email.trim().to_lowercase())
+            .await
+        {
+            Ok(Some(user)) => user,
+            Ok(None) => return Ok(()),
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch user, error: {:}", err),
+                })
+            }
+        };
+        // Whoever owns an unverified email may not be the user who typed it in.
+        let email = match user.email {
+            Some(email) if user.email_verified_at.is_some() && !user.is_suspended() => email,
+            _ => return Ok(()),
+        };
+        let token = self
+            .email_token_service
+            .issue_token(
+                user.id,
+                EmailTokenPurpose::MagicLink,
+                chrono::Duration::minutes(MAGIC_LINK_MINUTES),
+            )
+            .await?;
+        self.email_service.send_magic_link(email, token).await
+    }
+
+    /// Use up the token of a login link, returns the user to log in.
+    pub async fn redeem_magic_link(&self, token: String) -> ApplicationResult<User> {
+        let magic_link_token = self
+            .email_token_service
+            .find_token(EmailTokenPurpose::MagicLink, &token)
+            .await?;
+        self.email_token_service
+            .use_token(&magic_link_token)
+            .await?;
+        let user = match self
+            .user_repository
+            .get_user_by_id(magic_link_token.user_id)
+            .await
+        {
+            Ok(Some(user)) => user,
+            Ok(None) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::UnAuthenticated,
+                    message: "magic link token is invalid".to_owned(),
+                })
+            }
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch user, error: {:}", err),
+                })
+            }
+        };
+        if user.is_suspended() {
+            return Err(ApplicationError {
+                code: ErrorCode::AccountSuspended,
+                message: "user is suspended".to_owned(),
+            });
+        }
+        // A link proves the email but not that the old password is safe again.
+        if user.password_reset_required {
+            return Err(ApplicationError {
+                code: ErrorCode::PasswordResetRequired,
+                message: "password has to be reset".to_owned(),
+            });
+        }
+        Ok(user)
+    }
+}
@@ -1,2 +1,17 @@
-pub(crate) mod todo_service;
-pub(crate) mod user_service;
+pub mod admin_service;
+pub mod audit_logger;
+pub mod data_export_service;
+pub mod email_service;
+pub mod email_token_service;
+pub mod identity_service;
+pub mod invite_service;
+pub mod magic_link_service;
+pub mod password_reset_service;
+pub mod personal_access_token_service;
+pub mod rate_limit_service;
+pub mod tag_service;
+pub mod todo_list_service;
+pub mod todo_service;
+pub mod token_service;
+pub mod two_factor_service;
+pub mod user_service;
@@ -0,0 +1,152 @@
+use crate::auth::password_policy::PasswordPolicy;
+use crate::domains::entities::email_token::EmailTokenPurpose;
+use crate::domains::entities::user::User;
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::password_hasher::PasswordHasher;
+use crate::domains::repositories::refresh_token_repository::RefreshTokenRepository;
+use crate::domains::repositories::user_repository::UserRepository;
+use crate::domains::ApplicationResult;
+use crate::infrastructures::di_container::DIContainer;
+use crate::services::email_service::EmailService;
+use crate::services::email_token_service::EmailTokenService;
+use crate::services::user_service::user_inputs;
+use std::sync::Arc;
+
+const PASSWORD_RESET_MINUTES: i64 = 60;
+
+#[derive(Clone)]
+pub struct PasswordResetService {
+    email_service: EmailService,
+    email_token_service: EmailTokenService,
+    password_hasher: Box<dyn PasswordHasher + Send + Sync>,
+    password_policy: Arc<PasswordPolicy>,
+    user_repository: Box<dyn UserRepository + Send + Sync>,
+    refresh_token_repository: Box<dyn RefreshTokenRepository + Send + Sync>,
+}
+
+impl PasswordResetService {
+    pub fn new(
+        di_container: Arc<dyn DIContainer>,
+        email_service: EmailService,
+        password_hasher: Box<dyn PasswordHasher + Send + Sync>,
+        password_policy: Arc<PasswordPolicy>,
+    ) -> Self {
+        Self {
+            email_service,
+            email_token_service: EmailTokenService::new(di_container.clone()),
+            password_hasher,
+            password_policy,
+            user_repository: di_container.user_repository(),
+            refresh_token_repository: di_container.refresh_token_repository(),
+        }
+    }
+
+    /// Mail a reset link if a user has the email.
+    /// An unknown email isn't an error, so callers can't tell which emails are registered.
+    pub async fn request_password_reset(&self, email: String) -> ApplicationResult<()> {
+        let user = match self
+            .user_repository
+            .get_user_by_email(email.trim().to_lowercase())
+            .await
+        {
+            Ok(Some(user)) => user,
+            Ok(None) => return Ok(()),
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch user, error: {:}", err),
+                })
+            }
+        };
+        self.send_password_reset_link(user).await
+    }
+
+    /// Mail a reset link to the user, users without an email are skipped.
+    pub async fn send_password_reset_link(&self, user: User) -> ApplicationResult<()> {
+        let email = match user.email {
+            Some(email) => email,
+            None => return Ok(()),
+        };
+        let token = self
+            .email_token_service
+            .issue_token(
+                user.id,
+                EmailTokenPurpose::PasswordReset,
+                chrono::Duration::minutes(PASSWORD_RESET_MINUTES),
+            )
+            .await?;
+        self.email_service.send_password_reset(email, token).await
+    }
+
+    /// Set a new password with the token of a reset link, then log the user out everywhere.
+    /// Returns the id of the user whose password was reset.
+    pub async fn reset_password(
+        &self,
+        token: String,
+        new_password: String,
+    ) -> ApplicationResult<i32> {
+        let reset_token = self
+            .email_token_service
+            .find_token(EmailTokenPurpose::PasswordReset, &token)
+            .await?;
+        // Checked before the token is used up, so a rejected password leaves the link working.
+        let user = match self
+            .user_repository
+            .get_user_by_id(reset_token.user_id)
+            .await
+        {
+            Ok(Some(user)) => user,
+            Ok(None) => return Err(password_reset_token_invalid()),
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch user, error: {:}", err),
+                })
+            }
+        };
+        self.password_policy.validate(
+            &new_password,
+            &user_inputs(&user.username, user.email.as_deref()),
+        )?;
+        let hash = match self.password_hasher.hash(&new_password) {
+            Ok(hash) => hash,
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to hash password, error: {:}", err),
+                })
+            }
+        };
+        self.email_token_service.use_token(&reset_token).await?;
+
+        let now = chrono::Utc::now();
+        if let Err(err) = self
+            .user_repository
+            .update_password(reset_token.user_id, hash, now)
+            .await
+        {
+            return Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to update password, error: {:}", err),
+            });
+        }
+        match self
+            .refresh_token_repository
+            .revoke_user_refresh_tokens(reset_token.user_id, now)
+            .await
+        {
+            Ok(_) => Ok(reset_token.user_id),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to revoke refresh tokens, error: {:}", err),
+            }),
+        }
+    }
+}
+
+fn password_reset_token_invalid() -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::UnAuthenticated,
+        message: "password reset token is invalid".to_owned(),
+    }
+}
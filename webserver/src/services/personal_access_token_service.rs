@@ -0,0 +1,190 @@
+use crate::auth::{generate_token, hash_token, Principal, PERSONAL_ACCESS_TOKEN_PREFIX};
+use crate::domains::entities::personal_access_token::{
+    CreatedPersonalAccessToken, PersonalAccessToken, Scope,
+};
+use crate::domains::entities::user::Role;
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::repositories::personal_access_token_repository::PersonalAccessTokenRepository;
+use crate::domains::repositories::user_repository::UserRepository;
+use crate::domains::ApplicationResult;
+use crate::infrastructures::di_container::DIContainer;
+use std::sync::Arc;
+
+#[derive(Clone)]
+pub struct PersonalAccessTokenService {
+    personal_access_token_repository: Box<dyn PersonalAccessTokenRepository + Send + Sync>,
+    user_repository: Box<dyn UserRepository + Send + Sync>,
+}
+
+impl PersonalAccessTokenService {
+    pub fn new(di_container: Arc<dyn DIContainer>) -> Self {
+        Self {
+            personal_access_token_repository: di_container.personal_access_token_repository(),
+            user_repository: di_container.user_repository(),
+        }
+    }
+
+    pub async fn get_personal_access_tokens(
+        &self,
+        user_id: i32,
+    ) -> ApplicationResult<Vec<PersonalAccessToken>> {
+        match self
+            .personal_access_token_repository
+            .get_personal_access_tokens(user_id)
+            .await
+        {
+            Ok(tokens) => Ok(tokens),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch personal access tokens, error: {:}", err),
+            }),
+        }
+    }
+
+    pub async fn create_personal_access_token(
+        &self,
+        user_id: i32,
+        name: String,
+        scopes: Vec<Scope>,
+        expires_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> ApplicationResult<CreatedPersonalAccessToken> {
+        if scopes.is_empty() {
+            return Err(ApplicationError {
+                code: ErrorCode::BadRequest,
+                message: "personal access token needs at least one scope".to_owned(),
+            });
+        }
+        let token = format!("{}{}", PERSONAL_ACCESS_TOKEN_PREFIX, generate_token());
+        let new_token = PersonalAccessToken {
+            id: 0,
+            user_id,
+            name,
+            token_hash: hash_token(&token),
+            scopes: scopes
+                .iter()
+                .map(|scope| scope.as_ref().to_owned())
+                .collect(),
+            expires_at,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: chrono::Utc::now(),
+        };
+        match self
+            .personal_access_token_repository
+            .create_personal_access_token(new_token)
+            .await
+        {
+            Ok(created) => Ok(CreatedPersonalAccessToken {
+                token,
+                personal_access_token: created,
+            }),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to create personal access token, error: {:}", err),
+            }),
+        }
+    }
+
+    pub async fn revoke_personal_access_token(
+        &self,
+        id: i32,
+        user_id: i32,
+    ) -> ApplicationResult<bool> {
+        let now = chrono::Utc::now();
+        match self
+            .personal_access_token_repository
+            .revoke_personal_access_token(id, user_id, now)
+            .await
+        {
+            Ok(true) => Ok(true),
+            Ok(false) => Err(ApplicationError {
+                code: ErrorCode::NotFound,
+                message: format!("personal access token is not found, id: {}", id),
+            }),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to revoke personal access token, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Resolve the owner and scopes of a personal access token.
+    pub async fn authenticate(&self, token: &str) -> ApplicationResult<Principal> {
+        let personal_access_token = match self
+            .personal_access_token_repository
+            .get_personal_access_token_by_hash(hash_token(token))
+            .await
+        {
+            Ok(Some(personal_access_token)) => personal_access_token,
+            Ok(None) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::UnAuthenticated,
+                    message: "personal access token is invalid".to_owned(),
+                })
+            }
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch personal access token, error: {:}", err),
+                })
+            }
+        };
+        if personal_access_token.revoked_at.is_some() {
+            return Err(ApplicationError {
+                code: ErrorCode::TokenRevoked,
+                message: "personal access token is revoked".to_owned(),
+            });
+        }
+        let now = chrono::Utc::now();
+        if let Some(expires_at) = personal_access_token.expires_at {
+            if expires_at < now {
+                return Err(ApplicationError {
+                    code: ErrorCode::TokenExpired,
+                    message: "personal access token is expired".to_owned(),
+                });
+            }
+        }
+        match self
+            .user_repository
+            .get_user_by_id(personal_access_token.user_id)
+            .await
+        {
+            Ok(Some(user)) if !user.is_suspended() => {}
+            Ok(Some(_)) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::AccountSuspended,
+                    message: "user is suspended".to_owned(),
+                })
+            }
+            Ok(None) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::UnAuthenticated,
+                    message: "personal access token is invalid".to_owned(),
+                })
+            }
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch user, error: {:}", err),
+                })
+            }
+        }
+        if let Err(err) = self
+            .personal_access_token_repository
+            .touch_personal_access_token(personal_access_token.id, now)
+            .await
+        {
+            return Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to update personal access token, error: {:}", err),
+            });
+        }
+        Ok(Principal {
+            user_id: personal_access_token.user_id,
+            family: None,
+            session_id: None,
+            scopes: Some(personal_access_token.granted_scopes()),
+            role: Role::User,
+        })
+    }
+}
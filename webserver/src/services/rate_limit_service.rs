@@ -0,0 +1,144 @@
+use crate::domains::entities::quota::Quota;
+use crate::domains::rate_limit_store::RateLimitStore;
+use std::time::Duration;
+
+/// Any request of a client.
+const REQUEST_QUOTA: Quota = Quota {
+    burst: 100,
+    period: Duration::from_millis(100),
+};
+/// Login attempts of a client, whichever account they are for.
+const LOGIN_IP_QUOTA: Quota = Quota {
+    burst: 20,
+    period: Duration::from_secs(3),
+};
+/// Login attempts for an account, whichever client they come from.
+const LOGIN_ACCOUNT_QUOTA: Quota = Quota {
+    burst: 10,
+    period: Duration::from_secs(6),
+};
+/// Failed logins of an account, it is locked out when they run out.
+const LOGIN_FAILURE_QUOTA: Quota = Quota {
+    burst: 5,
+    period: Duration::from_secs(300),
+};
+/// Links mailed to an address, whatever they are for.
+const EMAIL_ADDRESS_QUOTA: Quota = Quota {
+    burst: 3,
+    period: Duration::from_secs(300),
+};
+/// Links a client asks to mail, to any address.
+const EMAIL_IP_QUOTA: Quota = Quota {
+    burst: 10,
+    period: Duration::from_secs(60),
+};
+
+/// Throttles clients and accounts with token buckets.
+/// A store which can't be reached lets requests through, so it can't take the whole API down.
+#[derive(Clone)]
+pub struct RateLimitService {
+    store: Box<dyn RateLimitStore + Send + Sync>,
+}
+
+impl RateLimitService {
+    pub fn new(store: Box<dyn RateLimitStore + Send + Sync>) -> Self {
+        Self { store }
+    }
+
+    /// Returns how long the client must wait before sending a request.
+    pub async fn check_request(&self, ip: &str) -> Option<Duration> {
+        self.take(&format!("request:{}", ip), REQUEST_QUOTA).await
+    }
+
+    /// Returns how long to wait before trying to log in to the account,
+    /// checked before the credentials so throttled attempts don't cost a password hash.
+    /// The account is the user it belongs to when there is one, see `account_key`.
+    pub async fn check_login(
+        &self,
+        ip: Option<&str>,
+        account: &str,
+        user_id: Option<i32>,
+    ) -> Option<Duration> {
+        let account = account_key(account, user_id);
+        if let Some(wait) = self
+            .peek(&format!("login_failure:{}", account), LOGIN_FAILURE_QUOTA)
+            .await
+        {
+            return Some(wait);
+        }
+        if let Some(ip) = ip {
+            if let Some(wait) = self.take(&format!("login_ip:{}", ip), LOGIN_IP_QUOTA).await {
+                return Some(wait);
+            }
+        }
+        self.take(&format!("login_account:{}", account), LOGIN_ACCOUNT_QUOTA)
+            .await
+    }
+
+    /// Returns how long to wait before mailing a link to the address,
+    /// so the email flows can't be used to flood an inbox.
+    pub async fn check_email(&self, ip: Option<&str>, email: &str) -> Option<Duration> {
+        if let Some(ip) = ip {
+            if let Some(wait) = self.take(&format!("email_ip:{}", ip), EMAIL_IP_QUOTA).await {
+                return Some(wait);
+            }
+        }
+        self.take(
+            &format!("email_address:{}", normalize_account(email)),
+            EMAIL_ADDRESS_QUOTA,
+        )
+        .await
+    }
+
+    pub async fn record_login_failure(&self, account: &str, user_id: Option<i32>) {
+        let account = account_key(account, user_id);
+        self.take(&format!("login_failure:{}", account), LOGIN_FAILURE_QUOTA)
+            .await;
+    }
+
+    /// Forgive the earlier failures of the account.
+    pub async fn record_login_success(&self, account: &str, user_id: Option<i32>) {
+        let account = account_key(account, user_id);
+        if let Err(err) = self
+            .store
+            .reset(&format!("login_failure:{}", account))
+            .await
+        {
+            println!("failed to reset rate limit, err: {:}", err);
+        }
+    }
+
+    async fn take(&self, key: &str, quota: Quota) -> Option<Duration> {
+        match self.store.take(key, quota).await {
+            Ok(wait) => wait,
+            Err(err) => {
+                println!("failed to take rate limit, err: {:}", err);
+                None
+            }
+        }
+    }
+
+    async fn peek(&self, key: &str, quota: Quota) -> Option<Duration> {
+        match self.store.peek(key, quota).await {
+            Ok(wait) => wait,
+            Err(err) => {
+                println!("failed to peek rate limit, err: {:}", err);
+                None
+            }
+        }
+    }
+}
+
+/// Usernames and emails are matched case-insensitively.
+fn normalize_account(account: &str) -> String {
+    account.trim().to_lowercase()
+}
+
+/// The buckets of a known user are keyed on its id, so its username and email share one lockout,
+/// only an account which doesn't exist is keyed on what was typed.
+fn account_key(account: &str, user_id: Option<i32>) -> String {
+    match user_id {
+        Some(user_id) => format!("user:{}", user_id),
+        None => format!("account:{}", normalize_account(account)),
+    }
+}
@@ -0,0 +1,216 @@
+use crate::domains::entities::tag::Tag;
+use crate::domains::errors::{ApplicationError, ErrorCode, Violation};
+use crate::domains::repositories::tag_repository::TagRepository;
+use crate::domains::ApplicationResult;
+use crate::infrastructures::di_container::DIContainer;
+use std::sync::Arc;
+
+pub const DEFAULT_TAG_COLOR: &str = "#9e9e9e";
+const MAX_TAG_NAME_LENGTH: usize = 64;
+
+/// Tags of a user, each user labels todos with their own tags.
+#[derive(Clone)]
+pub struct TagService {
+    tag_repository: Box<dyn TagRepository + Send + Sync>,
+}
+
+impl TagService {
+    pub fn new(di_container: Arc<dyn DIContainer>) -> Self {
+        Self {
+            tag_repository: di_container.tag_repository(),
+        }
+    }
+
+    /// Tags of the user by name.
+    pub async fn get_tags(&self, user_id: i32) -> ApplicationResult<Vec<Tag>> {
+        match self.tag_repository.get_user_tags(user_id).await {
+            Ok(tags) => Ok(tags),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch tags, error: {:}", err),
+            }),
+        }
+    }
+
+    pub async fn get_tag(&self, id: i32, user_id: i32) -> ApplicationResult<Tag> {
+        match self.tag_repository.get_tag_by_id(id, user_id).await {
+            Ok(Some(tag)) => Ok(tag),
+            Ok(None) => Err(tag_not_found(id)),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch tag, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Tags the user put on the todo, the ones of other members of its list aren't shown.
+    pub async fn get_todo_tags(&self, todo_id: i32, user_id: i32) -> ApplicationResult<Vec<Tag>> {
+        match self.tag_repository.get_todo_tags(todo_id, user_id).await {
+            Ok(tags) => Ok(tags),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch todo tags, error: {:}", err),
+            }),
+        }
+    }
+
+    pub async fn create_tag(
+        &self,
+        user_id: i32,
+        name: String,
+        color: Option<String>,
+    ) -> ApplicationResult<Tag> {
+        let color = color.unwrap_or_else(|| DEFAULT_TAG_COLOR.to_owned());
+        let (name, color) = validate_tag(&name, &color)?;
+        let now = chrono::Utc::now();
+        let tag = Tag {
+            id: 0,
+            user_id,
+            name,
+            color,
+            created_at: now,
+            updated_at: now,
+        };
+        match self.tag_repository.create_tag(tag).await {
+            Ok(Some(created)) => Ok(created),
+            Ok(None) => Err(tag_name_taken()),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to create tag, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Rename the tag or change its color, the color stays without one.
+    /// A name another tag has is refused, those tags can be merged instead.
+    pub async fn update_tag(
+        &self,
+        id: i32,
+        user_id: i32,
+        name: String,
+        color: Option<String>,
+    ) -> ApplicationResult<Tag> {
+        let tag = self.get_tag(id, user_id).await?;
+        let color = color.unwrap_or(tag.color);
+        let (name, color) = validate_tag(&name, &color)?;
+        match self
+            .tag_repository
+            .update_tag(id, user_id, name, color, chrono::Utc::now())
+            .await
+        {
+            Ok(Some(updated)) => Ok(updated),
+            Ok(None) => Err(tag_name_taken()),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to update tag, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Delete the tag, it is taken off every todo.
+    pub async fn delete_tag(&self, id: i32, user_id: i32) -> ApplicationResult<bool> {
+        match self.tag_repository.delete_tag(id, user_id).await {
+            Ok(true) => Ok(true),
+            Ok(false) => Err(tag_not_found(id)),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to delete tag(id: {}), error: {:}", id, err),
+            }),
+        }
+    }
+
+    /// Put the target tag on every todo of the source tag and delete the source tag, at once.
+    pub async fn merge_tags(
+        &self,
+        source_id: i32,
+        target_id: i32,
+        user_id: i32,
+    ) -> ApplicationResult<Tag> {
+        if source_id == target_id {
+            return Err(ApplicationError {
+                code: ErrorCode::BadRequest,
+                message: "a tag can't be merged into itself".to_owned(),
+            });
+        }
+        match self
+            .tag_repository
+            .merge_tags(source_id, target_id, user_id, chrono::Utc::now())
+            .await
+        {
+            Ok(Some(merged)) => Ok(merged),
+            Ok(None) => Err(ApplicationError {
+                code: ErrorCode::NotFound,
+                message: format!(
+                    "tag is not found, source: {}, target: {}",
+                    source_id, target_id
+                ),
+            }),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to merge tags, error: {:}", err),
+            }),
+        }
+    }
+}
+
+/// The trimmed name and the lowercase color, every broken rule is reported.
+fn validate_tag(name: &str, color: &str) -> ApplicationResult<(String, String)> {
+    let name = name.trim();
+    let color = color.trim().to_lowercase();
+    let mut violations = vec![];
+    if name.is_empty() {
+        violations.push(violation("name", "required", "name is required".to_owned()));
+    } else if name.chars().count() > MAX_TAG_NAME_LENGTH {
+        violations.push(violation(
+            "name",
+            "maxLength",
+            format!("name must be at most {} characters", MAX_TAG_NAME_LENGTH),
+        ));
+    }
+    if !is_hex_color(&color) {
+        violations.push(violation(
+            "color",
+            "pattern",
+            "color must be a hex color like #1e88e5".to_owned(),
+        ));
+    }
+    if violations.is_empty() {
+        return Ok((name.to_owned(), color));
+    }
+    Err(ApplicationError {
+        message: violations
+            .iter()
+            .map(|violation| violation.message.as_str())
+            .collect::<Vec<&str>>()
+            .join(", "),
+        code: ErrorCode::ValidationFailed(violations),
+    })
+}
+
+fn is_hex_color(color: &str) -> bool {
+    color.len() == 7
+        && color.starts_with('#')
+        && color.chars().skip(1).all(|c| c.is_ascii_hexdigit())
+}
+
+fn violation(field: &str, rule: &str, message: String) -> Violation {
+    Violation {
+        field: field.to_owned(),
+        rule: rule.to_owned(),
+        message,
+    }
+}
+
+fn tag_name_taken() -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::Conflict,
+        message: "a tag with the name exists already".to_owned(),
+    }
+}
+
+pub(crate) fn tag_not_found(id: i32) -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::NotFound,
+        message: format!("tag is not found, id: {}", id),
+    }
+}
@@ -0,0 +1,480 @@
+use crate::domains::entities::todo_list::TodoList;
+use crate::domains::entities::todo_list_member::{TodoListAccess, TodoListMember, TodoListRole};
+use crate::domains::errors::{ApplicationError, ErrorCode, Violation};
+use crate::domains::repositories::todo_list_member_repository::TodoListMemberRepository;
+use crate::domains::repositories::todo_list_repository::TodoListRepository;
+use crate::domains::repositories::user_repository::UserRepository;
+use crate::domains::ApplicationResult;
+use crate::infrastructures::di_container::DIContainer;
+use std::sync::Arc;
+
+const DEFAULT_LIST_NAME: &str = "Todos";
+const MAX_LIST_NAME_LENGTH: usize = 255;
+
+/// Todo lists and who they are shared with.
+/// Lists the user isn't a member of are reported as not found, so their ids don't leak.
+#[derive(Clone)]
+pub struct TodoListService {
+    todo_list_repository: Box<dyn TodoListRepository + Send + Sync>,
+    todo_list_member_repository: Box<dyn TodoListMemberRepository + Send + Sync>,
+    user_repository: Box<dyn UserRepository + Send + Sync>,
+}
+
+impl TodoListService {
+    pub fn new(di_container: Arc<dyn DIContainer>) -> Self {
+        Self {
+            todo_list_repository: di_container.todo_list_repository(),
+            todo_list_member_repository: di_container.todo_list_member_repository(),
+            user_repository: di_container.user_repository(),
+        }
+    }
+
+    /// Lists the user owns or shares, their default one first.
+    pub async fn get_todo_lists(&self, user_id: i32) -> ApplicationResult<Vec<TodoList>> {
+        // Users who never had a todo get their default list on the first look.
+        self.get_default_todo_list(user_id).await?;
+        match self.todo_list_repository.get_user_todo_lists(user_id).await {
+            Ok(todo_lists) => Ok(todo_lists),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch todo lists, error: {:}", err),
+            }),
+        }
+    }
+
+    pub async fn get_todo_list(&self, id: i32, user_id: i32) -> ApplicationResult<TodoList> {
+        self.authorize_todo_list(id, user_id, TodoListAccess::View)
+            .await
+    }
+
+    /// The list, when the role of the user in it allows the access.
+    pub async fn authorize_todo_list(
+        &self,
+        id: i32,
+        user_id: i32,
+        access: TodoListAccess,
+    ) -> ApplicationResult<TodoList> {
+        let role = self.get_role(id, user_id).await?;
+        if !role.allows(access) {
+            return Err(ApplicationError {
+                code: ErrorCode::Forbidden,
+                message: format!(
+                    "{} of todo list can't {} it, id: {}",
+                    role.as_ref(),
+                    access.as_ref(),
+                    id
+                ),
+            });
+        }
+        match self.todo_list_repository.get_todo_list_by_id(id).await {
+            Ok(Some(todo_list)) => Ok(todo_list),
+            Ok(None) => Err(todo_list_not_found(id)),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch todo list, error: {:}", err),
+            }),
+        }
+    }
+
+    /// The given list when the user has the access, or their default list without one.
+    pub async fn resolve_todo_list(
+        &self,
+        id: Option<i32>,
+        user_id: i32,
+        access: TodoListAccess,
+    ) -> ApplicationResult<TodoList> {
+        match id {
+            Some(id) => self.authorize_todo_list(id, user_id, access).await,
+            None => self.get_default_todo_list(user_id).await,
+        }
+    }
+
+    /// Role of the user in the list, a pending invitation grants none.
+    pub async fn get_role(&self, id: i32, user_id: i32) -> ApplicationResult<TodoListRole> {
+        match self.find_member(id, user_id).await? {
+            Some(member) if member.is_accepted() => Ok(member.granted_role()),
+            _ => Err(todo_list_not_found(id)),
+        }
+    }
+
+    /// The default list of the user, it is created when they don't have one yet.
+    pub async fn get_default_todo_list(&self, user_id: i32) -> ApplicationResult<TodoList> {
+        if let Some(todo_list) = self.find_default_todo_list(user_id).await? {
+            return Ok(todo_list);
+        }
+        let now = chrono::Utc::now();
+        let todo_list = TodoList {
+            id: 0,
+            user_id,
+            name: DEFAULT_LIST_NAME.to_owned(),
+            is_default: true,
+            created_at: now,
+            updated_at: now,
+        };
+        match self.todo_list_repository.create_todo_list(todo_list).await {
+            Ok(Some(created)) => Ok(created),
+            // A concurrent request created it first.
+            Ok(None) => match self.find_default_todo_list(user_id).await? {
+                Some(todo_list) => Ok(todo_list),
+                None => Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to create default todo list, user: {}", user_id),
+                }),
+            },
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to create todo list, error: {:}", err),
+            }),
+        }
+    }
+
+    pub async fn create_todo_list(
+        &self,
+        user_id: i32,
+        name: String,
+    ) -> ApplicationResult<TodoList> {
+        let name = validate_list_name(&name)?;
+        let now = chrono::Utc::now();
+        let todo_list = TodoList {
+            id: 0,
+            user_id,
+            name,
+            is_default: false,
+            created_at: now,
+            updated_at: now,
+        };
+        match self.todo_list_repository.create_todo_list(todo_list).await {
+            Ok(Some(created)) => Ok(created),
+            Ok(None) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: "failed to create todo list".to_owned(),
+            }),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to create todo list, error: {:}", err),
+            }),
+        }
+    }
+
+    pub async fn rename_todo_list(
+        &self,
+        id: i32,
+        user_id: i32,
+        name: String,
+    ) -> ApplicationResult<TodoList> {
+        let name = validate_list_name(&name)?;
+        self.authorize_todo_list(id, user_id, TodoListAccess::Manage)
+            .await?;
+        match self
+            .todo_list_repository
+            .rename_todo_list(id, name, chrono::Utc::now())
+            .await
+        {
+            Ok(Some(renamed)) => Ok(renamed),
+            Ok(None) => Err(todo_list_not_found(id)),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to rename todo list, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Delete the list with its todos, the default list stays.
+    pub async fn delete_todo_list(&self, id: i32, user_id: i32) -> ApplicationResult<bool> {
+        let todo_list = self
+            .authorize_todo_list(id, user_id, TodoListAccess::Manage)
+            .await?;
+        if todo_list.is_default {
+            return Err(ApplicationError {
+                code: ErrorCode::BadRequest,
+                message: "the default todo list can't be deleted".to_owned(),
+            });
+        }
+        match self.todo_list_repository.delete_todo_list(id).await {
+            Ok(true) => Ok(true),
+            Ok(false) => Err(todo_list_not_found(id)),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to delete todo list(id: {}), error: {:}", id, err),
+            }),
+        }
+    }
+
+    /// Members and pending invitations of a list the user shares.
+    pub async fn get_members(
+        &self,
+        id: i32,
+        user_id: i32,
+    ) -> ApplicationResult<Vec<TodoListMember>> {
+        self.get_role(id, user_id).await?;
+        match self
+            .todo_list_member_repository
+            .get_todo_list_members(id)
+            .await
+        {
+            Ok(members) => Ok(members),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch todo list members, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Invitations to lists the user hasn't accepted yet.
+    pub async fn get_invitations(&self, user_id: i32) -> ApplicationResult<Vec<TodoListMember>> {
+        match self
+            .todo_list_member_repository
+            .get_user_invitations(user_id)
+            .await
+        {
+            Ok(invitations) => Ok(invitations),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch invitations, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Invite the user with the username to the list, only the owner can.
+    /// The list is shared with them once they accept.
+    pub async fn invite_member(
+        &self,
+        id: i32,
+        user_id: i32,
+        username: String,
+        role: TodoListRole,
+    ) -> ApplicationResult<TodoListMember> {
+        ensure_assignable(role)?;
+        self.authorize_todo_list(id, user_id, TodoListAccess::Manage)
+            .await?;
+        let invitee = match self
+            .user_repository
+            .get_user_by_username(username.trim().to_owned())
+            .await
+        {
+            Ok(Some(invitee)) => invitee,
+            Ok(None) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::NotFound,
+                    message: format!("user is not found, username: {}", username),
+                })
+            }
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch user, error: {:}", err),
+                })
+            }
+        };
+        let now = chrono::Utc::now();
+        let member = TodoListMember {
+            id: 0,
+            list_id: id,
+            user_id: invitee.id,
+            role: role.as_ref().to_owned(),
+            invited_by: Some(user_id),
+            accepted_at: None,
+            created_at: now,
+            updated_at: now,
+            username: invitee.username,
+            list_name: String::new(),
+        };
+        match self
+            .todo_list_member_repository
+            .create_todo_list_member(member)
+            .await
+        {
+            Ok(true) => self.get_member(id, invitee.id).await,
+            Ok(false) => Err(ApplicationError {
+                code: ErrorCode::Conflict,
+                message: format!(
+                    "user is a member or invited already, username: {}",
+                    username
+                ),
+            }),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to invite member, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Accept the invitation of the user to the list, which is shared with them from then on.
+    pub async fn accept_invitation(&self, id: i32, user_id: i32) -> ApplicationResult<TodoList> {
+        match self
+            .todo_list_member_repository
+            .accept_invitation(id, user_id, chrono::Utc::now())
+            .await
+        {
+            Ok(true) => self.get_todo_list(id, user_id).await,
+            Ok(false) => Err(ApplicationError {
+                code: ErrorCode::NotFound,
+                message: format!("invitation is not found, list: {}", id),
+            }),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to accept invitation, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Change the role of a member, only the owner can and the owner stays one.
+    pub async fn set_member_role(
+        &self,
+        id: i32,
+        user_id: i32,
+        member_id: i32,
+        role: TodoListRole,
+    ) -> ApplicationResult<TodoListMember> {
+        ensure_assignable(role)?;
+        self.authorize_todo_list(id, user_id, TodoListAccess::Manage)
+            .await?;
+        let member = self.get_member(id, member_id).await?;
+        ensure_not_owner(&member)?;
+        match self
+            .todo_list_member_repository
+            .update_member_role(id, member_id, role.as_ref().to_owned(), chrono::Utc::now())
+            .await
+        {
+            Ok(true) => self.get_member(id, member_id).await,
+            Ok(false) => Err(member_not_found(id, member_id)),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to update member role, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Remove a member or an invitation from the list.
+    /// The owner removes anyone but themselves, the others can only leave or decline.
+    pub async fn remove_member(
+        &self,
+        id: i32,
+        user_id: i32,
+        member_id: i32,
+    ) -> ApplicationResult<bool> {
+        let member = if member_id == user_id {
+            match self.find_member(id, user_id).await? {
+                Some(member) => member,
+                None => return Err(todo_list_not_found(id)),
+            }
+        } else {
+            self.authorize_todo_list(id, user_id, TodoListAccess::Manage)
+                .await?;
+            self.get_member(id, member_id).await?
+        };
+        ensure_not_owner(&member)?;
+        match self
+            .todo_list_member_repository
+            .delete_todo_list_member(id, member_id)
+            .await
+        {
+            Ok(true) => Ok(true),
+            Ok(false) => Err(member_not_found(id, member_id)),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to remove member, error: {:}", err),
+            }),
+        }
+    }
+
+    async fn get_member(&self, id: i32, user_id: i32) -> ApplicationResult<TodoListMember> {
+        match self.find_member(id, user_id).await? {
+            Some(member) => Ok(member),
+            None => Err(member_not_found(id, user_id)),
+        }
+    }
+
+    async fn find_member(
+        &self,
+        id: i32,
+        user_id: i32,
+    ) -> ApplicationResult<Option<TodoListMember>> {
+        match self
+            .todo_list_member_repository
+            .get_todo_list_member(id, user_id)
+            .await
+        {
+            Ok(member) => Ok(member),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch todo list member, error: {:}", err),
+            }),
+        }
+    }
+
+    async fn find_default_todo_list(&self, user_id: i32) -> ApplicationResult<Option<TodoList>> {
+        match self
+            .todo_list_repository
+            .get_default_todo_list(user_id)
+            .await
+        {
+            Ok(todo_list) => Ok(todo_list),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch todo list, error: {:}", err),
+            }),
+        }
+    }
+}
+
+/// The trimmed name, it can't be empty or too long.
+fn validate_list_name(name: &str) -> ApplicationResult<String> {
+    let name = name.trim();
+    let (rule, message) = if name.is_empty() {
+        ("required", "name is required".to_owned())
+    } else if name.chars().count() > MAX_LIST_NAME_LENGTH {
+        (
+            "maxLength",
+            format!("name must be at most {} characters", MAX_LIST_NAME_LENGTH),
+        )
+    } else {
+        return Ok(name.to_owned());
+    };
+    Err(ApplicationError {
+        code: ErrorCode::ValidationFailed(vec![Violation {
+            field: "name".to_owned(),
+            rule: rule.to_owned(),
+            message: message.to_owned(),
+        }]),
+        message,
+    })
+}
+
+/// A list has a single owner, the one who created it.
+fn ensure_assignable(role: TodoListRole) -> ApplicationResult<()> {
+    if role == TodoListRole::Owner {
+        return Err(ApplicationError {
+            code: ErrorCode::BadRequest,
+            message: "the owner role can't be given to a member".to_owned(),
+        });
+    }
+    Ok(())
+}
+
+fn ensure_not_owner(member: &TodoListMember) -> ApplicationResult<()> {
+    if member.granted_role() == TodoListRole::Owner {
+        return Err(ApplicationError {
+            code: ErrorCode::BadRequest,
+            message: "the owner of a todo list can't leave or be removed".to_owned(),
+        });
+    }
+    Ok(())
+}
+
+fn member_not_found(id: i32, user_id: i32) -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::NotFound,
+        message: format!(
+            "todo list member is not found, list: {}, user: {}",
+            id, user_id
+        ),
+    }
+}
+
+fn todo_list_not_found(id: i32) -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::NotFound,
+        message: format!("todo list is not found, id: {}", id),
+    }
+}
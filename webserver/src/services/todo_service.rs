@@ -1,21 +1,43 @@
-use crate::domains::entities::todo::Todo;
-use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::entities::tag::{TagFilter, TodoTag};
+use crate::domains::entities::todo::{Todo, TodoFilter};
+use crate::domains::entities::todo_list_member::TodoListAccess;
+use crate::domains::entities::todo_series::TodoSeries;
+use crate::domains::errors::{ApplicationError, ErrorCode, Violation};
+use crate::domains::recurrence::RecurrenceRule;
+use crate::domains::repositories::tag_repository::TagRepository;
 use crate::domains::repositories::todo_repository::TodoRepository;
+use crate::domains::repositories::todo_series_repository::TodoSeriesRepository;
 use crate::domains::ApplicationResult;
 use crate::infrastructures::di_container::DIContainer;
+use crate::services::tag_service::tag_not_found;
+use crate::services::todo_list_service::TodoListService;
+use chrono_tz::Tz;
 use std::sync::Arc;
 
+const DEFAULT_TIME_ZONE: &str = "UTC";
+
+/// Todos of the lists a user shares, a missing list id means their default list.
+/// Every member reads the todos of a list, viewers can't change them.
+/// Completing an occurrence of a recurring todo creates the next one.
 #[derive(Clone)]
 pub struct TodoService {
     pub todo_repository: Box<dyn TodoRepository + Send + Sync>,
+    tag_repository: Box<dyn TagRepository + Send + Sync>,
+    todo_series_repository: Box<dyn TodoSeriesRepository + Send + Sync>,
+    todo_list_service: TodoListService,
 }
 
 impl TodoService {
     pub fn new(di_container: Arc<dyn DIContainer>) -> Self {
         Self {
             todo_repository: di_container.todo_repository(),
+            tag_repository: di_container.tag_repository(),
+            todo_series_repository: di_container.todo_series_repository(),
+            todo_list_service: TodoListService::new(di_container),
         }
     }
+
+    /// Todos the user created in every list.
     pub async fn get_all_todos(&self, user_id: i32) -> ApplicationResult<Vec<Todo>> {
         match self.todo_repository.get_all_todos(user_id).await {
             Ok(todos) => Ok(todos),
@@ -26,7 +48,49 @@ impl TodoService {
         }
     }
 
-    pub async fn create_todo(&self, body: String, user_id: i32) -> ApplicationResult<Todo> {
+    /// Todos of the list which match the filter.
+    pub async fn get_list_todos(
+        &self,
+        user_id: i32,
+        list_id: Option<i32>,
+        mut filter: TodoFilter,
+    ) -> ApplicationResult<Vec<Todo>> {
+        let todo_list = self
+            .todo_list_service
+            .resolve_todo_list(list_id, user_id, TodoListAccess::View)
+            .await?;
+        filter.tag_filter = match filter.tag_filter {
+            Some(tag_filter) if !tag_filter.tag_ids.is_empty() => {
+                Some(self.resolve_tag_filter(user_id, tag_filter).await?)
+            }
+            _ => None,
+        };
+        match self
+            .todo_repository
+            .get_list_todos(todo_list.id, &filter, chrono::Utc::now())
+            .await
+        {
+            Ok(todos) => Ok(todos),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch todos, error: {:}", err),
+            }),
+        }
+    }
+
+    pub async fn create_todo(
+        &self,
+        body: String,
+        user_id: i32,
+        list_id: Option<i32>,
+        start_at: Option<chrono::DateTime<chrono::Utc>>,
+        due_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> ApplicationResult<Todo> {
+        validate_dates(start_at, due_at)?;
+        let todo_list = self
+            .todo_list_service
+            .resolve_todo_list(list_id, user_id, TodoListAccess::Edit)
+            .await?;
         let now = chrono::Utc::now();
         let todo = Todo {
             id: 0,
@@ -34,7 +98,14 @@ impl TodoService {
             complete: false,
             created_at: now,
             updated_at: now,
-            user_id,
+            list_id: todo_list.id,
+            created_by: Some(user_id),
+            updated_by: Some(user_id),
+            start_at,
+            due_at,
+            series_id: None,
+            occurrence_at: None,
+            previous_id: None,
         };
         match self.todo_repository.create_todo(todo).await {
             Ok(created) => Ok(created),
@@ -45,35 +116,360 @@ impl TodoService {
         }
     }
 
+    /// Replace the todo, a date left out with `None` stays and `Some(None)` clears it.
+    /// Only this occurrence of a recurring todo changes, the series goes on as scheduled.
     pub async fn update_todo(
         &self,
         id: i32,
+        user_id: i32,
         body: String,
         complete: bool,
+        start_at: Option<Option<chrono::DateTime<chrono::Utc>>>,
+        due_at: Option<Option<chrono::DateTime<chrono::Utc>>>,
     ) -> ApplicationResult<Todo> {
-        let mut todo = match self.todo_repository.get_todo_by_id(id).await {
-            Ok(ret) => match ret {
-                Some(t) => t,
-                None => {
-                    return Err(ApplicationError {
-                        code: ErrorCode::NotFound,
-                        message: format!("todo is not found, id: {}", id),
-                    });
+        let todo = self
+            .authorize_todo(id, user_id, TodoListAccess::Edit)
+            .await?;
+        let start_at = start_at.unwrap_or(todo.start_at);
+        let due_at = due_at.unwrap_or(todo.due_at);
+        validate_dates(start_at, due_at)?;
+        let was_complete = todo.complete;
+        let updated = self
+            .replace_todo(todo, user_id, body, complete, start_at, due_at)
+            .await?;
+        if !was_complete && updated.complete {
+            self.schedule_next_occurrence(&updated, user_id).await?;
+        }
+        Ok(updated)
+    }
+
+    /// Replace the occurrence like `update_todo` and carry the change over to the series.
+    /// Its open occurrences get the body, and a moved due time moves the ones to come.
+    pub async fn update_todo_series(
+        &self,
+        id: i32,
+        user_id: i32,
+        body: String,
+        complete: bool,
+        start_at: Option<Option<chrono::DateTime<chrono::Utc>>>,
+        due_at: Option<Option<chrono::DateTime<chrono::Utc>>>,
+    ) -> ApplicationResult<Todo> {
+        let todo = self
+            .authorize_todo(id, user_id, TodoListAccess::Edit)
+            .await?;
+        let start_at = start_at.unwrap_or(todo.start_at);
+        let due_at = due_at.unwrap_or(todo.due_at);
+        validate_dates(start_at, due_at)?;
+        let mut series = self.get_series_of(&todo).await?;
+        let due_at = match due_at {
+            Some(due_at) => due_at,
+            None => {
+                return Err(ApplicationError {
+                    code: ErrorCode::BadRequest,
+                    message: "a recurring todo has to be due".to_owned(),
+                })
+            }
+        };
+        let occurrence_at = todo.occurrence_at.unwrap_or(series.dtstart);
+        if occurrence_at != due_at {
+            // The ones to come are counted from the moved occurrence on, a COUNT includes the ones before.
+            series.occurrences_before = series.occurrences_until(occurrence_at);
+            series.dtstart = due_at;
+        }
+        let was_complete = todo.complete;
+        let mut updated = self
+            .replace_todo(todo, user_id, body, complete, start_at, Some(due_at))
+            .await?;
+        series.body = updated.body.to_owned();
+        series.updated_at = updated.updated_at;
+        match self
+            .todo_series_repository
+            .update_todo_series(series, id, due_at)
+            .await
+        {
+            Ok(Some(_)) => updated.occurrence_at = Some(due_at),
+            Ok(None) => return Err(todo_not_found(id)),
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to update todo series, error: {:}", err),
+                })
+            }
+        }
+        if !was_complete && updated.complete {
+            self.schedule_next_occurrence(&updated, user_id).await?;
+        }
+        Ok(updated)
+    }
+
+    /// Make the todo recur by the RRULE in the time zone, counting from its due time.
+    /// A todo which recurs already gets the new rule, whose `COUNT` includes the occurrences
+    /// the series has had, `None` stops the recurrence.
+    pub async fn set_recurrence(
+        &self,
+        id: i32,
+        user_id: i32,
+        rrule: Option<String>,
+        time_zone: Option<String>,
+    ) -> ApplicationResult<Todo> {
+        let todo = self
+            .authorize_todo(id, user_id, TodoListAccess::Edit)
+            .await?;
+        let rrule = match rrule {
+            Some(rrule) => rrule,
+            None => {
+                if let Some(series_id) = todo.series_id {
+                    if let Err(err) = self
+                        .todo_series_repository
+                        .delete_todo_series(series_id)
+                        .await
+                    {
+                        return Err(ApplicationError {
+                            code: ErrorCode::SystemError,
+                            message: format!("failed to delete todo series, error: {:}", err),
+                        });
+                    }
                 }
-            },
+                return self.get_todo(id).await;
+            }
+        };
+        let (rule, time_zone) =
+            validate_recurrence(&rrule, time_zone.as_deref().unwrap_or(DEFAULT_TIME_ZONE))?;
+        let dtstart = match todo.due_at {
+            Some(due_at) => due_at,
+            None => {
+                return Err(ApplicationError {
+                    code: ErrorCode::BadRequest,
+                    message: "a recurring todo has to be due".to_owned(),
+                })
+            }
+        };
+        let now = chrono::Utc::now();
+        let saved = match todo.series_id {
+            Some(_) => {
+                let current = self.get_series_of(&todo).await?;
+                let occurrences_before =
+                    current.occurrences_until(todo.occurrence_at.unwrap_or(current.dtstart));
+                validate_count(&rule, occurrences_before)?;
+                let series = TodoSeries {
+                    body: todo.body.to_owned(),
+                    rrule: rule.to_string(),
+                    time_zone,
+                    dtstart,
+                    occurrences_before,
+                    updated_at: now,
+                    ..current
+                };
+                self.todo_series_repository
+                    .update_todo_series(series, id, dtstart)
+                    .await
+            }
+            None => {
+                let series = TodoSeries {
+                    id: 0,
+                    list_id: todo.list_id,
+                    body: todo.body.to_owned(),
+                    rrule: rule.to_string(),
+                    time_zone,
+                    dtstart,
+                    occurrences_before: 0,
+                    created_at: now,
+                    updated_at: now,
+                };
+                self.todo_series_repository
+                    .create_todo_series(series, id)
+                    .await
+                    .map(Some)
+            }
+        };
+        match saved {
+            Ok(Some(_)) => self.get_todo(id).await,
+            Ok(None) => Err(todo_not_found(id)),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to save todo series, error: {:}", err),
+            }),
+        }
+    }
+
+    /// The series of a recurring todo the user can read.
+    pub async fn get_todo_series(&self, id: i32, user_id: i32) -> ApplicationResult<TodoSeries> {
+        let series = match self.todo_series_repository.get_todo_series(id).await {
+            Ok(Some(series)) => series,
+            Ok(None) => return Err(todo_series_not_found(id)),
             Err(err) => {
                 return Err(ApplicationError {
                     code: ErrorCode::SystemError,
-                    message: format!("failed to create todo, error: {:}", err),
-                });
+                    message: format!("failed to fetch todo series, error: {:}", err),
+                })
             }
         };
+        match self
+            .todo_list_service
+            .authorize_todo_list(series.list_id, user_id, TodoListAccess::View)
+            .await
+        {
+            Ok(_) => Ok(series),
+            Err(err) if matches!(err.code, ErrorCode::NotFound) => Err(todo_series_not_found(id)),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub async fn toggle_complete(&self, id: i32, user_id: i32) -> ApplicationResult<bool> {
+        let todo = self
+            .authorize_todo(id, user_id, TodoListAccess::Edit)
+            .await?;
+        let now = chrono::Utc::now();
+        match self.todo_repository.toggle_complete(id, user_id, now).await {
+            Ok(true) => {}
+            Ok(false) => return Err(todo_not_found(id)),
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to complete todo(id: {}), error: {:}", id, err),
+                })
+            }
+        }
+        if !todo.complete {
+            self.schedule_next_occurrence(&todo, user_id).await?;
+        }
+        Ok(true)
+    }
+
+    pub async fn toggle_all_complete(
+        &self,
+        user_id: i32,
+        list_id: Option<i32>,
+    ) -> ApplicationResult<bool> {
+        let todo_list = self
+            .todo_list_service
+            .resolve_todo_list(list_id, user_id, TodoListAccess::Edit)
+            .await?;
         let now = chrono::Utc::now();
+        // Occurrences which get completed are followed by their next ones.
+        let completed: Vec<Todo> = match self
+            .todo_repository
+            .get_list_todos(todo_list.id, &TodoFilter::default(), now)
+            .await
+        {
+            Ok(todos) => todos
+                .into_iter()
+                .filter(|todo| !todo.complete && todo.series_id.is_some())
+                .collect(),
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch todos, error: {:}", err),
+                })
+            }
+        };
+        if let Err(err) = self
+            .todo_repository
+            .toggle_all_complete(todo_list.id, user_id, now)
+            .await
+        {
+            return Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to complete all todos, error: {:}", err),
+            });
+        }
+        for todo in &completed {
+            self.schedule_next_occurrence(todo, user_id).await?;
+        }
+        Ok(true)
+    }
+
+    pub async fn delete_todo(&self, id: i32, user_id: i32) -> ApplicationResult<bool> {
+        self.authorize_todo(id, user_id, TodoListAccess::Edit)
+            .await?;
+        match self.todo_repository.delete_todo(id).await {
+            Ok(true) => Ok(true),
+            Ok(false) => Err(todo_not_found(id)),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to delete todo(id: {}), error: {:}", id, err),
+            }),
+        }
+    }
+
+    pub async fn clear_completed_todo(
+        &self,
+        user_id: i32,
+        list_id: Option<i32>,
+    ) -> ApplicationResult<bool> {
+        let todo_list = self
+            .todo_list_service
+            .resolve_todo_list(list_id, user_id, TodoListAccess::Edit)
+            .await?;
+        match self
+            .todo_repository
+            .delete_completed_todo(todo_list.id)
+            .await
+        {
+            Ok(ret) => Ok(ret),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to delete all completed todos, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Put a tag of the user on the todo, nothing changes when it is there already.
+    /// The tags are the user's own, so seeing the list is enough.
+    pub async fn add_tag(&self, id: i32, user_id: i32, tag_id: i32) -> ApplicationResult<bool> {
+        self.authorize_todo(id, user_id, TodoListAccess::View)
+            .await?;
+        self.ensure_own_tag(tag_id, user_id).await?;
+        let todo_tag = TodoTag {
+            todo_id: id,
+            tag_id,
+            created_at: chrono::Utc::now(),
+        };
+        match self.tag_repository.add_todo_tag(todo_tag).await {
+            Ok(_) => Ok(true),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to tag todo(id: {}), error: {:}", id, err),
+            }),
+        }
+    }
+
+    pub async fn remove_tag(&self, id: i32, user_id: i32, tag_id: i32) -> ApplicationResult<bool> {
+        self.authorize_todo(id, user_id, TodoListAccess::View)
+            .await?;
+        self.ensure_own_tag(tag_id, user_id).await?;
+        match self.tag_repository.remove_todo_tag(id, tag_id).await {
+            Ok(true) => Ok(true),
+            Ok(false) => Err(ApplicationError {
+                code: ErrorCode::NotFound,
+                message: format!("todo(id: {}) doesn't have the tag, id: {}", id, tag_id),
+            }),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to untag todo(id: {}), error: {:}", id, err),
+            }),
+        }
+    }
+
+    async fn replace_todo(
+        &self,
+        mut todo: Todo,
+        user_id: i32,
+        body: String,
+        complete: bool,
+        start_at: Option<chrono::DateTime<chrono::Utc>>,
+        due_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> ApplicationResult<Todo> {
+        let id = todo.id;
         todo.body = body;
         todo.complete = complete;
-        todo.updated_at = now;
+        todo.start_at = start_at;
+        todo.due_at = due_at;
+        todo.updated_at = chrono::Utc::now();
+        todo.updated_by = Some(user_id);
         match self.todo_repository.update_todo(todo).await {
-            Ok(updated) => Ok(updated),
+            Ok(Some(updated)) => Ok(updated),
+            Ok(None) => Err(todo_not_found(id)),
             Err(err) => Err(ApplicationError {
                 code: ErrorCode::SystemError,
                 message: format!("failed to update todo, error: {:}", err),
@@ -81,45 +477,229 @@ impl TodoService {
         }
     }
 
-    pub async fn toggle_complete(&self, id: i32) -> ApplicationResult<bool> {
+    /// Create the occurrence which follows the completed one, unless the series has ended.
+    /// It is the first one after the completed occurrence and after now, so missed ones are skipped,
+    /// and it starts as long before it is due as the completed one did.
+    /// Completing an occurrence again doesn't create another one.
+    async fn schedule_next_occurrence(&self, todo: &Todo, user_id: i32) -> ApplicationResult<()> {
+        if todo.series_id.is_none() {
+            return Ok(());
+        }
+        let series = self.get_series_of(todo).await?;
         let now = chrono::Utc::now();
-        match self.todo_repository.toggle_complete(id, now).await {
-            Ok(ret) => Ok(ret),
+        let occurrence_at = todo.occurrence_at.unwrap_or(series.dtstart);
+        let due_at = match series.next_occurrence(occurrence_at.max(now)) {
+            Some(due_at) => due_at,
+            None => return Ok(()),
+        };
+        let start_at = match (todo.start_at, todo.due_at) {
+            (Some(start_at), Some(previous_due_at)) => Some(due_at - (previous_due_at - start_at)),
+            _ => None,
+        };
+        let next = Todo {
+            id: 0,
+            body: series.body,
+            complete: false,
+            created_at: now,
+            updated_at: now,
+            list_id: series.list_id,
+            created_by: Some(user_id),
+            updated_by: Some(user_id),
+            start_at,
+            due_at: Some(due_at),
+            series_id: Some(series.id),
+            occurrence_at: Some(due_at),
+            previous_id: Some(todo.id),
+        };
+        match self
+            .todo_series_repository
+            .create_next_occurrence(next)
+            .await
+        {
+            Ok(_) => Ok(()),
             Err(err) => Err(ApplicationError {
                 code: ErrorCode::SystemError,
-                message: format!("failed to complete todo(id: {}), error: {:}", id, err),
+                message: format!("failed to create next occurrence, error: {:}", err),
             }),
         }
     }
 
-    pub async fn toggle_all_complete(&self) -> ApplicationResult<bool> {
-        let now = chrono::Utc::now();
-        match self.todo_repository.toggle_all_complete(now).await {
-            Ok(ret) => Ok(ret),
+    async fn get_series_of(&self, todo: &Todo) -> ApplicationResult<TodoSeries> {
+        let series_id = match todo.series_id {
+            Some(series_id) => series_id,
+            None => {
+                return Err(ApplicationError {
+                    code: ErrorCode::BadRequest,
+                    message: format!("todo doesn't recur, id: {}", todo.id),
+                })
+            }
+        };
+        match self.todo_series_repository.get_todo_series(series_id).await {
+            Ok(Some(series)) => Ok(series),
+            Ok(None) => Err(todo_series_not_found(series_id)),
             Err(err) => Err(ApplicationError {
                 code: ErrorCode::SystemError,
-                message: format!("failed to complete all todos, error: {:}", err),
+                message: format!("failed to fetch todo series, error: {:}", err),
             }),
         }
     }
 
-    pub async fn delete_todo(&self, id: i32) -> ApplicationResult<bool> {
-        match self.todo_repository.delete_todo(id).await {
-            Ok(ret) => Ok(ret),
+    async fn get_todo(&self, id: i32) -> ApplicationResult<Todo> {
+        match self.todo_repository.get_todo_by_id(id).await {
+            Ok(Some(todo)) => Ok(todo),
+            Ok(None) => Err(todo_not_found(id)),
             Err(err) => Err(ApplicationError {
                 code: ErrorCode::SystemError,
-                message: format!("failed to complete all todos, error: {:}", err),
+                message: format!("failed to fetch todo, error: {:}", err),
             }),
         }
     }
 
-    pub async fn clear_completed_todo(&self) -> ApplicationResult<bool> {
-        match self.todo_repository.delete_completed_todo().await {
-            Ok(ret) => Ok(ret),
+    async fn ensure_own_tag(&self, tag_id: i32, user_id: i32) -> ApplicationResult<()> {
+        match self.tag_repository.get_tag_by_id(tag_id, user_id).await {
+            Ok(Some(_)) => Ok(()),
+            Ok(None) => Err(tag_not_found(tag_id)),
             Err(err) => Err(ApplicationError {
                 code: ErrorCode::SystemError,
-                message: format!("failed to delete all completed todos, error: {:}", err),
+                message: format!("failed to fetch tag, error: {:}", err),
             }),
         }
     }
+
+    /// The filter with its distinct tags.
+    /// Only tags of the user can be filtered by, so tags of other members stay private.
+    async fn resolve_tag_filter(
+        &self,
+        user_id: i32,
+        mut tag_filter: TagFilter,
+    ) -> ApplicationResult<TagFilter> {
+        tag_filter.tag_ids.sort_unstable();
+        tag_filter.tag_ids.dedup();
+        for tag_id in &tag_filter.tag_ids {
+            self.ensure_own_tag(*tag_id, user_id).await?;
+        }
+        Ok(tag_filter)
+    }
+
+    /// The todo, when the user may change the todos of its list.
+    /// Todos of lists the user isn't a member of are reported as not found.
+    async fn authorize_todo(
+        &self,
+        id: i32,
+        user_id: i32,
+        access: TodoListAccess,
+    ) -> ApplicationResult<Todo> {
+        let todo = match self.todo_repository.get_todo_by_id(id).await {
+            Ok(Some(todo)) => todo,
+            Ok(None) => return Err(todo_not_found(id)),
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch todo, error: {:}", err),
+                });
+            }
+        };
+        match self
+            .todo_list_service
+            .authorize_todo_list(todo.list_id, user_id, access)
+            .await
+        {
+            Ok(_) => Ok(todo),
+            Err(err) if matches!(err.code, ErrorCode::NotFound) => Err(todo_not_found(id)),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+fn validate_dates(
+    start_at: Option<chrono::DateTime<chrono::Utc>>,
+    due_at: Option<chrono::DateTime<chrono::Utc>>,
+) -> ApplicationResult<()> {
+    match (start_at, due_at) {
+        (Some(start_at), Some(due_at)) if start_at > due_at => Err(ApplicationError {
+            code: ErrorCode::BadRequest,
+            message: "a todo can't start after it is due".to_owned(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// The rule and the name of the time zone, every broken rule is reported.
+fn validate_recurrence(
+    rrule: &str,
+    time_zone: &str,
+) -> ApplicationResult<(RecurrenceRule, String)> {
+    let mut violations = vec![];
+    let rule = match rrule.trim().parse::<RecurrenceRule>() {
+        Ok(rule) => Some(rule),
+        Err(err) => {
+            violations.push(violation(
+                "rrule",
+                "pattern",
+                format!("rrule is invalid, {}", err),
+            ));
+            None
+        }
+    };
+    let zone = match time_zone.parse::<Tz>() {
+        Ok(zone) => Some(zone),
+        Err(_) => {
+            violations.push(violation(
+                "timeZone",
+                "pattern",
+                "timeZone must be an IANA time zone like Europe/Berlin".to_owned(),
+            ));
+            None
+        }
+    };
+    if let (Some(rule), Some(zone)) = (rule, zone) {
+        return Ok((rule, zone.name().to_owned()));
+    }
+    Err(ApplicationError {
+        message: violations
+            .iter()
+            .map(|violation| violation.message.as_str())
+            .collect::<Vec<&str>>()
+            .join(", "),
+        code: ErrorCode::ValidationFailed(violations),
+    })
+}
+
+/// A `COUNT` includes the occurrences the series has had, so it has to leave some.
+fn validate_count(rule: &RecurrenceRule, occurrences_before: i32) -> ApplicationResult<()> {
+    match rule.count {
+        Some(count) if i64::from(count) <= i64::from(occurrences_before) => {
+            let message = format!(
+                "COUNT must be more than the {} occurrences the series has had",
+                occurrences_before
+            );
+            Err(ApplicationError {
+                message: message.to_owned(),
+                code: ErrorCode::ValidationFailed(vec![violation("rrule", "count", message)]),
+            })
+        }
+        _ => Ok(()),
+    }
+}
+
+fn violation(field: &str, rule: &str, message: String) -> Violation {
+    Violation {
+        field: field.to_owned(),
+        rule: rule.to_owned(),
+        message,
+    }
+}
+
+fn todo_series_not_found(id: i32) -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::NotFound,
+        message: format!("todo series is not found, id: {}", id),
+    }
+}
+
+fn todo_not_found(id: i32) -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::NotFound,
+        message: format!("todo is not found, id: {}", id),
+    }
 }
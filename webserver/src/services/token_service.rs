@@ -0,0 +1,329 @@
+use crate::auth::key_ring::KeyRing;
+use crate::auth::{create_jwt, generate_token, hash_token};
+use crate::domains::entities::audit_event::RequestMetadata;
+use crate::domains::entities::refresh_token::RefreshToken;
+use crate::domains::entities::session::Session;
+use crate::domains::entities::token_pair::TokenPair;
+use crate::domains::entities::user::User;
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::repositories::refresh_token_repository::RefreshTokenRepository;
+use crate::domains::repositories::session_repository::SessionRepository;
+use crate::domains::repositories::user_repository::UserRepository;
+use crate::domains::ApplicationResult;
+use crate::infrastructures::di_container::DIContainer;
+use std::sync::Arc;
+
+const REFRESH_TOKEN_DAYS: i64 = 30;
+/// Requests of a session update when it was last seen at most this often.
+const LAST_SEEN_INTERVAL_SECONDS: i64 = 60;
+
+#[derive(Clone)]
+pub struct TokenService {
+    key_ring: Arc<KeyRing>,
+    refresh_token_repository: Box<dyn RefreshTokenRepository + Send + Sync>,
+    session_repository: Box<dyn SessionRepository + Send + Sync>,
+    user_repository: Box<dyn UserRepository + Send + Sync>,
+}
+
+impl TokenService {
+    pub fn new(di_container: Arc<dyn DIContainer>, key_ring: Arc<KeyRing>) -> Self {
+        Self {
+            key_ring,
+            refresh_token_repository: di_container.refresh_token_repository(),
+            session_repository: di_container.session_repository(),
+            user_repository: di_container.user_repository(),
+        }
+    }
+
+    /// Issue an access token and a refresh token which starts a new token family,
+    /// recorded as a session of the device the request came from.
+    /// Suspended users get no tokens, so they can neither log in nor refresh.
+    pub async fn issue_tokens(
+        &self,
+        user_id: i32,
+        metadata: &RequestMetadata,
+    ) -> ApplicationResult<TokenPair> {
+        let user = self.active_user(user_id).await?;
+        let now = chrono::Utc::now();
+        let session = Session {
+            id: 0,
+            user_id,
+            family: generate_token(),
+            user_agent: metadata.user_agent.to_owned(),
+            ip_address: metadata.ip_address.to_owned(),
+            created_at: now,
+            last_seen_at: now,
+        };
+        let session = match self.session_repository.create_session(session).await {
+            Ok(session) => session,
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to create session, error: {:}", err),
+                })
+            }
+        };
+        self.issue_tokens_in_family(&user, session.family, Some(session.id))
+            .await
+    }
+
+    /// Exchange a refresh token for a new pair, the presented token can't be used again.
+    /// Presenting an already rotated token revokes its whole family,
+    /// because either the client or an attacker holds a stolen copy.
+    pub async fn refresh_tokens(&self, token: String) -> ApplicationResult<TokenPair> {
+        let refresh_token = match self
+            .refresh_token_repository
+            .get_refresh_token_by_hash(hash_token(&token))
+            .await
+        {
+            Ok(Some(refresh_token)) => refresh_token,
+            Ok(None) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::UnAuthenticated,
+                    message: "refresh token is invalid".to_owned(),
+                })
+            }
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch refresh token, error: {:}", err),
+                })
+            }
+        };
+        if refresh_token.revoked_at.is_some() {
+            return Err(refresh_token_revoked());
+        }
+        let now = chrono::Utc::now();
+        if refresh_token.used_at.is_some() {
+            self.revoke_family(refresh_token.family).await?;
+            return Err(refresh_token_revoked());
+        }
+        if refresh_token.expires_at < now {
+            return Err(ApplicationError {
+                code: ErrorCode::TokenExpired,
+                message: "refresh token is expired".to_owned(),
+            });
+        }
+        match self
+            .refresh_token_repository
+            .use_refresh_token(refresh_token.id, now)
+            .await
+        {
+            Ok(true) => {}
+            // Lost a race against another request rotating the same token.
+            Ok(false) => {
+                self.revoke_family(refresh_token.family).await?;
+                return Err(refresh_token_revoked());
+            }
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to rotate refresh token, error: {:}", err),
+                })
+            }
+        }
+        let user = self.active_user(refresh_token.user_id).await?;
+        let session = match self
+            .session_repository
+            .get_session_by_family(refresh_token.family.to_owned())
+            .await
+        {
+            Ok(session) => session,
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch session, error: {:}", err),
+                })
+            }
+        };
+        self.issue_tokens_in_family(
+            &user,
+            refresh_token.family,
+            session.map(|session| session.id),
+        )
+        .await
+    }
+
+    pub async fn revoke_family(&self, family: String) -> ApplicationResult<bool> {
+        let now = chrono::Utc::now();
+        match self
+            .refresh_token_repository
+            .revoke_family(family, now)
+            .await
+        {
+            Ok(ret) => Ok(ret),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to revoke refresh tokens, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Access tokens of a revoked family are rejected before they expire,
+    /// so a logout or a password reset ends the session at once.
+    pub async fn ensure_family_active(&self, family: String) -> ApplicationResult<()> {
+        match self
+            .refresh_token_repository
+            .is_family_revoked(family)
+            .await
+        {
+            Ok(false) => Ok(()),
+            Ok(true) => Err(ApplicationError {
+                code: ErrorCode::TokenRevoked,
+                message: "session is revoked".to_owned(),
+            }),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch refresh token, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Sessions of the user which haven't been logged out or revoked, the last seen first.
+    pub async fn get_sessions(&self, user_id: i32) -> ApplicationResult<Vec<Session>> {
+        match self
+            .session_repository
+            .get_active_user_sessions(user_id, chrono::Utc::now())
+            .await
+        {
+            Ok(sessions) => Ok(sessions),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch sessions, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Sign a session of the user out, its access tokens are rejected from the next request.
+    pub async fn revoke_session(&self, user_id: i32, id: i32) -> ApplicationResult<bool> {
+        match self.active_session(id).await? {
+            Some(session) if session.user_id == user_id => self.revoke_family(session.family).await,
+            _ => Err(ApplicationError {
+                code: ErrorCode::NotFound,
+                message: format!("session is not found, id: {}", id),
+            }),
+        }
+    }
+
+    /// Access tokens of an ended session are rejected before they expire,
+    /// and the session is seen at the address of the request.
+    pub async fn ensure_session_active(
+        &self,
+        id: i32,
+        user_id: i32,
+        metadata: &RequestMetadata,
+    ) -> ApplicationResult<()> {
+        let session = match self.active_session(id).await? {
+            Some(session) if session.user_id == user_id => session,
+            _ => {
+                return Err(ApplicationError {
+                    code: ErrorCode::TokenRevoked,
+                    message: "session is revoked".to_owned(),
+                })
+            }
+        };
+        let now = chrono::Utc::now();
+        if session.last_seen_at + chrono::Duration::seconds(LAST_SEEN_INTERVAL_SECONDS) > now
+            && (metadata.ip_address.is_none() || metadata.ip_address == session.ip_address)
+        {
+            return Ok(());
+        }
+        match self
+            .session_repository
+            .touch_session(id, metadata.ip_address.to_owned(), now)
+            .await
+        {
+            Ok(_) => Ok(()),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to update session, error: {:}", err),
+            }),
+        }
+    }
+
+    async fn active_session(&self, id: i32) -> ApplicationResult<Option<Session>> {
+        match self
+            .session_repository
+            .get_active_session(id, chrono::Utc::now())
+            .await
+        {
+            Ok(session) => Ok(session),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch session, error: {:}", err),
+            }),
+        }
+    }
+
+    async fn active_user(&self, user_id: i32) -> ApplicationResult<User> {
+        let user = match self.user_repository.get_user_by_id(user_id).await {
+            Ok(Some(user)) => user,
+            Ok(None) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::UnAuthenticated,
+                    message: "user is not found".to_owned(),
+                })
+            }
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch user, error: {:}", err),
+                })
+            }
+        };
+        if user.is_suspended() {
+            return Err(ApplicationError {
+                code: ErrorCode::AccountSuspended,
+                message: "user is suspended".to_owned(),
+            });
+        }
+        Ok(user)
+    }
+
+    async fn issue_tokens_in_family(
+        &self,
+        user: &User,
+        family: String,
+        session_id: Option<i32>,
+    ) -> ApplicationResult<TokenPair> {
+        let now = chrono::Utc::now();
+        let token = generate_token();
+        let refresh_token = RefreshToken {
+            id: 0,
+            user_id: user.id,
+            family: family.to_owned(),
+            token_hash: hash_token(&token),
+            expires_at: now + chrono::Duration::days(REFRESH_TOKEN_DAYS),
+            used_at: None,
+            revoked_at: None,
+            created_at: now,
+        };
+        if let Err(err) = self
+            .refresh_token_repository
+            .create_refresh_token(refresh_token)
+            .await
+        {
+            return Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to create refresh token, error: {:}", err),
+            });
+        }
+        Ok(TokenPair {
+            access_token: create_jwt(
+                &self.key_ring,
+                user.id,
+                Some(family),
+                session_id,
+                user.granted_role(),
+            )?,
+            refresh_token: token,
+        })
+    }
+}
+
+fn refresh_token_revoked() -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::TokenRevoked,
+        message: "refresh token is revoked".to_owned(),
+    }
+}
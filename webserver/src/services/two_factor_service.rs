@@ -0,0 +1,338 @@
+use crate::auth::key_ring::KeyRing;
+use crate::auth::{hash_token, totp};
+use crate::domains::entities::audit_event::RequestMetadata;
+use crate::domains::entities::login_result::LoginResult;
+use crate::domains::entities::token_pair::TokenPair;
+use crate::domains::entities::totp_credential::{TotpCredential, TotpEnrollment};
+use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::domains::repositories::recovery_code_repository::RecoveryCodeRepository;
+use crate::domains::repositories::totp_credential_repository::TotpCredentialRepository;
+use crate::domains::repositories::user_repository::UserRepository;
+use crate::domains::ApplicationResult;
+use crate::infrastructures::di_container::DIContainer;
+use crate::services::token_service::TokenService;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+const TOTP_ISSUER: &str = "rsapps";
+const CHALLENGE_MINUTES: i64 = 5;
+const RECOVERY_CODES: usize = 10;
+
+/// Claims of a login which waits for the second factor.
+/// There is no `sub`, so the challenge can't be used as an access token.
+#[derive(Serialize, Deserialize)]
+struct SecondFactorClaims {
+    uid: i32,
+    mfa: String,
+    exp: usize,
+}
+
+#[derive(Clone)]
+pub struct TwoFactorService {
+    key_ring: Arc<KeyRing>,
+    token_service: TokenService,
+    user_repository: Box<dyn UserRepository + Send + Sync>,
+    totp_credential_repository: Box<dyn TotpCredentialRepository + Send + Sync>,
+    recovery_code_repository: Box<dyn RecoveryCodeRepository + Send + Sync>,
+}
+
+impl TwoFactorService {
+    pub fn new(
+        di_container: Arc<dyn DIContainer>,
+        key_ring: Arc<KeyRing>,
+        token_service: TokenService,
+    ) -> Self {
+        Self {
+            key_ring,
+            token_service,
+            user_repository: di_container.user_repository(),
+            totp_credential_repository: di_container.totp_credential_repository(),
+            recovery_code_repository: di_container.recovery_code_repository(),
+        }
+    }
+
+    /// Finish the first factor of a login.
+    /// A user with two-factor authentication gets a challenge instead of the tokens.
+    pub async fn start_login(
+        &self,
+        user_id: i32,
+        metadata: &RequestMetadata,
+    ) -> ApplicationResult<LoginResult> {
+        if !self.is_enabled(user_id).await? {
+            let tokens = self.token_service.issue_tokens(user_id, metadata).await?;
+            return Ok(LoginResult {
+                tokens: Some(tokens),
+                challenge_token: None,
+            });
+        }
+        let challenge_token = self.key_ring.encode(&SecondFactorClaims {
+            uid: user_id,
+            mfa: "totp".to_owned(),
+            exp: (chrono::Utc::now() + chrono::Duration::minutes(CHALLENGE_MINUTES)).timestamp()
+                as usize,
+        })?;
+        Ok(LoginResult {
+            tokens: None,
+            challenge_token: Some(challenge_token),
+        })
+    }
+
+    /// Complete a login with a TOTP code or a recovery code.
+    pub async fn verify_second_factor(
+        &self,
+        challenge_token: String,
+        code: String,
+        metadata: &RequestMetadata,
+    ) -> ApplicationResult<TokenPair> {
+        let user_id = self.challenged_user_id(&challenge_token)?;
+        let credential = self.get_confirmed_credential(user_id).await?;
+        self.verify_code(&credential, &code).await?;
+        self.token_service.issue_tokens(user_id, metadata).await
+    }
+
+    /// The user a challenge was issued to.
+    pub fn challenged_user_id(&self, challenge_token: &str) -> ApplicationResult<i32> {
+        Ok(self
+            .key_ring
+            .decode::<SecondFactorClaims>(challenge_token)?
+            .uid)
+    }
+
+    pub async fn is_enabled(&self, user_id: i32) -> ApplicationResult<bool> {
+        match self.get_credential(user_id).await? {
+            Some(credential) => Ok(credential.confirmed_at.is_some()),
+            None => Ok(false),
+        }
+    }
+
+    /// Start enrolling a new secret, it replaces a secret which isn't confirmed yet.
+    pub async fn enroll_totp(&self, user_id: i32) -> ApplicationResult<TotpEnrollment> {
+        let user = match self.user_repository.get_user_by_id(user_id).await {
+            Ok(Some(user)) => user,
+            Ok(None) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::NotFound,
+                    message: "user is not registered".to_owned(),
+                })
+            }
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch user, error: {:}", err),
+                })
+            }
+        };
+        let credential = TotpCredential {
+            id: 0,
+            user_id,
+            secret: totp::generate_secret(),
+            confirmed_at: None,
+            last_used_step: None,
+            created_at: chrono::Utc::now(),
+        };
+        match self
+            .totp_credential_repository
+            .save_totp_credential(credential)
+            .await
+        {
+            Ok(Some(saved)) => Ok(TotpEnrollment {
+                otpauth_uri: totp::otpauth_uri(TOTP_ISSUER, &user.username, &saved.secret),
+                secret: saved.secret,
+            }),
+            Ok(None) => Err(two_factor_enabled()),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to save TOTP credential, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Enable two-factor authentication with a code of the enrolled secret.
+    /// Returns the recovery codes, they are shown only once.
+    pub async fn confirm_totp(&self, user_id: i32, code: String) -> ApplicationResult<Vec<String>> {
+        let credential = match self.get_credential(user_id).await? {
+            Some(credential) if credential.confirmed_at.is_some() => {
+                return Err(two_factor_enabled())
+            }
+            Some(credential) => credential,
+            None => {
+                return Err(ApplicationError {
+                    code: ErrorCode::NotFound,
+                    message: "TOTP secret is not enrolled".to_owned(),
+                })
+            }
+        };
+        let now = chrono::Utc::now();
+        let step = match totp::decode_base32(&credential.secret)
+            .and_then(|secret| totp::verify(&secret, code.trim(), now.timestamp()))
+        {
+            Some(step) => step,
+            None => return Err(code_invalid()),
+        };
+        match self
+            .totp_credential_repository
+            .confirm_totp_credential(user_id, step, now)
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => return Err(two_factor_enabled()),
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to confirm TOTP credential, error: {:}", err),
+                })
+            }
+        }
+        self.replace_recovery_codes(user_id).await
+    }
+
+    /// Replace the recovery codes, the old ones can't be used anymore.
+    pub async fn regenerate_recovery_codes(
+        &self,
+        user_id: i32,
+        code: String,
+    ) -> ApplicationResult<Vec<String>> {
+        let credential = self.get_confirmed_credential(user_id).await?;
+        self.verify_code(&credential, &code).await?;
+        self.replace_recovery_codes(user_id).await
+    }
+
+    pub async fn disable_totp(&self, user_id: i32, code: String) -> ApplicationResult<bool> {
+        let credential = self.get_confirmed_credential(user_id).await?;
+        self.verify_code(&credential, &code).await?;
+        if let Err(err) = self
+            .recovery_code_repository
+            .delete_user_recovery_codes(user_id)
+            .await
+        {
+            return Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to delete recovery codes, error: {:}", err),
+            });
+        }
+        match self
+            .totp_credential_repository
+            .delete_totp_credential(user_id)
+            .await
+        {
+            Ok(ret) => Ok(ret),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to delete TOTP credential, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Accept a TOTP code once, or use up a recovery code.
+    async fn verify_code(&self, credential: &TotpCredential, code: &str) -> ApplicationResult<()> {
+        let now = chrono::Utc::now();
+        let code = code.trim();
+        let accepted = if totp::is_code(code) {
+            match totp::decode_base32(&credential.secret)
+                .and_then(|secret| totp::verify(&secret, code, now.timestamp()))
+            {
+                Some(step) => self
+                    .totp_credential_repository
+                    .use_totp_step(credential.user_id, step)
+                    .await
+                    .map_err(|err| ApplicationError {
+                        code: ErrorCode::SystemError,
+                        message: format!("failed to use TOTP code, error: {:}", err),
+                    })?,
+                None => false,
+            }
+        } else {
+            self.recovery_code_repository
+                .use_recovery_code(
+                    credential.user_id,
+                    hash_token(&normalize_recovery_code(code)),
+                    now,
+                )
+                .await
+                .map_err(|err| ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to use recovery code, error: {:}", err),
+                })?
+        };
+        if !accepted {
+            return Err(code_invalid());
+        }
+        Ok(())
+    }
+
+    async fn replace_recovery_codes(&self, user_id: i32) -> ApplicationResult<Vec<String>> {
+        let codes: Vec<String> = (0..RECOVERY_CODES)
+            .map(|_| generate_recovery_code())
+            .collect();
+        let code_hashes = codes
+            .iter()
+            .map(|code| hash_token(&normalize_recovery_code(code)))
+            .collect();
+        match self
+            .recovery_code_repository
+            .replace_recovery_codes(user_id, code_hashes, chrono::Utc::now())
+            .await
+        {
+            Ok(_) => Ok(codes),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to create recovery codes, error: {:}", err),
+            }),
+        }
+    }
+
+    async fn get_credential(&self, user_id: i32) -> ApplicationResult<Option<TotpCredential>> {
+        match self
+            .totp_credential_repository
+            .get_totp_credential(user_id)
+            .await
+        {
+            Ok(credential) => Ok(credential),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch TOTP credential, error: {:}", err),
+            }),
+        }
+    }
+
+    async fn get_confirmed_credential(&self, user_id: i32) -> ApplicationResult<TotpCredential> {
+        match self.get_credential(user_id).await? {
+            Some(credential) if credential.confirmed_at.is_some() => Ok(credential),
+            _ => Err(ApplicationError {
+                code: ErrorCode::BadRequest,
+                message: "two-factor authentication is not enabled".to_owned(),
+            }),
+        }
+    }
+}
+
+/// A recovery code like `1a2b3-c4d5e`.
+fn generate_recovery_code() -> String {
+    let mut bytes = [0u8; 5];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let code = hex::encode(bytes);
+    format!("{}-{}", &code[..5], &code[5..])
+}
+
+/// Users may type recovery codes in upper case or without the dash.
+fn normalize_recovery_code(code: &str) -> String {
+    code.chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .collect::<String>()
+        .to_lowercase()
+}
+
+fn two_factor_enabled() -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::Conflict,
+        message: "two-factor authentication is enabled already".to_owned(),
+    }
+}
+
+fn code_invalid() -> ApplicationError {
+    ApplicationError {
+        code: ErrorCode::UnAuthenticated,
+        message: "authentication code is invalid".to_owned(),
+    }
+}
@@ -1,23 +1,97 @@
-use crate::domains::entities::user::User;
-use crate::domains::errors::{ApplicationError, ErrorCode};
+use crate::auth::hash_token;
+use crate::auth::key_ring::KeyRing;
+use crate::auth::password_policy::PasswordPolicy;
+use crate::domains::entities::invite_code::RegistrationMode;
+use crate::domains::entities::user::{Role, User};
+use crate::domains::errors::{ApplicationError, ErrorCode, Violation};
+use crate::domains::password_hasher::PasswordHasher;
+use crate::domains::repositories::invite_code_repository::InviteCodeRepository;
+use crate::domains::repositories::refresh_token_repository::RefreshTokenRepository;
 use crate::domains::repositories::user_repository::UserRepository;
 use crate::domains::ApplicationResult;
 use crate::infrastructures::di_container::DIContainer;
+use crate::services::email_service::EmailService;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+const EMAIL_VERIFICATION_HOURS: i64 = 24;
+/// As long as `users.username` holds.
+const MAX_USERNAME_LENGTH: usize = 16;
+
+/// Claims of an email verification link.
+/// There is no `sub`, so the link can't be used as an access token and an access token can't verify an email.
+#[derive(Serialize, Deserialize)]
+struct EmailVerificationClaims {
+    uid: i32,
+    email: String,
+    exp: usize,
+}
+
 #[derive(Clone)]
 pub struct UserService {
+    key_ring: Arc<KeyRing>,
+    email_service: EmailService,
+    password_hasher: Box<dyn PasswordHasher + Send + Sync>,
+    password_policy: Arc<PasswordPolicy>,
+    registration_mode: RegistrationMode,
     user_repository: Box<dyn UserRepository + Send + Sync>,
+    refresh_token_repository: Box<dyn RefreshTokenRepository + Send + Sync>,
+    invite_code_repository: Box<dyn InviteCodeRepository + Send + Sync>,
 }
 
 impl UserService {
-    pub fn new(di_container: Arc<dyn DIContainer>) -> Self {
+    pub fn new(
+        di_container: Arc<dyn DIContainer>,
+        key_ring: Arc<KeyRing>,
+        email_service: EmailService,
+        password_hasher: Box<dyn PasswordHasher + Send + Sync>,
+        password_policy: Arc<PasswordPolicy>,
+        registration_mode: RegistrationMode,
+    ) -> Self {
         Self {
+            key_ring,
+            email_service,
+            password_hasher,
+            password_policy,
+            registration_mode,
             user_repository: di_container.user_repository(),
+            refresh_token_repository: di_container.refresh_token_repository(),
+            invite_code_repository: di_container.invite_code_repository(),
         }
     }
 
-    pub async fn sign_up(self, username: String, password: String) -> ApplicationResult<User> {
+    pub fn registration_mode(&self) -> RegistrationMode {
+        self.registration_mode
+    }
+
+    /// Sign up the user, and send a verification link when an email is given.
+    /// The email can't be used to log in until it is verified.
+    /// An invite code is needed when registration is invite only, and is used up with the sign up.
+    pub async fn sign_up(
+        self,
+        username: String,
+        password: String,
+        email: Option<String>,
+        invite_code: Option<String>,
+    ) -> ApplicationResult<User> {
+        match (self.registration_mode, invite_code.as_ref()) {
+            (RegistrationMode::Closed, _) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::Forbidden,
+                    message: "registration is closed".to_owned(),
+                })
+            }
+            (RegistrationMode::InviteOnly, None) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::Forbidden,
+                    message: "an invite code is needed to sign up".to_owned(),
+                })
+            }
+            _ => {}
+        }
+        validate_username(&username)?;
+        self.password_policy
+            .validate(&password, &user_inputs(&username, email.as_deref()))?;
         match self
             .user_repository
             .get_user_by_username(username.to_owned())
@@ -42,38 +116,306 @@ impl UserService {
             }
         };
 
+        let email = match email {
+            Some(email) => Some(self.clone().available_email(email).await?),
+            None => None,
+        };
+
         let now = chrono::Utc::now();
-        let hash = bcrypt::hash(password, 10).unwrap();
+        let hash = self.hash_password(&password)?;
         let new_user = User {
             id: 0,
-            email: None,
+            email,
             username,
             password_hash: Some(hash),
             created_at: now,
             updated_at: now,
+            email_verified_at: None,
+            role: Role::User.as_ref().to_owned(),
+            suspended_at: None,
+            password_reset_required: false,
+        };
+        let result = match invite_code {
+            Some(invite_code) => {
+                self.invite_code_repository
+                    .create_user_with_invite_code(new_user, hash_token(invite_code.trim()), now)
+                    .await
+            }
+            None => self.user_repository.create_user(new_user).await.map(Some),
+        };
+        let created = match result {
+            Ok(Some(created)) => created,
+            Ok(None) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::Forbidden,
+                    message: "invite code is invalid, used up or expired".to_owned(),
+                })
+            }
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to create user, error: {:}", err),
+                })
+            }
+        };
+        // The user is signed up already, a lost mail can be sent again with `send_email_verification`.
+        if created.email.is_some() {
+            if let Err(err) = self.clone().send_email_verification(created.id).await {
+                println!("failed to send email verification, err: {:}", err);
+            }
+        }
+        Ok(created)
+    }
+
+    /// Change the password, which needs the current one.
+    /// Other sessions of the user are logged out, the session of `family` stays.
+    pub async fn change_password(
+        self,
+        id: i32,
+        family: Option<String>,
+        current_password: String,
+        new_password: String,
+    ) -> ApplicationResult<()> {
+        let user = self.clone().get_user(id).await?;
+        if !user.valid_password(self.password_hasher.as_ref(), &current_password) {
+            return Err(ApplicationError {
+                code: ErrorCode::InvalidCredentials,
+                message: "current password is invalid".to_owned(),
+            });
+        }
+        self.password_policy.validate(
+            &new_password,
+            &user_inputs(&user.username, user.email.as_deref()),
+        )?;
+        let hash = self.hash_password(&new_password)?;
+        let now = chrono::Utc::now();
+        if let Err(err) = self.user_repository.update_password(id, hash, now).await {
+            return Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to update password, error: {:}", err),
+            });
+        }
+        let revoked = match family {
+            Some(family) => {
+                self.refresh_token_repository
+                    .revoke_other_refresh_tokens(id, family, now)
+                    .await
+            }
+            None => {
+                self.refresh_token_repository
+                    .revoke_user_refresh_tokens(id, now)
+                    .await
+            }
+        };
+        match revoked {
+            Ok(_) => Ok(()),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to revoke refresh tokens, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Rename the user or change the email, fields which are `None` stay.
+    /// A new email gets a verification link, and can't be used to log in until it is verified.
+    pub async fn update_profile(
+        self,
+        id: i32,
+        username: Option<String>,
+        email: Option<String>,
+    ) -> ApplicationResult<User> {
+        let user = self.clone().get_user(id).await?;
+        let username = match username {
+            Some(username) if username != user.username => {
+                validate_username(&username)?;
+                match self
+                    .user_repository
+                    .get_user_by_username(username.to_owned())
+                    .await
+                {
+                    Ok(Some(_)) => {
+                        return Err(ApplicationError {
+                            code: ErrorCode::Conflict,
+                            message: "username is taken".to_owned(),
+                        })
+                    }
+                    Ok(None) => username,
+                    Err(err) => {
+                        return Err(ApplicationError {
+                            code: ErrorCode::SystemError,
+                            message: format!("failed to fetch user, error: {:}", err),
+                        })
+                    }
+                }
+            }
+            _ => user.username,
+        };
+        let email = match email {
+            Some(email) if Some(email.trim().to_lowercase()) != user.email => {
+                Some(self.clone().available_email(email).await?)
+            }
+            _ => user.email.to_owned(),
+        };
+        let email_changed = email != user.email;
+        let updated = match self
+            .user_repository
+            .update_profile(id, username, email, chrono::Utc::now())
+            .await
+        {
+            Ok(Some(updated)) => updated,
+            Ok(None) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::NotFound,
+                    message: "user is not registered".to_owned(),
+                })
+            }
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to update user, error: {:}", err),
+                })
+            }
+        };
+        if email_changed {
+            if let Err(err) = self.clone().send_email_verification(id).await {
+                println!("failed to send email verification, err: {:}", err);
+            }
+        }
+        Ok(updated)
+    }
+
+    /// Delete the user with their todos, tokens and identities.
+    /// A user with a password has to confirm it.
+    pub async fn delete_account(
+        self,
+        id: i32,
+        password: Option<String>,
+    ) -> ApplicationResult<bool> {
+        let user = self.clone().get_user(id).await?;
+        if user.password_hash.is_some() {
+            let valid = password.map_or(false, |password| {
+                user.valid_password(self.password_hasher.as_ref(), &password)
+            });
+            if !valid {
+                return Err(ApplicationError {
+                    code: ErrorCode::InvalidCredentials,
+                    message: "password is invalid".to_owned(),
+                });
+            }
+        }
+        // Refresh tokens are deleted with the user, so their families count as revoked
+        // and access tokens of every session are rejected right away.
+        match self.user_repository.delete_user(id).await {
+            Ok(ret) => Ok(ret),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to delete user, error: {:}", err),
+            }),
+        }
+    }
+
+    /// Rules the password of a new user would break, so forms can show them while it is typed.
+    pub fn password_violations(
+        &self,
+        username: &str,
+        email: Option<&str>,
+        password: &str,
+    ) -> Vec<Violation> {
+        self.password_policy
+            .violations(password, &user_inputs(username, email))
+    }
+
+    /// Send a link which verifies the current email of the user.
+    pub async fn send_email_verification(self, id: i32) -> ApplicationResult<()> {
+        let user = self.clone().get_user(id).await?;
+        let email = match (user.email, user.email_verified_at) {
+            (Some(_), Some(_)) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::Conflict,
+                    message: "email is verified already".to_owned(),
+                })
+            }
+            (Some(email), None) => email,
+            (None, _) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::BadRequest,
+                    message: "user doesn't have an email".to_owned(),
+                })
+            }
         };
-        match self.user_repository.create_user(new_user).await {
-            Ok(created) => Ok(created),
+        let token = self.key_ring.encode(&EmailVerificationClaims {
+            uid: user.id,
+            email: email.to_owned(),
+            exp: (chrono::Utc::now() + chrono::Duration::hours(EMAIL_VERIFICATION_HOURS))
+                .timestamp() as usize,
+        })?;
+        self.email_service
+            .send_email_verification(email, token)
+            .await
+    }
+
+    /// Verify the email a link was sent to, the link is void once the user changes the email.
+    pub async fn verify_email(self, token: String) -> ApplicationResult<User> {
+        let claims = self.key_ring.decode::<EmailVerificationClaims>(&token)?;
+        match self
+            .user_repository
+            .verify_email(claims.uid, claims.email, chrono::Utc::now())
+            .await
+        {
+            Ok(Some(user)) => Ok(user),
+            Ok(None) => Err(ApplicationError {
+                code: ErrorCode::BadRequest,
+                message: "email of the user has changed".to_owned(),
+            }),
             Err(err) => Err(ApplicationError {
                 code: ErrorCode::SystemError,
-                message: format!("failed to create user, error: {:}", err),
+                message: format!("failed to verify email, error: {:}", err),
             }),
         }
     }
 
-    pub async fn get_all_users(self) -> ApplicationResult<Vec<User>> {
-        match self.user_repository.get_all_users().await {
-            Ok(users) => Ok(users),
+    async fn available_email(self, email: String) -> ApplicationResult<String> {
+        let email = email.trim().to_lowercase();
+        if !email.contains('@') {
+            return Err(ApplicationError {
+                code: ErrorCode::BadRequest,
+                message: "email is invalid".to_owned(),
+            });
+        }
+        match self
+            .user_repository
+            .get_user_by_email(email.to_owned())
+            .await
+        {
+            Ok(Some(_)) => Err(ApplicationError {
+                code: ErrorCode::Conflict,
+                message: "email is registered already".to_owned(),
+            }),
+            Ok(None) => Ok(email),
             Err(err) => Err(ApplicationError {
                 code: ErrorCode::SystemError,
-                message: format!("failed to fetch users, error: {:}", err),
+                message: format!("failed to fetch user, error: {:}", err),
+            }),
+        }
+    }
+
+    async fn get_user(self, id: i32) -> ApplicationResult<User> {
+        match self.user_repository.get_user_by_id(id).await {
+            Ok(Some(user)) => Ok(user),
+            Ok(None) => Err(ApplicationError {
+                code: ErrorCode::NotFound,
+                message: "user is not registered".to_owned(),
+            }),
+            Err(err) => Err(ApplicationError {
+                code: ErrorCode::SystemError,
+                message: format!("failed to fetch user, error: {:}", err),
             }),
         }
     }
 
     pub async fn get_user_by_id(self, id: i32, password: String) -> ApplicationResult<User> {
         let user = self.user_repository.get_user_by_id(id).await;
-        self.handle_user(user, password)
+        self.handle_user(user, password).await
     }
 
     pub async fn get_user_by_email(
@@ -81,8 +423,36 @@ impl UserService {
         email: String,
         password: String,
     ) -> ApplicationResult<User> {
-        let user = self.user_repository.get_user_by_email(email).await;
-        self.handle_user(user, password)
+        let user = self
+            .user_repository
+            .get_user_by_email(email.trim().to_lowercase())
+            .await;
+        let user = self.handle_user(user, password).await?;
+        if user.email_verified_at.is_none() {
+            return Err(ApplicationError {
+                code: ErrorCode::EmailNotVerified,
+                message: "email is not verified".to_owned(),
+            });
+        }
+        Ok(user)
+    }
+
+    /// The id of the user with the email, verified or not, without checking a password.
+    pub async fn find_user_id_by_email(self, email: String) -> ApplicationResult<Option<i32>> {
+        let user = self
+            .user_repository
+            .get_user_by_email(email.trim().to_lowercase())
+            .await;
+        find_user_id(user)
+    }
+
+    /// The id of the user with the username, without checking a password.
+    pub async fn find_user_id_by_username(
+        self,
+        username: String,
+    ) -> ApplicationResult<Option<i32>> {
+        let user = self.user_repository.get_user_by_username(username).await;
+        find_user_id(user)
     }
 
     pub async fn get_user_by_username(
@@ -91,34 +461,131 @@ impl UserService {
         password: String,
     ) -> ApplicationResult<User> {
         let user = self.user_repository.get_user_by_username(username).await;
-        self.handle_user(user, password)
+        self.handle_user(user, password).await
     }
 
-    fn handle_user(
+    /// Every failure is the same `InvalidCredentials`, and costs one password verification,
+    /// so neither the response nor its timing tells whether the user exists.
+    /// A hash of an older algorithm or parameters is replaced once the password is known to be right.
+    async fn handle_user(
         self,
         result: anyhow::Result<Option<User>>,
         password: String,
     ) -> ApplicationResult<User> {
-        match result {
-            Ok(user) => match user {
-                Some(user) => {
-                    if !user.valid_password(password) {
-                        return Err(ApplicationError {
-                            code: ErrorCode::UnAuthenticated,
-                            message: "user is not authenticated".to_owned(),
-                        });
-                    }
-                    Ok(user)
-                }
-                None => Err(ApplicationError {
-                    code: ErrorCode::NotFound,
-                    message: "user is not registered".to_owned(),
-                }),
-            },
+        let user = match result {
+            Ok(user) => user,
+            Err(err) => {
+                return Err(ApplicationError {
+                    code: ErrorCode::SystemError,
+                    message: format!("failed to fetch user, error: {:}", err),
+                })
+            }
+        };
+        let valid = match user.as_ref() {
+            Some(user) if user.password_hash.is_some() => {
+                user.valid_password(self.password_hasher.as_ref(), &password)
+            }
+            _ => {
+                self.password_hasher.verify_dummy(&password);
+                false
+            }
+        };
+        match user {
+            // Only told once the password is right, so it doesn't reveal the account to guesses.
+            Some(user) if valid && user.is_suspended() => Err(ApplicationError {
+                code: ErrorCode::AccountSuspended,
+                message: "user is suspended".to_owned(),
+            }),
+            Some(user) if valid && user.password_reset_required => Err(ApplicationError {
+                code: ErrorCode::PasswordResetRequired,
+                message: "password has to be reset".to_owned(),
+            }),
+            Some(user) if valid => {
+                self.rehash_password(&user, &password).await;
+                Ok(user)
+            }
+            _ => Err(ApplicationError {
+                code: ErrorCode::InvalidCredentials,
+                message: "username or password is invalid".to_owned(),
+            }),
+        }
+    }
+
+    /// The login goes on with the old hash when rehashing fails.
+    async fn rehash_password(&self, user: &User, password: &str) {
+        match user.password_hash.as_ref() {
+            Some(hash) if self.password_hasher.needs_rehash(hash) => {}
+            _ => return,
+        }
+        let hash = match self.hash_password(password) {
+            Ok(hash) => hash,
+            Err(err) => {
+                println!("failed to rehash password, err: {:}", err);
+                return;
+            }
+        };
+        if let Err(err) = self
+            .user_repository
+            .update_password(user.id, hash, chrono::Utc::now())
+            .await
+        {
+            println!("failed to rehash password, err: {:}", err);
+        }
+    }
+
+    fn hash_password(&self, password: &str) -> ApplicationResult<String> {
+        match self.password_hasher.hash(password) {
+            Ok(hash) => Ok(hash),
             Err(err) => Err(ApplicationError {
                 code: ErrorCode::SystemError,
-                message: format!("failed to fetch user, error: {:}", err),
+                message: format!("failed to hash password, error: {:}", err),
             }),
         }
     }
 }
+
+fn find_user_id(result: anyhow::Result<Option<User>>) -> ApplicationResult<Option<i32>> {
+    match result {
+        Ok(user) => Ok(user.map(|user| user.id)),
+        Err(err) => Err(ApplicationError {
+            code: ErrorCode::SystemError,
+            message: format!("failed to fetch user, error: {:}", err),
+        }),
+    }
+}
+
+fn validate_username(username: &str) -> ApplicationResult<()> {
+    let (rule, message) = if username.trim().is_empty() {
+        ("required", "username is required".to_owned())
+    } else if username.chars().count() > MAX_USERNAME_LENGTH {
+        (
+            "maxLength",
+            format!(
+                "username must be at most {} characters",
+                MAX_USERNAME_LENGTH
+            ),
+        )
+    } else {
+        return Ok(());
+    };
+    Err(ApplicationError {
+        code: ErrorCode::ValidationFailed(vec![Violation {
+            field: "username".to_owned(),
+            rule: rule.to_owned(),
+            message: message.to_owned(),
+        }]),
+        message,
+    })
+}
+
+/// Words of the user a password shouldn't be built on.
+pub fn user_inputs<'a>(username: &'a str, email: Option<&'a str>) -> Vec<&'a str> {
+    let mut inputs = vec![username];
+    if let Some(email) = email {
+        inputs.push(email);
+        if let Some(local_part) = email.split('@').next() {
+            inputs.push(local_part);
+        }
+    }
+    inputs
+}
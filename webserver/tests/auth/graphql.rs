@@ -0,0 +1,96 @@
+use crate::fixtures::{app_state, create_user, get_db, key_ring};
+use rsapps_webserver::auth::oidc::OidcProviders;
+use rsapps_webserver::auth::Claims;
+use rsapps_webserver::domains::entities::audit_event::RequestMetadata;
+use rsapps_webserver::gql::handle_graphql;
+use rsapps_webserver::State;
+use serde_json::{json, Value};
+use sqlx::Postgres;
+use tide::http::{Method, Request, Response, StatusCode, Url};
+use tide::{Body, Server};
+
+const REFRESH_TOKEN: &str = "
+mutation RefreshToken($token: String!) {
+  refreshToken(token: $token) {
+    accessToken
+    refreshToken
+  }
+}
+";
+
+async fn app() -> anyhow::Result<Server<State>> {
+    let state = app_state(OidcProviders::from_env().await?).await?;
+    let mut app = Server::with_state(state);
+    app.at("/graphql").post(handle_graphql);
+    Ok(app)
+}
+
+async fn post(app: &Server<State>, body: Value, access_token: Option<&str>) -> Response {
+    let mut request = Request::new(
+        Method::Post,
+        Url::parse("http://localhost/graphql").unwrap(),
+    );
+    if let Some(access_token) = access_token {
+        request.insert_header("Authorization", format!("Bearer {}", access_token));
+    }
+    request.set_body(Body::from_json(&body).unwrap());
+    app.respond(request).await.unwrap()
+}
+
+#[sqlx_macros::test]
+async fn test_refresh_token_with_expired_access_token() {
+    let app = app().await.unwrap();
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let tokens = app
+        .state()
+        .token_service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+    let mut claims = key_ring().decode::<Claims>(&tokens.access_token).unwrap();
+    claims.exp = (chrono::Utc::now() - chrono::Duration::hours(1)).timestamp() as usize;
+    let expired = key_ring().encode(&claims).unwrap();
+    let body = json!({
+        "query": REFRESH_TOKEN,
+        "variables": {"token": tokens.refresh_token},
+    });
+
+    // An expired access token is rejected before any resolver runs, so the client refreshes without one.
+    let response = post(&app, body.clone(), Some(&expired)).await;
+    assert_eq!(StatusCode::Unauthorized, response.status());
+
+    let mut response = post(&app, body, None).await;
+    assert_eq!(StatusCode::Ok, response.status());
+    let refreshed: Value = response.body_json().await.unwrap();
+    let pair = &refreshed["data"]["refreshToken"];
+    assert_ne!(tokens.refresh_token, pair["refreshToken"]);
+    let access_token = pair["accessToken"].as_str().unwrap();
+    let refreshed_claims = key_ring().decode::<Claims>(access_token).unwrap();
+    assert_eq!(user.id.to_string(), refreshed_claims.sub);
+}
+
+#[sqlx_macros::test]
+async fn test_access_token_without_session() {
+    let app = app().await.unwrap();
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let tokens = app
+        .state()
+        .token_service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+    let body = json!({"query": "{ twoFactorEnabled }"});
+
+    let response = post(&app, body.clone(), Some(&tokens.access_token)).await;
+    assert_eq!(StatusCode::Ok, response.status());
+
+    // Without a session or a family the token can't be checked against a suspension.
+    let mut claims = key_ring().decode::<Claims>(&tokens.access_token).unwrap();
+    claims.sid = None;
+    claims.fam = None;
+    let unchecked = key_ring().encode(&claims).unwrap();
+    let response = post(&app, body, Some(&unchecked)).await;
+    assert_eq!(StatusCode::Unauthorized, response.status());
+}
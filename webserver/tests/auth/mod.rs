@@ -0,0 +1,2 @@
+pub(crate) mod graphql;
+pub(crate) mod oidc;
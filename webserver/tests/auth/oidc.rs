@@ -0,0 +1,130 @@
+use crate::fixtures::{app_state, key_ring, FRONTEND_URL};
+use crate::mock_idp::start_mock_idp;
+use rsapps_webserver::auth::generate_token;
+use rsapps_webserver::auth::oidc::{handle_oidc_callback, handle_oidc_login, OidcProviders};
+use rsapps_webserver::auth::Claims;
+use rsapps_webserver::State;
+use serde_json::json;
+use std::fs;
+use tide::http::{Method, Request, Response, StatusCode, Url};
+use tide::Server;
+
+async fn app(issuer: &str) -> anyhow::Result<Server<State>> {
+    let config_file = std::env::temp_dir().join(format!("rsapps-oidc-{}.json", generate_token()));
+    fs::write(
+        &config_file,
+        json!({
+            "redirect_url": FRONTEND_URL,
+            "providers": [{
+                "name": "mock",
+                "issuer": issuer,
+                "client_id": "rsapps",
+                "redirect_uri": "http://localhost/auth/mock/callback",
+                "scopes": ["email"],
+            }],
+        })
+        .to_string(),
+    )?;
+    let oidc_providers = OidcProviders::from_file(&config_file.to_string_lossy()).await;
+    fs::remove_file(&config_file)?;
+
+    let mut app = Server::with_state(app_state(oidc_providers?).await?);
+    app.at("/auth/:provider/login").get(handle_oidc_login);
+    app.at("/auth/:provider/callback").get(handle_oidc_callback);
+    Ok(app)
+}
+
+async fn get(app: &Server<State>, url: &str, cookie: Option<&str>) -> Response {
+    let mut request = Request::new(Method::Get, Url::parse(url).unwrap());
+    if let Some(cookie) = cookie {
+        request.insert_header("Cookie", cookie);
+    }
+    app.respond(request).await.unwrap()
+}
+
+fn location(response: &Response) -> String {
+    response.header("Location").unwrap().as_str().to_owned()
+}
+
+/// Log in at the mock provider, returns the redirect to our callback and the login state cookie.
+async fn authorize(app: &Server<State>) -> (String, String) {
+    let login = get(app, "http://localhost/auth/mock/login", None).await;
+    assert_eq!(StatusCode::Found, login.status());
+    let set_cookie = login.header("Set-Cookie").unwrap().as_str();
+    let cookie = set_cookie.split(';').next().unwrap().to_owned();
+
+    let authorized = surf::get(location(&login)).await.unwrap();
+    assert_eq!(StatusCode::Found, authorized.status());
+    let callback = authorized.header("Location").unwrap().as_str().to_owned();
+    (callback, cookie)
+}
+
+fn fragment(response: &Response) -> Vec<(String, String)> {
+    let url = Url::parse(&location(response)).unwrap();
+    assert!(url.as_str().starts_with(FRONTEND_URL));
+    url.fragment()
+        .unwrap_or("")
+        .split('&')
+        .filter_map(|pair| {
+            let mut pair = pair.splitn(2, '=');
+            Some((pair.next()?.to_owned(), pair.next()?.to_owned()))
+        })
+        .collect()
+}
+
+#[sqlx_macros::test]
+async fn test_oidc_login_issues_tokens() {
+    let subject = generate_token();
+    let issuer = start_mock_idp(&subject).await.unwrap();
+    let app = app(&issuer).await.unwrap();
+
+    let (callback, cookie) = authorize(&app).await;
+    let response = get(&app, &callback, Some(&cookie)).await;
+    let fragment = fragment(&response);
+    let access_token = &fragment
+        .iter()
+        .find(|(key, _)| key == "access_token")
+        .expect("access token is in the fragment")
+        .1;
+    let claims = key_ring().decode::<Claims>(access_token).unwrap();
+
+    // Logging in again with the same subject signs in the same user.
+    let (callback, cookie) = authorize(&app).await;
+    let response = get(&app, &callback, Some(&cookie)).await;
+    let access_token = &fragment(&response)
+        .into_iter()
+        .find(|(key, _)| key == "access_token")
+        .unwrap()
+        .1;
+    let again = key_ring().decode::<Claims>(access_token).unwrap();
+    assert_eq!(claims.sub, again.sub);
+}
+
+#[sqlx_macros::test]
+async fn test_oidc_callback_rejects_other_state() {
+    let issuer = start_mock_idp(&generate_token()).await.unwrap();
+    let app = app(&issuer).await.unwrap();
+
+    let (callback, _) = authorize(&app).await;
+    let (_, other_cookie) = authorize(&app).await;
+    let response = get(&app, &callback, Some(&other_cookie)).await;
+    assert_eq!(
+        vec![("error".to_owned(), "UnAuthenticated".to_owned())],
+        fragment(&response)
+    );
+
+    let response = get(&app, &callback, None).await;
+    assert_eq!(
+        vec![("error".to_owned(), "UnAuthenticated".to_owned())],
+        fragment(&response)
+    );
+}
+
+#[sqlx_macros::test]
+async fn test_oidc_login_unknown_provider() {
+    let issuer = start_mock_idp(&generate_token()).await.unwrap();
+    let app = app(&issuer).await.unwrap();
+
+    let response = get(&app, "http://localhost/auth/unknown/login", None).await;
+    assert_eq!(StatusCode::NotFound, response.status());
+}
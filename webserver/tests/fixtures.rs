@@ -1,5 +1,40 @@
-use sqlx::{pool::PoolOptions, Database, Pool};
+use rsapps_webserver::auth::key_ring::KeyRing;
+use rsapps_webserver::auth::oidc::OidcProviders;
+use rsapps_webserver::auth::password_policy::PasswordPolicy;
+use rsapps_webserver::domains::entities::invite_code::RegistrationMode;
+use rsapps_webserver::domains::entities::user::{Role, User};
+use rsapps_webserver::domains::password_hasher::PasswordHasher;
+use rsapps_webserver::domains::repositories::user_repository::UserRepository;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::infrastructures::mailers::outbox_mailer::OutboxMailer;
+use rsapps_webserver::infrastructures::password_hashers::argon2_password_hasher::Argon2PasswordHasher;
+use rsapps_webserver::infrastructures::rate_limit_stores::memory_rate_limit_store::MemoryRateLimitStore;
+use rsapps_webserver::infrastructures::repositories::user_repository::PostgreSQLUserRepository;
+use rsapps_webserver::services::admin_service::AdminService;
+use rsapps_webserver::services::audit_logger::AuditLogger;
+use rsapps_webserver::services::data_export_service::DataExportService;
+use rsapps_webserver::services::email_service::EmailService;
+use rsapps_webserver::services::identity_service::IdentityService;
+use rsapps_webserver::services::invite_service::InviteService;
+use rsapps_webserver::services::magic_link_service::MagicLinkService;
+use rsapps_webserver::services::password_reset_service::PasswordResetService;
+use rsapps_webserver::services::personal_access_token_service::PersonalAccessTokenService;
+use rsapps_webserver::services::rate_limit_service::RateLimitService;
+use rsapps_webserver::services::tag_service::TagService;
+use rsapps_webserver::services::todo_list_service::TodoListService;
+use rsapps_webserver::services::todo_service::TodoService;
+use rsapps_webserver::services::token_service::TokenService;
+use rsapps_webserver::services::two_factor_service::TwoFactorService;
+use rsapps_webserver::services::user_service::UserService;
+use rsapps_webserver::State;
+use sqlx::{pool::PoolOptions, Database, Pool, Postgres};
+use std::collections::HashSet;
 use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+static USER_SEQUENCE: AtomicUsize = AtomicUsize::new(0);
+pub const FRONTEND_URL: &str = "http://localhost:8080/";
 
 pub async fn get_db<DB: Database>() -> anyhow::Result<Pool<DB>> {
     Ok(PoolOptions::<DB>::new()
@@ -7,3 +42,105 @@ pub async fn get_db<DB: Database>() -> anyhow::Result<Pool<DB>> {
         .connect(&env::var("DATABASE_URL")?)
         .await?)
 }
+
+/// A username which doesn't collide with other tests or previous runs.
+pub fn unique_username() -> String {
+    let sequence = USER_SEQUENCE.fetch_add(1, Ordering::SeqCst) % 100;
+    format!(
+        "t{:013}{:02}",
+        chrono::Utc::now().timestamp_nanos() % 10_000_000_000_000,
+        sequence
+    )
+}
+
+/// Argon2id with small parameters, so tests which log in stay fast.
+pub fn password_hasher() -> Box<dyn PasswordHasher + Send + Sync> {
+    Box::new(Argon2PasswordHasher::new(4096, 1, 1).unwrap())
+}
+
+pub fn password_policy() -> Arc<PasswordPolicy> {
+    Arc::new(PasswordPolicy::new(8, 3, HashSet::new()))
+}
+
+pub async fn create_user(db: &Pool<Postgres>) -> anyhow::Result<User> {
+    create_user_with(db, None, false, None).await
+}
+
+/// A user with the email, verified when asked to, and the password hash.
+pub async fn create_user_with(
+    db: &Pool<Postgres>,
+    email: Option<String>,
+    verified: bool,
+    password_hash: Option<String>,
+) -> anyhow::Result<User> {
+    let now = chrono::Utc::now();
+    let username = unique_username();
+    let repository = PostgreSQLUserRepository { db: db.clone() };
+    repository
+        .create_user(User {
+            id: 0,
+            username,
+            email,
+            password_hash,
+            created_at: now,
+            updated_at: now,
+            email_verified_at: if verified { Some(now) } else { None },
+            role: Role::User.as_ref().to_owned(),
+            suspended_at: None,
+            password_reset_required: false,
+        })
+        .await
+}
+
+/// An address which doesn't collide with other tests or previous runs.
+pub fn unique_email() -> String {
+    format!("{}@example.com", unique_username())
+}
+
+pub fn key_ring() -> Arc<KeyRing> {
+    Arc::new(KeyRing::from_secret("testsecret"))
+}
+
+/// The state of the server with the login providers, mails go to an outbox.
+pub async fn app_state(oidc_providers: OidcProviders) -> anyhow::Result<State> {
+    let key_ring = key_ring();
+    let di_container = Arc::new(PgDIContainer {
+        db: get_db::<Postgres>().await?,
+    });
+    let token_service = TokenService::new(di_container.clone(), key_ring.clone());
+    let password_reset_service = PasswordResetService::new(
+        di_container.clone(),
+        EmailService::new(Box::new(OutboxMailer::default()), FRONTEND_URL.to_owned()),
+        password_hasher(),
+        password_policy(),
+    );
+    Ok(State {
+        key_ring: key_ring.clone(),
+        oidc_providers: Arc::new(oidc_providers),
+        user_service: UserService::new(
+            di_container.clone(),
+            key_ring.clone(),
+            EmailService::new(Box::new(OutboxMailer::default()), FRONTEND_URL.to_owned()),
+            password_hasher(),
+            password_policy(),
+            RegistrationMode::Open,
+        ),
+        todo_service: TodoService::new(di_container.clone()),
+        todo_list_service: TodoListService::new(di_container.clone()),
+        tag_service: TagService::new(di_container.clone()),
+        token_service: token_service.clone(),
+        personal_access_token_service: PersonalAccessTokenService::new(di_container.clone()),
+        identity_service: IdentityService::new(di_container.clone(), RegistrationMode::Open),
+        password_reset_service: password_reset_service.clone(),
+        magic_link_service: MagicLinkService::new(
+            di_container.clone(),
+            EmailService::new(Box::new(OutboxMailer::default()), FRONTEND_URL.to_owned()),
+        ),
+        two_factor_service: TwoFactorService::new(di_container.clone(), key_ring, token_service),
+        rate_limit_service: RateLimitService::new(Box::new(MemoryRateLimitStore::default())),
+        data_export_service: DataExportService::new(di_container.clone()),
+        admin_service: AdminService::new(di_container.clone(), password_reset_service),
+        invite_service: InviteService::new(di_container.clone()),
+        audit_logger: AuditLogger::new(di_container),
+    })
+}
@@ -1 +1,2 @@
+pub(crate) mod todo_list_member_repository;
 pub(crate) mod todo_repository;
@@ -0,0 +1,126 @@
+use crate::fixtures::{create_user, get_db};
+use rsapps_webserver::domains::entities::todo_list::TodoList;
+use rsapps_webserver::domains::entities::todo_list_member::{TodoListMember, TodoListRole};
+use rsapps_webserver::domains::repositories::todo_list_member_repository::TodoListMemberRepository;
+use rsapps_webserver::domains::repositories::todo_list_repository::TodoListRepository;
+use rsapps_webserver::infrastructures::repositories::todo_list_member_repository::PostgreSQLTodoListMemberRepository;
+use rsapps_webserver::infrastructures::repositories::todo_list_repository::PostgreSQLTodoListRepository;
+use sqlx::Postgres;
+
+fn new_list(user_id: i32) -> TodoList {
+    let now = chrono::Utc::now();
+    TodoList {
+        id: 0,
+        user_id,
+        name: "shared".to_owned(),
+        is_default: false,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+fn new_member(list: &TodoList, user_id: i32, role: TodoListRole) -> TodoListMember {
+    let now = chrono::Utc::now();
+    TodoListMember {
+        id: 0,
+        list_id: list.id,
+        user_id,
+        role: role.as_ref().to_owned(),
+        invited_by: Some(list.user_id),
+        accepted_at: None,
+        created_at: now,
+        updated_at: now,
+        username: String::new(),
+        list_name: String::new(),
+    }
+}
+
+#[sqlx_macros::test]
+async fn test_create_todo_list_adds_owner() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let list_repository = PostgreSQLTodoListRepository { db: db.clone() };
+    let repository = PostgreSQLTodoListMemberRepository { db };
+    let list = list_repository
+        .create_todo_list(new_list(owner.id))
+        .await
+        .unwrap()
+        .unwrap();
+
+    let member = repository
+        .get_todo_list_member(list.id, owner.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(TodoListRole::Owner, member.granted_role());
+    assert!(member.is_accepted());
+    assert_eq!(owner.username, member.username);
+    assert_eq!("shared", member.list_name);
+}
+
+#[sqlx_macros::test]
+async fn test_shared_lists_are_listed_once_accepted() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let invitee = create_user(&db).await.unwrap();
+    let list_repository = PostgreSQLTodoListRepository { db: db.clone() };
+    let repository = PostgreSQLTodoListMemberRepository { db };
+    let list = list_repository
+        .create_todo_list(new_list(owner.id))
+        .await
+        .unwrap()
+        .unwrap();
+
+    let member = new_member(&list, invitee.id, TodoListRole::Viewer);
+    assert!(repository
+        .create_todo_list_member(member.clone())
+        .await
+        .unwrap());
+    assert!(!repository.create_todo_list_member(member).await.unwrap());
+    assert_eq!(
+        1,
+        repository
+            .get_user_invitations(invitee.id)
+            .await
+            .unwrap()
+            .len()
+    );
+    assert!(list_repository
+        .get_user_todo_lists(invitee.id)
+        .await
+        .unwrap()
+        .is_empty());
+
+    assert!(repository
+        .accept_invitation(list.id, invitee.id, chrono::Utc::now())
+        .await
+        .unwrap());
+    assert!(!repository
+        .accept_invitation(list.id, invitee.id, chrono::Utc::now())
+        .await
+        .unwrap());
+    assert!(repository
+        .get_user_invitations(invitee.id)
+        .await
+        .unwrap()
+        .is_empty());
+    let shared = list_repository
+        .get_user_todo_lists(invitee.id)
+        .await
+        .unwrap();
+    assert_eq!(1, shared.len());
+    assert_eq!(list.id, shared[0].id);
+
+    let members = repository.get_todo_list_members(list.id).await.unwrap();
+    assert_eq!(2, members.len());
+    assert_eq!(owner.id, members[0].user_id);
+    assert!(repository
+        .delete_todo_list_member(list.id, invitee.id)
+        .await
+        .unwrap());
+    assert!(list_repository
+        .get_user_todo_lists(invitee.id)
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -1,11 +1,288 @@
-use crate::fixtures::get_db;
+use crate::fixtures::{create_user, get_db};
+use rsapps_webserver::domains::entities::todo::{Todo, TodoFilter, TodoOrder};
+use rsapps_webserver::domains::entities::todo_list::TodoList;
+use rsapps_webserver::domains::repositories::todo_list_repository::TodoListRepository;
 use rsapps_webserver::domains::repositories::todo_repository::TodoRepository;
+use rsapps_webserver::infrastructures::repositories::todo_list_repository::PostgreSQLTodoListRepository;
 use rsapps_webserver::infrastructures::repositories::todo_repository::PostgreSQLTodoRepository;
-use sqlx::Postgres;
+use sqlx::{Pool, Postgres};
+
+async fn create_list(db: &Pool<Postgres>, user_id: i32, is_default: bool) -> TodoList {
+    let now = chrono::Utc::now();
+    PostgreSQLTodoListRepository { db: db.clone() }
+        .create_todo_list(TodoList {
+            id: 0,
+            user_id,
+            name: "list".to_owned(),
+            is_default,
+            created_at: now,
+            updated_at: now,
+        })
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+fn new_todo(body: &str, complete: bool, list: &TodoList) -> Todo {
+    let now = chrono::Utc::now();
+    Todo {
+        id: 0,
+        body: body.to_owned(),
+        complete,
+        created_at: now,
+        updated_at: now,
+        list_id: list.id,
+        created_by: Some(list.user_id),
+        updated_by: Some(list.user_id),
+        start_at: None,
+        due_at: None,
+        series_id: None,
+        occurrence_at: None,
+        previous_id: None,
+    }
+}
+
+async fn list_bodies(
+    repository: &PostgreSQLTodoRepository,
+    list_id: i32,
+    filter: TodoFilter,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Vec<String> {
+    repository
+        .get_list_todos(list_id, &filter, now)
+        .await
+        .unwrap()
+        .iter()
+        .map(|todo| todo.body.to_owned())
+        .collect()
+}
 
 #[sqlx_macros::test]
 async fn test_get_all_todos() {
     let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let repository = PostgreSQLTodoRepository { db };
+    assert_eq!(0, repository.get_all_todos(user.id).await.unwrap().len());
+}
+
+#[sqlx_macros::test]
+async fn test_get_all_todos_is_scoped_to_creator() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let other = create_user(&db).await.unwrap();
+    let owner_list = create_list(&db, owner.id, true).await;
+    let repository = PostgreSQLTodoRepository { db };
+    let todo = repository
+        .create_todo(new_todo("owned", false, &owner_list))
+        .await
+        .unwrap();
+    assert_eq!(Some(owner.id), todo.created_by);
+    assert_eq!(Some(owner.id), todo.updated_by);
+
+    assert!(repository.get_todo_by_id(todo.id).await.unwrap().is_some());
+    assert_eq!(1, repository.get_all_todos(owner.id).await.unwrap().len());
+    assert_eq!(0, repository.get_all_todos(other.id).await.unwrap().len());
+}
+
+#[sqlx_macros::test]
+async fn test_update_todo_records_updated_by() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let editor = create_user(&db).await.unwrap();
+    let owner_list = create_list(&db, owner.id, true).await;
+    let repository = PostgreSQLTodoRepository { db };
+    let todo = repository
+        .create_todo(new_todo("owned", false, &owner_list))
+        .await
+        .unwrap();
+
+    let mut edited = todo.clone();
+    edited.body = "edited".to_owned();
+    edited.updated_by = Some(editor.id);
+    let updated = repository.update_todo(edited).await.unwrap().unwrap();
+    assert_eq!("edited", updated.body);
+    assert_eq!(Some(owner.id), updated.created_by);
+    assert_eq!(Some(editor.id), updated.updated_by);
+}
+
+#[sqlx_macros::test]
+async fn test_toggle_complete_records_updated_by() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let editor = create_user(&db).await.unwrap();
+    let owner_list = create_list(&db, owner.id, true).await;
+    let repository = PostgreSQLTodoRepository { db };
+    let todo = repository
+        .create_todo(new_todo("owned", false, &owner_list))
+        .await
+        .unwrap();
+
+    assert!(repository
+        .toggle_complete(todo.id, editor.id, chrono::Utc::now())
+        .await
+        .unwrap());
+    let fetched = repository.get_todo_by_id(todo.id).await.unwrap().unwrap();
+    assert!(fetched.complete);
+    assert_eq!(Some(editor.id), fetched.updated_by);
+    assert!(!repository
+        .toggle_complete(0, editor.id, chrono::Utc::now())
+        .await
+        .unwrap());
+}
+
+#[sqlx_macros::test]
+async fn test_toggle_all_complete_is_scoped_to_list() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let owner_list = create_list(&db, owner.id, true).await;
+    let other_list = create_list(&db, owner.id, false).await;
     let repository = PostgreSQLTodoRepository { db };
-    assert_eq!(0, repository.get_all_todos(1).await.unwrap().len());
+    repository
+        .create_todo(new_todo("owned", false, &owner_list))
+        .await
+        .unwrap();
+    repository
+        .create_todo(new_todo("other", false, &other_list))
+        .await
+        .unwrap();
+
+    repository
+        .toggle_all_complete(owner_list.id, owner.id, chrono::Utc::now())
+        .await
+        .unwrap();
+
+    let owned = repository
+        .get_list_todos(owner_list.id, &TodoFilter::default(), chrono::Utc::now())
+        .await
+        .unwrap();
+    assert!(owned.iter().all(|todo| todo.complete));
+    let others = repository
+        .get_list_todos(other_list.id, &TodoFilter::default(), chrono::Utc::now())
+        .await
+        .unwrap();
+    assert!(others.iter().all(|todo| !todo.complete));
+}
+
+#[sqlx_macros::test]
+async fn test_delete_todo() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let owner_list = create_list(&db, owner.id, true).await;
+    let repository = PostgreSQLTodoRepository { db };
+    let todo = repository
+        .create_todo(new_todo("owned", false, &owner_list))
+        .await
+        .unwrap();
+
+    assert!(repository.delete_todo(todo.id).await.unwrap());
+    assert!(!repository.delete_todo(todo.id).await.unwrap());
+    assert_eq!(0, repository.get_all_todos(owner.id).await.unwrap().len());
+}
+
+#[sqlx_macros::test]
+async fn test_delete_completed_todo_is_scoped_to_list() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let owner_list = create_list(&db, owner.id, true).await;
+    let other_list = create_list(&db, owner.id, false).await;
+    let repository = PostgreSQLTodoRepository { db };
+    repository
+        .create_todo(new_todo("owned", true, &owner_list))
+        .await
+        .unwrap();
+    repository
+        .create_todo(new_todo("other", true, &other_list))
+        .await
+        .unwrap();
+
+    repository
+        .delete_completed_todo(owner_list.id)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        0,
+        repository
+            .get_list_todos(owner_list.id, &TodoFilter::default(), chrono::Utc::now())
+            .await
+            .unwrap()
+            .len()
+    );
+    assert_eq!(
+        1,
+        repository
+            .get_list_todos(other_list.id, &TodoFilter::default(), chrono::Utc::now())
+            .await
+            .unwrap()
+            .len()
+    );
+}
+
+#[sqlx_macros::test]
+async fn test_get_list_todos_by_due_time() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let owner_list = create_list(&db, owner.id, true).await;
+    let repository = PostgreSQLTodoRepository { db };
+    let now = chrono::Utc::now();
+    for (body, complete, due_at) in &[
+        ("someday", false, None),
+        ("late", false, Some(now - chrono::Duration::days(1))),
+        ("done", true, Some(now - chrono::Duration::days(2))),
+        ("soon", false, Some(now + chrono::Duration::days(1))),
+    ] {
+        repository
+            .create_todo(Todo {
+                due_at: *due_at,
+                ..new_todo(body, *complete, &owner_list)
+            })
+            .await
+            .unwrap();
+    }
+    let get = |filter: TodoFilter| list_bodies(&repository, owner_list.id, filter, now);
+
+    assert_eq!(
+        vec!["someday", "late", "done", "soon"],
+        get(TodoFilter::default()).await
+    );
+    assert_eq!(
+        vec!["done", "late", "soon", "someday"],
+        get(TodoFilter {
+            order_by: TodoOrder::DueAt,
+            ..TodoFilter::default()
+        })
+        .await
+    );
+    assert_eq!(
+        vec!["late"],
+        get(TodoFilter {
+            overdue: Some(true),
+            ..TodoFilter::default()
+        })
+        .await
+    );
+    assert_eq!(
+        vec!["someday", "done", "soon"],
+        get(TodoFilter {
+            overdue: Some(false),
+            ..TodoFilter::default()
+        })
+        .await
+    );
+    assert_eq!(
+        vec!["late", "done"],
+        get(TodoFilter {
+            due_before: Some(now),
+            ..TodoFilter::default()
+        })
+        .await
+    );
+    assert_eq!(
+        vec!["late", "soon"],
+        get(TodoFilter {
+            due_after: Some(now - chrono::Duration::days(1)),
+            ..TodoFilter::default()
+        })
+        .await
+    );
 }
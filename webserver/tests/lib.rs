@@ -1,2 +1,5 @@
+mod auth;
 mod fixtures;
 mod infrastructures;
+mod mock_idp;
+mod services;
@@ -0,0 +1,177 @@
+use async_std::net::TcpListener;
+use ring::rand::SystemRandom;
+use ring::signature::{EcdsaKeyPair, ECDSA_P256_SHA256_FIXED_SIGNING};
+use rsapps_webserver::auth::generate_token;
+use rsapps_webserver::auth::key_ring::KeyRing;
+use rsapps_webserver::auth::oidc::pkce_challenge;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::sync::{Arc, Mutex};
+use tide::http::Url;
+use tide::{Body, Redirect, Request, Response, Server, StatusCode};
+
+/// A local OpenID provider which logs every visitor of `/authorize` in as `subject`.
+#[derive(Clone)]
+struct MockIdp {
+    issuer: String,
+    subject: String,
+    key_ring: Arc<KeyRing>,
+    codes: Arc<Mutex<HashMap<String, PendingCode>>>,
+}
+
+struct PendingCode {
+    client_id: String,
+    redirect_uri: String,
+    nonce: String,
+    code_challenge: String,
+}
+
+#[derive(Deserialize)]
+struct AuthorizeQuery {
+    client_id: String,
+    redirect_uri: String,
+    state: String,
+    nonce: String,
+    code_challenge: String,
+    code_challenge_method: String,
+}
+
+#[derive(Deserialize)]
+struct TokenForm {
+    code: String,
+    client_id: String,
+    redirect_uri: String,
+    code_verifier: String,
+}
+
+#[derive(Serialize)]
+struct IdTokenClaims {
+    iss: String,
+    sub: String,
+    aud: String,
+    exp: usize,
+    nonce: String,
+    email: String,
+}
+
+/// Start the provider on a free port and return its issuer URL.
+pub async fn start_mock_idp(subject: &str) -> anyhow::Result<String> {
+    let listener = TcpListener::bind("127.0.0.1:0").await?;
+    let issuer = format!("http://{}", listener.local_addr()?);
+    let mut app = Server::with_state(MockIdp {
+        issuer: issuer.to_owned(),
+        subject: subject.to_owned(),
+        key_ring: Arc::new(signing_key_ring()?),
+        codes: Arc::new(Mutex::new(HashMap::new())),
+    });
+    app.at("/.well-known/openid-configuration").get(discovery);
+    app.at("/jwks").get(jwks);
+    app.at("/authorize").get(authorize);
+    app.at("/token").post(token);
+    async_std::task::spawn(app.listen(listener));
+    Ok(issuer)
+}
+
+async fn discovery(request: Request<MockIdp>) -> tide::Result<Body> {
+    let issuer = &request.state().issuer;
+    Body::from_json(&json!({
+        "issuer": issuer,
+        "authorization_endpoint": format!("{}/authorize", issuer),
+        "token_endpoint": format!("{}/token", issuer),
+        "jwks_uri": format!("{}/jwks", issuer),
+    }))
+}
+
+async fn jwks(request: Request<MockIdp>) -> tide::Result<Body> {
+    Body::from_json(&request.state().key_ring.jwks())
+}
+
+async fn authorize(request: Request<MockIdp>) -> tide::Result<Response> {
+    let query: AuthorizeQuery = request.query()?;
+    if query.code_challenge_method != "S256" {
+        return Ok(Response::new(StatusCode::BadRequest));
+    }
+    let code = generate_token();
+    let mut redirect = Url::parse(&query.redirect_uri)?;
+    redirect
+        .query_pairs_mut()
+        .append_pair("code", &code)
+        .append_pair("state", &query.state);
+    request.state().codes.lock().unwrap().insert(
+        code,
+        PendingCode {
+            client_id: query.client_id,
+            redirect_uri: query.redirect_uri,
+            nonce: query.nonce,
+            code_challenge: query.code_challenge,
+        },
+    );
+    Ok(Redirect::new(redirect.as_str()).into())
+}
+
+async fn token(mut request: Request<MockIdp>) -> tide::Result<Response> {
+    let form: TokenForm = request.body_form().await?;
+    let state = request.state();
+    let pending = match state.codes.lock().unwrap().remove(&form.code) {
+        Some(pending) => pending,
+        None => return Ok(Response::new(StatusCode::BadRequest)),
+    };
+    if pending.client_id != form.client_id
+        || pending.redirect_uri != form.redirect_uri
+        || pending.code_challenge != pkce_challenge(&form.code_verifier)
+    {
+        return Ok(Response::new(StatusCode::BadRequest));
+    }
+    let id_token = state
+        .key_ring
+        .encode(&IdTokenClaims {
+            iss: state.issuer.to_owned(),
+            sub: state.subject.to_owned(),
+            aud: pending.client_id,
+            exp: (chrono::Utc::now() + chrono::Duration::minutes(5)).timestamp() as usize,
+            nonce: pending.nonce,
+            email: "mock.user@example.com".to_owned(),
+        })
+        .map_err(|err| tide::Error::from_str(StatusCode::InternalServerError, err.message))?;
+    Ok(Response::builder(StatusCode::Ok)
+        .body(Body::from_json(&json!({
+            "access_token": generate_token(),
+            "token_type": "Bearer",
+            "id_token": id_token,
+        }))?)
+        .build())
+}
+
+/// A fresh ES256 key, written to disk because the key ring loads private keys from files.
+fn signing_key_ring() -> anyhow::Result<KeyRing> {
+    let der = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_FIXED_SIGNING, &SystemRandom::new())
+        .map_err(|_| anyhow::anyhow!("failed to generate a key"))?;
+    let dir = std::env::temp_dir().join(format!("rsapps-mock-idp-{}", generate_token()));
+    fs::create_dir_all(&dir)?;
+    let key_file = dir.join("idp.pem");
+    fs::write(
+        &key_file,
+        pem::encode(&pem::Pem {
+            tag: "PRIVATE KEY".to_owned(),
+            contents: der.as_ref().to_vec(),
+        }),
+    )?;
+    let config_file = dir.join("keys.json");
+    fs::write(
+        &config_file,
+        json!({
+            "signing_key": "idp",
+            "keys": [{
+                "kid": "idp",
+                "algorithm": "ES256",
+                "private_key_file": key_file,
+            }],
+        })
+        .to_string(),
+    )?;
+    let key_ring = KeyRing::from_file(&config_file.to_string_lossy())?;
+    fs::remove_dir_all(&dir)?;
+    Ok(key_ring)
+}
@@ -0,0 +1,306 @@
+use crate::fixtures::{
+    create_user, create_user_with, get_db, password_hasher, password_policy, unique_email,
+};
+use rsapps_webserver::auth::key_ring::KeyRing;
+use rsapps_webserver::auth::Claims;
+use rsapps_webserver::domains::entities::audit_event::RequestMetadata;
+use rsapps_webserver::domains::entities::invite_code::RegistrationMode;
+use rsapps_webserver::domains::entities::user::{Role, User};
+use rsapps_webserver::domains::errors::ErrorCode;
+use rsapps_webserver::domains::password_hasher::PasswordHasher;
+use rsapps_webserver::domains::repositories::user_repository::UserRepository;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::infrastructures::mailers::outbox_mailer::OutboxMailer;
+use rsapps_webserver::infrastructures::repositories::user_repository::PostgreSQLUserRepository;
+use rsapps_webserver::services::admin_service::AdminService;
+use rsapps_webserver::services::email_service::EmailService;
+use rsapps_webserver::services::password_reset_service::PasswordResetService;
+use rsapps_webserver::services::token_service::TokenService;
+use rsapps_webserver::services::user_service::UserService;
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+
+struct Services {
+    admin_service: AdminService,
+    password_reset_service: PasswordResetService,
+    token_service: TokenService,
+    user_service: UserService,
+    outbox: OutboxMailer,
+}
+
+fn services(db: &Pool<Postgres>) -> Services {
+    let outbox = OutboxMailer::default();
+    let di_container = Arc::new(PgDIContainer { db: db.clone() });
+    let key_ring = Arc::new(KeyRing::from_secret("testsecret"));
+    let email_service =
+        EmailService::new(Box::new(outbox.clone()), "http://localhost:8080".to_owned());
+    let password_reset_service = PasswordResetService::new(
+        di_container.clone(),
+        email_service.clone(),
+        password_hasher(),
+        password_policy(),
+    );
+    Services {
+        admin_service: AdminService::new(di_container.clone(), password_reset_service.clone()),
+        password_reset_service,
+        token_service: TokenService::new(di_container.clone(), key_ring.clone()),
+        user_service: UserService::new(
+            di_container,
+            key_ring,
+            email_service,
+            password_hasher(),
+            password_policy(),
+            RegistrationMode::Open,
+        ),
+        outbox,
+    }
+}
+
+async fn create_user_with_password(db: &Pool<Postgres>, role: Role) -> User {
+    let user = create_user_with(
+        db,
+        Some(unique_email()),
+        true,
+        Some(password_hasher().hash("password").unwrap()),
+    )
+    .await
+    .unwrap();
+    PostgreSQLUserRepository { db: db.clone() }
+        .update_role(user.id, role.as_ref().to_owned(), chrono::Utc::now())
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+fn role_claim(access_token: &str) -> Role {
+    KeyRing::from_secret("testsecret")
+        .decode::<Claims>(access_token)
+        .unwrap()
+        .role
+}
+
+#[sqlx_macros::test]
+async fn test_get_users() {
+    let db = get_db::<Postgres>().await.unwrap();
+    create_user(&db).await.unwrap();
+    create_user(&db).await.unwrap();
+    let services = services(&db);
+
+    let page = services.admin_service.get_users(0, 1).await.unwrap();
+    assert_eq!(1, page.users.len());
+    assert!(page.total_count >= 2);
+    let next = services.admin_service.get_users(1, 1).await.unwrap();
+    assert!(page.users[0].id < next.users[0].id);
+
+    let err = services
+        .admin_service
+        .get_users(0, 1000)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+    let err = services
+        .admin_service
+        .get_users(-1, 10)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+}
+
+#[sqlx_macros::test]
+async fn test_suspend_user() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let admin = create_user_with_password(&db, Role::Admin).await;
+    let user = create_user_with_password(&db, Role::User).await;
+    let services = services(&db);
+    let session = services
+        .token_service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+
+    let err = services
+        .admin_service
+        .suspend_user(admin.id, admin.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+
+    let suspended = services
+        .admin_service
+        .suspend_user(admin.id, user.id)
+        .await
+        .unwrap();
+    assert!(suspended.is_suspended());
+    // The session ends at once and no new one can be started.
+    let err = services
+        .token_service
+        .refresh_tokens(session.refresh_token)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenRevoked));
+    let err = services
+        .token_service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::AccountSuspended));
+    let err = services
+        .user_service
+        .clone()
+        .get_user_by_username(user.username.to_owned(), "password".to_owned())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::AccountSuspended));
+    // A wrong password doesn't tell the account is suspended.
+    let err = services
+        .user_service
+        .clone()
+        .get_user_by_username(user.username.to_owned(), "wrong".to_owned())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::InvalidCredentials));
+
+    let unsuspended = services
+        .admin_service
+        .unsuspend_user(user.id)
+        .await
+        .unwrap();
+    assert!(!unsuspended.is_suspended());
+    services
+        .user_service
+        .clone()
+        .get_user_by_username(user.username, "password".to_owned())
+        .await
+        .unwrap();
+    services
+        .token_service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+}
+
+#[sqlx_macros::test]
+async fn test_force_password_reset() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user_with_password(&db, Role::User).await;
+    let services = services(&db);
+    let session = services
+        .token_service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+
+    let updated = services
+        .admin_service
+        .force_password_reset(user.id)
+        .await
+        .unwrap();
+    assert!(updated.password_reset_required);
+    let err = services
+        .token_service
+        .refresh_tokens(session.refresh_token)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenRevoked));
+    let err = services
+        .user_service
+        .clone()
+        .get_user_by_username(user.username.to_owned(), "password".to_owned())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::PasswordResetRequired));
+
+    let token = {
+        let mails = services.outbox.mails.lock().unwrap();
+        let mail = mails.last().unwrap();
+        assert_eq!(user.email.to_owned().unwrap(), mail.to);
+        mail.body
+            .split("reset_password=")
+            .nth(1)
+            .unwrap()
+            .trim()
+            .to_owned()
+    };
+    services
+        .password_reset_service
+        .reset_password(token, "amber kettle 92 drift".to_owned())
+        .await
+        .unwrap();
+    let user = services
+        .user_service
+        .clone()
+        .get_user_by_username(user.username, "amber kettle 92 drift".to_owned())
+        .await
+        .unwrap();
+    assert!(!user.password_reset_required);
+
+    // Users without a password or an email can't be made to reset it.
+    let without_email = create_user(&db).await.unwrap();
+    let err = services
+        .admin_service
+        .force_password_reset(without_email.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+}
+
+#[sqlx_macros::test]
+async fn test_set_role() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let admin = create_user_with_password(&db, Role::Admin).await;
+    let user = create_user_with_password(&db, Role::User).await;
+    let services = services(&db);
+    let session = services
+        .token_service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+    assert_eq!(Role::User, role_claim(&session.access_token));
+
+    let err = services
+        .admin_service
+        .set_role(Some(admin.id), admin.id, Role::User)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+
+    let promoted = services
+        .admin_service
+        .set_role(Some(admin.id), user.id, Role::Admin)
+        .await
+        .unwrap();
+    assert_eq!(Role::Admin, promoted.granted_role());
+    // Tokens of the old role are revoked, new ones carry the new role.
+    let err = services
+        .token_service
+        .refresh_tokens(session.refresh_token)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenRevoked));
+    let session = services
+        .token_service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+    assert_eq!(Role::Admin, role_claim(&session.access_token));
+
+    let err = services
+        .admin_service
+        .set_role(None, 0, Role::Admin)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+}
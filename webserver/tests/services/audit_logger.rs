@@ -0,0 +1,138 @@
+use crate::fixtures::{create_user, get_db};
+use rsapps_webserver::domains::entities::audit_event::{
+    AuditEvent, AuditEventFilter, AuditEventType, RequestMetadata,
+};
+use rsapps_webserver::domains::errors::ErrorCode;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::services::audit_logger::AuditLogger;
+use sqlx::Postgres;
+use std::sync::Arc;
+
+#[sqlx_macros::test]
+async fn test_record_and_get_audit_events() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let admin = create_user(&db).await.unwrap();
+    let logger = AuditLogger::new(Arc::new(PgDIContainer { db }));
+    let metadata = RequestMetadata {
+        ip_address: Some("192.0.2.10".to_owned()),
+        user_agent: Some("test agent".to_owned()),
+        request_id: Some("request-1".to_owned()),
+    };
+
+    logger
+        .record(AuditEvent {
+            user_id: Some(user.id),
+            account: Some(user.username.to_owned()),
+            detail: Some("unauthenticated".to_owned()),
+            ..AuditEvent::new(AuditEventType::LoginFailed, &metadata)
+        })
+        .await;
+    logger
+        .record(AuditEvent {
+            user_id: Some(user.id),
+            detail: Some("password".to_owned()),
+            ..AuditEvent::new(AuditEventType::LoginSucceeded, &metadata)
+        })
+        .await;
+    logger
+        .record(AuditEvent {
+            user_id: Some(user.id),
+            actor_id: Some(admin.id),
+            detail: Some("suspendUser".to_owned()),
+            ..AuditEvent::new(AuditEventType::AdminAction, &RequestMetadata::default())
+        })
+        .await;
+
+    // The latest first, with the request the event came with.
+    let filter = AuditEventFilter {
+        user_id: Some(user.id),
+        ..AuditEventFilter::default()
+    };
+    let page = logger
+        .get_audit_events(filter.to_owned(), 0, 10)
+        .await
+        .unwrap();
+    assert_eq!(3, page.total_count);
+    let event_types: Vec<Option<AuditEventType>> = page
+        .audit_events
+        .iter()
+        .map(|audit_event| audit_event.known_event_type())
+        .collect();
+    assert_eq!(
+        vec![
+            Some(AuditEventType::AdminAction),
+            Some(AuditEventType::LoginSucceeded),
+            Some(AuditEventType::LoginFailed),
+        ],
+        event_types
+    );
+    let login = &page.audit_events[1];
+    assert_eq!(Some("192.0.2.10".to_owned()), login.ip_address);
+    assert_eq!(Some("test agent".to_owned()), login.user_agent);
+    assert_eq!(Some("request-1".to_owned()), login.request_id);
+
+    // Paged, and filtered by the type or by the admin who caused it.
+    let page = logger.get_audit_events(filter, 1, 1).await.unwrap();
+    assert_eq!(3, page.total_count);
+    assert_eq!(1, page.audit_events.len());
+    assert_eq!(
+        Some(AuditEventType::LoginSucceeded),
+        page.audit_events[0].known_event_type()
+    );
+    let page = logger
+        .get_audit_events(
+            AuditEventFilter {
+                event_type: Some(AuditEventType::LoginFailed),
+                user_id: Some(user.id),
+                ..AuditEventFilter::default()
+            },
+            0,
+            10,
+        )
+        .await
+        .unwrap();
+    assert_eq!(1, page.total_count);
+    let page = logger
+        .get_audit_events(
+            AuditEventFilter {
+                user_id: Some(admin.id),
+                since: Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+                ..AuditEventFilter::default()
+            },
+            0,
+            10,
+        )
+        .await
+        .unwrap();
+    assert_eq!(1, page.total_count);
+    assert_eq!(Some(admin.id), page.audit_events[0].actor_id);
+    let page = logger
+        .get_audit_events(
+            AuditEventFilter {
+                user_id: Some(user.id),
+                until: Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+                ..AuditEventFilter::default()
+            },
+            0,
+            10,
+        )
+        .await
+        .unwrap();
+    assert_eq!(0, page.total_count);
+}
+
+#[sqlx_macros::test]
+async fn test_get_audit_events_with_bad_page() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let logger = AuditLogger::new(Arc::new(PgDIContainer { db }));
+
+    for &(offset, limit) in &[(-1, 10), (0, 0), (0, 101)] {
+        let err = logger
+            .get_audit_events(AuditEventFilter::default(), offset, limit)
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err.code, ErrorCode::BadRequest));
+    }
+}
@@ -0,0 +1,143 @@
+use crate::fixtures::{create_user, get_db};
+use rsapps_webserver::auth::key_ring::KeyRing;
+use rsapps_webserver::domains::entities::audit_event::{
+    AuditEvent, AuditEventType, RequestMetadata,
+};
+use rsapps_webserver::domains::entities::personal_access_token::Scope;
+use rsapps_webserver::domains::errors::ErrorCode;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::services::audit_logger::AuditLogger;
+use rsapps_webserver::services::data_export_service::{
+    DataExportService, EXPORT_FORMAT, EXPORT_VERSION, MANIFEST_FILE,
+};
+use rsapps_webserver::services::personal_access_token_service::PersonalAccessTokenService;
+use rsapps_webserver::services::tag_service::TagService;
+use rsapps_webserver::services::todo_service::TodoService;
+use rsapps_webserver::services::token_service::TokenService;
+use serde_json::Value;
+use sqlx::Postgres;
+use std::io::{Cursor, Read};
+use std::sync::Arc;
+use zip::ZipArchive;
+
+fn read_json(archive: &mut ZipArchive<Cursor<Vec<u8>>>, name: &str) -> (String, Value) {
+    let mut raw = String::new();
+    archive
+        .by_name(name)
+        .unwrap()
+        .read_to_string(&mut raw)
+        .unwrap();
+    let value = serde_json::from_str(&raw).unwrap();
+    (raw, value)
+}
+
+#[sqlx_macros::test]
+async fn test_export_user() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let other = create_user(&db).await.unwrap();
+    let di_container = Arc::new(PgDIContainer { db });
+    let todo_service = TodoService::new(di_container.clone());
+    let todo = todo_service
+        .create_todo("mine".to_owned(), user.id, None, None, None)
+        .await
+        .unwrap();
+    let tag = TagService::new(di_container.clone())
+        .create_tag(user.id, "home".to_owned(), None)
+        .await
+        .unwrap();
+    todo_service
+        .add_tag(todo.id, user.id, tag.id)
+        .await
+        .unwrap();
+    todo_service
+        .create_todo("not mine".to_owned(), other.id, None, None, None)
+        .await
+        .unwrap();
+    let metadata = RequestMetadata {
+        ip_address: Some("192.0.2.1".to_owned()),
+        user_agent: Some("Mozilla/5.0 (X11; Linux x86_64) Firefox/85.0".to_owned()),
+        request_id: None,
+    };
+    let session = TokenService::new(
+        di_container.clone(),
+        Arc::new(KeyRing::from_secret("testsecret")),
+    )
+    .issue_tokens(user.id, &metadata)
+    .await
+    .unwrap();
+    AuditLogger::new(di_container.clone())
+        .record(AuditEvent {
+            user_id: Some(user.id),
+            ..AuditEvent::new(AuditEventType::LoginSucceeded, &metadata)
+        })
+        .await;
+    let created = PersonalAccessTokenService::new(di_container.clone())
+        .create_personal_access_token(user.id, "script".to_owned(), vec![Scope::Profile], None)
+        .await
+        .unwrap();
+
+    let export = DataExportService::new(di_container)
+        .export_user(user.id)
+        .await
+        .unwrap();
+    assert!(export.file_name.ends_with(".zip"));
+    let mut archive = ZipArchive::new(Cursor::new(export.content)).unwrap();
+
+    let (_, manifest) = read_json(&mut archive, MANIFEST_FILE);
+    assert_eq!(EXPORT_FORMAT, manifest["format"]);
+    assert_eq!(EXPORT_VERSION, manifest["version"]);
+    assert_eq!(user.id, manifest["user_id"]);
+    let files = manifest["files"].as_array().unwrap();
+    assert_eq!(archive.len(), files.len() + 1);
+    for file in files {
+        let name = file["name"].as_str().unwrap();
+        let (raw, content) = read_json(&mut archive, name);
+        let records = content.as_array().map_or(1, |records| records.len());
+        assert_eq!(records, file["records"]);
+        // No secret leaves the server, not even hashed.
+        assert!(!raw.contains("hash"));
+        assert!(!raw.contains("secret"));
+        assert!(!raw.contains(&session.refresh_token));
+        assert!(!raw.contains(&created.token));
+    }
+
+    let (_, exported_user) = read_json(&mut archive, "user.json");
+    assert_eq!(user.username, exported_user["username"]);
+    assert_eq!(false, exported_user["has_password"]);
+    assert_eq!("user", exported_user["role"]);
+    assert_eq!(Value::Null, exported_user["suspended_at"]);
+    assert_eq!(false, exported_user["password_reset_required"]);
+    let (_, todo_lists) = read_json(&mut archive, "todo_lists.json");
+    assert_eq!(1, todo_lists.as_array().unwrap().len());
+    let (_, todos) = read_json(&mut archive, "todos.json");
+    assert_eq!(1, todos.as_array().unwrap().len());
+    assert_eq!("mine", todos[0]["body"]);
+    assert_eq!(todo_lists[0]["id"], todos[0]["list_id"]);
+    let (_, tags) = read_json(&mut archive, "tags.json");
+    assert_eq!("home", tags[0]["name"]);
+    assert_eq!(todos[0]["id"], tags[0]["todo_ids"][0]);
+    let (_, sessions) = read_json(&mut archive, "sessions.json");
+    assert_eq!(1, sessions.as_array().unwrap().len());
+    assert_eq!("192.0.2.1", sessions[0]["ip_address"]);
+    assert_eq!("Firefox on Linux", sessions[0]["device_name"]);
+    let (_, refresh_tokens) = read_json(&mut archive, "refresh_tokens.json");
+    assert_eq!(1, refresh_tokens.as_array().unwrap().len());
+    assert_eq!(sessions[0]["family"], refresh_tokens[0]["family"]);
+    let (_, audit_events) = read_json(&mut archive, "audit_events.json");
+    assert_eq!(1, audit_events.as_array().unwrap().len());
+    assert_eq!("login_succeeded", audit_events[0]["event_type"]);
+    assert_eq!("192.0.2.1", audit_events[0]["ip_address"]);
+    assert_eq!(metadata.user_agent.unwrap(), audit_events[0]["user_agent"]);
+    let (_, tokens) = read_json(&mut archive, "personal_access_tokens.json");
+    assert_eq!("script", tokens[0]["name"]);
+}
+
+#[sqlx_macros::test]
+async fn test_export_unknown_user() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = DataExportService::new(Arc::new(PgDIContainer { db }));
+    let err = service.export_user(user.id + 1000000).await.err().unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+}
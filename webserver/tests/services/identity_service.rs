@@ -0,0 +1,72 @@
+use crate::fixtures::get_db;
+use rsapps_webserver::auth::generate_token;
+use rsapps_webserver::domains::entities::invite_code::RegistrationMode;
+use rsapps_webserver::domains::errors::ErrorCode;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::services::identity_service::IdentityService;
+use sqlx::Postgres;
+use std::sync::Arc;
+
+#[sqlx_macros::test]
+async fn test_sign_in_links_subject_to_user() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let service = IdentityService::new(Arc::new(PgDIContainer { db }), RegistrationMode::Open);
+    let subject = generate_token();
+
+    let user = service
+        .sign_in(
+            "mock".to_owned(),
+            subject.to_owned(),
+            Some("jane.doe@example.com".to_owned()),
+            Some("Jane.Doe@example.com".to_owned()),
+        )
+        .await
+        .unwrap();
+    assert!(user.username.starts_with("janedoe"));
+    assert_eq!(None, user.email);
+    assert_eq!(None, user.password_hash);
+
+    let again = service
+        .sign_in("mock".to_owned(), subject.to_owned(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(user.id, again.id);
+
+    // The same subject at another provider is another person.
+    let other = service
+        .sign_in(
+            "other".to_owned(),
+            subject,
+            None,
+            Some("Jane.Doe@example.com".to_owned()),
+        )
+        .await
+        .unwrap();
+    assert_ne!(user.id, other.id);
+    assert_ne!(user.username, other.username);
+}
+
+#[sqlx_macros::test]
+async fn test_sign_in_needs_open_registration_for_new_users() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let di_container = Arc::new(PgDIContainer { db });
+    let subject = generate_token();
+    let user = IdentityService::new(di_container.clone(), RegistrationMode::Open)
+        .sign_in("mock".to_owned(), subject.to_owned(), None, None)
+        .await
+        .unwrap();
+    let service = IdentityService::new(di_container, RegistrationMode::InviteOnly);
+
+    let again = service
+        .sign_in("mock".to_owned(), subject, None, None)
+        .await
+        .unwrap();
+    assert_eq!(user.id, again.id);
+
+    let err = service
+        .sign_in("mock".to_owned(), generate_token(), None, None)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::Forbidden));
+}
@@ -0,0 +1,62 @@
+use crate::fixtures::{create_user, get_db};
+use rsapps_webserver::domains::errors::ErrorCode;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::services::invite_service::InviteService;
+use sqlx::Postgres;
+use std::sync::Arc;
+
+#[sqlx_macros::test]
+async fn test_create_invite_code() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let admin = create_user(&db).await.unwrap();
+    let service = InviteService::new(Arc::new(PgDIContainer { db }));
+    let expires_at = chrono::Utc::now() + chrono::Duration::days(7);
+
+    let created = service
+        .create_invite_code(Some(admin.id), 3, Some(expires_at))
+        .await
+        .unwrap();
+    assert_eq!(Some(admin.id), created.invite_code.created_by);
+    assert_eq!(3, created.invite_code.max_uses);
+    assert_eq!(0, created.invite_code.use_count);
+    assert_ne!(created.code, created.invite_code.code_hash);
+
+    let invite_codes = service.get_invite_codes().await.unwrap();
+    assert!(invite_codes
+        .iter()
+        .any(|invite_code| invite_code.id == created.invite_code.id));
+
+    let err = service
+        .create_invite_code(Some(admin.id), 0, None)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+    let err = service
+        .create_invite_code(
+            Some(admin.id),
+            1,
+            Some(chrono::Utc::now() - chrono::Duration::minutes(1)),
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+}
+
+#[sqlx_macros::test]
+async fn test_revoke_invite_code() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let service = InviteService::new(Arc::new(PgDIContainer { db }));
+    let created = service.create_invite_code(None, 1, None).await.unwrap();
+    let id = created.invite_code.id;
+
+    assert!(service.revoke_invite_code(id).await.unwrap());
+    assert!(!service.revoke_invite_code(id).await.unwrap());
+    let invite_codes = service.get_invite_codes().await.unwrap();
+    let revoked = invite_codes
+        .iter()
+        .find(|invite_code| invite_code.id == id)
+        .unwrap();
+    assert!(revoked.revoked_at.is_some());
+}
@@ -0,0 +1,108 @@
+use crate::fixtures::{create_user_with, get_db, password_hasher, password_policy, unique_email};
+use rsapps_webserver::domains::errors::ErrorCode;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::infrastructures::mailers::outbox_mailer::OutboxMailer;
+use rsapps_webserver::services::email_service::EmailService;
+use rsapps_webserver::services::magic_link_service::MagicLinkService;
+use rsapps_webserver::services::password_reset_service::PasswordResetService;
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+
+fn link_token(outbox: &OutboxMailer, parameter: &str) -> String {
+    let mails = outbox.mails.lock().unwrap();
+    mails
+        .last()
+        .unwrap()
+        .body
+        .split(&format!("{}=", parameter))
+        .nth(1)
+        .unwrap()
+        .trim()
+        .to_owned()
+}
+
+fn magic_link_service(db: &Pool<Postgres>, outbox: &OutboxMailer) -> MagicLinkService {
+    MagicLinkService::new(
+        Arc::new(PgDIContainer { db: db.clone() }),
+        EmailService::new(Box::new(outbox.clone()), "http://localhost:8080".to_owned()),
+    )
+}
+
+#[sqlx_macros::test]
+async fn test_redeem_magic_link() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user_with(&db, Some(unique_email()), true, None)
+        .await
+        .unwrap();
+    let outbox = OutboxMailer::default();
+    let service = magic_link_service(&db, &outbox);
+
+    service
+        .request_magic_link(user.email.to_owned().unwrap().to_uppercase())
+        .await
+        .unwrap();
+    let first = link_token(&outbox, "magic_link");
+    service
+        .request_magic_link(user.email.to_owned().unwrap())
+        .await
+        .unwrap();
+    let second = link_token(&outbox, "magic_link");
+    assert_ne!(first, second);
+
+    let logged_in = service.redeem_magic_link(second.to_owned()).await.unwrap();
+    assert_eq!(user.id, logged_in.id);
+
+    // A link works once, and redeeming one voids the others.
+    for token in &[second, first] {
+        let err = service
+            .redeem_magic_link(token.to_owned())
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err.code, ErrorCode::UnAuthenticated));
+    }
+}
+
+#[sqlx_macros::test]
+async fn test_request_magic_link_for_unverified_or_unknown_email() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user_with(&db, Some(unique_email()), false, None)
+        .await
+        .unwrap();
+    let outbox = OutboxMailer::default();
+    let service = magic_link_service(&db, &outbox);
+
+    service
+        .request_magic_link(user.email.unwrap())
+        .await
+        .unwrap();
+    service.request_magic_link(unique_email()).await.unwrap();
+    assert!(outbox.mails.lock().unwrap().is_empty());
+}
+
+#[sqlx_macros::test]
+async fn test_password_reset_link_is_not_a_magic_link() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user_with(&db, Some(unique_email()), true, None)
+        .await
+        .unwrap();
+    let outbox = OutboxMailer::default();
+    let password_reset_service = PasswordResetService::new(
+        Arc::new(PgDIContainer { db: db.clone() }),
+        EmailService::new(Box::new(outbox.clone()), "http://localhost:8080".to_owned()),
+        password_hasher(),
+        password_policy(),
+    );
+    password_reset_service
+        .request_password_reset(user.email.unwrap())
+        .await
+        .unwrap();
+    let token = link_token(&outbox, "reset_password");
+
+    let err = magic_link_service(&db, &outbox)
+        .redeem_magic_link(token)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::UnAuthenticated));
+}
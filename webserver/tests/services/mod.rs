@@ -0,0 +1,15 @@
+pub(crate) mod admin_service;
+pub(crate) mod audit_logger;
+pub(crate) mod data_export_service;
+pub(crate) mod identity_service;
+pub(crate) mod invite_service;
+pub(crate) mod magic_link_service;
+pub(crate) mod password_reset_service;
+pub(crate) mod personal_access_token_service;
+pub(crate) mod rate_limit_service;
+pub(crate) mod tag_service;
+pub(crate) mod todo_list_service;
+pub(crate) mod todo_service;
+pub(crate) mod token_service;
+pub(crate) mod two_factor_service;
+pub(crate) mod user_service;
@@ -0,0 +1,157 @@
+use crate::fixtures::{create_user_with, get_db, password_hasher, password_policy, unique_email};
+use rsapps_webserver::auth::key_ring::KeyRing;
+use rsapps_webserver::domains::entities::audit_event::RequestMetadata;
+use rsapps_webserver::domains::errors::ErrorCode;
+use rsapps_webserver::domains::repositories::user_repository::UserRepository;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::infrastructures::mailers::outbox_mailer::OutboxMailer;
+use rsapps_webserver::infrastructures::repositories::user_repository::PostgreSQLUserRepository;
+use rsapps_webserver::services::email_service::EmailService;
+use rsapps_webserver::services::password_reset_service::PasswordResetService;
+use rsapps_webserver::services::token_service::TokenService;
+use sqlx::Postgres;
+use std::sync::Arc;
+
+fn reset_token(outbox: &OutboxMailer) -> String {
+    let mails = outbox.mails.lock().unwrap();
+    mails
+        .last()
+        .unwrap()
+        .body
+        .split("reset_password=")
+        .nth(1)
+        .unwrap()
+        .trim()
+        .to_owned()
+}
+
+#[sqlx_macros::test]
+async fn test_reset_password() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user_with(
+        &db,
+        Some(unique_email()),
+        true,
+        Some(bcrypt::hash("password", 4).unwrap()),
+    )
+    .await
+    .unwrap();
+    let outbox = OutboxMailer::default();
+    let di_container = Arc::new(PgDIContainer { db: db.clone() });
+    let service = PasswordResetService::new(
+        di_container.clone(),
+        EmailService::new(Box::new(outbox.clone()), "http://localhost:8080".to_owned()),
+        password_hasher(),
+        password_policy(),
+    );
+    let token_service =
+        TokenService::new(di_container, Arc::new(KeyRing::from_secret("testsecret")));
+    let session = token_service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+
+    service
+        .request_password_reset(user.email.to_owned().unwrap().to_uppercase())
+        .await
+        .unwrap();
+    let token = reset_token(&outbox);
+    // A weak password is rejected, and leaves the link working.
+    let err = service
+        .reset_password(token.to_owned(), "password".to_owned())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::ValidationFailed(_)));
+    let user_id = service
+        .reset_password(token.to_owned(), "amber kettle 92 drift".to_owned())
+        .await
+        .unwrap();
+    assert_eq!(user.id, user_id);
+
+    let updated = PostgreSQLUserRepository { db }
+        .get_user_by_id(user.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(updated.valid_password(password_hasher().as_ref(), "amber kettle 92 drift"));
+    assert!(!updated.valid_password(password_hasher().as_ref(), "password"));
+
+    // Existing sessions are logged out and the link works only once.
+    let err = token_service
+        .refresh_tokens(session.refresh_token)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenRevoked));
+    let err = service
+        .reset_password(token, "silver otter 15 canyon".to_owned())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::UnAuthenticated));
+}
+
+#[sqlx_macros::test]
+async fn test_reset_password_voids_other_links() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user_with(
+        &db,
+        Some(unique_email()),
+        true,
+        Some(bcrypt::hash("password", 4).unwrap()),
+    )
+    .await
+    .unwrap();
+    let outbox = OutboxMailer::default();
+    let service = PasswordResetService::new(
+        Arc::new(PgDIContainer { db }),
+        EmailService::new(Box::new(outbox.clone()), "http://localhost:8080".to_owned()),
+        password_hasher(),
+        password_policy(),
+    );
+    let email = user.email.unwrap();
+    service
+        .request_password_reset(email.to_owned())
+        .await
+        .unwrap();
+    let first = reset_token(&outbox);
+    service.request_password_reset(email).await.unwrap();
+    let second = reset_token(&outbox);
+    assert_ne!(first, second);
+
+    service
+        .reset_password(second, "amber kettle 92 drift".to_owned())
+        .await
+        .unwrap();
+    let err = service
+        .reset_password(first, "silver otter 15 canyon".to_owned())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::UnAuthenticated));
+}
+
+#[sqlx_macros::test]
+async fn test_request_password_reset_for_unknown_email() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let outbox = OutboxMailer::default();
+    let service = PasswordResetService::new(
+        Arc::new(PgDIContainer { db }),
+        EmailService::new(Box::new(outbox.clone()), "http://localhost:8080".to_owned()),
+        password_hasher(),
+        password_policy(),
+    );
+    service
+        .request_password_reset(unique_email())
+        .await
+        .unwrap();
+    assert!(outbox.mails.lock().unwrap().is_empty());
+
+    let err = service
+        .reset_password("unknown".to_owned(), "amber kettle 92 drift".to_owned())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::UnAuthenticated));
+}
@@ -0,0 +1,97 @@
+use crate::fixtures::{create_user, get_db};
+use rsapps_webserver::domains::entities::personal_access_token::Scope;
+use rsapps_webserver::domains::errors::ErrorCode;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::services::personal_access_token_service::PersonalAccessTokenService;
+use sqlx::Postgres;
+use std::sync::Arc;
+
+#[sqlx_macros::test]
+async fn test_authenticate_personal_access_token() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = PersonalAccessTokenService::new(Arc::new(PgDIContainer { db }));
+    let created = service
+        .create_personal_access_token(user.id, "script".to_owned(), vec![Scope::TodosRead], None)
+        .await
+        .unwrap();
+
+    let principal = service.authenticate(&created.token).await.unwrap();
+    assert_eq!(user.id, principal.user_id);
+    assert!(principal.has_scope(Scope::TodosRead));
+    assert!(!principal.has_scope(Scope::TodosWrite));
+    assert!(!principal.is_session());
+
+    let tokens = service.get_personal_access_tokens(user.id).await.unwrap();
+    assert_eq!(1, tokens.len());
+    assert_eq!("script", tokens[0].name);
+    assert_ne!(created.token, tokens[0].token_hash);
+}
+
+#[sqlx_macros::test]
+async fn test_revoke_personal_access_token() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let other = create_user(&db).await.unwrap();
+    let service = PersonalAccessTokenService::new(Arc::new(PgDIContainer { db }));
+    let created = service
+        .create_personal_access_token(user.id, "script".to_owned(), vec![Scope::Profile], None)
+        .await
+        .unwrap();
+    let id = created.personal_access_token.id;
+
+    let err = service
+        .revoke_personal_access_token(id, other.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+    assert!(service
+        .revoke_personal_access_token(id, user.id)
+        .await
+        .unwrap());
+
+    let err = service.authenticate(&created.token).await.err().unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenRevoked));
+    assert_eq!(
+        0,
+        service
+            .get_personal_access_tokens(user.id)
+            .await
+            .unwrap()
+            .len()
+    );
+}
+
+#[sqlx_macros::test]
+async fn test_expired_personal_access_token() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = PersonalAccessTokenService::new(Arc::new(PgDIContainer { db }));
+    let created = service
+        .create_personal_access_token(
+            user.id,
+            "script".to_owned(),
+            vec![Scope::TodosWrite],
+            Some(chrono::Utc::now() - chrono::Duration::days(1)),
+        )
+        .await
+        .unwrap();
+
+    let err = service.authenticate(&created.token).await.err().unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenExpired));
+}
+
+#[sqlx_macros::test]
+async fn test_personal_access_token_needs_scope() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = PersonalAccessTokenService::new(Arc::new(PgDIContainer { db }));
+
+    let err = service
+        .create_personal_access_token(user.id, "script".to_owned(), vec![], None)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+}
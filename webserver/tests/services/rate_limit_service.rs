@@ -0,0 +1,124 @@
+use crate::fixtures::{unique_email, unique_username};
+use rsapps_webserver::infrastructures::rate_limit_stores::memory_rate_limit_store::MemoryRateLimitStore;
+use rsapps_webserver::services::rate_limit_service::RateLimitService;
+
+fn rate_limit_service() -> RateLimitService {
+    RateLimitService::new(Box::new(MemoryRateLimitStore::default()))
+}
+
+#[async_std::test]
+async fn test_lockout_after_failures() {
+    let service = rate_limit_service();
+    let account = unique_username();
+    for _ in 0..5 {
+        assert!(service
+            .check_login(Some("192.0.2.1"), &account, None)
+            .await
+            .is_none());
+        service.record_login_failure(&account, None).await;
+    }
+    // Locked out whichever client tries, and case doesn't matter.
+    let wait = service
+        .check_login(Some("192.0.2.2"), &account.to_uppercase(), None)
+        .await
+        .unwrap();
+    assert!(wait.as_secs() > 60);
+    assert!(service
+        .check_login(Some("192.0.2.1"), &unique_username(), None)
+        .await
+        .is_none());
+}
+
+#[async_std::test]
+async fn test_lockout_is_shared_by_username_and_email() {
+    let service = rate_limit_service();
+    let username = unique_username();
+    let email = format!("{}@example.com", username);
+    // Alternating the identifiers doesn't double the attempts of the user.
+    for account in [&username, &email, &username, &email, &username].iter() {
+        assert!(service
+            .check_login(Some("192.0.2.1"), account, Some(1))
+            .await
+            .is_none());
+        service.record_login_failure(account, Some(1)).await;
+    }
+    assert!(service
+        .check_login(Some("192.0.2.1"), &email, Some(1))
+        .await
+        .is_some());
+    assert!(service
+        .check_login(Some("192.0.2.1"), &username, Some(1))
+        .await
+        .is_some());
+    // The same text which doesn't belong to the user has its own bucket.
+    assert!(service
+        .check_login(Some("192.0.2.1"), &email, None)
+        .await
+        .is_none());
+}
+
+#[async_std::test]
+async fn test_success_clears_failures() {
+    let service = rate_limit_service();
+    let account = unique_username();
+    for _ in 0..4 {
+        service.record_login_failure(&account, None).await;
+    }
+    service.record_login_success(&account, None).await;
+    for _ in 0..4 {
+        service.record_login_failure(&account, None).await;
+    }
+    assert!(service.check_login(None, &account, None).await.is_none());
+}
+
+#[async_std::test]
+async fn test_throttle_attempts() {
+    let service = rate_limit_service();
+    // An account can't be tried from many clients faster than its quota.
+    let account = unique_username();
+    for i in 0..10 {
+        assert!(service
+            .check_login(Some(&format!("192.0.2.{}", i)), &account, None)
+            .await
+            .is_none());
+    }
+    assert!(service
+        .check_login(Some("192.0.2.100"), &account, None)
+        .await
+        .is_some());
+
+    // A client can't try many accounts faster than its quota.
+    for _ in 0..20 {
+        assert!(service
+            .check_login(Some("198.51.100.1"), &unique_username(), None)
+            .await
+            .is_none());
+    }
+    assert!(service
+        .check_login(Some("198.51.100.1"), &unique_username(), None)
+        .await
+        .is_some());
+
+    for _ in 0..100 {
+        assert!(service.check_request("203.0.113.1").await.is_none());
+    }
+    assert!(service.check_request("203.0.113.1").await.is_some());
+}
+
+#[async_std::test]
+async fn test_throttle_emails() {
+    let service = rate_limit_service();
+    let email = unique_email();
+    for _ in 0..3 {
+        assert!(service
+            .check_email(Some("192.0.2.1"), &email)
+            .await
+            .is_none());
+    }
+    // The address is throttled whichever client asks, and case doesn't matter.
+    assert!(service
+        .check_email(Some("192.0.2.2"), &email.to_uppercase())
+        .await
+        .is_some());
+    assert!(service.check_email(None, &unique_email()).await.is_none());
+}
@@ -0,0 +1,319 @@
+use crate::fixtures::{create_user, get_db};
+use rsapps_webserver::domains::entities::tag::{TagFilter, TagMatch};
+use rsapps_webserver::domains::entities::todo::TodoFilter;
+use rsapps_webserver::domains::entities::todo_list_member::TodoListRole;
+use rsapps_webserver::domains::errors::ErrorCode;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::services::tag_service::{TagService, DEFAULT_TAG_COLOR};
+use rsapps_webserver::services::todo_list_service::TodoListService;
+use rsapps_webserver::services::todo_service::TodoService;
+use sqlx::Postgres;
+use std::sync::Arc;
+
+fn by_tags(tag_ids: Vec<i32>, tag_match: Option<TagMatch>) -> TodoFilter {
+    TodoFilter {
+        tag_filter: Some(TagFilter { tag_ids, tag_match }),
+        ..TodoFilter::default()
+    }
+}
+
+#[sqlx_macros::test]
+async fn test_tag_is_validated() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = TagService::new(Arc::new(PgDIContainer { db }));
+
+    let tag = service
+        .create_tag(user.id, " Work ".to_owned(), None)
+        .await
+        .unwrap();
+    assert_eq!("Work", tag.name);
+    assert_eq!(DEFAULT_TAG_COLOR, tag.color);
+    let tag = service
+        .update_tag(
+            tag.id,
+            user.id,
+            "Work".to_owned(),
+            Some("#1E88E5".to_owned()),
+        )
+        .await
+        .unwrap();
+    assert_eq!("#1e88e5", tag.color);
+
+    let err = service
+        .create_tag(user.id, "work".to_owned(), None)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::Conflict));
+    for (name, color) in &[("  ", "#1e88e5"), ("Home", "blue"), ("Home", "#1e88e")] {
+        let err = service
+            .create_tag(user.id, name.to_string(), Some(color.to_string()))
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err.code, ErrorCode::ValidationFailed(_)));
+    }
+    match service
+        .create_tag(user.id, "".to_owned(), Some("red".to_owned()))
+        .await
+        .err()
+        .unwrap()
+        .code
+    {
+        ErrorCode::ValidationFailed(violations) => assert_eq!(2, violations.len()),
+        _ => panic!("the tag should fail validation"),
+    }
+}
+
+#[sqlx_macros::test]
+async fn test_todos_are_filtered_by_tags() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let other = create_user(&db).await.unwrap();
+    let di_container = Arc::new(PgDIContainer { db });
+    let service = TagService::new(di_container.clone());
+    let todo_service = TodoService::new(di_container);
+    let work = service
+        .create_tag(user.id, "work".to_owned(), None)
+        .await
+        .unwrap();
+    let urgent = service
+        .create_tag(user.id, "urgent".to_owned(), None)
+        .await
+        .unwrap();
+    let others = service
+        .create_tag(other.id, "work".to_owned(), None)
+        .await
+        .unwrap();
+    let report = todo_service
+        .create_todo("report".to_owned(), user.id, None, None, None)
+        .await
+        .unwrap();
+    let incident = todo_service
+        .create_todo("incident".to_owned(), user.id, None, None, None)
+        .await
+        .unwrap();
+    todo_service
+        .create_todo("milk".to_owned(), user.id, None, None, None)
+        .await
+        .unwrap();
+    for (todo_id, tag_id) in &[
+        (report.id, work.id),
+        (incident.id, work.id),
+        (incident.id, urgent.id),
+    ] {
+        assert!(todo_service
+            .add_tag(*todo_id, user.id, *tag_id)
+            .await
+            .unwrap());
+    }
+    // Tagging twice changes nothing.
+    assert!(todo_service
+        .add_tag(report.id, user.id, work.id)
+        .await
+        .unwrap());
+
+    let filter = |tag_match| by_tags(vec![work.id, urgent.id, urgent.id], tag_match);
+    let any = todo_service
+        .get_list_todos(user.id, None, filter(None))
+        .await
+        .unwrap();
+    assert_eq!(
+        vec![report.id, incident.id],
+        any.iter().map(|todo| todo.id).collect::<Vec<i32>>()
+    );
+    let all = todo_service
+        .get_list_todos(user.id, None, filter(Some(TagMatch::All)))
+        .await
+        .unwrap();
+    assert_eq!(1, all.len());
+    assert_eq!(incident.id, all[0].id);
+    let everything = todo_service
+        .get_list_todos(user.id, None, by_tags(vec![], None))
+        .await
+        .unwrap();
+    assert_eq!(3, everything.len());
+
+    let tags = service.get_todo_tags(incident.id, user.id).await.unwrap();
+    assert_eq!(
+        vec!["urgent", "work"],
+        tags.iter()
+            .map(|tag| tag.name.as_str())
+            .collect::<Vec<&str>>()
+    );
+
+    // Tags of other users can't be filtered by or put on todos.
+    let err = todo_service
+        .get_list_todos(user.id, None, by_tags(vec![others.id], None))
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+    let err = todo_service
+        .add_tag(report.id, user.id, others.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+    let err = todo_service
+        .add_tag(report.id, other.id, others.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+
+    assert!(todo_service
+        .remove_tag(incident.id, user.id, urgent.id)
+        .await
+        .unwrap());
+    let err = todo_service
+        .remove_tag(incident.id, user.id, urgent.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+}
+
+#[sqlx_macros::test]
+async fn test_rename_and_merge_tags() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let di_container = Arc::new(PgDIContainer { db });
+    let service = TagService::new(di_container.clone());
+    let todo_service = TodoService::new(di_container);
+    let job = service
+        .create_tag(user.id, "job".to_owned(), None)
+        .await
+        .unwrap();
+    let work = service
+        .create_tag(user.id, "work".to_owned(), None)
+        .await
+        .unwrap();
+    let report = todo_service
+        .create_todo("report".to_owned(), user.id, None, None, None)
+        .await
+        .unwrap();
+    let meeting = todo_service
+        .create_todo("meeting".to_owned(), user.id, None, None, None)
+        .await
+        .unwrap();
+    todo_service
+        .add_tag(report.id, user.id, job.id)
+        .await
+        .unwrap();
+    todo_service
+        .add_tag(meeting.id, user.id, job.id)
+        .await
+        .unwrap();
+    todo_service
+        .add_tag(meeting.id, user.id, work.id)
+        .await
+        .unwrap();
+
+    let err = service
+        .update_tag(job.id, user.id, "Work".to_owned(), None)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::Conflict));
+    let err = service
+        .merge_tags(job.id, job.id, user.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+
+    let merged = service.merge_tags(job.id, work.id, user.id).await.unwrap();
+    assert_eq!(work.id, merged.id);
+    let err = service.get_tag(job.id, user.id).await.err().unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+    for todo_id in &[report.id, meeting.id] {
+        let tags = service.get_todo_tags(*todo_id, user.id).await.unwrap();
+        assert_eq!(1, tags.len());
+        assert_eq!(work.id, tags[0].id);
+    }
+    let err = service
+        .merge_tags(job.id, work.id, user.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+
+    let renamed = service
+        .update_tag(work.id, user.id, "Job".to_owned(), None)
+        .await
+        .unwrap();
+    assert_eq!("Job", renamed.name);
+    assert_eq!(work.color, renamed.color);
+    assert!(service.delete_tag(work.id, user.id).await.unwrap());
+    assert!(service
+        .get_todo_tags(report.id, user.id)
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[sqlx_macros::test]
+async fn test_viewer_can_tag_todos() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let viewer = create_user(&db).await.unwrap();
+    let stranger = create_user(&db).await.unwrap();
+    let di_container = Arc::new(PgDIContainer { db });
+    let service = TagService::new(di_container.clone());
+    let todo_list_service = TodoListService::new(di_container.clone());
+    let todo_service = TodoService::new(di_container);
+    let shared = todo_list_service
+        .create_todo_list(owner.id, "Shared".to_owned())
+        .await
+        .unwrap();
+    let todo = todo_service
+        .create_todo("read me".to_owned(), owner.id, Some(shared.id), None, None)
+        .await
+        .unwrap();
+    todo_list_service
+        .invite_member(shared.id, owner.id, viewer.username, TodoListRole::Viewer)
+        .await
+        .unwrap();
+    todo_list_service
+        .accept_invitation(shared.id, viewer.id)
+        .await
+        .unwrap();
+    let later = service
+        .create_tag(viewer.id, "later".to_owned(), None)
+        .await
+        .unwrap();
+
+    // Tags are private to the user, so a viewer can put its own on a todo it can see.
+    assert!(todo_service
+        .add_tag(todo.id, viewer.id, later.id)
+        .await
+        .unwrap());
+    let tags = service.get_todo_tags(todo.id, viewer.id).await.unwrap();
+    assert_eq!(
+        vec![later.id],
+        tags.iter().map(|tag| tag.id).collect::<Vec<i32>>()
+    );
+    assert!(service
+        .get_todo_tags(todo.id, owner.id)
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(todo_service
+        .remove_tag(todo.id, viewer.id, later.id)
+        .await
+        .unwrap());
+
+    // Someone who can't see the list still can't tag its todos.
+    let theirs = service
+        .create_tag(stranger.id, "later".to_owned(), None)
+        .await
+        .unwrap();
+    let err = todo_service
+        .add_tag(todo.id, stranger.id, theirs.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+}
@@ -0,0 +1,337 @@
+use crate::fixtures::{create_user, get_db, unique_username};
+use rsapps_webserver::domains::entities::todo::TodoFilter;
+use rsapps_webserver::domains::entities::todo_list_member::TodoListRole;
+use rsapps_webserver::domains::errors::ErrorCode;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::services::todo_list_service::TodoListService;
+use rsapps_webserver::services::todo_service::TodoService;
+use sqlx::Postgres;
+use std::sync::Arc;
+
+#[sqlx_macros::test]
+async fn test_default_todo_list() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = TodoListService::new(Arc::new(PgDIContainer { db }));
+
+    let todo_lists = service.get_todo_lists(user.id).await.unwrap();
+    assert_eq!(1, todo_lists.len());
+    assert!(todo_lists[0].is_default);
+    let default_list = service.get_default_todo_list(user.id).await.unwrap();
+    assert_eq!(todo_lists[0].id, default_list.id);
+
+    let err = service
+        .delete_todo_list(default_list.id, user.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+}
+
+#[sqlx_macros::test]
+async fn test_todos_are_kept_per_list() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let di_container = Arc::new(PgDIContainer { db });
+    let service = TodoListService::new(di_container.clone());
+    let todo_service = TodoService::new(di_container);
+    let work = service
+        .create_todo_list(user.id, " Work ".to_owned())
+        .await
+        .unwrap();
+    assert_eq!("Work", work.name);
+    assert!(!work.is_default);
+
+    todo_service
+        .create_todo("report".to_owned(), user.id, Some(work.id), None, None)
+        .await
+        .unwrap();
+    todo_service
+        .create_todo("milk".to_owned(), user.id, None, None, None)
+        .await
+        .unwrap();
+    todo_service
+        .toggle_all_complete(user.id, Some(work.id))
+        .await
+        .unwrap();
+    todo_service
+        .clear_completed_todo(user.id, None)
+        .await
+        .unwrap();
+
+    let work_todos = todo_service
+        .get_list_todos(user.id, Some(work.id), TodoFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(1, work_todos.len());
+    assert!(work_todos[0].complete);
+    let default_todos = todo_service
+        .get_list_todos(user.id, None, TodoFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(1, default_todos.len());
+    assert_eq!("milk", default_todos[0].body);
+    assert!(!default_todos[0].complete);
+
+    // Deleting a list deletes its todos.
+    assert!(service.delete_todo_list(work.id, user.id).await.unwrap());
+    assert_eq!(1, todo_service.get_all_todos(user.id).await.unwrap().len());
+}
+
+#[sqlx_macros::test]
+async fn test_other_users_todo_list_is_not_found() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let other = create_user(&db).await.unwrap();
+    let di_container = Arc::new(PgDIContainer { db });
+    let service = TodoListService::new(di_container.clone());
+    let todo_service = TodoService::new(di_container);
+    let work = service
+        .create_todo_list(owner.id, "Work".to_owned())
+        .await
+        .unwrap();
+
+    let err = service
+        .rename_todo_list(work.id, other.id, "Mine".to_owned())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+    let err = service
+        .delete_todo_list(work.id, other.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+    let err = todo_service
+        .create_todo("sneaked in".to_owned(), other.id, Some(work.id), None, None)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+    let err = todo_service
+        .get_list_todos(other.id, Some(work.id), TodoFilter::default())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+}
+
+#[sqlx_macros::test]
+async fn test_todo_list_name_is_validated() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = TodoListService::new(Arc::new(PgDIContainer { db }));
+    for name in &["  ".to_owned(), "a".repeat(256)] {
+        let err = service
+            .create_todo_list(user.id, name.to_owned())
+            .await
+            .err()
+            .unwrap();
+        assert!(matches!(err.code, ErrorCode::ValidationFailed(_)));
+    }
+}
+
+#[sqlx_macros::test]
+async fn test_viewer_can_not_change_todos() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let viewer = create_user(&db).await.unwrap();
+    let di_container = Arc::new(PgDIContainer { db });
+    let service = TodoListService::new(di_container.clone());
+    let todo_service = TodoService::new(di_container);
+    let shared = service
+        .create_todo_list(owner.id, "Shared".to_owned())
+        .await
+        .unwrap();
+    let todo = todo_service
+        .create_todo("read me".to_owned(), owner.id, Some(shared.id), None, None)
+        .await
+        .unwrap();
+    service
+        .invite_member(shared.id, owner.id, viewer.username, TodoListRole::Viewer)
+        .await
+        .unwrap();
+
+    // A pending invitation doesn't share the list yet.
+    let err = todo_service
+        .get_list_todos(viewer.id, Some(shared.id), TodoFilter::default())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+    service
+        .accept_invitation(shared.id, viewer.id)
+        .await
+        .unwrap();
+    let todos = todo_service
+        .get_list_todos(viewer.id, Some(shared.id), TodoFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(1, todos.len());
+
+    let errors = [
+        todo_service
+            .create_todo("no".to_owned(), viewer.id, Some(shared.id), None, None)
+            .await
+            .err(),
+        todo_service
+            .update_todo(todo.id, viewer.id, "no".to_owned(), true, None, None)
+            .await
+            .err(),
+        todo_service.toggle_complete(todo.id, viewer.id).await.err(),
+        todo_service.delete_todo(todo.id, viewer.id).await.err(),
+        todo_service
+            .toggle_all_complete(viewer.id, Some(shared.id))
+            .await
+            .err(),
+        todo_service
+            .clear_completed_todo(viewer.id, Some(shared.id))
+            .await
+            .err(),
+        service
+            .rename_todo_list(shared.id, viewer.id, "Mine".to_owned())
+            .await
+            .err(),
+        service.delete_todo_list(shared.id, viewer.id).await.err(),
+    ];
+    for err in &errors {
+        assert!(matches!(err.as_ref().unwrap().code, ErrorCode::Forbidden));
+    }
+    let todos = todo_service
+        .get_list_todos(owner.id, Some(shared.id), TodoFilter::default())
+        .await
+        .unwrap();
+    assert_eq!("read me", todos[0].body);
+    assert!(!todos[0].complete);
+}
+
+#[sqlx_macros::test]
+async fn test_editor_changes_shared_todos() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let editor = create_user(&db).await.unwrap();
+    let di_container = Arc::new(PgDIContainer { db });
+    let service = TodoListService::new(di_container.clone());
+    let todo_service = TodoService::new(di_container);
+    let shared = service
+        .create_todo_list(owner.id, "Shared".to_owned())
+        .await
+        .unwrap();
+    let todo = todo_service
+        .create_todo("owned".to_owned(), owner.id, Some(shared.id), None, None)
+        .await
+        .unwrap();
+    let invitation = service
+        .invite_member(shared.id, owner.id, editor.username, TodoListRole::Editor)
+        .await
+        .unwrap();
+    assert_eq!(Some(owner.id), invitation.invited_by);
+    assert_eq!(1, service.get_invitations(editor.id).await.unwrap().len());
+    service
+        .accept_invitation(shared.id, editor.id)
+        .await
+        .unwrap();
+
+    let todo_lists = service.get_todo_lists(editor.id).await.unwrap();
+    assert_eq!(2, todo_lists.len());
+    assert_eq!(editor.id, todo_lists[0].user_id);
+    assert_eq!(shared.id, todo_lists[1].id);
+
+    let created = todo_service
+        .create_todo("added".to_owned(), editor.id, Some(shared.id), None, None)
+        .await
+        .unwrap();
+    assert_eq!(Some(editor.id), created.created_by);
+    let updated = todo_service
+        .update_todo(todo.id, editor.id, "edited".to_owned(), true, None, None)
+        .await
+        .unwrap();
+    assert_eq!(Some(owner.id), updated.created_by);
+    assert_eq!(Some(editor.id), updated.updated_by);
+
+    service
+        .set_member_role(shared.id, owner.id, editor.id, TodoListRole::Viewer)
+        .await
+        .unwrap();
+    let err = todo_service
+        .delete_todo(created.id, editor.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::Forbidden));
+
+    let err = service
+        .remove_member(shared.id, owner.id, owner.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+    assert!(service
+        .remove_member(shared.id, editor.id, editor.id)
+        .await
+        .unwrap());
+    let err = todo_service
+        .get_list_todos(editor.id, Some(shared.id), TodoFilter::default())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+}
+
+#[sqlx_macros::test]
+async fn test_invite_member_is_checked() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let invitee = create_user(&db).await.unwrap();
+    let service = TodoListService::new(Arc::new(PgDIContainer { db }));
+    let shared = service
+        .create_todo_list(owner.id, "Shared".to_owned())
+        .await
+        .unwrap();
+
+    let err = service
+        .invite_member(shared.id, owner.id, unique_username(), TodoListRole::Editor)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+    let err = service
+        .invite_member(
+            shared.id,
+            owner.id,
+            invitee.username.to_owned(),
+            TodoListRole::Owner,
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+    let err = service
+        .invite_member(
+            shared.id,
+            invitee.id,
+            owner.username.to_owned(),
+            TodoListRole::Editor,
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+
+    service
+        .invite_member(
+            shared.id,
+            owner.id,
+            invitee.username.to_owned(),
+            TodoListRole::Viewer,
+        )
+        .await
+        .unwrap();
+    let err = service
+        .invite_member(shared.id, owner.id, invitee.username, TodoListRole::Editor)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::Conflict));
+}
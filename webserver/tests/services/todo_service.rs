@@ -0,0 +1,510 @@
+use crate::fixtures::{create_user, get_db};
+use chrono::TimeZone;
+use rsapps_webserver::domains::entities::todo::{Todo, TodoFilter, TodoOrder};
+use rsapps_webserver::domains::errors::ErrorCode;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::services::tag_service::TagService;
+use rsapps_webserver::services::todo_service::TodoService;
+use sqlx::Postgres;
+use std::sync::Arc;
+
+#[sqlx_macros::test]
+async fn test_other_users_todo_is_not_found() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let owner = create_user(&db).await.unwrap();
+    let other = create_user(&db).await.unwrap();
+    let service = TodoService::new(Arc::new(PgDIContainer { db }));
+    let todo = service
+        .create_todo("owned".to_owned(), owner.id, None, None, None)
+        .await
+        .unwrap();
+
+    let err = service
+        .update_todo(todo.id, other.id, "stolen".to_owned(), true, None, None)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+    let err = service
+        .toggle_complete(todo.id, other.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+    let err = service.delete_todo(todo.id, other.id).await.err().unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+
+    let todos = service.get_all_todos(owner.id).await.unwrap();
+    assert_eq!(1, todos.len());
+    assert_eq!("owned", todos[0].body);
+    assert!(!todos[0].complete);
+}
+
+#[sqlx_macros::test]
+async fn test_todo_dates() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = TodoService::new(Arc::new(PgDIContainer { db }));
+    let now = chrono::Utc::now();
+    let yesterday = now - chrono::Duration::days(1);
+    let tomorrow = now + chrono::Duration::days(1);
+
+    let err = service
+        .create_todo(
+            "backwards".to_owned(),
+            user.id,
+            None,
+            Some(tomorrow),
+            Some(yesterday),
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+    let report = service
+        .create_todo(
+            "report".to_owned(),
+            user.id,
+            None,
+            Some(yesterday),
+            Some(tomorrow),
+        )
+        .await
+        .unwrap();
+    // Postgres keeps microseconds only.
+    assert_eq!(
+        Some(yesterday.timestamp_millis()),
+        report.start_at.map(|t| t.timestamp_millis())
+    );
+    assert_eq!(
+        Some(tomorrow.timestamp_millis()),
+        report.due_at.map(|t| t.timestamp_millis())
+    );
+    let late = service
+        .create_todo("late".to_owned(), user.id, None, None, Some(yesterday))
+        .await
+        .unwrap();
+    assert!(late.is_overdue(now));
+
+    let overdue = service
+        .get_list_todos(
+            user.id,
+            None,
+            TodoFilter {
+                overdue: Some(true),
+                ..TodoFilter::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(1, overdue.len());
+    assert_eq!(late.id, overdue[0].id);
+    let by_due = service
+        .get_list_todos(
+            user.id,
+            None,
+            TodoFilter {
+                order_by: TodoOrder::DueAt,
+                ..TodoFilter::default()
+            },
+        )
+        .await
+        .unwrap();
+    assert_eq!(late.id, by_due[0].id);
+
+    // Dates left out stay, only the ones given as `None` are cleared.
+    let updated = service
+        .update_todo(
+            report.id,
+            user.id,
+            "final report".to_owned(),
+            false,
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!("final report", updated.body);
+    assert_eq!(report.start_at, updated.start_at);
+    assert_eq!(report.due_at, updated.due_at);
+    let updated = service
+        .update_todo(late.id, user.id, "late".to_owned(), false, None, Some(None))
+        .await
+        .unwrap();
+    assert_eq!(None, updated.due_at);
+    assert!(!updated.is_overdue(now));
+    let err = service
+        .update_todo(
+            report.id,
+            user.id,
+            "report".to_owned(),
+            false,
+            Some(Some(tomorrow)),
+            Some(Some(yesterday)),
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+    // A start given alone can't be after the due time which stays.
+    let err = service
+        .update_todo(
+            report.id,
+            user.id,
+            "report".to_owned(),
+            false,
+            Some(Some(tomorrow + chrono::Duration::days(1))),
+            None,
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+}
+
+#[sqlx_macros::test]
+async fn test_recurring_todo() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let di_container = Arc::new(PgDIContainer { db });
+    let service = TodoService::new(di_container.clone());
+    let due_at = chrono::Utc.ymd(2031, 1, 1).and_hms(9, 0, 0);
+    let rent = service
+        .create_todo(
+            "pay rent".to_owned(),
+            user.id,
+            None,
+            Some(due_at - chrono::Duration::days(3)),
+            Some(due_at),
+        )
+        .await
+        .unwrap();
+
+    let err = service
+        .set_recurrence(
+            rent.id,
+            user.id,
+            Some("FREQ=HOURLY".to_owned()),
+            Some("Mars/Olympus_Mons".to_owned()),
+        )
+        .await
+        .err()
+        .unwrap();
+    match err.code {
+        ErrorCode::ValidationFailed(violations) => {
+            let fields: Vec<&str> = violations.iter().map(|v| v.field.as_str()).collect();
+            assert_eq!(vec!["rrule", "timeZone"], fields);
+        }
+        _ => panic!("unexpected error: {}", err.message),
+    }
+    let undue = service
+        .create_todo("someday".to_owned(), user.id, None, None, None)
+        .await
+        .unwrap();
+    let err = service
+        .set_recurrence(undue.id, user.id, Some("FREQ=DAILY".to_owned()), None)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+
+    let rent = service
+        .set_recurrence(
+            rent.id,
+            user.id,
+            Some("freq=monthly".to_owned()),
+            Some("Europe/Berlin".to_owned()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(Some(due_at), rent.occurrence_at);
+    let series = service
+        .get_todo_series(rent.series_id.unwrap(), user.id)
+        .await
+        .unwrap();
+    assert_eq!("FREQ=MONTHLY", series.rrule);
+    assert_eq!("Europe/Berlin", series.time_zone);
+    let tag_service = TagService::new(di_container);
+    let tag = tag_service
+        .create_tag(user.id, "home".to_owned(), None)
+        .await
+        .unwrap();
+    service.add_tag(rent.id, user.id, tag.id).await.unwrap();
+
+    // Completing an occurrence again doesn't create another one.
+    for _ in 0..3 {
+        service.toggle_complete(rent.id, user.id).await.unwrap();
+    }
+    let occurrences = series_todos(&service, user.id, series.id).await;
+    assert_eq!(2, occurrences.len());
+    let next = &occurrences[1];
+    assert!(!next.complete);
+    assert_eq!("pay rent", next.body);
+    assert_eq!(
+        Some(chrono::Utc.ymd(2031, 2, 1).and_hms(9, 0, 0)),
+        next.due_at
+    );
+    assert_eq!(next.due_at, next.occurrence_at);
+    assert_eq!(
+        Some(chrono::Utc.ymd(2031, 1, 29).and_hms(9, 0, 0)),
+        next.start_at
+    );
+    let tags = tag_service.get_todo_tags(next.id, user.id).await.unwrap();
+    assert_eq!(
+        vec![tag.id],
+        tags.iter().map(|t| t.id).collect::<Vec<i32>>()
+    );
+
+    // The occurrences stay at 10:00 in Berlin across the change to summer time.
+    service
+        .update_todo(next.id, user.id, next.body.to_owned(), true, None, None)
+        .await
+        .unwrap();
+    let march = series_todos(&service, user.id, series.id).await.remove(2);
+    assert_eq!(
+        Some(chrono::Utc.ymd(2031, 3, 1).and_hms(9, 0, 0)),
+        march.due_at
+    );
+    service.toggle_complete(march.id, user.id).await.unwrap();
+    let april = series_todos(&service, user.id, series.id).await.remove(3);
+    assert_eq!(
+        Some(chrono::Utc.ymd(2031, 4, 1).and_hms(8, 0, 0)),
+        april.due_at
+    );
+
+    let april = service
+        .set_recurrence(april.id, user.id, None, None)
+        .await
+        .unwrap();
+    assert_eq!(None, april.series_id);
+    assert_eq!(None, april.occurrence_at);
+    service.toggle_complete(april.id, user.id).await.unwrap();
+    let todos = service
+        .get_list_todos(user.id, None, TodoFilter::default())
+        .await
+        .unwrap();
+    assert_eq!(5, todos.len());
+    assert!(todos.iter().all(|todo| todo.series_id.is_none()));
+}
+
+#[sqlx_macros::test]
+async fn test_overdue_recurring_todo() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = TodoService::new(Arc::new(PgDIContainer { db }));
+    let standup = service
+        .create_todo(
+            "write standup notes".to_owned(),
+            user.id,
+            None,
+            None,
+            Some(chrono::Utc.ymd(2021, 1, 4).and_hms(9, 0, 0)),
+        )
+        .await
+        .unwrap();
+    let standup = service
+        .set_recurrence(standup.id, user.id, Some("FREQ=DAILY".to_owned()), None)
+        .await
+        .unwrap();
+    let series_id = standup.series_id.unwrap();
+
+    // The missed occurrences are skipped.
+    service.toggle_complete(standup.id, user.id).await.unwrap();
+    let next = series_todos(&service, user.id, series_id).await.remove(1);
+    let now = chrono::Utc::now();
+    let due_at = next.due_at.unwrap();
+    assert!(now < due_at && due_at <= now + chrono::Duration::days(1));
+    assert_eq!(Some(standup.id), next.previous_id);
+
+    // Once the next occurrence was moved, the rule schedules another time after now,
+    // but the overdue occurrence has its next one already.
+    let moved = due_at - chrono::Duration::days(2);
+    service
+        .update_todo_series(next.id, user.id, next.body, false, None, Some(Some(moved)))
+        .await
+        .unwrap();
+    for _ in 0..2 {
+        service.toggle_complete(standup.id, user.id).await.unwrap();
+    }
+    let occurrences = series_todos(&service, user.id, series_id).await;
+    assert_eq!(2, occurrences.len());
+    assert!(occurrences[0].complete);
+    assert_eq!(Some(moved), occurrences[1].due_at);
+}
+
+#[sqlx_macros::test]
+async fn test_recurring_todo_scope() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = TodoService::new(Arc::new(PgDIContainer { db }));
+    let monday = chrono::Utc.ymd(2031, 1, 6).and_hms(18, 0, 0);
+    let plants = service
+        .create_todo("water plants".to_owned(), user.id, None, None, Some(monday))
+        .await
+        .unwrap();
+    let plants = service
+        .set_recurrence(plants.id, user.id, Some("FREQ=WEEKLY".to_owned()), None)
+        .await
+        .unwrap();
+    let series_id = plants.series_id.unwrap();
+
+    // Only this occurrence changes.
+    let plants = service
+        .update_todo(
+            plants.id,
+            user.id,
+            "water the cactus".to_owned(),
+            false,
+            None,
+            Some(Some(monday + chrono::Duration::days(1))),
+        )
+        .await
+        .unwrap();
+    assert_eq!(Some(monday), plants.occurrence_at);
+    service.toggle_complete(plants.id, user.id).await.unwrap();
+    let next = series_todos(&service, user.id, series_id).await.remove(1);
+    assert_eq!("water plants", next.body);
+    assert_eq!(Some(monday + chrono::Duration::weeks(1)), next.due_at);
+
+    // The whole series moves to Tuesdays.
+    let tuesday = monday + chrono::Duration::weeks(1) + chrono::Duration::days(1);
+    let next = service
+        .update_todo_series(
+            next.id,
+            user.id,
+            "water all plants".to_owned(),
+            false,
+            None,
+            Some(Some(tuesday)),
+        )
+        .await
+        .unwrap();
+    assert_eq!(Some(tuesday), next.occurrence_at);
+    let series = service.get_todo_series(series_id, user.id).await.unwrap();
+    assert_eq!("water all plants", series.body);
+    assert_eq!(tuesday, series.dtstart);
+    service.toggle_complete(next.id, user.id).await.unwrap();
+    let after = series_todos(&service, user.id, series_id).await.remove(2);
+    assert_eq!("water all plants", after.body);
+    assert_eq!(Some(tuesday + chrono::Duration::weeks(1)), after.due_at);
+
+    let plain = service
+        .create_todo("once".to_owned(), user.id, None, None, Some(monday))
+        .await
+        .unwrap();
+    let err = service
+        .update_todo_series(plain.id, user.id, "once".to_owned(), true, None, None)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+}
+
+#[sqlx_macros::test]
+async fn test_recurring_todo_count() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = TodoService::new(Arc::new(PgDIContainer { db }));
+    let monday = chrono::Utc.ymd(2031, 1, 6).and_hms(18, 0, 0);
+    let plants = service
+        .create_todo("water plants".to_owned(), user.id, None, None, Some(monday))
+        .await
+        .unwrap();
+    let plants = service
+        .set_recurrence(
+            plants.id,
+            user.id,
+            Some("FREQ=WEEKLY;COUNT=3".to_owned()),
+            None,
+        )
+        .await
+        .unwrap();
+    let series_id = plants.series_id.unwrap();
+    service.toggle_complete(plants.id, user.id).await.unwrap();
+
+    // Moving the series keeps it at three occurrences.
+    let second = series_todos(&service, user.id, series_id).await.remove(1);
+    let tuesday = monday + chrono::Duration::weeks(1) + chrono::Duration::days(1);
+    service
+        .update_todo_series(
+            second.id,
+            user.id,
+            second.body.to_owned(),
+            true,
+            None,
+            Some(Some(tuesday)),
+        )
+        .await
+        .unwrap();
+    let series = service.get_todo_series(series_id, user.id).await.unwrap();
+    assert_eq!("FREQ=WEEKLY;COUNT=3", series.rrule);
+    assert_eq!(1, series.occurrences_before);
+    let third = series_todos(&service, user.id, series_id).await.remove(2);
+    assert_eq!(Some(tuesday + chrono::Duration::weeks(1)), third.due_at);
+
+    // A new rule counts the occurrences the series has had too.
+    let err = service
+        .set_recurrence(
+            third.id,
+            user.id,
+            Some("FREQ=WEEKLY;COUNT=2".to_owned()),
+            None,
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::ValidationFailed(_)));
+    service
+        .set_recurrence(
+            third.id,
+            user.id,
+            Some("FREQ=WEEKLY;COUNT=4".to_owned()),
+            None,
+        )
+        .await
+        .unwrap();
+    service.toggle_complete(third.id, user.id).await.unwrap();
+    let fourth = series_todos(&service, user.id, series_id).await.remove(3);
+    service.toggle_complete(fourth.id, user.id).await.unwrap();
+    assert_eq!(4, series_todos(&service, user.id, series_id).await.len());
+}
+
+#[sqlx_macros::test]
+async fn test_toggle_all_complete_continues_series() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = TodoService::new(Arc::new(PgDIContainer { db }));
+    let due_at = chrono::Utc.ymd(2031, 1, 1).and_hms(9, 0, 0);
+    let rent = service
+        .create_todo("pay rent".to_owned(), user.id, None, None, Some(due_at))
+        .await
+        .unwrap();
+    let rent = service
+        .set_recurrence(rent.id, user.id, Some("FREQ=MONTHLY".to_owned()), None)
+        .await
+        .unwrap();
+    service
+        .create_todo("once".to_owned(), user.id, None, None, None)
+        .await
+        .unwrap();
+
+    service.toggle_all_complete(user.id, None).await.unwrap();
+    let occurrences = series_todos(&service, user.id, rent.series_id.unwrap()).await;
+    assert_eq!(2, occurrences.len());
+    assert!(occurrences[0].complete);
+    assert!(!occurrences[1].complete);
+    assert_eq!(
+        Some(chrono::Utc.ymd(2031, 2, 1).and_hms(9, 0, 0)),
+        occurrences[1].due_at
+    );
+}
+
+async fn series_todos(service: &TodoService, user_id: i32, series_id: i32) -> Vec<Todo> {
+    service
+        .get_list_todos(user_id, None, TodoFilter::default())
+        .await
+        .unwrap()
+        .into_iter()
+        .filter(|todo| todo.series_id == Some(series_id))
+        .collect()
+}
@@ -0,0 +1,242 @@
+use crate::fixtures::{create_user, get_db};
+use rsapps_webserver::auth::key_ring::KeyRing;
+use rsapps_webserver::auth::{generate_token, hash_token, Claims};
+use rsapps_webserver::domains::entities::audit_event::RequestMetadata;
+use rsapps_webserver::domains::entities::refresh_token::RefreshToken;
+use rsapps_webserver::domains::errors::ErrorCode;
+use rsapps_webserver::domains::repositories::refresh_token_repository::RefreshTokenRepository;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::infrastructures::repositories::refresh_token_repository::PostgreSQLRefreshTokenRepository;
+use rsapps_webserver::services::token_service::TokenService;
+use sqlx::Postgres;
+use std::sync::Arc;
+
+fn key_ring() -> Arc<KeyRing> {
+    Arc::new(KeyRing::from_secret("testsecret"))
+}
+
+#[sqlx_macros::test]
+async fn test_refresh_tokens_rotates_token() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = TokenService::new(Arc::new(PgDIContainer { db }), key_ring());
+    let issued = service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+
+    let rotated = service
+        .refresh_tokens(issued.refresh_token.to_owned())
+        .await
+        .unwrap();
+    assert_ne!(issued.refresh_token, rotated.refresh_token);
+    assert!(service
+        .refresh_tokens(rotated.refresh_token.to_owned())
+        .await
+        .is_ok());
+}
+
+#[sqlx_macros::test]
+async fn test_refresh_token_reuse_revokes_family() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = TokenService::new(Arc::new(PgDIContainer { db }), key_ring());
+    let issued = service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+    let rotated = service
+        .refresh_tokens(issued.refresh_token.to_owned())
+        .await
+        .unwrap();
+
+    let err = service
+        .refresh_tokens(issued.refresh_token)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenRevoked));
+    let err = service
+        .refresh_tokens(rotated.refresh_token)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenRevoked));
+}
+
+#[sqlx_macros::test]
+async fn test_revoke_family() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let repository = PostgreSQLRefreshTokenRepository { db: db.clone() };
+    let service = TokenService::new(Arc::new(PgDIContainer { db }), key_ring());
+    let issued = service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+    let other = service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+    let stored = repository
+        .get_refresh_token_by_hash(hash_token(&issued.refresh_token))
+        .await
+        .unwrap()
+        .unwrap();
+
+    let other_stored = repository
+        .get_refresh_token_by_hash(hash_token(&other.refresh_token))
+        .await
+        .unwrap()
+        .unwrap();
+
+    service
+        .revoke_family(stored.family.to_owned())
+        .await
+        .unwrap();
+
+    let err = service
+        .refresh_tokens(issued.refresh_token)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenRevoked));
+    let err = service
+        .ensure_family_active(stored.family)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenRevoked));
+    assert!(service
+        .ensure_family_active(other_stored.family)
+        .await
+        .is_ok());
+    assert!(service.refresh_tokens(other.refresh_token).await.is_ok());
+}
+
+fn session_id(access_token: &str) -> i32 {
+    key_ring()
+        .decode::<Claims>(access_token)
+        .unwrap()
+        .sid
+        .unwrap()
+}
+
+#[sqlx_macros::test]
+async fn test_sessions() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let other_user = create_user(&db).await.unwrap();
+    let service = TokenService::new(Arc::new(PgDIContainer { db }), key_ring());
+    let metadata = RequestMetadata {
+        ip_address: Some("192.0.2.1".to_owned()),
+        user_agent: Some(
+            "Mozilla/5.0 (X11; Linux x86_64; rv:85.0) Gecko/20100101 Firefox/85.0".to_owned(),
+        ),
+        request_id: None,
+    };
+    let issued = service.issue_tokens(user.id, &metadata).await.unwrap();
+    let other = service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+
+    // The session outlives rotations of its refresh token.
+    let sid = session_id(&issued.access_token);
+    let rotated = service.refresh_tokens(issued.refresh_token).await.unwrap();
+    assert_eq!(sid, session_id(&rotated.access_token));
+    let sessions = service.get_sessions(user.id).await.unwrap();
+    assert_eq!(2, sessions.len());
+    let session = sessions.iter().find(|session| session.id == sid).unwrap();
+    assert_eq!("Firefox on Linux", session.device_name());
+    assert_eq!(Some("192.0.2.1".to_owned()), session.ip_address);
+
+    // A request from somewhere else moves the session there.
+    service
+        .ensure_session_active(
+            sid,
+            user.id,
+            &RequestMetadata {
+                ip_address: Some("198.51.100.7".to_owned()),
+                ..RequestMetadata::default()
+            },
+        )
+        .await
+        .unwrap();
+    let sessions = service.get_sessions(user.id).await.unwrap();
+    assert_eq!(sid, sessions[0].id);
+    assert_eq!(Some("198.51.100.7".to_owned()), sessions[0].ip_address);
+
+    // Only the owner can revoke a session, which ends it at once.
+    let err = service
+        .revoke_session(other_user.id, sid)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+    let err = service
+        .ensure_session_active(sid, other_user.id, &RequestMetadata::default())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenRevoked));
+    assert!(service.revoke_session(user.id, sid).await.unwrap());
+    let err = service
+        .ensure_session_active(sid, user.id, &RequestMetadata::default())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenRevoked));
+    let err = service
+        .refresh_tokens(rotated.refresh_token)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenRevoked));
+    let err = service.revoke_session(user.id, sid).await.err().unwrap();
+    assert!(matches!(err.code, ErrorCode::NotFound));
+
+    let sessions = service.get_sessions(user.id).await.unwrap();
+    assert_eq!(1, sessions.len());
+    assert_eq!(session_id(&other.access_token), sessions[0].id);
+    assert_eq!("Unknown device", sessions[0].device_name());
+}
+
+#[sqlx_macros::test]
+async fn test_expired_refresh_token() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let repository = PostgreSQLRefreshTokenRepository { db: db.clone() };
+    let service = TokenService::new(Arc::new(PgDIContainer { db }), key_ring());
+    let now = chrono::Utc::now();
+    let token = generate_token();
+    repository
+        .create_refresh_token(RefreshToken {
+            id: 0,
+            user_id: user.id,
+            family: generate_token(),
+            token_hash: hash_token(&token),
+            expires_at: now - chrono::Duration::minutes(1),
+            used_at: None,
+            revoked_at: None,
+            created_at: now - chrono::Duration::days(30),
+        })
+        .await
+        .unwrap();
+
+    let err = service.refresh_tokens(token).await.err().unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenExpired));
+}
+
+#[sqlx_macros::test]
+async fn test_unknown_refresh_token() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let service = TokenService::new(Arc::new(PgDIContainer { db }), key_ring());
+
+    let err = service
+        .refresh_tokens(generate_token())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::UnAuthenticated));
+}
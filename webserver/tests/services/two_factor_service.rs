@@ -0,0 +1,168 @@
+use crate::fixtures::{create_user, get_db};
+use rsapps_webserver::auth::key_ring::KeyRing;
+use rsapps_webserver::auth::totp;
+use rsapps_webserver::auth::Claims;
+use rsapps_webserver::domains::entities::audit_event::RequestMetadata;
+use rsapps_webserver::domains::errors::ErrorCode;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::services::token_service::TokenService;
+use rsapps_webserver::services::two_factor_service::TwoFactorService;
+use sqlx::Postgres;
+use std::sync::Arc;
+
+fn two_factor_service(db: sqlx::PgPool) -> TwoFactorService {
+    let di_container = Arc::new(PgDIContainer { db });
+    let key_ring = Arc::new(KeyRing::from_secret("testsecret"));
+    TwoFactorService::new(
+        di_container.clone(),
+        key_ring.clone(),
+        TokenService::new(di_container, key_ring),
+    )
+}
+
+/// The code of a time step, steps next to the current one are accepted too.
+fn code_at(secret: &str, step: i64) -> String {
+    totp::code(&totp::decode_base32(secret).unwrap(), step)
+}
+
+fn current_step() -> i64 {
+    totp::step(chrono::Utc::now().timestamp())
+}
+
+#[sqlx_macros::test]
+async fn test_login_with_totp() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = two_factor_service(db);
+
+    let result = service
+        .start_login(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+    assert!(result.tokens.is_some());
+    assert!(result.challenge_token.is_none());
+
+    let enrollment = service.enroll_totp(user.id).await.unwrap();
+    assert!(enrollment
+        .otpauth_uri
+        .starts_with(&format!("otpauth://totp/rsapps:{}?", user.username)));
+    assert!(!service.is_enabled(user.id).await.unwrap());
+    let err = service
+        .confirm_totp(user.id, "000000".to_owned())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::UnAuthenticated));
+    let step = current_step();
+    let recovery_codes = service
+        .confirm_totp(user.id, code_at(&enrollment.secret, step))
+        .await
+        .unwrap();
+    assert_eq!(10, recovery_codes.len());
+    assert!(service.is_enabled(user.id).await.unwrap());
+    let err = service.enroll_totp(user.id).await.err().unwrap();
+    assert!(matches!(err.code, ErrorCode::Conflict));
+
+    let result = service
+        .start_login(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+    assert!(result.tokens.is_none());
+    let challenge_token = result.challenge_token.unwrap();
+
+    // The code of the confirmation can't be replayed.
+    let err = service
+        .verify_second_factor(
+            challenge_token.to_owned(),
+            code_at(&enrollment.secret, step),
+            &RequestMetadata::default(),
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::UnAuthenticated));
+    service
+        .verify_second_factor(
+            challenge_token.to_owned(),
+            code_at(&enrollment.secret, step + 1),
+            &RequestMetadata::default(),
+        )
+        .await
+        .unwrap();
+
+    // A challenge isn't an access token.
+    assert!(KeyRing::from_secret("testsecret")
+        .decode::<Claims>(&challenge_token)
+        .is_err());
+}
+
+#[sqlx_macros::test]
+async fn test_recovery_codes() {
+    let db = get_db::<Postgres>().await.unwrap();
+    let user = create_user(&db).await.unwrap();
+    let service = two_factor_service(db);
+    let enrollment = service.enroll_totp(user.id).await.unwrap();
+    let recovery_codes = service
+        .confirm_totp(user.id, code_at(&enrollment.secret, current_step()))
+        .await
+        .unwrap();
+    let challenge_token = service
+        .start_login(user.id, &RequestMetadata::default())
+        .await
+        .unwrap()
+        .challenge_token
+        .unwrap();
+
+    // Recovery codes are accepted in upper case, and only once.
+    let code = recovery_codes[0].to_uppercase();
+    service
+        .verify_second_factor(
+            challenge_token.to_owned(),
+            code.to_owned(),
+            &RequestMetadata::default(),
+        )
+        .await
+        .unwrap();
+    let err = service
+        .verify_second_factor(
+            challenge_token.to_owned(),
+            code,
+            &RequestMetadata::default(),
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::UnAuthenticated));
+
+    // Regenerating voids the old codes.
+    let regenerated = service
+        .regenerate_recovery_codes(user.id, recovery_codes[1].to_owned())
+        .await
+        .unwrap();
+    let err = service
+        .verify_second_factor(
+            challenge_token.to_owned(),
+            recovery_codes[2].to_owned(),
+            &RequestMetadata::default(),
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::UnAuthenticated));
+
+    assert!(service
+        .disable_totp(user.id, regenerated[0].to_owned())
+        .await
+        .unwrap());
+    assert!(!service.is_enabled(user.id).await.unwrap());
+    let err = service
+        .verify_second_factor(
+            challenge_token,
+            regenerated[1].to_owned(),
+            &RequestMetadata::default(),
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+}
@@ -0,0 +1,649 @@
+use crate::fixtures::{
+    create_user, get_db, password_hasher, password_policy, unique_email, unique_username,
+};
+use rsapps_webserver::auth::key_ring::KeyRing;
+use rsapps_webserver::auth::Claims;
+use rsapps_webserver::domains::entities::audit_event::RequestMetadata;
+use rsapps_webserver::domains::entities::invite_code::RegistrationMode;
+use rsapps_webserver::domains::errors::{ApplicationError, ErrorCode};
+use rsapps_webserver::domains::repositories::user_repository::UserRepository;
+use rsapps_webserver::infrastructures::di_container::PgDIContainer;
+use rsapps_webserver::infrastructures::mailers::outbox_mailer::OutboxMailer;
+use rsapps_webserver::infrastructures::repositories::user_repository::PostgreSQLUserRepository;
+use rsapps_webserver::services::email_service::EmailService;
+use rsapps_webserver::services::invite_service::InviteService;
+use rsapps_webserver::services::todo_service::TodoService;
+use rsapps_webserver::services::token_service::TokenService;
+use rsapps_webserver::services::user_service::UserService;
+use sqlx::Postgres;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const STRONG_PASSWORD: &str = "violet crane 48 harbor";
+
+async fn user_service(outbox: &OutboxMailer) -> UserService {
+    user_service_with_mode(outbox, RegistrationMode::Open).await
+}
+
+async fn user_service_with_mode(
+    outbox: &OutboxMailer,
+    registration_mode: RegistrationMode,
+) -> UserService {
+    let db = get_db::<Postgres>().await.unwrap();
+    UserService::new(
+        Arc::new(PgDIContainer { db }),
+        Arc::new(KeyRing::from_secret("testsecret")),
+        EmailService::new(Box::new(outbox.clone()), "http://localhost:8080".to_owned()),
+        password_hasher(),
+        password_policy(),
+        registration_mode,
+    )
+}
+
+#[sqlx_macros::test]
+async fn test_email_login_needs_verification() {
+    let outbox = OutboxMailer::default();
+    let service = user_service(&outbox).await;
+    let email = unique_email();
+    let user = service
+        .clone()
+        .sign_up(
+            unique_username(),
+            STRONG_PASSWORD.to_owned(),
+            Some(email.to_uppercase()),
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(Some(email.to_owned()), user.email);
+    assert_eq!(None, user.email_verified_at);
+
+    let err = service
+        .clone()
+        .get_user_by_email(email.to_owned(), STRONG_PASSWORD.to_owned())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::EmailNotVerified));
+
+    let mails = outbox.mails.lock().unwrap().clone();
+    assert_eq!(1, mails.len());
+    assert_eq!(email, mails[0].to);
+    let token = mails[0]
+        .body
+        .split("verify_email=")
+        .nth(1)
+        .unwrap()
+        .trim()
+        .to_owned();
+    let verified = service.clone().verify_email(token).await.unwrap();
+    assert_eq!(user.id, verified.id);
+    assert!(verified.email_verified_at.is_some());
+
+    let logged_in = service
+        .clone()
+        .get_user_by_email(email, STRONG_PASSWORD.to_owned())
+        .await
+        .unwrap();
+    assert_eq!(user.id, logged_in.id);
+
+    let err = service
+        .send_email_verification(user.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::Conflict));
+}
+
+#[sqlx_macros::test]
+async fn test_sign_up_with_duplicated_email() {
+    let outbox = OutboxMailer::default();
+    let service = user_service(&outbox).await;
+    let email = unique_email();
+    service
+        .clone()
+        .sign_up(
+            unique_username(),
+            STRONG_PASSWORD.to_owned(),
+            Some(email.to_owned()),
+            None,
+        )
+        .await
+        .unwrap();
+
+    let err = service
+        .sign_up(
+            unique_username(),
+            STRONG_PASSWORD.to_owned(),
+            Some(email),
+            None,
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::Conflict));
+    assert_eq!(1, outbox.mails.lock().unwrap().len());
+}
+
+#[sqlx_macros::test]
+async fn test_sign_up_without_email() {
+    let outbox = OutboxMailer::default();
+    let service = user_service(&outbox).await;
+    let user = service
+        .clone()
+        .sign_up(unique_username(), STRONG_PASSWORD.to_owned(), None, None)
+        .await
+        .unwrap();
+    assert_eq!(None, user.email);
+    assert!(outbox.mails.lock().unwrap().is_empty());
+
+    let err = service
+        .send_email_verification(user.id)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::BadRequest));
+}
+
+#[sqlx_macros::test]
+async fn test_sign_up_rejects_weak_password() {
+    let outbox = OutboxMailer::default();
+    let service = user_service(&outbox).await;
+    let username = unique_username();
+    let err = service
+        .clone()
+        .sign_up(username.to_owned(), format!("{}!", username), None, None)
+        .await
+        .err()
+        .unwrap();
+    let rules: Vec<String> = match err.code {
+        ErrorCode::ValidationFailed(violations) => violations
+            .into_iter()
+            .map(|violation| violation.rule)
+            .collect(),
+        code => panic!("unexpected code {:?}", code),
+    };
+    assert_eq!(vec!["strength"], rules);
+
+    let violations = service.password_violations(&username, None, "");
+    assert_eq!(2, violations.len());
+    assert!(service
+        .password_violations(&username, None, STRONG_PASSWORD)
+        .is_empty());
+}
+
+#[sqlx_macros::test]
+async fn test_sign_up_with_invite_code() {
+    let outbox = OutboxMailer::default();
+    let service = user_service_with_mode(&outbox, RegistrationMode::InviteOnly).await;
+    let db = get_db::<Postgres>().await.unwrap();
+    let invite_service = InviteService::new(Arc::new(PgDIContainer { db }));
+    let created = invite_service
+        .create_invite_code(None, 2, None)
+        .await
+        .unwrap();
+
+    let err = service
+        .clone()
+        .sign_up(unique_username(), STRONG_PASSWORD.to_owned(), None, None)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::Forbidden));
+    let err = service
+        .clone()
+        .sign_up(
+            unique_username(),
+            STRONG_PASSWORD.to_owned(),
+            None,
+            Some("unknown".to_owned()),
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::Forbidden));
+
+    let mut user_ids = vec![];
+    for _ in 0..2 {
+        let user = service
+            .clone()
+            .sign_up(
+                unique_username(),
+                STRONG_PASSWORD.to_owned(),
+                None,
+                Some(created.code.to_owned()),
+            )
+            .await
+            .unwrap();
+        user_ids.push(user.id);
+    }
+    let username = unique_username();
+    let err = service
+        .clone()
+        .sign_up(
+            username.to_owned(),
+            STRONG_PASSWORD.to_owned(),
+            None,
+            Some(created.code.to_owned()),
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::Forbidden));
+    let repository = PostgreSQLUserRepository {
+        db: get_db::<Postgres>().await.unwrap(),
+    };
+    assert!(repository
+        .get_user_by_username(username)
+        .await
+        .unwrap()
+        .is_none());
+
+    let redemptions = invite_service
+        .get_invite_redemptions(created.invite_code.id)
+        .await
+        .unwrap();
+    assert_eq!(
+        user_ids,
+        redemptions
+            .iter()
+            .map(|redemption| redemption.user_id)
+            .collect::<Vec<i32>>()
+    );
+}
+
+#[sqlx_macros::test]
+async fn test_sign_up_with_revoked_invite_code() {
+    let outbox = OutboxMailer::default();
+    let service = user_service_with_mode(&outbox, RegistrationMode::Open).await;
+    let db = get_db::<Postgres>().await.unwrap();
+    let invite_service = InviteService::new(Arc::new(PgDIContainer { db }));
+    let created = invite_service
+        .create_invite_code(None, 1, None)
+        .await
+        .unwrap();
+    invite_service
+        .revoke_invite_code(created.invite_code.id)
+        .await
+        .unwrap();
+
+    let err = service
+        .clone()
+        .sign_up(
+            unique_username(),
+            STRONG_PASSWORD.to_owned(),
+            None,
+            Some(created.code),
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::Forbidden));
+}
+
+#[sqlx_macros::test]
+async fn test_sign_up_when_registration_is_closed() {
+    let outbox = OutboxMailer::default();
+    let service = user_service_with_mode(&outbox, RegistrationMode::Closed).await;
+    let db = get_db::<Postgres>().await.unwrap();
+    let invite_service = InviteService::new(Arc::new(PgDIContainer { db }));
+    let created = invite_service
+        .create_invite_code(None, 1, None)
+        .await
+        .unwrap();
+
+    let err = service
+        .clone()
+        .sign_up(
+            unique_username(),
+            STRONG_PASSWORD.to_owned(),
+            None,
+            Some(created.code),
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::Forbidden));
+}
+
+#[sqlx_macros::test]
+async fn test_verify_email_rejects_invalid_token() {
+    let service = user_service(&OutboxMailer::default()).await;
+    let err = service
+        .verify_email("invalid".to_owned())
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::UnAuthenticated));
+}
+
+/// The error of a failed login, and the median time it took.
+async fn failed_login(service: &UserService, username: &str) -> (ApplicationError, Duration) {
+    let mut times = vec![];
+    let mut error = None;
+    // The first round warms up the dummy hash.
+    for _ in 0..6 {
+        let started = Instant::now();
+        let err = service
+            .clone()
+            .get_user_by_username(username.to_owned(), "wrong password".to_owned())
+            .await
+            .err()
+            .unwrap();
+        times.push(started.elapsed());
+        error = Some(err);
+    }
+    times.remove(0);
+    times.sort();
+    (error.unwrap(), times[times.len() / 2])
+}
+
+#[sqlx_macros::test]
+async fn test_login_failures_look_the_same() {
+    let outbox = OutboxMailer::default();
+    let service = user_service(&outbox).await;
+    let user = service
+        .clone()
+        .sign_up(unique_username(), STRONG_PASSWORD.to_owned(), None, None)
+        .await
+        .unwrap();
+    let db = get_db::<Postgres>().await.unwrap();
+    let provider_user = create_user(&db).await.unwrap();
+
+    let (wrong_password, wrong_password_time) = failed_login(&service, &user.username).await;
+    let (missing_user, missing_user_time) = failed_login(&service, &unique_username()).await;
+    let (no_password, no_password_time) = failed_login(&service, &provider_user.username).await;
+
+    for err in &[&wrong_password, &missing_user, &no_password] {
+        assert!(matches!(err.code, ErrorCode::InvalidCredentials));
+        assert_eq!(wrong_password.message, err.message);
+    }
+    // Without the dummy verification a missing user would answer in a fraction of the time.
+    for time in &[missing_user_time, no_password_time] {
+        let ratio = time.as_secs_f64() / wrong_password_time.as_secs_f64();
+        assert!(
+            ratio > 0.5 && ratio < 2.0,
+            "{:?} vs {:?}",
+            time,
+            wrong_password_time
+        );
+    }
+}
+
+#[sqlx_macros::test]
+async fn test_find_user_id() {
+    let outbox = OutboxMailer::default();
+    let service = user_service(&outbox).await;
+    let email = unique_email();
+    let user = service
+        .clone()
+        .sign_up(
+            unique_username(),
+            STRONG_PASSWORD.to_owned(),
+            Some(email.to_owned()),
+            None,
+        )
+        .await
+        .unwrap();
+
+    // The username and the email lead to the same user, verified or not.
+    assert_eq!(
+        Some(user.id),
+        service
+            .clone()
+            .find_user_id_by_username(user.username.to_owned())
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        Some(user.id),
+        service
+            .clone()
+            .find_user_id_by_email(format!(" {} ", email.to_uppercase()))
+            .await
+            .unwrap()
+    );
+    assert_eq!(
+        None,
+        service
+            .clone()
+            .find_user_id_by_email(unique_email())
+            .await
+            .unwrap()
+    );
+}
+
+#[sqlx_macros::test]
+async fn test_login_rehashes_legacy_password() {
+    let outbox = OutboxMailer::default();
+    let service = user_service(&outbox).await;
+    let db = get_db::<Postgres>().await.unwrap();
+    let repository = PostgreSQLUserRepository { db: db.clone() };
+    let user = create_user(&db).await.unwrap();
+    repository
+        .update_password(
+            user.id,
+            bcrypt::hash("password", 4).unwrap(),
+            chrono::Utc::now(),
+        )
+        .await
+        .unwrap();
+
+    service
+        .clone()
+        .get_user_by_username(user.username.to_owned(), "password".to_owned())
+        .await
+        .unwrap();
+    let rehashed = repository
+        .get_user_by_id(user.id)
+        .await
+        .unwrap()
+        .unwrap()
+        .password_hash
+        .unwrap();
+    assert!(rehashed.starts_with("$argon2id$"));
+
+    // The new hash works, and isn't replaced again.
+    service
+        .clone()
+        .get_user_by_username(user.username.to_owned(), "password".to_owned())
+        .await
+        .unwrap();
+    let unchanged = repository
+        .get_user_by_id(user.id)
+        .await
+        .unwrap()
+        .unwrap()
+        .password_hash
+        .unwrap();
+    assert_eq!(rehashed, unchanged);
+}
+
+fn family(access_token: &str) -> String {
+    KeyRing::from_secret("testsecret")
+        .decode::<Claims>(access_token)
+        .unwrap()
+        .fam
+        .unwrap()
+}
+
+#[sqlx_macros::test]
+async fn test_change_password() {
+    let outbox = OutboxMailer::default();
+    let service = user_service(&outbox).await;
+    let db = get_db::<Postgres>().await.unwrap();
+    let token_service = TokenService::new(
+        Arc::new(PgDIContainer { db }),
+        Arc::new(KeyRing::from_secret("testsecret")),
+    );
+    let user = service
+        .clone()
+        .sign_up(unique_username(), STRONG_PASSWORD.to_owned(), None, None)
+        .await
+        .unwrap();
+    let current = token_service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+    let other = token_service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+
+    let err = service
+        .clone()
+        .change_password(
+            user.id,
+            Some(family(&current.access_token)),
+            "wrong password".to_owned(),
+            "amber kettle 92 drift".to_owned(),
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::InvalidCredentials));
+    let err = service
+        .clone()
+        .change_password(
+            user.id,
+            Some(family(&current.access_token)),
+            STRONG_PASSWORD.to_owned(),
+            "password".to_owned(),
+        )
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::ValidationFailed(_)));
+
+    service
+        .clone()
+        .change_password(
+            user.id,
+            Some(family(&current.access_token)),
+            STRONG_PASSWORD.to_owned(),
+            "amber kettle 92 drift".to_owned(),
+        )
+        .await
+        .unwrap();
+    service
+        .clone()
+        .get_user_by_username(user.username, "amber kettle 92 drift".to_owned())
+        .await
+        .unwrap();
+    // The other session is logged out, the one which changed the password stays.
+    token_service
+        .ensure_family_active(family(&current.access_token))
+        .await
+        .unwrap();
+    let err = token_service
+        .ensure_family_active(family(&other.access_token))
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenRevoked));
+}
+
+#[sqlx_macros::test]
+async fn test_update_profile() {
+    let outbox = OutboxMailer::default();
+    let service = user_service(&outbox).await;
+    let user = service
+        .clone()
+        .sign_up(unique_username(), STRONG_PASSWORD.to_owned(), None, None)
+        .await
+        .unwrap();
+    let other = service
+        .clone()
+        .sign_up(unique_username(), STRONG_PASSWORD.to_owned(), None, None)
+        .await
+        .unwrap();
+
+    let err = service
+        .clone()
+        .update_profile(user.id, Some(other.username), None)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::Conflict));
+    let err = service
+        .clone()
+        .update_profile(user.id, Some("a".repeat(17)), None)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::ValidationFailed(_)));
+
+    let username = unique_username();
+    let email = unique_email();
+    let updated = service
+        .clone()
+        .update_profile(user.id, Some(username.to_owned()), Some(email.to_owned()))
+        .await
+        .unwrap();
+    assert_eq!(username, updated.username);
+    assert_eq!(Some(email.to_owned()), updated.email);
+    assert_eq!(None, updated.email_verified_at);
+    assert_eq!(email, outbox.mails.lock().unwrap().last().unwrap().to);
+
+    // Leaving the fields out keeps them.
+    let unchanged = service
+        .clone()
+        .update_profile(user.id, None, None)
+        .await
+        .unwrap();
+    assert_eq!(username, unchanged.username);
+    assert_eq!(Some(email), unchanged.email);
+}
+
+#[sqlx_macros::test]
+async fn test_delete_account() {
+    let outbox = OutboxMailer::default();
+    let service = user_service(&outbox).await;
+    let db = get_db::<Postgres>().await.unwrap();
+    let di_container = Arc::new(PgDIContainer { db: db.clone() });
+    let token_service = TokenService::new(
+        di_container.clone(),
+        Arc::new(KeyRing::from_secret("testsecret")),
+    );
+    let todo_service = TodoService::new(di_container);
+    let user = service
+        .clone()
+        .sign_up(unique_username(), STRONG_PASSWORD.to_owned(), None, None)
+        .await
+        .unwrap();
+    todo_service
+        .create_todo("todo".to_owned(), user.id, None, None, None)
+        .await
+        .unwrap();
+    let session = token_service
+        .issue_tokens(user.id, &RequestMetadata::default())
+        .await
+        .unwrap();
+
+    let err = service
+        .clone()
+        .delete_account(user.id, None)
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::InvalidCredentials));
+
+    assert!(service
+        .clone()
+        .delete_account(user.id, Some(STRONG_PASSWORD.to_owned()))
+        .await
+        .unwrap());
+    let repository = PostgreSQLUserRepository { db };
+    assert!(repository.get_user_by_id(user.id).await.unwrap().is_none());
+    assert!(todo_service
+        .get_all_todos(user.id)
+        .await
+        .unwrap()
+        .is_empty());
+    let err = token_service
+        .ensure_family_active(family(&session.access_token))
+        .await
+        .err()
+        .unwrap();
+    assert!(matches!(err.code, ErrorCode::TokenRevoked));
+}
@@ -1,4 +1,4 @@
-use crate::utils::{request, FetchError};
+use crate::utils::{request, FetchError, Tokens};
 use graphql_client::GraphQLQuery;
 use wasm_bindgen::prelude::*;
 
@@ -6,22 +6,89 @@ use wasm_bindgen::prelude::*;
 #[graphql(schema_path = "gql/schema.json", query_path = "gql/sign_up.graphql")]
 pub struct SignUpUser;
 
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/password_violations.graphql"
+)]
+pub struct PasswordViolations;
+
 #[derive(GraphQLQuery)]
 #[graphql(schema_path = "gql/schema.json", query_path = "gql/login.graphql")]
 pub struct Login;
 
-pub async fn sign_up(username: String, password: String) -> Result<String, FetchError> {
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/verify_second_factor.graphql"
+)]
+pub struct VerifySecondFactor;
+
+/// A login either succeeds or waits for the second factor.
+pub enum LoginResult {
+    Tokens(Tokens),
+    Challenge(String),
+}
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/login_providers.graphql"
+)]
+pub struct LoginProviders;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/request_password_reset.graphql"
+)]
+pub struct RequestPasswordReset;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/request_magic_link.graphql"
+)]
+pub struct RequestMagicLink;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/redeem_magic_link.graphql"
+)]
+pub struct RedeemMagicLink;
+
+pub async fn sign_up(
+    username: String,
+    password: String,
+    email: Option<String>,
+    invite_code: Option<String>,
+) -> Result<Tokens, FetchError> {
     let request_body = SignUpUser::build_query(sign_up_user::Variables {
-        user: sign_up_user::NewUser { username, password },
+        user: sign_up_user::NewUser {
+            username,
+            password,
+            email,
+        },
+        invite_code,
     });
     let resp = request::<sign_up_user::Variables>(request_body).await?;
 
     match resp.into_serde::<graphql_client::Response<sign_up_user::ResponseData>>() {
         Ok(data) => match data.data {
-            Some(data) => Ok(data.sign_up),
+            Some(data) => Ok(Tokens {
+                access_token: data.sign_up.access_token,
+                refresh_token: data.sign_up.refresh_token,
+            }),
             None => {
-                return Err(FetchError {
-                    err: JsValue::from_str("failed to sign up user"),
+                let violations = violation_messages(&data.errors.unwrap_or_default());
+                let message = if violations.is_empty() {
+                    "failed to sign up user".to_owned()
+                } else {
+                    violations.join("\n")
+                };
+                Err(FetchError {
+                    err: JsValue::from_str(&message),
                 })
             }
         },
@@ -31,21 +98,157 @@ pub async fn sign_up(username: String, password: String) -> Result<String, Fetch
     }
 }
 
-pub async fn login_with_username(username: String, password: String) -> Result<String, FetchError> {
+/// Messages of the rules the password breaks, empty when it is fine.
+pub async fn password_violations(
+    username: String,
+    email: Option<String>,
+    password: String,
+) -> Result<Vec<String>, FetchError> {
+    let request_body = PasswordViolations::build_query(password_violations::Variables {
+        username,
+        email,
+        password,
+    });
+    let resp = request::<password_violations::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<password_violations::ResponseData>>() {
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) => Ok(data
+            .password_violations
+            .into_iter()
+            .map(|violation| violation.message)
+            .collect()),
+        _ => Err(FetchError {
+            err: JsValue::from_str("failed to check password"),
+        }),
+    }
+}
+
+/// Messages of the rules a rejected input broke, the server lists them in the error extensions.
+fn violation_messages(errors: &[graphql_client::Error]) -> Vec<String> {
+    errors
+        .iter()
+        .filter_map(|error| error.extensions.as_ref()?.get("violations")?.as_array())
+        .flatten()
+        .filter_map(|violation| Some(violation.get("message")?.as_str()?.to_owned()))
+        .collect()
+}
+
+pub async fn login_with_username(
+    username: String,
+    password: String,
+) -> Result<LoginResult, FetchError> {
     let request_body = Login::build_query(login::Variables { username, password });
     let resp = request::<login::Variables>(request_body).await?;
 
     match resp.into_serde::<graphql_client::Response<login::ResponseData>>() {
-        Ok(data) => match data.data {
-            Some(data) => Ok(data.login),
-            None => {
-                return Err(FetchError {
-                    err: JsValue::from_str("failed to login up user"),
-                })
-            }
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) => match (data.login.tokens, data.login.challenge_token) {
+            (Some(tokens), _) => Ok(LoginResult::Tokens(Tokens {
+                access_token: tokens.access_token,
+                refresh_token: tokens.refresh_token,
+            })),
+            (None, Some(challenge_token)) => Ok(LoginResult::Challenge(challenge_token)),
+            (None, None) => Err(FetchError {
+                err: JsValue::from_str("failed to login up user"),
+            }),
         },
-        Err(_) => Err(FetchError {
+        _ => Err(FetchError {
             err: JsValue::from_str("failed to login up user"),
         }),
     }
 }
+
+pub async fn verify_second_factor(
+    challenge_token: String,
+    code: String,
+) -> Result<Tokens, FetchError> {
+    let request_body = VerifySecondFactor::build_query(verify_second_factor::Variables {
+        challenge_token,
+        code,
+    });
+    let resp = request::<verify_second_factor::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<verify_second_factor::ResponseData>>() {
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) => Ok(Tokens {
+            access_token: data.verify_second_factor.access_token,
+            refresh_token: data.verify_second_factor.refresh_token,
+        }),
+        _ => Err(FetchError {
+            err: JsValue::from_str("authentication code is invalid"),
+        }),
+    }
+}
+
+pub async fn get_login_providers() -> Result<Vec<String>, FetchError> {
+    let request_body = LoginProviders::build_query(login_providers::Variables {});
+    let resp = request::<login_providers::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<login_providers::ResponseData>>() {
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) => Ok(data.login_providers),
+        _ => Err(FetchError {
+            err: JsValue::from_str("failed to fetch login providers"),
+        }),
+    }
+}
+
+pub async fn request_password_reset(email: String) -> Result<(), FetchError> {
+    let request_body =
+        RequestPasswordReset::build_query(request_password_reset::Variables { email });
+    let resp = request::<request_password_reset::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<request_password_reset::ResponseData>>() {
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) if data.request_password_reset => Ok(()),
+        _ => Err(FetchError {
+            err: JsValue::from_str("failed to request password reset"),
+        }),
+    }
+}
+
+pub async fn request_magic_link(email: String) -> Result<(), FetchError> {
+    let request_body = RequestMagicLink::build_query(request_magic_link::Variables { email });
+    let resp = request::<request_magic_link::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<request_magic_link::ResponseData>>() {
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) if data.request_magic_link => Ok(()),
+        _ => Err(FetchError {
+            err: JsValue::from_str("failed to request login link"),
+        }),
+    }
+}
+
+pub async fn redeem_magic_link(token: String) -> Result<LoginResult, FetchError> {
+    let request_body = RedeemMagicLink::build_query(redeem_magic_link::Variables { token });
+    let resp = request::<redeem_magic_link::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<redeem_magic_link::ResponseData>>() {
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) => match (
+            data.redeem_magic_link.tokens,
+            data.redeem_magic_link.challenge_token,
+        ) {
+            (Some(tokens), _) => Ok(LoginResult::Tokens(Tokens {
+                access_token: tokens.access_token,
+                refresh_token: tokens.refresh_token,
+            })),
+            (None, Some(challenge_token)) => Ok(LoginResult::Challenge(challenge_token)),
+            (None, None) => Err(FetchError {
+                err: JsValue::from_str("failed to login with the link"),
+            }),
+        },
+        _ => Err(FetchError {
+            err: JsValue::from_str("failed to login, the link may be expired"),
+        }),
+    }
+}
@@ -1,6 +1,9 @@
-use crate::utils::FetchError;
+use crate::utils::{server_url, take_magic_link, FetchError, Tokens};
 use crate::{App, AppMessage};
-use gql::{login_with_username, sign_up};
+use gql::{
+    get_login_providers, login_with_username, password_violations, redeem_magic_link,
+    request_magic_link, request_password_reset, sign_up, verify_second_factor, LoginResult,
+};
 use yew::events::{FocusEvent, InputData};
 use yew::prelude::*;
 use yewtil::future::LinkFuture;
@@ -10,33 +13,55 @@ mod gql;
 pub struct FormState {
     username: String,
     password: String,
+    email: String,
+    invite_code: String,
+    code: String,
     is_sign_up: bool,
+    /// Rules the password breaks, shown while signing up.
+    password_feedback: Vec<String>,
 }
 
 pub enum LoginFetchState {
-    LoginSuccess(String),
+    LoginSuccess(Tokens),
+    SecondFactorRequired(String),
     Failed(FetchError),
 }
 
 pub enum LoadingState {
     Login,
     SignUp,
+    SecondFactor,
 }
 
 pub enum LoginMessage {
     SignUp,
     Login,
+    VerifyCode,
     Loading(LoadingState),
     Fetch(LoginFetchState),
     ChangeUsername(String),
     ChangePassword(String),
+    ChangeEmail(String),
+    ChangeInviteCode(String),
+    ChangeCode(String),
+    /// The rules a password breaks, checked as it is typed.
+    PasswordChecked(String, Vec<String>),
+    CancelSecondFactor,
     ToggleLogin,
     ToggleSignUp,
+    LoginProviders(Vec<String>),
+    ForgotPassword,
+    PasswordResetRequested,
+    RequestMagicLink,
+    MagicLinkRequested,
 }
 
 #[derive(Properties, Clone)]
 pub struct LoginAppProps {
     pub app_link: ComponentLink<App>,
+    /// A login with a provider which waits for the second factor.
+    #[prop_or_default]
+    pub challenge_token: Option<String>,
 }
 
 pub struct LoginApp {
@@ -44,6 +69,8 @@ pub struct LoginApp {
     state: FormState,
     link: ComponentLink<Self>,
     is_loading: bool,
+    providers: Vec<String>,
+    challenge_token: Option<String>,
 }
 
 impl Component for LoginApp {
@@ -51,15 +78,41 @@ impl Component for LoginApp {
     type Properties = LoginAppProps;
 
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        // Login with a provider is optional, so the form works without the list.
+        link.send_future(async {
+            LoginMessage::LoginProviders(get_login_providers().await.unwrap_or_default())
+        });
+        let challenge_token = props.challenge_token.to_owned();
+        // Opened from a login link, which logs in like a password would.
+        let magic_link = take_magic_link();
+        if let Some(token) = magic_link.to_owned() {
+            link.send_future(async move {
+                match redeem_magic_link(token).await {
+                    Ok(LoginResult::Tokens(tokens)) => {
+                        LoginMessage::Fetch(LoginFetchState::LoginSuccess(tokens))
+                    }
+                    Ok(LoginResult::Challenge(challenge_token)) => {
+                        LoginMessage::Fetch(LoginFetchState::SecondFactorRequired(challenge_token))
+                    }
+                    Err(err) => LoginMessage::Fetch(LoginFetchState::Failed(err)),
+                }
+            });
+        }
         Self {
             props,
             state: FormState {
                 username: "".to_string(),
                 password: "".to_string(),
+                email: "".to_string(),
+                invite_code: "".to_string(),
+                code: "".to_string(),
                 is_sign_up: false,
+                password_feedback: vec![],
             },
             link,
-            is_loading: false,
+            is_loading: magic_link.is_some(),
+            providers: vec![],
+            challenge_token,
         }
     }
 
@@ -75,12 +128,23 @@ impl Component for LoginApp {
                 self.link
                     .send_message(LoginMessage::Loading(LoadingState::Login));
             }
+            LoginMessage::VerifyCode => {
+                self.is_loading = true;
+                self.link
+                    .send_message(LoginMessage::Loading(LoadingState::SecondFactor));
+            }
             LoginMessage::Loading(LoadingState::SignUp) => {
                 let username = self.state.username.to_owned();
                 let password = self.state.password.to_owned();
+                // The email is optional, a verification link is sent when it is given.
+                let email =
+                    Some(self.state.email.trim().to_owned()).filter(|email| !email.is_empty());
+                // The invite code is only needed when registration is invite only.
+                let invite_code = Some(self.state.invite_code.trim().to_owned())
+                    .filter(|invite_code| !invite_code.is_empty());
                 self.link.send_future(async move {
-                    match sign_up(username, password).await {
-                        Ok(jwt) => LoginMessage::Fetch(LoginFetchState::LoginSuccess(jwt)),
+                    match sign_up(username, password, email, invite_code).await {
+                        Ok(tokens) => LoginMessage::Fetch(LoginFetchState::LoginSuccess(tokens)),
                         Err(err) => LoginMessage::Fetch(LoginFetchState::Failed(err)),
                     }
                 })
@@ -90,22 +154,64 @@ impl Component for LoginApp {
                 let password = self.state.password.to_owned();
                 self.link.send_future(async move {
                     match login_with_username(username, password).await {
-                        Ok(jwt) => LoginMessage::Fetch(LoginFetchState::LoginSuccess(jwt)),
+                        Ok(LoginResult::Tokens(tokens)) => {
+                            LoginMessage::Fetch(LoginFetchState::LoginSuccess(tokens))
+                        }
+                        Ok(LoginResult::Challenge(challenge_token)) => LoginMessage::Fetch(
+                            LoginFetchState::SecondFactorRequired(challenge_token),
+                        ),
                         Err(err) => LoginMessage::Fetch(LoginFetchState::Failed(err)),
                     }
                 })
             }
-            LoginMessage::Fetch(LoginFetchState::LoginSuccess(jwt)) => {
+            LoginMessage::Loading(LoadingState::SecondFactor) => {
+                let challenge_token = self.challenge_token.to_owned().unwrap_or_default();
+                let code = self.state.code.trim().to_owned();
+                self.link.send_future(async move {
+                    match verify_second_factor(challenge_token, code).await {
+                        Ok(tokens) => LoginMessage::Fetch(LoginFetchState::LoginSuccess(tokens)),
+                        Err(err) => LoginMessage::Fetch(LoginFetchState::Failed(err)),
+                    }
+                })
+            }
+            LoginMessage::Fetch(LoginFetchState::LoginSuccess(tokens)) => {
                 self.is_loading = false;
                 self.props
                     .app_link
-                    .send_message(AppMessage::Authenticated(jwt));
+                    .send_message(AppMessage::Authenticated(tokens));
+            }
+            LoginMessage::Fetch(LoginFetchState::SecondFactorRequired(challenge_token)) => {
+                self.is_loading = false;
+                self.challenge_token = Some(challenge_token);
             }
             LoginMessage::ChangeUsername(username) => {
                 self.state.username = username;
+                self.check_password();
             }
             LoginMessage::ChangePassword(password) => {
                 self.state.password = password;
+                self.check_password();
+            }
+            LoginMessage::ChangeEmail(email) => {
+                self.state.email = email;
+                self.check_password();
+            }
+            LoginMessage::ChangeInviteCode(invite_code) => {
+                self.state.invite_code = invite_code;
+            }
+            LoginMessage::PasswordChecked(password, violations) => {
+                // Answers to earlier keystrokes may arrive late.
+                if password != self.state.password {
+                    return false;
+                }
+                self.state.password_feedback = violations;
+            }
+            LoginMessage::ChangeCode(code) => {
+                self.state.code = code;
+            }
+            LoginMessage::CancelSecondFactor => {
+                self.challenge_token = None;
+                self.state.code = "".to_string();
             }
             LoginMessage::Fetch(LoginFetchState::Failed(err)) => {
                 self.is_loading = false;
@@ -116,9 +222,53 @@ impl Component for LoginApp {
             }
             LoginMessage::ToggleLogin => {
                 self.state.is_sign_up = false;
+                self.state.password_feedback = vec![];
             }
             LoginMessage::ToggleSignUp => {
                 self.state.is_sign_up = true;
+                self.check_password();
+            }
+            LoginMessage::LoginProviders(providers) => {
+                self.providers = providers;
+            }
+            LoginMessage::ForgotPassword => {
+                let window = yew::utils::window();
+                let email = match window.prompt_with_message("Enter the email of your account") {
+                    Ok(Some(email)) if !email.trim().is_empty() => email.trim().to_owned(),
+                    _ => return false,
+                };
+                self.link.send_future(async move {
+                    match request_password_reset(email).await {
+                        Ok(_) => LoginMessage::PasswordResetRequested,
+                        Err(err) => LoginMessage::Fetch(LoginFetchState::Failed(err)),
+                    }
+                })
+            }
+            LoginMessage::PasswordResetRequested => {
+                // The server doesn't tell whether the email is registered.
+                yew::utils::window()
+                    .alert_with_message("If the email is registered, a reset link is on its way.")
+                    .unwrap();
+            }
+            LoginMessage::RequestMagicLink => {
+                let window = yew::utils::window();
+                let email = match window.prompt_with_message("Enter the email of your account") {
+                    Ok(Some(email)) if !email.trim().is_empty() => email.trim().to_owned(),
+                    _ => return false,
+                };
+                self.link.send_future(async move {
+                    match request_magic_link(email).await {
+                        Ok(_) => LoginMessage::MagicLinkRequested,
+                        Err(err) => LoginMessage::Fetch(LoginFetchState::Failed(err)),
+                    }
+                })
+            }
+            LoginMessage::MagicLinkRequested => {
+                yew::utils::window()
+                    .alert_with_message(
+                        "If the email is registered and verified, a login link is on its way.",
+                    )
+                    .unwrap();
             }
         }
         true
@@ -132,7 +282,13 @@ impl Component for LoginApp {
         html! {
             <div class="login-page">
                 <div class="login-form">
-                    {self.render_login()}
+                    {
+                        if self.challenge_token.is_some() {
+                            self.render_second_factor()
+                        } else {
+                            self.render_login()
+                        }
+                    }
                 </div>
             </div>
         }
@@ -140,6 +296,26 @@ impl Component for LoginApp {
 }
 
 impl LoginApp {
+    /// Ask the server which rules the password breaks, the same ones sign up checks.
+    fn check_password(&mut self) {
+        if !self.state.is_sign_up {
+            return;
+        }
+        let password = self.state.password.to_owned();
+        if password.is_empty() {
+            self.state.password_feedback = vec![];
+            return;
+        }
+        let username = self.state.username.to_owned();
+        let email = Some(self.state.email.trim().to_owned()).filter(|email| !email.is_empty());
+        self.link.send_future(async move {
+            let violations = password_violations(username, email, password.to_owned())
+                .await
+                .unwrap_or_default();
+            LoginMessage::PasswordChecked(password, violations)
+        });
+    }
+
     fn render_login(&self) -> Html {
         let is_sign_up = self.state.is_sign_up;
         html! {
@@ -167,7 +343,43 @@ impl LoginApp {
                         required=true
                         oninput=self.link.callback(|data: InputData| LoginMessage::ChangePassword(data.value))
                     />
-                    <button type="submit" disabled=self.is_loading>
+                    {
+                        if is_sign_up && !self.state.password_feedback.is_empty() {
+                            html! {
+                                <ul class="password-feedback">
+                                    { for self.state.password_feedback.iter().map(|message| html! { <li>{ message }</li> }) }
+                                </ul>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                    {
+                        if is_sign_up {
+                            html! {
+                                <>
+                                    <input
+                                        type="email"
+                                        placeholder="email (optional)"
+                                        name="email"
+                                        oninput=self.link.callback(|data: InputData| LoginMessage::ChangeEmail(data.value))
+                                    />
+                                    <input
+                                        type="text"
+                                        placeholder="invite code (if you have one)"
+                                        name="invite_code"
+                                        oninput=self.link.callback(|data: InputData| LoginMessage::ChangeInviteCode(data.value))
+                                    />
+                                </>
+                            }
+                        } else {
+                            html! {}
+                        }
+                    }
+                    <button
+                        type="submit"
+                        disabled=self.is_loading || (is_sign_up && !self.state.password_feedback.is_empty())
+                    >
                         {
                             if self.is_loading {
                                 html! {
@@ -190,8 +402,86 @@ impl LoginApp {
                                 { if is_sign_up { "Login" } else { "Sign up" } }
                             </a>
                         </p>
+                        {
+                            if is_sign_up {
+                                html! {}
+                            } else {
+                                html! {
+                                    <p class="message">
+                                        <a
+                                            href="#"
+                                            onclick=self.link.callback(|_| LoginMessage::ForgotPassword)
+                                        >
+                                            {"Forgot password?"}
+                                        </a>
+                                    </p>
+                                    <p class="message">
+                                        <a
+                                            href="#"
+                                            onclick=self.link.callback(|_| LoginMessage::RequestMagicLink)
+                                        >
+                                            {"Email me a login link"}
+                                        </a>
+                                    </p>
+                                }
+                            }
+                        }
                 </form>
+                {self.render_providers()}
+            </div>
+        }
+    }
+
+    fn render_second_factor(&self) -> Html {
+        html! {
+            <form
+                onsubmit=self.link.callback(|e: FocusEvent| {
+                    e.prevent_default();
+                    LoginMessage::VerifyCode
+                })>
+                <p class="message">{"Enter the code of your authenticator app, or a recovery code."}</p>
+                <input
+                    type="text"
+                    placeholder="code"
+                    name="code"
+                    autocomplete="one-time-code"
+                    required=true
+                    oninput=self.link.callback(|data: InputData| LoginMessage::ChangeCode(data.value))
+                />
+                <button type="submit" disabled=self.is_loading>
+                    {
+                        if self.is_loading {
+                            html! {
+                                <i class="fa fa-spinner fa-spin"></i>
+                            }
+                        } else {
+                            html! { "Verify" }
+                        }
+                    }
+                </button>
+                <p class="message">
+                    <a href="#" onclick=self.link.callback(|_| LoginMessage::CancelSecondFactor)>
+                        {"Back to login"}
+                    </a>
+                </p>
+            </form>
+        }
+    }
+
+    fn render_providers(&self) -> Html {
+        html! {
+            <div class="login-providers">
+                {self.providers.iter().map(|provider| self.render_provider(provider)).collect::<Html>()}
             </div>
         }
     }
+
+    fn render_provider(&self, provider: &str) -> Html {
+        let href = format!("{}/auth/{}/login", server_url(), provider);
+        html! {
+            <a class="button" href=href>
+                { format!("Login with {}", provider) }
+            </a>
+        }
+    }
 }
@@ -6,18 +6,28 @@ extern crate strum;
 use yew::prelude::*;
 
 mod login;
+mod settings;
 mod todo;
 mod utils;
 use login::LoginApp;
+use settings::SettingsApp;
 use todo::TodoApp;
-use utils::{get_jwt, set_jwt};
+use utils::{
+    get_jwt, reset_password_from_link, set_tokens, take_login_redirect, verify_email_from_link,
+    LoginRedirect, Tokens,
+};
 
 pub enum AppMessage {
-    Authenticated(String),
+    Authenticated(Tokens),
+    ShowSettings,
+    ShowTodos,
 }
 
 pub struct App {
     is_login: bool,
+    /// The settings page is shown instead of the todos.
+    show_settings: bool,
+    challenge_token: Option<String>,
     link: ComponentLink<Self>,
 }
 
@@ -26,19 +36,34 @@ impl Component for App {
     type Properties = ();
 
     fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut challenge_token = None;
+        match take_login_redirect() {
+            Some(LoginRedirect::Tokens(tokens)) => set_tokens(tokens),
+            Some(LoginRedirect::Challenge(token)) => challenge_token = Some(token),
+            None => {}
+        }
+        wasm_bindgen_futures::spawn_local(verify_email_from_link());
+        wasm_bindgen_futures::spawn_local(reset_password_from_link());
         let mut is_login = false;
         if get_jwt().is_some() {
             is_login = true;
         }
-        Self { is_login, link }
+        Self {
+            is_login,
+            show_settings: false,
+            challenge_token,
+            link,
+        }
     }
 
     fn update(&mut self, msg: Self::Message) -> bool {
         match msg {
-            AppMessage::Authenticated(jwt) => {
-                set_jwt(jwt);
+            AppMessage::Authenticated(tokens) => {
+                set_tokens(tokens);
                 self.is_login = true;
             }
+            AppMessage::ShowSettings => self.show_settings = true,
+            AppMessage::ShowTodos => self.show_settings = false,
         }
         true
     }
@@ -49,10 +74,12 @@ impl Component for App {
 
     fn view(&self) -> Html {
         html! {
-            if self.is_login {
-                html! {<TodoApp />}
+            if self.is_login && self.show_settings {
+                html! {<SettingsApp app_link=self.link.clone() />}
+            } else if self.is_login {
+                html! {<TodoApp app_link=self.link.clone() />}
             } else {
-                html! {<LoginApp app_link=self.link.clone() />}
+                html! {<LoginApp app_link=self.link.clone() challenge_token=self.challenge_token.clone() />}
             }
         }
     }
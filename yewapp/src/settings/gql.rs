@@ -0,0 +1,53 @@
+use crate::utils::{request, FetchError};
+use graphql_client::GraphQLQuery;
+use wasm_bindgen::prelude::*;
+
+type DateTimeUtc = String;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/my_sessions.graphql"
+)]
+pub struct MySessions;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/revoke_session.graphql"
+)]
+pub struct RevokeSession;
+
+pub async fn fetch_my_sessions() -> Result<Vec<my_sessions::MySessionsMySessions>, FetchError> {
+    let request_body = MySessions::build_query(my_sessions::Variables {});
+    let resp = request::<my_sessions::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<my_sessions::ResponseData>>() {
+        Ok(data) => match data.data {
+            Some(data) => Ok(data.my_sessions),
+            None => Err(FetchError {
+                err: JsValue::from_str("failed to fetch sessions"),
+            }),
+        },
+        Err(_) => Err(FetchError {
+            err: JsValue::from_str("failed to fetch sessions"),
+        }),
+    }
+}
+
+pub async fn revoke_session(id: i64) -> Result<bool, FetchError> {
+    let request_body = RevokeSession::build_query(revoke_session::Variables { id });
+    let resp = request::<revoke_session::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<revoke_session::ResponseData>>() {
+        Ok(data) => match data.data {
+            Some(data) => Ok(data.revoke_session),
+            None => Err(FetchError {
+                err: JsValue::from_str(format!("failed to revoke session, id: {}", id).as_str()),
+            }),
+        },
+        Err(_) => Err(FetchError {
+            err: JsValue::from_str(format!("failed to revoke session, id: {}", id).as_str()),
+        }),
+    }
+}
@@ -0,0 +1,150 @@
+mod gql;
+
+use crate::utils::FetchError;
+use crate::{App, AppMessage};
+use gql::{fetch_my_sessions, my_sessions, revoke_session};
+use yew::prelude::*;
+use yewtil::future::LinkFuture;
+
+pub enum SettingsFetchState {
+    FetchSessionsSuccess(Vec<my_sessions::MySessionsMySessions>),
+    RevokeSessionSuccess(bool),
+    Failed(FetchError),
+}
+
+pub enum SettingsMessage {
+    Revoke(i64),
+    Fetch(SettingsFetchState),
+    Back,
+}
+
+#[derive(Properties, Clone)]
+pub struct SettingsAppProps {
+    pub app_link: ComponentLink<App>,
+}
+
+/// Where the user is logged in, with a way to sign the other devices out.
+pub struct SettingsApp {
+    props: SettingsAppProps,
+    sessions: Vec<my_sessions::MySessionsMySessions>,
+    link: ComponentLink<Self>,
+}
+
+impl Component for SettingsApp {
+    type Message = SettingsMessage;
+    type Properties = SettingsAppProps;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        link.send_future(fetch_sessions());
+        Self {
+            props,
+            sessions: vec![],
+            link,
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> bool {
+        match msg {
+            SettingsMessage::Revoke(id) => {
+                self.link.send_future(async move {
+                    match revoke_session(id).await {
+                        Ok(ret) => {
+                            SettingsMessage::Fetch(SettingsFetchState::RevokeSessionSuccess(ret))
+                        }
+                        Err(err) => SettingsMessage::Fetch(SettingsFetchState::Failed(err)),
+                    }
+                });
+                return false;
+            }
+            SettingsMessage::Fetch(SettingsFetchState::FetchSessionsSuccess(sessions)) => {
+                self.sessions = sessions;
+            }
+            SettingsMessage::Fetch(SettingsFetchState::RevokeSessionSuccess(_)) => {
+                self.link.send_future(fetch_sessions());
+                return false;
+            }
+            SettingsMessage::Fetch(SettingsFetchState::Failed(err)) => {
+                yew::web_sys::console::log_1(&err.err);
+                return false;
+            }
+            SettingsMessage::Back => {
+                self.props.app_link.send_message(AppMessage::ShowTodos);
+                return false;
+            }
+        }
+        true
+    }
+
+    fn change(&mut self, _props: Self::Properties) -> bool {
+        false
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <div id="todomvc-wrapper">
+                <section class="todoapp">
+                    <header class="header">
+                        <h1>{ "sessions" }</h1>
+                    </header>
+                    <section class="main">
+                        <ul class="todo-list">
+                            { self.sessions.iter().map(|session| self.render_session(session)).collect::<Html>() }
+                        </ul>
+                    </section>
+                </section>
+                <footer class="info">
+                    <p>{ "Sign out the devices you don't use anymore" }</p>
+                    <p><a href="#" onclick=self.link.callback(|_| SettingsMessage::Back)>{ "Back to todos" }</a></p>
+                </footer>
+            </div>
+        }
+    }
+}
+
+async fn fetch_sessions() -> SettingsMessage {
+    match fetch_my_sessions().await {
+        Ok(sessions) => SettingsMessage::Fetch(SettingsFetchState::FetchSessionsSuccess(sessions)),
+        Err(err) => SettingsMessage::Fetch(SettingsFetchState::Failed(err)),
+    }
+}
+
+/// Times come in RFC 3339, shown to the minute.
+fn format_time(time: &str) -> String {
+    format!(
+        "{} UTC",
+        time.chars().take(16).collect::<String>().replace('T', " ")
+    )
+}
+
+impl SettingsApp {
+    fn render_session(&self, session: &my_sessions::MySessionsMySessions) -> Html {
+        let id = session.id;
+        let mut description = format!(
+            "{}, {}, last seen {}, logged in {}",
+            session.device,
+            session.ip_address.as_deref().unwrap_or("unknown address"),
+            format_time(&session.last_seen_at),
+            format_time(&session.created_at),
+        );
+        if session.current {
+            description.push_str(" (this device)");
+        }
+        html! {
+            <li>
+                <div class="view">
+                    <label>{ description }</label>
+                    {
+                        // The current session is ended with Logout.
+                        if session.current {
+                            html! {<></>}
+                        } else {
+                            html! {
+                                <button class="destroy" title="Sign out" onclick=self.link.callback(move |_| SettingsMessage::Revoke(id)) />
+                            }
+                        }
+                    }
+                </div>
+            </li>
+        }
+    }
+}
@@ -50,8 +50,61 @@ pub struct ClearCompletedTodo;
 )]
 pub struct UpdateTodoQuery;
 
-pub async fn fetch_all_todos() -> Result<Vec<all_todos::AllTodosTodos>, FetchError> {
-    let request_body = AllTodos::build_query(all_todos::Variables {});
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/todo_lists.graphql",
+    response_derives = "Clone"
+)]
+pub struct TodoLists;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/create_todo_list.graphql"
+)]
+pub struct CreateTodoList;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/delete_todo_list.graphql"
+)]
+pub struct DeleteTodoList;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/todo_list_invitations.graphql",
+    response_derives = "Clone"
+)]
+pub struct TodoListInvitations;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/invite_to_todo_list.graphql"
+)]
+pub struct InviteToTodoList;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/accept_todo_list_invitation.graphql"
+)]
+pub struct AcceptTodoListInvitation;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/leave_todo_list.graphql"
+)]
+pub struct LeaveTodoList;
+
+pub async fn fetch_all_todos(
+    list_id: Option<i64>,
+) -> Result<Vec<all_todos::AllTodosTodos>, FetchError> {
+    let request_body = AllTodos::build_query(all_todos::Variables { list_id });
     let resp = request::<all_todos::Variables>(request_body).await?;
 
     match resp.into_serde::<graphql_client::Response<all_todos::ResponseData>>() {
@@ -67,9 +120,15 @@ pub async fn fetch_all_todos() -> Result<Vec<all_todos::AllTodosTodos>, FetchErr
 
 pub async fn create_todo(
     body: String,
+    list_id: Option<i64>,
 ) -> Result<create_new_todo::CreateNewTodoCreateTodo, FetchError> {
     let request_body = CreateNewTodo::build_query(create_new_todo::Variables {
-        todo: create_new_todo::NewTodo { body },
+        todo: create_new_todo::NewTodo {
+            body,
+            list_id,
+            start_at: None,
+            due_at: None,
+        },
     });
     let resp = request::<create_new_todo::Variables>(request_body).await?;
 
@@ -86,13 +145,23 @@ pub async fn create_todo(
     }
 }
 
+/// Replace the todo, dates without a value are sent as null and cleared.
 pub async fn update_todo(
     id: i64,
     body: String,
     complete: bool,
+    start_at: Option<DateTimeUtc>,
+    due_at: Option<DateTimeUtc>,
 ) -> Result<update_todo_query::UpdateTodoQueryUpdateTodo, FetchError> {
     let request_body = UpdateTodoQuery::build_query(update_todo_query::Variables {
-        todo: update_todo_query::UpdatedTodo { id, body, complete },
+        todo: update_todo_query::UpdatedTodo {
+            id,
+            body,
+            complete,
+            start_at,
+            due_at,
+            scope: None,
+        },
     });
     let resp = request::<update_todo_query::Variables>(request_body).await?;
 
@@ -126,8 +195,8 @@ pub async fn toggle_complete_todo(id: i64) -> Result<bool, FetchError> {
     }
 }
 
-pub async fn toggle_complete_all_todos() -> Result<bool, FetchError> {
-    let request_body = ToggleAllComplete::build_query(toggle_all_complete::Variables {});
+pub async fn toggle_complete_all_todos(list_id: Option<i64>) -> Result<bool, FetchError> {
+    let request_body = ToggleAllComplete::build_query(toggle_all_complete::Variables { list_id });
     let resp = request::<toggle_all_complete::Variables>(request_body).await?;
 
     match resp.into_serde::<graphql_client::Response<toggle_all_complete::ResponseData>>() {
@@ -160,8 +229,8 @@ pub async fn remove_todo(id: i64) -> Result<bool, FetchError> {
     }
 }
 
-pub async fn remove_completed_todo() -> Result<bool, FetchError> {
-    let request_body = ClearCompletedTodo::build_query(clear_completed_todo::Variables {});
+pub async fn remove_completed_todo(list_id: Option<i64>) -> Result<bool, FetchError> {
+    let request_body = ClearCompletedTodo::build_query(clear_completed_todo::Variables { list_id });
     let resp = request::<clear_completed_todo::Variables>(request_body).await?;
 
     match resp.into_serde::<graphql_client::Response<clear_completed_todo::ResponseData>>() {
@@ -176,3 +245,120 @@ pub async fn remove_completed_todo() -> Result<bool, FetchError> {
         }),
     }
 }
+
+pub async fn fetch_todo_lists() -> Result<Vec<todo_lists::TodoListsTodoLists>, FetchError> {
+    let request_body = TodoLists::build_query(todo_lists::Variables {});
+    let resp = request::<todo_lists::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<todo_lists::ResponseData>>() {
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) => Ok(data.todo_lists),
+        _ => Err(FetchError {
+            err: JsValue::from_str("failed to fetch todo lists"),
+        }),
+    }
+}
+
+pub async fn create_todo_list(name: String) -> Result<i64, FetchError> {
+    let request_body = CreateTodoList::build_query(create_todo_list::Variables { name });
+    let resp = request::<create_todo_list::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<create_todo_list::ResponseData>>() {
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) => Ok(data.create_todo_list.id),
+        _ => Err(FetchError {
+            err: JsValue::from_str("failed to create todo list"),
+        }),
+    }
+}
+
+pub async fn remove_todo_list(id: i64) -> Result<bool, FetchError> {
+    let request_body = DeleteTodoList::build_query(delete_todo_list::Variables { id });
+    let resp = request::<delete_todo_list::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<delete_todo_list::ResponseData>>() {
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) => Ok(data.delete_todo_list),
+        _ => Err(FetchError {
+            err: JsValue::from_str(format!("failed to delete todo list, id: {}", id).as_str()),
+        }),
+    }
+}
+
+pub async fn fetch_invitations(
+) -> Result<Vec<todo_list_invitations::TodoListInvitationsTodoListInvitations>, FetchError> {
+    let request_body = TodoListInvitations::build_query(todo_list_invitations::Variables {});
+    let resp = request::<todo_list_invitations::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<todo_list_invitations::ResponseData>>() {
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) => Ok(data.todo_list_invitations),
+        _ => Err(FetchError {
+            err: JsValue::from_str("failed to fetch invitations"),
+        }),
+    }
+}
+
+/// Invite the user to the list, as an editor or only to view it.
+pub async fn invite_to_todo_list(
+    list_id: i64,
+    username: String,
+    can_edit: bool,
+) -> Result<i64, FetchError> {
+    let role = if can_edit {
+        invite_to_todo_list::TodoListRole::EDITOR
+    } else {
+        invite_to_todo_list::TodoListRole::VIEWER
+    };
+    let request_body = InviteToTodoList::build_query(invite_to_todo_list::Variables {
+        list_id,
+        username,
+        role,
+    });
+    let resp = request::<invite_to_todo_list::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<invite_to_todo_list::ResponseData>>() {
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) => Ok(data.invite_to_todo_list.user_id),
+        _ => Err(FetchError {
+            err: JsValue::from_str("failed to invite to todo list"),
+        }),
+    }
+}
+
+pub async fn accept_invitation(list_id: i64) -> Result<i64, FetchError> {
+    let request_body =
+        AcceptTodoListInvitation::build_query(accept_todo_list_invitation::Variables { list_id });
+    let resp = request::<accept_todo_list_invitation::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<accept_todo_list_invitation::ResponseData>>() {
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) => Ok(data.accept_todo_list_invitation.id),
+        _ => Err(FetchError {
+            err: JsValue::from_str(
+                format!("failed to accept invitation, list: {}", list_id).as_str(),
+            ),
+        }),
+    }
+}
+
+/// Leave a shared list, or decline the invitation to it.
+pub async fn leave_todo_list(list_id: i64) -> Result<bool, FetchError> {
+    let request_body = LeaveTodoList::build_query(leave_todo_list::Variables { list_id });
+    let resp = request::<leave_todo_list::Variables>(request_body).await?;
+
+    match resp.into_serde::<graphql_client::Response<leave_todo_list::ResponseData>>() {
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) => Ok(data.leave_todo_list),
+        _ => Err(FetchError {
+            err: JsValue::from_str(format!("failed to leave todo list, id: {}", list_id).as_str()),
+        }),
+    }
+}
@@ -1,20 +1,31 @@
 mod gql;
 
-use crate::utils::{logout, FetchError};
+use crate::utils::{sign_out, FetchError};
+use crate::{App, AppMessage};
 use gql::{
-    all_todos, create_new_todo, create_todo, fetch_all_todos, remove_completed_todo, remove_todo,
+    accept_invitation, all_todos, create_new_todo, create_todo, create_todo_list, fetch_all_todos,
+    fetch_invitations, fetch_todo_lists, invite_to_todo_list, leave_todo_list,
+    remove_completed_todo, remove_todo, remove_todo_list, todo_list_invitations, todo_lists,
     toggle_complete_all_todos, toggle_complete_todo, update_todo, update_todo_query,
 };
 
 use strum::IntoEnumIterator;
 use wasm_bindgen::prelude::*;
-use yew::events::{InputData, KeyboardEvent};
+use yew::events::{ChangeData, InputData, KeyboardEvent};
 use yew::prelude::*;
 use yew::web_sys::HtmlInputElement;
 use yewtil::future::LinkFuture;
 
 pub enum TodoFetchState {
-    FetchAllTodosSuccess(Vec<all_todos::AllTodosTodos>),
+    /// Todos of the list with the id, the default list without one.
+    FetchAllTodosSuccess(Option<i64>, Vec<all_todos::AllTodosTodos>),
+    FetchTodoListsSuccess(Vec<todo_lists::TodoListsTodoLists>),
+    CreateTodoListSuccess(i64),
+    DeleteTodoListSuccess(bool),
+    FetchInvitationsSuccess(Vec<todo_list_invitations::TodoListInvitationsTodoListInvitations>),
+    InviteSuccess(i64),
+    AcceptInvitationSuccess(i64),
+    LeaveListSuccess(bool),
     CreateTodoSuccess(create_new_todo::CreateNewTodoCreateTodo),
     CompleteTodoSuccess(bool),
     CompleteAllTodoSuccess(bool),
@@ -36,6 +47,18 @@ struct TodoModel {
     body: String,
     complete: bool,
     editing: bool,
+    start_at: Option<String>,
+    due_at: Option<String>,
+    overdue: bool,
+    recurring: bool,
+    tags: Vec<TagModel>,
+}
+
+#[derive(Clone, PartialEq)]
+struct TagModel {
+    id: i64,
+    name: String,
+    color: String,
 }
 
 pub enum TodoMessage {
@@ -47,11 +70,21 @@ pub enum TodoMessage {
     Edit(usize),
     ChangeEditInput(usize, String),
     Update(usize),
+    /// A date picked as due, empty when it was cleared.
+    ChangeDue(usize, String),
     ToggleAll,
+    SelectList(i64),
+    AddList,
+    DeleteList,
+    ShareList,
+    LeaveList,
+    AcceptInvitation(i64),
+    DeclineInvitation(i64),
     SetFilter(Filter),
     CancelEdit(usize),
     Focus,
     Fetch(TodoFetchState),
+    Settings,
     Logout,
     None,
 }
@@ -84,12 +117,24 @@ impl Filter {
 #[derive(Clone)]
 pub struct TodoState {
     text: String,
+    /// Todo lists the user owns or shares, their default one first.
+    todo_lists: Vec<todo_lists::TodoListsTodoLists>,
+    /// Lists the user is invited to share.
+    invitations: Vec<todo_list_invitations::TodoListInvitationsTodoListInvitations>,
+    /// The shown list, the default list until the lists are fetched.
+    list_id: Option<i64>,
     list: Vec<TodoModel>,
     completed: i32,
     filter: Filter,
 }
 
+#[derive(Properties, Clone)]
+pub struct TodoAppProps {
+    pub app_link: ComponentLink<App>,
+}
+
 pub struct TodoApp {
+    props: TodoAppProps,
     state: TodoState,
     link: ComponentLink<Self>,
     edit_ref: NodeRef,
@@ -97,12 +142,16 @@ pub struct TodoApp {
 
 impl Component for TodoApp {
     type Message = TodoMessage;
-    type Properties = ();
+    type Properties = TodoAppProps;
 
-    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
         let app = TodoApp {
+            props,
             state: TodoState {
                 text: "".to_owned(),
+                todo_lists: vec![],
+                invitations: vec![],
+                list_id: None,
                 list: vec![],
                 completed: 0,
                 filter: Filter::All,
@@ -110,7 +159,9 @@ impl Component for TodoApp {
             link,
             edit_ref: NodeRef::default(),
         };
-        app.link.send_future(fetch_all());
+        app.link.send_future(fetch_all(None));
+        app.link.send_future(fetch_lists());
+        app.link.send_future(fetch_pending_invitations());
         app
     }
 
@@ -120,8 +171,9 @@ impl Component for TodoApp {
                 self.state.text = value;
             }
             TodoMessage::ClearCompleted => {
-                self.link.send_future(async {
-                    match remove_completed_todo().await {
+                let list_id = self.state.list_id;
+                self.link.send_future(async move {
+                    match remove_completed_todo(list_id).await {
                         Ok(ret) => {
                             TodoMessage::Fetch(TodoFetchState::DeleteCompletedTodoSuccess(ret))
                         }
@@ -134,8 +186,9 @@ impl Component for TodoApp {
                 if text.is_empty() {
                     return false;
                 }
-                self.link.send_future(async {
-                    match create_todo(text).await {
+                let list_id = self.state.list_id;
+                self.link.send_future(async move {
+                    match create_todo(text, list_id).await {
                         Ok(new_todo) => {
                             TodoMessage::Fetch(TodoFetchState::CreateTodoSuccess(new_todo))
                         }
@@ -183,26 +236,123 @@ impl Component for TodoApp {
                 if body.is_empty() {
                     self.link.send_message(TodoMessage::Delete(id));
                 }
-                let item_id = item.id;
-                let complete = item.complete;
+                self.link.send_future(save(item.to_owned(), body));
+                item.editing = false;
+            }
+            TodoMessage::ChangeDue(index, date) => {
+                let id = self.state.get_filtered_index(index);
+                let item = self.state.list.get_mut(id).unwrap();
+                // Due on the picked local day at the time it was due, the end of the day without one,
+                // a start on a later day is dropped.
+                item.due_at = if date.is_empty() {
+                    None
+                } else {
+                    local_due_at(&date, item.due_at.as_deref())
+                };
+                if let (Some(start_at), Some(due_at)) =
+                    (item.start_at.as_ref(), item.due_at.as_ref())
+                {
+                    if local_date(start_at) > local_date(due_at) {
+                        item.start_at = None;
+                    }
+                }
+                let body = item.body.to_owned();
+                self.link.send_future(save(item.to_owned(), body));
+            }
+            TodoMessage::ToggleAll => {
+                let list_id = self.state.list_id;
                 self.link.send_future(async move {
-                    match update_todo(item_id, body, complete).await {
-                        Ok(updated_todo) => {
-                            TodoMessage::Fetch(TodoFetchState::UpdateTodoSuccess(updated_todo))
-                        }
+                    match toggle_complete_all_todos(list_id).await {
+                        Ok(ret) => TodoMessage::Fetch(TodoFetchState::CompleteAllTodoSuccess(ret)),
                         Err(err) => TodoMessage::Fetch(TodoFetchState::Failed(err)),
                     }
                 });
-                item.editing = false;
             }
-            TodoMessage::ToggleAll => {
-                self.link.send_future(async {
-                    match toggle_complete_all_todos().await {
-                        Ok(ret) => TodoMessage::Fetch(TodoFetchState::CompleteAllTodoSuccess(ret)),
+            TodoMessage::SelectList(list_id) => {
+                self.state.list_id = Some(list_id);
+                self.state.list = vec![];
+                self.link.send_future(fetch_all(Some(list_id)));
+            }
+            TodoMessage::AddList => {
+                let window = yew::utils::window();
+                let name = match window.prompt_with_message("Name of the new list") {
+                    Ok(Some(name)) if !name.trim().is_empty() => name.trim().to_owned(),
+                    _ => return false,
+                };
+                self.link.send_future(async move {
+                    match create_todo_list(name).await {
+                        Ok(id) => TodoMessage::Fetch(TodoFetchState::CreateTodoListSuccess(id)),
                         Err(err) => TodoMessage::Fetch(TodoFetchState::Failed(err)),
                     }
                 });
             }
+            TodoMessage::DeleteList => {
+                let list_id = match self.state.list_id {
+                    Some(list_id) => list_id,
+                    None => return false,
+                };
+                let confirmed = yew::utils::window()
+                    .confirm_with_message("Delete the list with its todos?")
+                    .unwrap_or(false);
+                if !confirmed {
+                    return false;
+                }
+                self.link.send_future(async move {
+                    match remove_todo_list(list_id).await {
+                        Ok(ret) => TodoMessage::Fetch(TodoFetchState::DeleteTodoListSuccess(ret)),
+                        Err(err) => TodoMessage::Fetch(TodoFetchState::Failed(err)),
+                    }
+                });
+            }
+            TodoMessage::ShareList => {
+                let list_id = match self.state.list_id {
+                    Some(list_id) => list_id,
+                    None => return false,
+                };
+                let window = yew::utils::window();
+                let username = match window.prompt_with_message("Username to share the list with") {
+                    Ok(Some(username)) if !username.trim().is_empty() => username.trim().to_owned(),
+                    _ => return false,
+                };
+                let can_edit = window
+                    .confirm_with_message(
+                        "Let them change the todos? Cancel to only let them view.",
+                    )
+                    .unwrap_or(false);
+                self.link.send_future(async move {
+                    match invite_to_todo_list(list_id, username, can_edit).await {
+                        Ok(user_id) => TodoMessage::Fetch(TodoFetchState::InviteSuccess(user_id)),
+                        Err(err) => TodoMessage::Fetch(TodoFetchState::Failed(err)),
+                    }
+                });
+                return false;
+            }
+            TodoMessage::LeaveList => {
+                let list_id = match self.state.list_id {
+                    Some(list_id) => list_id,
+                    None => return false,
+                };
+                let confirmed = yew::utils::window()
+                    .confirm_with_message("Stop sharing the list?")
+                    .unwrap_or(false);
+                if !confirmed {
+                    return false;
+                }
+                self.link.send_future(leave(list_id));
+            }
+            TodoMessage::AcceptInvitation(list_id) => {
+                self.link.send_future(async move {
+                    match accept_invitation(list_id).await {
+                        Ok(list_id) => {
+                            TodoMessage::Fetch(TodoFetchState::AcceptInvitationSuccess(list_id))
+                        }
+                        Err(err) => TodoMessage::Fetch(TodoFetchState::Failed(err)),
+                    }
+                });
+            }
+            TodoMessage::DeclineInvitation(list_id) => {
+                self.link.send_future(leave(list_id));
+            }
             TodoMessage::SetFilter(filter) => {
                 self.state.filter = filter;
             }
@@ -216,7 +366,15 @@ impl Component for TodoApp {
                     elem.focus().unwrap();
                 }
             }
-            TodoMessage::Fetch(TodoFetchState::FetchAllTodosSuccess(todos)) => {
+            TodoMessage::Fetch(TodoFetchState::FetchAllTodosSuccess(list_id, todos)) => {
+                // Todos of a list which was switched away from arrive late.
+                let is_shown = match list_id {
+                    Some(_) => list_id == self.state.list_id,
+                    None => self.state.is_default_list_shown(),
+                };
+                if !is_shown {
+                    return false;
+                }
                 self.state.list = todos
                     .iter()
                     .map(|todo| TodoModel {
@@ -224,27 +382,76 @@ impl Component for TodoApp {
                         body: todo.body.to_owned(),
                         complete: todo.complete,
                         editing: false,
+                        start_at: todo.start_at.to_owned(),
+                        due_at: todo.due_at.to_owned(),
+                        overdue: todo.overdue,
+                        recurring: todo.series_id.is_some(),
+                        tags: todo
+                            .tags
+                            .iter()
+                            .map(|tag| TagModel {
+                                id: tag.id,
+                                name: tag.name.to_owned(),
+                                color: tag.color.to_owned(),
+                            })
+                            .collect(),
                     })
                     .collect::<Vec<TodoModel>>();
             }
+            TodoMessage::Fetch(TodoFetchState::FetchTodoListsSuccess(todo_lists)) => {
+                if self.state.list_id.is_none() {
+                    self.state.list_id = todo_lists
+                        .iter()
+                        .find(|todo_list| is_own_default(todo_list))
+                        .map(|todo_list| todo_list.id);
+                }
+                self.state.todo_lists = todo_lists;
+            }
+            TodoMessage::Fetch(TodoFetchState::CreateTodoListSuccess(list_id)) => {
+                self.link.send_message(TodoMessage::SelectList(list_id));
+                self.link.send_future(fetch_lists());
+            }
+            TodoMessage::Fetch(TodoFetchState::DeleteTodoListSuccess(_))
+            | TodoMessage::Fetch(TodoFetchState::LeaveListSuccess(_)) => {
+                self.state.list_id = None;
+                self.link.send_future(fetch_all(None));
+                self.link.send_future(fetch_lists());
+                self.link.send_future(fetch_pending_invitations());
+            }
+            TodoMessage::Fetch(TodoFetchState::FetchInvitationsSuccess(invitations)) => {
+                self.state.invitations = invitations;
+            }
+            TodoMessage::Fetch(TodoFetchState::InviteSuccess(_)) => return false,
+            TodoMessage::Fetch(TodoFetchState::AcceptInvitationSuccess(list_id)) => {
+                self.link.send_message(TodoMessage::SelectList(list_id));
+                self.link.send_future(fetch_lists());
+                self.link.send_future(fetch_pending_invitations());
+            }
             TodoMessage::Fetch(TodoFetchState::CreateTodoSuccess(_)) => {
                 self.state.text = "".to_string();
-                self.link.send_future(fetch_all());
+                self.link.send_future(fetch_all(self.state.list_id));
             }
             TodoMessage::Fetch(TodoFetchState::UpdateTodoSuccess(_)) => {
-                self.link.send_future(fetch_all());
+                self.link.send_future(fetch_all(self.state.list_id));
             }
             TodoMessage::Fetch(TodoFetchState::CompleteTodoSuccess(_))
             | TodoMessage::Fetch(TodoFetchState::CompleteAllTodoSuccess(_))
             | TodoMessage::Fetch(TodoFetchState::DeleteTodoSuccess(_))
             | TodoMessage::Fetch(TodoFetchState::DeleteCompletedTodoSuccess(_)) => {
-                self.link.send_future(fetch_all());
+                self.link.send_future(fetch_all(self.state.list_id));
             }
             TodoMessage::Fetch(TodoFetchState::Failed(err)) => {
                 yew::web_sys::console::log_1(&err.err);
             }
+            TodoMessage::Settings => {
+                self.props.app_link.send_message(AppMessage::ShowSettings);
+                return false;
+            }
             TodoMessage::Logout => {
-                logout();
+                self.link.send_future(async {
+                    sign_out().await;
+                    TodoMessage::None
+                });
             }
             TodoMessage::None => return false,
         }
@@ -261,6 +468,8 @@ impl Component for TodoApp {
                 <section class="todoapp">
                     <header class="header">
                         <h1>{ "todos" }</h1>
+                        {self.render_invitations()}
+                        {self.render_lists()}
                         {self.render_new_input()}
                     </header>
                     {self.render_main()}
@@ -269,6 +478,7 @@ impl Component for TodoApp {
                     <p>{ "Double-click to edit a todo" }</p>
                     <p>{ "Written by " }<a href="https://github.com/goppdsk/" target="_blank">{ "ここ" }</a></p>
                     <p>{ "Part of " }<a href="http://todomvc.com/" target="_blank">{ "TodoMVC" }</a></p>
+                    <p><a href="#" onclick=self.link.callback(|_| TodoMessage::Settings)>{ "Sessions" }</a></p>
                     <p><a href="#" onclick=self.link.callback(|_| TodoMessage::Logout)>{ "Logout" }</a></p>
                 </footer>
             </div>
@@ -276,13 +486,94 @@ impl Component for TodoApp {
     }
 }
 
-async fn fetch_all() -> TodoMessage {
-    match fetch_all_todos().await {
-        Ok(todos) => TodoMessage::Fetch(TodoFetchState::FetchAllTodosSuccess(todos)),
+async fn save(item: TodoModel, body: String) -> TodoMessage {
+    match update_todo(item.id, body, item.complete, item.start_at, item.due_at).await {
+        Ok(updated_todo) => TodoMessage::Fetch(TodoFetchState::UpdateTodoSuccess(updated_todo)),
         Err(err) => TodoMessage::Fetch(TodoFetchState::Failed(err)),
     }
 }
 
+async fn fetch_all(list_id: Option<i64>) -> TodoMessage {
+    match fetch_all_todos(list_id).await {
+        Ok(todos) => TodoMessage::Fetch(TodoFetchState::FetchAllTodosSuccess(list_id, todos)),
+        Err(err) => TodoMessage::Fetch(TodoFetchState::Failed(err)),
+    }
+}
+
+async fn fetch_lists() -> TodoMessage {
+    match fetch_todo_lists().await {
+        Ok(todo_lists) => TodoMessage::Fetch(TodoFetchState::FetchTodoListsSuccess(todo_lists)),
+        Err(err) => TodoMessage::Fetch(TodoFetchState::Failed(err)),
+    }
+}
+
+async fn fetch_pending_invitations() -> TodoMessage {
+    match fetch_invitations().await {
+        Ok(invitations) => TodoMessage::Fetch(TodoFetchState::FetchInvitationsSuccess(invitations)),
+        Err(err) => TodoMessage::Fetch(TodoFetchState::Failed(err)),
+    }
+}
+
+async fn leave(list_id: i64) -> TodoMessage {
+    match leave_todo_list(list_id).await {
+        Ok(ret) => TodoMessage::Fetch(TodoFetchState::LeaveListSuccess(ret)),
+        Err(err) => TodoMessage::Fetch(TodoFetchState::Failed(err)),
+    }
+}
+
+fn is_owner(todo_list: &todo_lists::TodoListsTodoLists) -> bool {
+    matches!(todo_list.role, todo_lists::TodoListRole::OWNER)
+}
+
+/// Lists shared with the user may be the default list of their owner.
+fn is_own_default(todo_list: &todo_lists::TodoListsTodoLists) -> bool {
+    todo_list.is_default && is_owner(todo_list)
+}
+
+/// The `YYYY-MM-DD` day of the RFC 3339 time in the browser's time zone.
+fn local_date(time: &str) -> String {
+    let date = js_sys::Date::new(&JsValue::from_str(time));
+    format!(
+        "{:04}-{:02}-{:02}",
+        date.get_full_year(),
+        date.get_month() + 1,
+        date.get_date()
+    )
+}
+
+/// The picked `YYYY-MM-DD` day in the browser's time zone at the local time of the due time,
+/// 23:59:59 without one.
+fn local_due_at(date: &str, due_at: Option<&str>) -> Option<String> {
+    let mut parts = date.splitn(3, '-').map(|part| part.parse::<i32>().ok());
+    let (year, month, day) = match (parts.next()??, parts.next()??, parts.next()??) {
+        (year, month, day) if year > 0 => (year as u32, month, day),
+        _ => return None,
+    };
+    let (hours, minutes, seconds) = match due_at {
+        Some(due_at) => {
+            let previous = js_sys::Date::new(&JsValue::from_str(due_at));
+            (
+                previous.get_hours() as i32,
+                previous.get_minutes() as i32,
+                previous.get_seconds() as i32,
+            )
+        }
+        None => (23, 59, 59),
+    };
+    let local = js_sys::Date::new_with_year_month_day_hr_min_sec(
+        year,
+        month - 1,
+        day,
+        hours,
+        minutes,
+        seconds,
+    );
+    if local.get_time().is_nan() {
+        return None;
+    }
+    local.to_iso_string().as_string()
+}
+
 impl TodoApp {
     fn render_main(&self) -> Html {
         let mut list = self
@@ -345,6 +636,69 @@ impl TodoApp {
         }
     }
 
+    fn render_lists(&self) -> Html {
+        let current = self
+            .state
+            .todo_lists
+            .iter()
+            .find(|todo_list| Some(todo_list.id) == self.state.list_id);
+        html! {
+            <ul class="filters todo-lists">
+                { for self.state.todo_lists.iter().map(|todo_list| {
+                    let id = todo_list.id;
+                    let class = if Some(id) == self.state.list_id { "selected" } else { "" };
+                    html! {
+                        <li>
+                            <a href="#" class=class onclick=self.link.callback(move |_| TodoMessage::SelectList(id))>
+                                { todo_list.name.to_owned() }
+                            </a>
+                        </li>
+                    }
+                }) }
+                <li><a href="#" onclick=self.link.callback(|_| TodoMessage::AddList)>{ "+ New list" }</a></li>
+                {
+                    match current {
+                        Some(todo_list) if is_owner(todo_list) => html! {
+                            <>
+                                <li><a href="#" onclick=self.link.callback(|_| TodoMessage::ShareList)>{ "Share list" }</a></li>
+                                {
+                                    if !todo_list.is_default {
+                                        html! {
+                                            <li><a href="#" onclick=self.link.callback(|_| TodoMessage::DeleteList)>{ "Delete list" }</a></li>
+                                        }
+                                    } else {
+                                        html! {}
+                                    }
+                                }
+                            </>
+                        },
+                        Some(_) => html! {
+                            <li><a href="#" onclick=self.link.callback(|_| TodoMessage::LeaveList)>{ "Leave list" }</a></li>
+                        },
+                        None => html! {},
+                    }
+                }
+            </ul>
+        }
+    }
+
+    fn render_invitations(&self) -> Html {
+        html! {
+            <ul class="filters todo-invitations">
+                { for self.state.invitations.iter().map(|invitation| {
+                    let list_id = invitation.list_id;
+                    html! {
+                        <li>
+                            { format!("Shared with you: {} ", invitation.list_name) }
+                            <a href="#" onclick=self.link.callback(move |_| TodoMessage::AcceptInvitation(list_id))>{ "Accept" }</a>
+                            <a href="#" onclick=self.link.callback(move |_| TodoMessage::DeclineInvitation(list_id))>{ "Decline" }</a>
+                        </li>
+                    }
+                }) }
+            </ul>
+        }
+    }
+
     fn render_toggle_all(&self) -> Html {
         html! {
             <>
@@ -380,6 +734,12 @@ impl TodoApp {
         if item.complete {
             class.push(" completed");
         }
+        if item.overdue {
+            class.push(" overdue");
+        }
+        if item.recurring {
+            class.push(" recurring");
+        }
         if item.editing {
             class.push(" editing");
         }
@@ -406,7 +766,31 @@ impl TodoApp {
                     onclick=self.link.callback(move |_| TodoMessage::Toggle(index))
                 />
                 <label ondblclick=self.link.callback(move |_| TodoMessage::Edit(index))>{item.body.to_owned()}</label>
+                <input
+                    class="due"
+                    type="date"
+                    title="Due date"
+                    value=item.due_at.as_deref().map(local_date).unwrap_or_default()
+                    onchange=self.link.callback(move |data: ChangeData| match data {
+                        ChangeData::Value(date) => TodoMessage::ChangeDue(index, date),
+                        _ => TodoMessage::None,
+                    })
+                />
                 <button class="destroy" onclick=self.link.callback(move |_| TodoMessage::Delete(index)) />
+                { self.render_tags(item) }
+            </div>
+        }
+    }
+
+    fn render_tags(&self, item: &TodoModel) -> Html {
+        if item.tags.is_empty() {
+            return html! {};
+        }
+        html! {
+            <div class="tags">
+                { for item.tags.iter().map(|tag| html! {
+                    <span key=tag.id.to_string() class="tag" style=format!("background-color: {}", tag.color)>{tag.name.to_owned()}</span>
+                }) }
             </div>
         }
     }
@@ -447,6 +831,16 @@ impl TodoApp {
 }
 
 impl TodoState {
+    fn is_default_list_shown(&self) -> bool {
+        match self.list_id {
+            Some(list_id) => self
+                .todo_lists
+                .iter()
+                .any(|todo_list| todo_list.id == list_id && is_own_default(todo_list)),
+            None => true,
+        }
+    }
+
     fn total(&self) -> i32 {
         self.list.len() as i32
     }
@@ -1,3 +1,4 @@
+use graphql_client::GraphQLQuery;
 use serde_json::json;
 use std::error::Error;
 use std::fmt;
@@ -18,33 +19,128 @@ impl Display for FetchError {
 }
 impl Error for FetchError {}
 
+pub struct Tokens {
+    pub access_token: String,
+    pub refresh_token: String,
+}
+
+/// What the server put in the URL fragment after a login with a provider.
+pub enum LoginRedirect {
+    Tokens(Tokens),
+    /// The login waits for the second factor.
+    Challenge(String),
+}
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/refresh_token.graphql"
+)]
+pub struct RefreshToken;
+
+#[derive(GraphQLQuery)]
+#[graphql(schema_path = "gql/schema.json", query_path = "gql/logout.graphql")]
+pub struct Logout;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/verify_email.graphql"
+)]
+pub struct VerifyEmail;
+
+#[derive(GraphQLQuery)]
+#[graphql(
+    schema_path = "gql/schema.json",
+    query_path = "gql/reset_password.graphql"
+)]
+pub struct ResetPassword;
+
 pub static GRAPHQL_ENDPOINT: &str = std::env!("GRAPHQL_ENDPOINT");
 static JWT_STORAGE_KEY: &str = "rsappsJwt";
+static REFRESH_TOKEN_STORAGE_KEY: &str = "rsappsRefreshToken";
 
 pub async fn request<V: serde::Serialize>(
     query: graphql_client::QueryBody<V>,
 ) -> Result<JsValue, FetchError> {
-    let json_body = json!(query);
-    let jwt = get_jwt().unwrap_or(String::from(""));
-    let headers = match JsValue::from_serde(&json!({
+    let json_body = json!(query).to_string();
+    let mut resp = send(&json_body, true).await?;
+    // The access token is short-lived, so try once to rotate it before giving up.
+    if resp.status() == 401 {
+        if refresh_tokens().await.is_err() {
+            logout();
+            return Err(FetchError {
+                err: JsValue::from_str("session is expired"),
+            });
+        }
+        resp = send(&json_body, true).await?;
+    }
+    if resp.status() == 429 {
+        let retry_after = resp.headers().get("Retry-After").ok().flatten();
+        return Err(FetchError {
+            err: JsValue::from_str(&format!(
+                "too many attempts, please retry after {} seconds",
+                retry_after.unwrap_or_else(|| "a few".to_owned())
+            )),
+        });
+    }
+
+    Ok(JsFuture::from(resp.json()?).await?)
+}
+
+/// Send the body, with the stored access token when it is `authorized`.
+async fn send(json_body: &str, authorized: bool) -> Result<Response, FetchError> {
+    let mut headers = json!({
         "Content-Type": "application/json",
-        "Authorization": format!("Bearer {}", jwt),
-    })) {
+    });
+    // Anonymous requests such as login and sign up must not carry an empty token.
+    if let Some(jwt) = get_jwt().filter(|_| authorized) {
+        headers["Authorization"] = json!(format!("Bearer {}", jwt));
+    }
+    let headers = match JsValue::from_serde(&headers) {
         Ok(headers) => headers,
         Err(_) => JsValue::NULL,
     };
     let mut opts = RequestInit::new();
     opts.method("POST");
     opts.mode(RequestMode::Cors);
-    opts.body(Some(JsValue::from_str(json_body.to_string().as_str())).as_ref());
+    opts.body(Some(JsValue::from_str(json_body)).as_ref());
     opts.headers(&headers);
     let request = Request::new_with_str_and_init(GRAPHQL_ENDPOINT, &opts)?;
 
     let window = yew::utils::window();
     let resp_value = JsFuture::from(window.fetch_with_request(&request)).await?;
-    let resp: Response = resp_value.dyn_into().unwrap();
+    Ok(resp_value.dyn_into().unwrap())
+}
 
-    Ok(JsFuture::from(resp.json()?).await?)
+async fn refresh_tokens() -> Result<(), FetchError> {
+    let token = match get_refresh_token() {
+        Some(token) => token,
+        None => {
+            return Err(FetchError {
+                err: JsValue::from_str("refresh token is not stored"),
+            })
+        }
+    };
+    let request_body = RefreshToken::build_query(refresh_token::Variables { token });
+    // The server rejects an expired access token before any resolver runs, so it is left out.
+    let resp = send(&json!(request_body).to_string(), false).await?;
+    let resp = JsFuture::from(resp.json()?).await?;
+
+    match resp.into_serde::<graphql_client::Response<refresh_token::ResponseData>>() {
+        Ok(graphql_client::Response {
+            data: Some(data), ..
+        }) => {
+            set_tokens(Tokens {
+                access_token: data.refresh_token.access_token,
+                refresh_token: data.refresh_token.refresh_token,
+            });
+            Ok(())
+        }
+        _ => Err(FetchError {
+            err: JsValue::from_str("failed to refresh token"),
+        }),
+    }
 }
 
 fn get_local_storage() -> Option<web_sys::Storage> {
@@ -54,26 +150,174 @@ fn get_local_storage() -> Option<web_sys::Storage> {
     }
 }
 
-pub fn get_jwt() -> Option<String> {
+fn get_item(key: &str) -> Option<String> {
     let storage = match get_local_storage() {
         Some(storage) => storage,
         None => return None,
     };
-    match storage.get_item(JWT_STORAGE_KEY) {
-        Ok(jwt) => jwt,
+    match storage.get_item(key) {
+        Ok(value) => value,
         Err(_) => None,
     }
 }
 
-pub fn set_jwt(jwt: String) {
+pub fn get_jwt() -> Option<String> {
+    get_item(JWT_STORAGE_KEY)
+}
+
+fn get_refresh_token() -> Option<String> {
+    get_item(REFRESH_TOKEN_STORAGE_KEY)
+}
+
+pub fn set_tokens(tokens: Tokens) {
     if let Some(storage) = get_local_storage() {
-        storage.set_item(JWT_STORAGE_KEY, jwt.as_str()).unwrap();
+        storage
+            .set_item(JWT_STORAGE_KEY, tokens.access_token.as_str())
+            .unwrap();
+        storage
+            .set_item(REFRESH_TOKEN_STORAGE_KEY, tokens.refresh_token.as_str())
+            .unwrap();
     }
 }
 
+/// The web server hosts the GraphQL endpoint and the login provider routes.
+pub fn server_url() -> &'static str {
+    GRAPHQL_ENDPOINT.trim_end_matches("/graphql")
+}
+
+/// Pick up the tokens the server put in the URL fragment after a login with a provider.
+pub fn take_login_redirect() -> Option<LoginRedirect> {
+    let location = yew::utils::window().location();
+    let hash = location.hash().ok()?;
+    let mut access_token = None;
+    let mut refresh_token = None;
+    let mut challenge_token = None;
+    let mut error = None;
+    for pair in hash.trim_start_matches('#').split('&') {
+        let mut pair = pair.splitn(2, '=');
+        match (pair.next(), pair.next()) {
+            (Some("access_token"), Some(value)) => access_token = Some(value.to_owned()),
+            (Some("refresh_token"), Some(value)) => refresh_token = Some(value.to_owned()),
+            (Some("challenge_token"), Some(value)) => challenge_token = Some(value.to_owned()),
+            (Some("error"), Some(value)) => error = Some(value.to_owned()),
+            _ => {}
+        }
+    }
+    if access_token.is_none() && challenge_token.is_none() && error.is_none() {
+        return None;
+    }
+    // Don't leave the tokens in the address bar and the history.
+    location.set_hash("").unwrap();
+    if let Some(error) = error {
+        yew::utils::window()
+            .alert_with_message(&format!("failed to login, error: {}", error))
+            .unwrap();
+    }
+    if let Some(challenge_token) = challenge_token {
+        return Some(LoginRedirect::Challenge(challenge_token));
+    }
+    Some(LoginRedirect::Tokens(Tokens {
+        access_token: access_token?,
+        refresh_token: refresh_token?,
+    }))
+}
+
+/// The token of a login link the page is opened from, it is dropped from the address bar.
+pub fn take_magic_link() -> Option<String> {
+    let window = yew::utils::window();
+    let search = window.location().search().unwrap_or_default();
+    let token = search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("magic_link="))?
+        .to_owned();
+    let _ = window
+        .history()
+        .and_then(|history| history.replace_state_with_url(&JsValue::NULL, "", Some("/")));
+    Some(token)
+}
+
+/// Verify the email when the page is opened from the link of a verification mail.
+pub async fn verify_email_from_link() {
+    let window = yew::utils::window();
+    let search = window.location().search().unwrap_or_default();
+    let token = match search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("verify_email="))
+    {
+        Some(token) => token.to_owned(),
+        None => return,
+    };
+    // The link can't be used twice, so drop it from the address bar.
+    let _ = window
+        .history()
+        .and_then(|history| history.replace_state_with_url(&JsValue::NULL, "", Some("/")));
+
+    let request_body = VerifyEmail::build_query(verify_email::Variables { token });
+    let message = match request::<verify_email::Variables>(request_body).await {
+        Ok(resp) => match resp.into_serde::<graphql_client::Response<verify_email::ResponseData>>()
+        {
+            Ok(graphql_client::Response {
+                data: Some(data), ..
+            }) if data.verify_email.email_verified => "Your email is verified.",
+            _ => "Failed to verify your email, the link may be expired.",
+        },
+        Err(_) => "Failed to verify your email, the link may be expired.",
+    };
+    window.alert_with_message(message).unwrap();
+}
+
+/// Choose a new password when the page is opened from the link of a password reset mail.
+pub async fn reset_password_from_link() {
+    let window = yew::utils::window();
+    let search = window.location().search().unwrap_or_default();
+    let token = match search
+        .trim_start_matches('?')
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("reset_password="))
+    {
+        Some(token) => token.to_owned(),
+        None => return,
+    };
+    let _ = window
+        .history()
+        .and_then(|history| history.replace_state_with_url(&JsValue::NULL, "", Some("/")));
+    let new_password = match window.prompt_with_message("Enter a new password") {
+        Ok(Some(password)) if !password.is_empty() => password,
+        _ => return,
+    };
+
+    let request_body = ResetPassword::build_query(reset_password::Variables {
+        token,
+        new_password,
+    });
+    let message = match request::<reset_password::Variables>(request_body).await {
+        Ok(resp) => {
+            match resp.into_serde::<graphql_client::Response<reset_password::ResponseData>>() {
+                Ok(graphql_client::Response {
+                    data: Some(data), ..
+                }) if data.reset_password => "Your password is changed, please login again.",
+                _ => "Failed to reset your password, the link may be expired.",
+            }
+        }
+        Err(_) => "Failed to reset your password, the link may be expired.",
+    };
+    window.alert_with_message(message).unwrap();
+}
+
+/// Revoke the login on the server, then forget the tokens.
+pub async fn sign_out() {
+    let request_body = Logout::build_query(logout::Variables {});
+    // The tokens are dropped locally even if the server can't be reached.
+    let _ = request::<logout::Variables>(request_body).await;
+    logout();
+}
+
 pub fn logout() {
     if let Some(storage) = get_local_storage() {
         storage.remove_item(JWT_STORAGE_KEY).unwrap();
+        storage.remove_item(REFRESH_TOKEN_STORAGE_KEY).unwrap();
         let window = yew::utils::window();
         window.location().set_href("/").unwrap();
     }